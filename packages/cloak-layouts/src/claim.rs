@@ -0,0 +1,75 @@
+//! Claim: PDA per miner + batch (scramble-registry)
+//!
+//! [miner_authority: 32][batch_hash: 32][slot: 8][slot_hash: 32][nonce: 16]
+//! [proof_hash: 32][mined_at_slot: 8][revealed_at_slot: 8][consumed_count: 2]
+//! [max_consumes: 2][expires_at_slot: 8][status: 1]
+//! [consumer_fingerprints: 18 * 4][_reserved: 3]
+
+use crate::{read_array, read_u128, read_u16, read_u64};
+
+pub const MINER_AUTHORITY: usize = 0;
+pub const BATCH_HASH: usize = 32;
+pub const SLOT: usize = 64;
+pub const SLOT_HASH: usize = 72;
+pub const NONCE: usize = 104;
+pub const PROOF_HASH: usize = 120;
+pub const MINED_AT_SLOT: usize = 152;
+pub const REVEALED_AT_SLOT: usize = 160;
+pub const CONSUMED_COUNT: usize = 168;
+pub const MAX_CONSUMES: usize = 170;
+pub const EXPIRES_AT_SLOT: usize = 172;
+pub const STATUS: usize = 180;
+pub const CONSUMER_FINGERPRINTS: usize = 181;
+/// Total: 256 bytes (aligned)
+pub const SIZE: usize = 256;
+
+/// `status` byte values
+pub const STATUS_MINED: u8 = 0;
+pub const STATUS_REVEALED: u8 = 1;
+pub const STATUS_ACTIVE: u8 = 2;
+pub const STATUS_CONSUMED: u8 = 3;
+pub const STATUS_EXPIRED: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClaimView {
+    pub miner_authority: [u8; 32],
+    pub batch_hash: [u8; 32],
+    pub slot: u64,
+    pub slot_hash: [u8; 32],
+    pub nonce: u128,
+    pub proof_hash: [u8; 32],
+    pub mined_at_slot: u64,
+    pub revealed_at_slot: u64,
+    pub consumed_count: u16,
+    pub max_consumes: u16,
+    pub expires_at_slot: u64,
+    pub status: u8,
+}
+
+impl ClaimView {
+    /// Wildcard claims (batch_hash = [0; 32]) can be consumed for any batch
+    pub fn is_wildcard(&self) -> bool {
+        self.batch_hash == [0u8; 32]
+    }
+}
+
+pub fn decode_claim(data: &[u8]) -> Option<ClaimView> {
+    if data.len() != SIZE {
+        return None;
+    }
+
+    Some(ClaimView {
+        miner_authority: read_array(data, MINER_AUTHORITY),
+        batch_hash: read_array(data, BATCH_HASH),
+        slot: read_u64(data, SLOT),
+        slot_hash: read_array(data, SLOT_HASH),
+        nonce: read_u128(data, NONCE),
+        proof_hash: read_array(data, PROOF_HASH),
+        mined_at_slot: read_u64(data, MINED_AT_SLOT),
+        revealed_at_slot: read_u64(data, REVEALED_AT_SLOT),
+        consumed_count: read_u16(data, CONSUMED_COUNT),
+        max_consumes: read_u16(data, MAX_CONSUMES),
+        expires_at_slot: read_u64(data, EXPIRES_AT_SLOT),
+        status: data[STATUS],
+    })
+}
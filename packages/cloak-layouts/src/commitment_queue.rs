@@ -0,0 +1,53 @@
+//! CommitmentQueue: ring buffer of recent deposit commitments (shield-pool)
+//!
+//! [total_commits: u64][reserved: u64][commitments: CAPACITY * 32]
+
+use crate::read_u64;
+
+pub const TOTAL_COMMITS: usize = 0;
+pub const HEADER_SIZE: usize = 16;
+pub const CAPACITY: usize = 256;
+/// Total: 16 + 8192 = 8208 bytes
+pub const SIZE: usize = HEADER_SIZE + CAPACITY * 32;
+
+/// Byte offset of ring slot `slot`
+#[inline(always)]
+pub const fn slot_offset(slot: usize) -> usize {
+    HEADER_SIZE + slot * 32
+}
+
+/// Absolute indices still held by the ring: `(first, count)`
+#[inline(always)]
+pub fn live_window(total_commits: u64) -> (u64, u64) {
+    let count = core::cmp::min(total_commits, CAPACITY as u64);
+    (total_commits - count, count)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitmentQueueView<'a> {
+    pub total_commits: u64,
+    data: &'a [u8],
+}
+
+impl<'a> CommitmentQueueView<'a> {
+    /// Commitments still held by the ring, oldest first
+    pub fn commitments(&self) -> impl Iterator<Item = [u8; 32]> + 'a {
+        let data = self.data;
+        let (first, count) = live_window(self.total_commits);
+        (first..first + count).map(move |index| {
+            let offset = slot_offset((index % CAPACITY as u64) as usize);
+            crate::read_array(data, offset)
+        })
+    }
+}
+
+pub fn decode_commitment_queue(data: &[u8]) -> Option<CommitmentQueueView<'_>> {
+    if data.len() != SIZE {
+        return None;
+    }
+
+    Some(CommitmentQueueView {
+        total_commits: read_u64(data, TOTAL_COMMITS),
+        data,
+    })
+}
@@ -0,0 +1,28 @@
+//! BLAKE3 `derive_key` contexts separating Cloak's hash domains
+//!
+//! The guest, host, relay and program start each of these hashes with
+//! [`hasher`]. Built with `domain-separation` it is
+//! `blake3::Hasher::new_derive_key(<context>)` instead of a plain hasher, so
+//! identical preimage bytes hash differently in each domain. The contexts are
+//! part of the circuit: changing one changes the verifying key.
+
+/// Note commitment: `C = H(amount || r || pk_spend)`
+pub const COMMITMENT: &str = "cloak 2026-10-16 note commitment v1";
+/// Spend key: `pk_spend = H(sk_spend)`
+pub const PK_SPEND: &str = "cloak 2026-10-16 spend key v1";
+/// Nullifier: `nf = H(sk_spend || leaf_index)`
+pub const NULLIFIER: &str = "cloak 2026-10-16 nullifier v1";
+/// Withdraw outputs hash, over `encode_output` bytes (or swap parameters)
+pub const OUTPUTS_HASH: &str = "cloak 2026-10-16 outputs hash v1";
+
+/// BLAKE3 hasher for `context`: `derive_key` mode with `domain-separation`,
+/// a plain hasher otherwise
+#[cfg(feature = "domain-separation")]
+pub fn hasher(context: &str) -> blake3::Hasher {
+    blake3::Hasher::new_derive_key(context)
+}
+
+#[cfg(all(feature = "blake3", not(feature = "domain-separation")))]
+pub fn hasher(_context: &str) -> blake3::Hasher {
+    blake3::Hasher::new()
+}
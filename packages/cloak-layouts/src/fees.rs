@@ -0,0 +1,56 @@
+//! Withdraw protocol fee
+//!
+//! The guest proves `amount = sum(outputs) + fee` and the program re-checks
+//! that conservation on-chain, so the relay and test tooling have to charge
+//! exactly the same fee. Every party computes it through `calculate_fee`,
+//! which works in `u128` so no `u64` amount can overflow the intermediate
+//! product.
+
+/// Fixed part of the native SOL withdraw fee: 0.0025 SOL in lamports
+pub const FIXED_FEE_LAMPORTS: u64 = 2_500_000;
+/// Variable part of the protocol fee, in thousandths of the amount (0.5%)
+pub const VARIABLE_FEE_PER_MILLE: u64 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct FeeSchedule {
+    /// Flat fee in base units
+    pub fixed: u64,
+    /// Fee proportional to the amount, in thousandths
+    pub variable_per_mille: u64,
+}
+
+impl FeeSchedule {
+    /// Native SOL withdraws, and anything the circuit proves: fixed + 0.5%
+    pub const NATIVE: Self = Self {
+        fixed: FIXED_FEE_LAMPORTS,
+        variable_per_mille: VARIABLE_FEE_PER_MILLE,
+    };
+    /// SPL withdraws and swaps: 0.5% only, the fixed part is paid in SOL
+    /// separately
+    pub const VARIABLE_ONLY: Self = Self {
+        fixed: 0,
+        variable_per_mille: VARIABLE_FEE_PER_MILLE,
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeError {
+    /// The fee does not fit in a `u64`
+    Overflow,
+}
+
+impl core::fmt::Display for FeeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FeeError::Overflow => f.write_str("fee overflows u64"),
+        }
+    }
+}
+
+/// `fixed + amount * variable_per_mille / 1_000`, rounded down
+pub fn calculate_fee(amount: u64, schedule: FeeSchedule) -> Result<u64, FeeError> {
+    let variable = u128::from(amount) * u128::from(schedule.variable_per_mille) / 1_000;
+    let fee = variable + u128::from(schedule.fixed);
+    u64::try_from(fee).map_err(|_| FeeError::Overflow)
+}
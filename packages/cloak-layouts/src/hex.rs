@@ -0,0 +1,64 @@
+//! 32-byte hex fields (roots, nullifiers, commitments, outputs hashes)
+//!
+//! Clients send these both bare and `0x`-prefixed; every service parses them
+//! through `parse_hex32` so both forms are accepted and malformed input is
+//! rejected with the same error everywhere.
+
+/// Hex digits in a 32-byte value, without the optional `0x` prefix
+pub const HEX32_LEN: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexError {
+    /// An odd number of hex digits, which can't be whole bytes
+    OddLength(usize),
+    /// Whole bytes, but not 32 of them
+    InvalidLength(usize),
+    /// A character that isn't a hex digit, with its position after any prefix
+    InvalidChar { c: char, index: usize },
+}
+
+impl core::fmt::Display for HexError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HexError::OddLength(len) => write!(f, "odd number of hex digits ({})", len),
+            HexError::InvalidLength(len) => write!(
+                f,
+                "expected {} hex digits (optionally 0x-prefixed), got {}",
+                HEX32_LEN, len
+            ),
+            HexError::InvalidChar { c, index } => {
+                write!(f, "invalid hex character {:?} at position {}", c, index)
+            }
+        }
+    }
+}
+
+/// Parse 32 bytes from 64 hex digits, with or without a `0x` prefix.
+/// Upper- and lowercase digits are both accepted.
+pub fn parse_hex32(s: &str) -> Result<[u8; 32], HexError> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    if let Some((index, c)) = digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(HexError::InvalidChar { c, index });
+    }
+    if digits.len() & 1 != 0 {
+        return Err(HexError::OddLength(digits.len()));
+    }
+    if digits.len() != HEX32_LEN {
+        return Err(HexError::InvalidLength(digits.len()));
+    }
+
+    let mut out = [0u8; 32];
+    for (byte, pair) in out.iter_mut().zip(digits.as_bytes().chunks_exact(2)) {
+        *byte = (nibble(pair[0]) << 4) | nibble(pair[1]);
+    }
+    Ok(out)
+}
+
+/// Value of an ASCII hex digit already checked by `parse_hex32`
+fn nibble(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        b'a'..=b'f' => digit - b'a' + 10,
+        _ => digit - b'A' + 10,
+    }
+}
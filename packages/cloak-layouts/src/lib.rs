@@ -0,0 +1,320 @@
+//! Byte layouts for Cloak's on-chain accounts.
+//!
+//! The programs read and write these accounts through raw offsets; the offset
+//! constants here are the single source for both the programs' state modules
+//! and the off-chain decoders (relay, indexer, miner), so a layout change only
+//! has to be made once.
+//!
+//! Decoders are pure functions over account data and return `None` when the
+//! data is not exactly the account's size (or, for `pool`, one of its legacy sizes).
+//!
+//! `outputs` is the one non-account layout: the withdraw output (and swap
+//! parameter) encoding hashed into `outputs_hash`, and `domains` holds the
+//! BLAKE3 contexts (and, with `blake3`, the hasher) that keep those hashes
+//! apart from note hashes. `fees` is the withdraw fee every conservation check
+//! has to agree on, and `hex` parses the 32-byte hex fields the services
+//! accept in requests.
+#![no_std]
+
+pub mod claim;
+pub mod commitment_queue;
+pub mod domains;
+pub mod fees;
+pub mod hex;
+pub mod miner;
+pub mod outputs;
+pub mod pool;
+pub mod registry;
+pub mod roots_ring;
+pub mod swap_state;
+
+pub use claim::{decode_claim, ClaimView};
+pub use commitment_queue::{decode_commitment_queue, CommitmentQueueView};
+pub use fees::{calculate_fee, FeeError, FeeSchedule};
+pub use hex::{parse_hex32, HexError};
+pub use miner::{decode_miner, MinerView};
+pub use outputs::{encode_output, encode_swap_outputs, hash_order};
+pub use pool::{decode_pool, PoolView};
+pub use registry::{decode_registry, RegistryView};
+pub use roots_ring::{decode_roots_ring, RootsRingView};
+pub use swap_state::{decode_swap_state, SwapStateView};
+
+/// Levels in the indexer's commitment tree, counting the leaf level
+/// (the indexer's TREE_HEIGHT)
+pub const MERKLE_TREE_HEIGHT: usize = 32;
+
+/// Siblings in a Merkle path from a leaf to the root, which is the number of
+/// levels the guest hashes through to prove membership
+pub const MERKLE_TREE_DEPTH: usize = MERKLE_TREE_HEIGHT - 1;
+
+#[inline(always)]
+fn read_array<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {
+    let mut out = [0u8; N];
+    out.copy_from_slice(&data[offset..offset + N]);
+    out
+}
+
+#[inline(always)]
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(read_array(data, offset))
+}
+
+#[inline(always)]
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(read_array(data, offset))
+}
+
+#[inline(always)]
+fn read_u128(data: &[u8], offset: usize) -> u128 {
+    u128::from_le_bytes(read_array(data, offset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layouts_end_at_account_size() {
+        assert_eq!(claim::STATUS + 1 + 18 * 4 + 3, claim::SIZE);
+        assert_eq!(miner::CONSUME_RATE_BPS + 8, miner::SIZE);
+        assert_eq!(outputs::AMOUNT + 8, outputs::SIZE);
+        assert_eq!(pool::AUTHORITY, pool::PRE_AUTHORITY_SIZE);
+        assert_eq!(pool::PENDING_AUTHORITY + 32, pool::PRE_FLAGS_SIZE);
+        assert_eq!(pool::FLAGS + 1, pool::SIZE);
+        assert_eq!(registry::ACTIVE_CLAIMS + 8, registry::SIZE);
+        assert_eq!(swap_state::BUMP + 1, swap_state::SIZE);
+        assert_eq!(
+            roots_ring::root_offset(roots_ring::MAX_ROOTS),
+            roots_ring::SIZE
+        );
+        assert_eq!(
+            commitment_queue::slot_offset(commitment_queue::CAPACITY),
+            commitment_queue::SIZE
+        );
+    }
+
+    #[test]
+    fn test_decoders_reject_wrong_size() {
+        assert!(decode_claim(&[0u8; claim::SIZE - 1]).is_none());
+        assert!(decode_miner(&[0u8; miner::SIZE + 1]).is_none());
+        assert!(decode_registry(&[0u8; 180]).is_none());
+        assert!(decode_swap_state(&[0u8; swap_state::SIZE - 1]).is_none());
+        assert!(decode_commitment_queue(&[0u8; 16]).is_none());
+        assert!(decode_roots_ring(&[0u8; roots_ring::SIZE + 1]).is_none());
+        assert!(decode_pool(&[0u8; pool::SIZE + 1]).is_none());
+    }
+
+    #[test]
+    fn test_legacy_pool_decodes_uncapped() {
+        let mut data = [0u8; pool::SIZE];
+        data[..32].copy_from_slice(&[0x11; 32]);
+        data[pool::MAX_WITHDRAW_BPS..pool::AUTHORITY].copy_from_slice(&2_500u16.to_le_bytes());
+        data[pool::AUTHORITY..pool::PENDING_AUTHORITY].copy_from_slice(&[0x22; 32]);
+        data[pool::FLAGS] = pool::FLAG_NO_OVERWRITE;
+
+        let capped = decode_pool(&data).unwrap();
+        assert_eq!(capped.mint, [0x11; 32]);
+        assert_eq!(capped.max_withdraw_bps, 2_500);
+        assert_eq!(capped.authority, [0x22; 32]);
+        assert!(capped.no_overwrite());
+
+        let pre_flags = decode_pool(&data[..pool::PRE_FLAGS_SIZE]).unwrap();
+        assert_eq!(pre_flags.max_withdraw_bps, 2_500);
+        assert!(!pre_flags.no_overwrite());
+
+        let pre_authority = decode_pool(&data[..pool::PRE_AUTHORITY_SIZE]).unwrap();
+        assert_eq!(pre_authority.max_withdraw_bps, 2_500);
+        assert_eq!(pre_authority.authority, [0u8; 32]);
+
+        let legacy = decode_pool(&data[..pool::LEGACY_SIZE]).unwrap();
+        assert_eq!(legacy.mint, [0x11; 32]);
+        assert_eq!(legacy.max_withdraw_bps, 0);
+        assert_eq!(legacy.authority, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_legacy_miner_decodes_without_consume_rate() {
+        let mut data = [0u8; miner::SIZE];
+        data[..32].copy_from_slice(&[0x33; 32]);
+        data[miner::TOTAL_MINED..miner::TOTAL_CONSUMED].copy_from_slice(&7u64.to_le_bytes());
+        data[miner::CONSUME_RATE_BPS..].copy_from_slice(&5_000u64.to_le_bytes());
+
+        assert_eq!(decode_miner(&data).unwrap().consume_rate_bps, 5_000);
+
+        let legacy = decode_miner(&data[..miner::LEGACY_SIZE]).unwrap();
+        assert_eq!(legacy.authority, [0x33; 32]);
+        assert_eq!(legacy.total_mined, 7);
+        assert_eq!(legacy.consume_rate_bps, 0);
+        assert!(decode_miner(&data[..miner::LEGACY_SIZE - 1]).is_none());
+    }
+
+    #[cfg(feature = "sorted-outputs")]
+    #[test]
+    fn test_hash_order_sorts_by_address_then_amount() {
+        let mut outputs = [([2u8; 32], 1u64), ([1; 32], 5), ([1; 32], 3)];
+        hash_order(&mut outputs, |output| *output);
+        assert_eq!(outputs, [([1; 32], 3), ([1; 32], 5), ([2; 32], 1)]);
+    }
+
+    #[test]
+    fn test_domain_contexts_are_distinct() {
+        let contexts = [
+            domains::COMMITMENT,
+            domains::PK_SPEND,
+            domains::NULLIFIER,
+            domains::OUTPUTS_HASH,
+        ];
+        for (i, a) in contexts.iter().enumerate() {
+            for b in &contexts[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_commitment_queue_iterates_oldest_first_after_wrap() {
+        let mut data = [0u8; commitment_queue::SIZE];
+        let total = commitment_queue::CAPACITY as u64 + 2;
+        data[..8].copy_from_slice(&total.to_le_bytes());
+        for index in 0..total {
+            let slot = (index % commitment_queue::CAPACITY as u64) as usize;
+            let offset = commitment_queue::slot_offset(slot);
+            data[offset..offset + 8].copy_from_slice(&index.to_le_bytes());
+        }
+
+        let queue = decode_commitment_queue(&data).unwrap();
+        let firsts: [u64; 3] = {
+            let mut it = queue
+                .commitments()
+                .map(|c| u64::from_le_bytes(c[..8].try_into().unwrap()));
+            [it.next().unwrap(), it.next().unwrap(), it.next().unwrap()]
+        };
+        assert_eq!(firsts, [2, 3, 4]);
+        assert_eq!(queue.commitments().count(), commitment_queue::CAPACITY);
+        assert_eq!(
+            queue.commitments().last().map(|c| c[0]),
+            Some((total - 1) as u8)
+        );
+    }
+
+    #[test]
+    fn test_encode_output_is_address_then_amount_le() {
+        let encoded = encode_output(&[0xAB; 32], 0x0102_0304_0506_0708);
+        assert_eq!(encoded[..32], [0xAB; 32]);
+        assert_eq!(encoded[32..], [8, 7, 6, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_encode_swap_outputs_field_order() {
+        let encoded = encode_swap_outputs(&[0xAA; 32], &[0xBB; 32], 1_000_000, 3_000_000_000);
+        assert_eq!(encoded.len(), 80);
+        assert_eq!(encoded[..32], [0xAA; 32]);
+        assert_eq!(encoded[32..64], [0xBB; 32]);
+        assert_eq!(encoded[64..72], [0x40, 0x42, 0x0F, 0, 0, 0, 0, 0]);
+        assert_eq!(encoded[72..], [0x00, 0x5E, 0xD0, 0xB2, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_fee_at_normal_amounts() {
+        assert_eq!(
+            calculate_fee(10_000_000, FeeSchedule::NATIVE),
+            Ok(2_550_000)
+        );
+        assert_eq!(
+            calculate_fee(1_000_000_000, FeeSchedule::NATIVE),
+            Ok(7_500_000)
+        );
+        assert_eq!(
+            calculate_fee(1_000_000_000, FeeSchedule::VARIABLE_ONLY),
+            Ok(5_000_000)
+        );
+        // Rounds down
+        assert_eq!(calculate_fee(1_999, FeeSchedule::VARIABLE_ONLY), Ok(9));
+    }
+
+    #[test]
+    fn test_fee_does_not_overflow_near_u64_max() {
+        // `amount * 5` alone would overflow a u64 here
+        let amount = u64::MAX - 1;
+        let variable = (u128::from(amount) * 5 / 1_000) as u64;
+        assert_eq!(
+            calculate_fee(amount, FeeSchedule::VARIABLE_ONLY),
+            Ok(variable)
+        );
+        assert_eq!(
+            calculate_fee(u64::MAX, FeeSchedule::NATIVE),
+            Ok(u64::MAX / 200 + fees::FIXED_FEE_LAMPORTS)
+        );
+
+        // Only a schedule charging more than the amount can exceed u64
+        let whole = FeeSchedule {
+            fixed: 1,
+            variable_per_mille: 1_000,
+        };
+        assert_eq!(calculate_fee(u64::MAX - 1, whole), Ok(u64::MAX));
+        assert_eq!(calculate_fee(u64::MAX, whole), Err(FeeError::Overflow));
+    }
+
+    #[test]
+    fn test_parse_hex32_accepts_optional_prefix() {
+        let mut expected = [0u8; 32];
+        expected[0] = 0xab;
+        expected[31] = 0x01;
+        let bare = "ab00000000000000000000000000000000000000000000000000000000000001";
+        let prefixed = "0xab00000000000000000000000000000000000000000000000000000000000001";
+
+        assert_eq!(parse_hex32(bare), Ok(expected));
+        assert_eq!(parse_hex32(prefixed), Ok(expected));
+        // Uppercase digits decode the same
+        let upper = "AB00000000000000000000000000000000000000000000000000000000000001";
+        assert_eq!(parse_hex32(upper), Ok(expected));
+    }
+
+    #[test]
+    fn test_parse_hex32_rejects_malformed_input() {
+        let short = "00000000000000000000000000000000000000000000000000000000000000";
+        assert_eq!(parse_hex32(short), Err(HexError::InvalidLength(62)));
+        let long = "000000000000000000000000000000000000000000000000000000000000000000";
+        assert_eq!(parse_hex32(long), Err(HexError::InvalidLength(66)));
+        assert_eq!(parse_hex32(""), Err(HexError::InvalidLength(0)));
+        assert_eq!(parse_hex32("0x"), Err(HexError::InvalidLength(0)));
+
+        let odd = "000000000000000000000000000000000000000000000000000000000000000";
+        assert_eq!(parse_hex32(odd), Err(HexError::OddLength(63)));
+
+        let non_hex = "0x00000000000000000000000000000000000000000000000000000000000000zz";
+        assert_eq!(
+            parse_hex32(non_hex),
+            Err(HexError::InvalidChar { c: 'z', index: 62 })
+        );
+        // Only one prefix is stripped
+        let double_prefix = "0x0x000000000000000000000000000000000000000000000000000000000000";
+        assert_eq!(
+            parse_hex32(double_prefix),
+            Err(HexError::InvalidChar { c: 'x', index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_roots_ring_age_counts_back_from_head() {
+        let mut data = [0u8; roots_ring::SIZE];
+        // Push roots 1..=66 the way the program does: advance head, then write
+        let mut head = 0usize;
+        for n in 1..=66u8 {
+            head = (head + 1) % roots_ring::MAX_ROOTS;
+            let offset = roots_ring::root_offset(head);
+            data[offset..offset + 32].copy_from_slice(&[n; 32]);
+        }
+        data[roots_ring::HEAD] = head as u8;
+
+        let ring = decode_roots_ring(&data).unwrap();
+        assert_eq!(ring.root_age(&[66; 32]), Some(0));
+        assert_eq!(ring.root_age(&[65; 32]), Some(1));
+        assert_eq!(ring.root_age(&[3; 32]), Some(63));
+        // Overwritten by the wrap
+        assert!(!ring.contains_root(&[2; 32]));
+
+        data[roots_ring::HEAD] = roots_ring::MAX_ROOTS as u8;
+        assert!(decode_roots_ring(&data).is_none());
+    }
+}
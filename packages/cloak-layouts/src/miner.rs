@@ -0,0 +1,49 @@
+//! Miner: PDA per authority (scramble-registry)
+//!
+//! [authority: 32][total_mined: 8][total_consumed: 8][registered_at_slot: 8]
+//! [consume_rate_bps: 8]
+//!
+//! Miners registered before `consume_rate_bps` existed are `LEGACY_SIZE`
+//! bytes and decode with a zero consume rate.
+
+use crate::{read_array, read_u64};
+
+pub const AUTHORITY: usize = 0;
+pub const TOTAL_MINED: usize = 32;
+pub const TOTAL_CONSUMED: usize = 40;
+pub const REGISTERED_AT_SLOT: usize = 48;
+pub const CONSUME_RATE_BPS: usize = 56;
+/// Total: 64 bytes
+pub const SIZE: usize = 64;
+/// Layout of miners registered before the consume rate
+pub const LEGACY_SIZE: usize = 56;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinerView {
+    pub authority: [u8; 32],
+    pub total_mined: u64,
+    pub total_consumed: u64,
+    pub registered_at_slot: u64,
+    pub consume_rate_bps: u64,
+}
+
+pub fn decode_miner(data: &[u8]) -> Option<MinerView> {
+    let consume_rate_bps = match data.len() {
+        SIZE => read_u64(data, CONSUME_RATE_BPS),
+        LEGACY_SIZE => 0,
+        _ => return None,
+    };
+
+    Some(MinerView {
+        authority: read_array(data, AUTHORITY),
+        total_mined: read_u64(data, TOTAL_MINED),
+        total_consumed: read_u64(data, TOTAL_CONSUMED),
+        registered_at_slot: read_u64(data, REGISTERED_AT_SLOT),
+        consume_rate_bps,
+    })
+}
+
+/// Whether `len` is the size of a miner account under any layout
+pub fn is_miner_size(len: usize) -> bool {
+    len == SIZE || len == LEGACY_SIZE
+}
@@ -0,0 +1,67 @@
+//! Withdraw output: the preimage hashed into a proof's `outputs_hash`
+//!
+//! [address: 32][amount: 8]
+//!
+//! `outputs_hash` is BLAKE3 over the encoded outputs concatenated in
+//! [`hash_order`]. The guest and the relay both serialize outputs through
+//! `encode_output`, so the relay recomputes exactly the hash a proof commits to.
+//!
+//! A swap withdraw commits to its swap parameters instead, as one
+//! `encode_swap_outputs` preimage (see [`swap`]) shared by the guest and the
+//! program's `withdraw_swap`.
+
+/// Most outputs one shield-pool withdraw can pay
+pub const MAX_OUTPUTS: usize = 5;
+
+pub const ADDRESS: usize = 0;
+pub const AMOUNT: usize = 32;
+/// Total: 40 bytes
+pub const SIZE: usize = 40;
+
+pub fn encode_output(address: &[u8; 32], amount: u64) -> [u8; SIZE] {
+    let mut out = [0u8; SIZE];
+    out[ADDRESS..AMOUNT].copy_from_slice(address);
+    out[AMOUNT..SIZE].copy_from_slice(&amount.to_le_bytes());
+    out
+}
+
+/// Put outputs in the order they are hashed: sorted by `key`, their
+/// (address, amount), with `sorted-outputs` so every permutation of the same
+/// outputs hashes identically; left as given otherwise. Outputs with equal
+/// keys encode to the same bytes, so the sort needn't be stable.
+#[cfg(feature = "sorted-outputs")]
+pub fn hash_order<T>(outputs: &mut [T], key: impl FnMut(&T) -> ([u8; 32], u64)) {
+    outputs.sort_unstable_by_key(key);
+}
+
+#[cfg(not(feature = "sorted-outputs"))]
+pub fn hash_order<T>(_outputs: &mut [T], _key: impl FnMut(&T) -> ([u8; 32], u64)) {}
+
+/// Swap withdraw preimage
+///
+/// [output_mint: 32][recipient_ata: 32][min_output_amount: 8][public_amount: 8]
+///
+/// Amounts are little-endian. `outputs_hash` is BLAKE3 over these 80 bytes.
+pub mod swap {
+    pub const OUTPUT_MINT: usize = 0;
+    pub const RECIPIENT_ATA: usize = 32;
+    pub const MIN_OUTPUT_AMOUNT: usize = 64;
+    pub const PUBLIC_AMOUNT: usize = 72;
+    /// Total: 80 bytes
+    pub const SIZE: usize = 80;
+}
+
+pub fn encode_swap_outputs(
+    output_mint: &[u8; 32],
+    recipient_ata: &[u8; 32],
+    min_output_amount: u64,
+    public_amount: u64,
+) -> [u8; swap::SIZE] {
+    let mut out = [0u8; swap::SIZE];
+    out[swap::OUTPUT_MINT..swap::RECIPIENT_ATA].copy_from_slice(output_mint);
+    out[swap::RECIPIENT_ATA..swap::MIN_OUTPUT_AMOUNT].copy_from_slice(recipient_ata);
+    out[swap::MIN_OUTPUT_AMOUNT..swap::PUBLIC_AMOUNT]
+        .copy_from_slice(&min_output_amount.to_le_bytes());
+    out[swap::PUBLIC_AMOUNT..swap::SIZE].copy_from_slice(&public_amount.to_le_bytes());
+    out
+}
@@ -0,0 +1,82 @@
+//! Pool: per-mint pool PDA, seeds = [b"pool", mint] (shield-pool)
+//!
+//! [mint: 32][max_withdraw_bps: 2][authority: 32][pending_authority: 32][flags: 1]
+//!
+//! Pools created before `max_withdraw_bps` existed are `LEGACY_SIZE` bytes
+//! (mint only) and decode with the cap disabled and no stored authority.
+//! Pools created before the authority fields existed are `PRE_AUTHORITY_SIZE`
+//! bytes and decode with their cap but no stored authority. Pools created
+//! before `flags` existed are `PRE_FLAGS_SIZE` bytes and decode
+//! with no flags set. An all-zero `authority` means the program's built-in
+//! admin key.
+
+use crate::{read_array, read_u16};
+
+pub const MINT: usize = 0;
+pub const MAX_WITHDRAW_BPS: usize = 32;
+pub const AUTHORITY: usize = 34;
+pub const PENDING_AUTHORITY: usize = 66;
+pub const FLAGS: usize = 98;
+/// Total: 99 bytes
+pub const SIZE: usize = 99;
+/// Layout of pools created before `flags`: cap and authority, no flags
+pub const PRE_FLAGS_SIZE: usize = 98;
+/// Layout of pools created before the authority fields: mint and cap
+pub const PRE_AUTHORITY_SIZE: usize = 34;
+/// Mint-only layout of pools created before the withdraw cap
+pub const LEGACY_SIZE: usize = 32;
+
+pub const SEED_PREFIX: &[u8] = b"pool";
+
+/// Deposits fail once the commitment queue is full instead of overwriting its
+/// oldest commitments
+pub const FLAG_NO_OVERWRITE: u8 = 1 << 0;
+/// Every flag the program understands
+pub const KNOWN_FLAGS: u8 = FLAG_NO_OVERWRITE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolView {
+    /// All zeros for native SOL
+    pub mint: [u8; 32],
+    /// Largest single withdraw as basis points of the pool balance (0 = uncapped)
+    pub max_withdraw_bps: u16,
+    /// Admin allowed to push roots and rotate authority (all zeros = built-in admin)
+    pub authority: [u8; 32],
+    /// Proposed next authority awaiting acceptance (all zeros = none)
+    pub pending_authority: [u8; 32],
+    /// `FLAG_*` bits
+    pub flags: u8,
+}
+
+impl PoolView {
+    pub fn no_overwrite(&self) -> bool {
+        self.flags & FLAG_NO_OVERWRITE != 0
+    }
+}
+
+pub fn decode_pool(data: &[u8]) -> Option<PoolView> {
+    match data.len() {
+        SIZE | PRE_FLAGS_SIZE => Some(PoolView {
+            mint: read_array(data, MINT),
+            max_withdraw_bps: read_u16(data, MAX_WITHDRAW_BPS),
+            authority: read_array(data, AUTHORITY),
+            pending_authority: read_array(data, PENDING_AUTHORITY),
+            flags: data.get(FLAGS).copied().unwrap_or(0),
+        }),
+        PRE_AUTHORITY_SIZE => Some(PoolView {
+            mint: read_array(data, MINT),
+            max_withdraw_bps: read_u16(data, MAX_WITHDRAW_BPS),
+            authority: [0u8; 32],
+            pending_authority: [0u8; 32],
+            flags: 0,
+        }),
+        LEGACY_SIZE => Some(PoolView {
+            mint: read_array(data, MINT),
+            max_withdraw_bps: 0,
+            authority: [0u8; 32],
+            pending_authority: [0u8; 32],
+            flags: 0,
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,62 @@
+//! ScrambleRegistry: singleton PDA with PoW parameters (scramble-registry)
+//!
+//! [admin: 32][current_difficulty: 32][last_retarget_slot: 8][solutions_observed: 8]
+//! [target_interval_slots: 8][fee_share_bps: 2][reveal_window: 8][claim_window: 8]
+//! [max_k: 2][min_difficulty: 32][max_difficulty: 32][total_claims: 8][active_claims: 8]
+
+use crate::{read_array, read_u16, read_u64};
+
+pub const ADMIN: usize = 0;
+pub const CURRENT_DIFFICULTY: usize = 32;
+pub const LAST_RETARGET_SLOT: usize = 64;
+pub const SOLUTIONS_OBSERVED: usize = 72;
+pub const TARGET_INTERVAL_SLOTS: usize = 80;
+pub const FEE_SHARE_BPS: usize = 88;
+pub const REVEAL_WINDOW: usize = 90;
+pub const CLAIM_WINDOW: usize = 98;
+pub const MAX_K: usize = 106;
+pub const MIN_DIFFICULTY: usize = 108;
+pub const MAX_DIFFICULTY: usize = 140;
+pub const TOTAL_CLAIMS: usize = 172;
+pub const ACTIVE_CLAIMS: usize = 180;
+/// Total: 188 bytes
+pub const SIZE: usize = 188;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistryView {
+    pub admin: [u8; 32],
+    pub current_difficulty: [u8; 32],
+    pub last_retarget_slot: u64,
+    pub solutions_observed: u64,
+    pub target_interval_slots: u64,
+    pub fee_share_bps: u16,
+    pub reveal_window: u64,
+    pub claim_window: u64,
+    pub max_k: u16,
+    pub min_difficulty: [u8; 32],
+    pub max_difficulty: [u8; 32],
+    pub total_claims: u64,
+    pub active_claims: u64,
+}
+
+pub fn decode_registry(data: &[u8]) -> Option<RegistryView> {
+    if data.len() != SIZE {
+        return None;
+    }
+
+    Some(RegistryView {
+        admin: read_array(data, ADMIN),
+        current_difficulty: read_array(data, CURRENT_DIFFICULTY),
+        last_retarget_slot: read_u64(data, LAST_RETARGET_SLOT),
+        solutions_observed: read_u64(data, SOLUTIONS_OBSERVED),
+        target_interval_slots: read_u64(data, TARGET_INTERVAL_SLOTS),
+        fee_share_bps: read_u16(data, FEE_SHARE_BPS),
+        reveal_window: read_u64(data, REVEAL_WINDOW),
+        claim_window: read_u64(data, CLAIM_WINDOW),
+        max_k: read_u16(data, MAX_K),
+        min_difficulty: read_array(data, MIN_DIFFICULTY),
+        max_difficulty: read_array(data, MAX_DIFFICULTY),
+        total_claims: read_u64(data, TOTAL_CLAIMS),
+        active_claims: read_u64(data, ACTIVE_CLAIMS),
+    })
+}
@@ -0,0 +1,49 @@
+//! RootsRing: ring buffer of recent Merkle roots, seeds = [b"roots_ring", mint] (shield-pool)
+//!
+//! [head: u8][pad: 7][roots: MAX_ROOTS * 32]
+
+pub const HEAD: usize = 0;
+pub const ROOTS: usize = 8;
+pub const MAX_ROOTS: usize = 64;
+/// Total: 8 + 2048 = 2056 bytes
+pub const SIZE: usize = ROOTS + MAX_ROOTS * 32;
+
+/// Byte offset of ring slot `slot`
+#[inline(always)]
+pub const fn root_offset(slot: usize) -> usize {
+    ROOTS + slot * 32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootsRingView<'a> {
+    /// Slot holding the most recently pushed root
+    pub head: u8,
+    data: &'a [u8],
+}
+
+impl RootsRingView<'_> {
+    /// Whether `root` is anywhere in the ring
+    pub fn contains_root(&self, root: &[u8; 32]) -> bool {
+        self.root_age(root).is_some()
+    }
+
+    /// Number of roots pushed after `root` (0 = newest), or `None` if it is not in the ring
+    pub fn root_age(&self, root: &[u8; 32]) -> Option<usize> {
+        (0..MAX_ROOTS).find(|&age| {
+            let slot = (self.head as usize + MAX_ROOTS - age) % MAX_ROOTS;
+            let offset = root_offset(slot);
+            &self.data[offset..offset + 32] == root
+        })
+    }
+}
+
+pub fn decode_roots_ring(data: &[u8]) -> Option<RootsRingView<'_>> {
+    if data.len() != SIZE || data[HEAD] as usize >= MAX_ROOTS {
+        return None;
+    }
+
+    Some(RootsRingView {
+        head: data[HEAD],
+        data,
+    })
+}
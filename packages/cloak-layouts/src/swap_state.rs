@@ -0,0 +1,48 @@
+//! SwapState: pending swap PDA, seeds = [b"swap_state", nullifier] (shield-pool)
+//!
+//! [nullifier: 32][sol_amount: 8][output_mint: 32][recipient_ata: 32]
+//! [min_output_amount: 8][created_slot: 8][timeout_slot: 8][bump: 1]
+
+use crate::{read_array, read_u64};
+
+pub const NULLIFIER: usize = 0;
+pub const SOL_AMOUNT: usize = 32;
+pub const OUTPUT_MINT: usize = 40;
+pub const RECIPIENT_ATA: usize = 72;
+pub const MIN_OUTPUT_AMOUNT: usize = 104;
+pub const CREATED_SLOT: usize = 112;
+pub const TIMEOUT_SLOT: usize = 120;
+pub const BUMP: usize = 128;
+/// Total: 129 bytes
+pub const SIZE: usize = 129;
+
+pub const SEED_PREFIX: &[u8] = b"swap_state";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapStateView {
+    pub nullifier: [u8; 32],
+    pub sol_amount: u64,
+    pub output_mint: [u8; 32],
+    pub recipient_ata: [u8; 32],
+    pub min_output_amount: u64,
+    pub created_slot: u64,
+    pub timeout_slot: u64,
+    pub bump: u8,
+}
+
+pub fn decode_swap_state(data: &[u8]) -> Option<SwapStateView> {
+    if data.len() != SIZE {
+        return None;
+    }
+
+    Some(SwapStateView {
+        nullifier: read_array(data, NULLIFIER),
+        sol_amount: read_u64(data, SOL_AMOUNT),
+        output_mint: read_array(data, OUTPUT_MINT),
+        recipient_ata: read_array(data, RECIPIENT_ATA),
+        min_output_amount: read_u64(data, MIN_OUTPUT_AMOUNT),
+        created_slot: read_u64(data, CREATED_SLOT),
+        timeout_slot: read_u64(data, TIMEOUT_SLOT),
+        bump: data[BUMP],
+    })
+}
@@ -0,0 +1,130 @@
+//! Relay backlog parsing and claim target selection
+//!
+//! The relay's `/backlog` endpoint reports queued withdraws. With
+//! `include_hashes=true` it also returns each job's batch hash, letting the
+//! miner mine claims bound to those specific jobs instead of wildcards.
+
+use serde::Deserialize;
+
+/// Batch hash of a wildcard claim, consumable by any withdraw
+pub const WILDCARD_BATCH_HASH: [u8; 32] = [0u8; 32];
+
+/// Response of the relay's `/backlog` endpoint
+#[derive(Debug, Deserialize)]
+pub struct BacklogResponse {
+    pub pending_count: usize,
+    pub queued_jobs: Vec<String>,
+    /// Only present when requested with `include_hashes=true`
+    #[serde(default)]
+    pub jobs: Option<Vec<BacklogJob>>,
+}
+
+/// A queued job as reported by an enriched backlog
+#[derive(Debug, Clone, Deserialize)]
+pub struct BacklogJob {
+    pub job_id: String,
+    /// Hex-encoded batch hash the relay looks up claims for
+    pub batch_hash: String,
+    pub age_secs: u64,
+}
+
+/// Choose the batch hashes to mine `count` claims for.
+///
+/// Pending jobs are targeted oldest first, skipping jobs that already have a
+/// usable claim (per `has_claim`) and jobs with malformed hashes. Any
+/// remaining slots fall back to wildcard claims.
+pub fn select_targets<F>(jobs: &[BacklogJob], count: usize, has_claim: F) -> Vec<[u8; 32]>
+where
+    F: Fn(&[u8; 32]) -> bool,
+{
+    let mut pending: Vec<&BacklogJob> = jobs.iter().collect();
+    pending.sort_by(|a, b| b.age_secs.cmp(&a.age_secs));
+
+    let mut targets: Vec<[u8; 32]> = Vec::with_capacity(count);
+    for job in pending {
+        if targets.len() == count {
+            break;
+        }
+        let Some(batch_hash) = parse_batch_hash(&job.batch_hash) else {
+            tracing::warn!("Ignoring job {} with malformed batch hash", job.job_id);
+            continue;
+        };
+        if batch_hash != WILDCARD_BATCH_HASH
+            && !has_claim(&batch_hash)
+            && !targets.contains(&batch_hash)
+        {
+            targets.push(batch_hash);
+        }
+    }
+
+    targets.resize(count, WILDCARD_BATCH_HASH);
+    targets
+}
+
+fn parse_batch_hash(hex_str: &str) -> Option<[u8; 32]> {
+    if hex_str.len() != 64 || !hex_str.is_ascii() {
+        return None;
+    }
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex_str[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BACKLOG: &str = r#"{
+        "pending_count": 3,
+        "queued_jobs": ["job-a", "job-b", "job-c"],
+        "jobs": [
+            {"job_id": "job-a", "batch_hash": "1111111111111111111111111111111111111111111111111111111111111111", "age_secs": 5},
+            {"job_id": "job-b", "batch_hash": "2222222222222222222222222222222222222222222222222222222222222222", "age_secs": 40},
+            {"job_id": "job-c", "batch_hash": "not-hex", "age_secs": 90}
+        ]
+    }"#;
+
+    #[test]
+    fn test_select_targets_oldest_first_then_wildcard() {
+        let backlog: BacklogResponse = serde_json::from_str(BACKLOG).unwrap();
+        let jobs = backlog.jobs.unwrap();
+
+        let targets = select_targets(&jobs, 4, |_| false);
+
+        assert_eq!(
+            targets,
+            vec![
+                [0x22; 32],
+                [0x11; 32],
+                WILDCARD_BATCH_HASH,
+                WILDCARD_BATCH_HASH
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_targets_skips_claimed_and_caps_count() {
+        let backlog: BacklogResponse = serde_json::from_str(BACKLOG).unwrap();
+        let jobs = backlog.jobs.unwrap();
+
+        assert_eq!(
+            select_targets(&jobs, 1, |hash| *hash == [0x22; 32]),
+            vec![[0x11; 32]]
+        );
+        assert_eq!(select_targets(&jobs, 1, |_| false), vec![[0x22; 32]]);
+    }
+
+    #[test]
+    fn test_select_targets_without_demand_is_wildcard() {
+        let backlog: BacklogResponse =
+            serde_json::from_str(r#"{"pending_count": 0, "queued_jobs": []}"#).unwrap();
+        assert!(backlog.jobs.is_none());
+
+        assert_eq!(
+            select_targets(&backlog.jobs.unwrap_or_default(), 2, |_| false),
+            vec![WILDCARD_BATCH_HASH; 2]
+        );
+    }
+}
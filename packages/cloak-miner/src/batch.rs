@@ -2,6 +2,9 @@
 //!
 //! Computes batch_hash = BLAKE3(job_ids) for PoW claims.
 //! Each claim covers k withdrawals identified by their job IDs.
+//!
+//! Stays BLAKE3 whatever hasher the notes use: the relay looks claims up by
+//! its own BLAKE3 `claim_manager::compute_batch_hash`.
 
 use blake3::Hasher;
 
@@ -77,6 +80,15 @@ mod tests {
         assert_eq!(hash, hash2);
     }
 
+    #[test]
+    fn test_batch_hash_matches_relay() {
+        // The relay hashes the job ID with plain BLAKE3
+        let hash = compute_single_job_hash("job-001");
+
+        assert_eq!(hash, *blake3::hash(b"job-001").as_bytes());
+        assert_eq!(hash, compute_single_job_hash("job-001"));
+    }
+
     #[test]
     fn test_batch_hash_order_matters() {
         let batch1 = vec!["job-A".to_string(), "job-B".to_string()];
@@ -13,6 +13,9 @@ const DOMAIN: &[u8] = b"CLOAK:SCRAMBLE:v1";
 /// Total preimage size: 17 + 8 + 32 + 32 + 32 + 16 = 137 bytes
 const PREIMAGE_SIZE: usize = 137;
 
+/// Synthetic target for benchmarking (every hash qualifies)
+const BENCH_DIFFICULTY: [u8; 32] = [0xFF; 32];
+
 /// Mining solution containing nonce and resulting hash
 #[derive(Debug, Clone)]
 pub struct MiningSolution {
@@ -236,6 +239,61 @@ impl MiningEngine {
     }
 }
 
+/// Hash rate measured by [`benchmark`]
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+    /// Hashes per second achieved by each thread
+    pub per_thread_hash_rate: Vec<f64>,
+    /// Total hashes computed across all threads
+    pub total_hashes: u64,
+}
+
+impl BenchmarkResult {
+    /// Combined hashes per second across all threads
+    pub fn aggregate_hash_rate(&self) -> f64 {
+        self.per_thread_hash_rate.iter().sum()
+    }
+}
+
+/// Run the mining hash loop on `threads` threads for `duration`
+///
+/// Each thread hashes and checks preimages exactly as [`MiningEngine::mine`]
+/// does, against a synthetic target and without touching the network.
+pub fn benchmark(duration: std::time::Duration, threads: usize) -> BenchmarkResult {
+    let counts: Vec<(u64, f64)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads.max(1))
+            .map(|thread| {
+                scope.spawn(move || {
+                    let engine = MiningEngine::new(
+                        BENCH_DIFFICULTY,
+                        thread as u64,
+                        [0u8; 32],
+                        Pubkey::new_from_array([thread as u8; 32]),
+                        [0u8; 32],
+                    );
+                    let start_time = std::time::Instant::now();
+                    let mut hashes = 0u64;
+                    while start_time.elapsed() < duration {
+                        let hash = engine.hash_preimage(hashes as u128);
+                        std::hint::black_box(engine.check_difficulty(&hash));
+                        hashes += 1;
+                    }
+                    (hashes, hashes as f64 / start_time.elapsed().as_secs_f64())
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("benchmark thread panicked"))
+            .collect()
+    });
+
+    BenchmarkResult {
+        per_thread_hash_rate: counts.iter().map(|(_, rate)| *rate).collect(),
+        total_hashes: counts.iter().map(|(hashes, _)| hashes).sum(),
+    }
+}
+
 /// Compare two 32-byte arrays as 256-bit little-endian unsigned integers
 ///
 /// Returns true if a < b
@@ -408,4 +466,14 @@ mod tests {
 
         assert_ne!(hash1, hash2);
     }
+
+    #[test]
+    fn test_benchmark_reports_positive_hash_rate() {
+        let result = benchmark(std::time::Duration::from_millis(50), 2);
+
+        assert_eq!(result.per_thread_hash_rate.len(), 2);
+        assert!(result.per_thread_hash_rate.iter().all(|rate| *rate > 0.0));
+        assert!(result.total_hashes > 0);
+        assert!(result.aggregate_hash_rate() > 0.0);
+    }
 }
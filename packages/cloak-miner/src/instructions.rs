@@ -159,6 +159,8 @@ pub fn build_reveal_claim_ix(
 /// - discriminator: 4 (u8)
 /// - expected_miner_authority: [u8; 32]
 /// - expected_batch_hash: [u8; 32]
+/// - nullifier: [u8; 32] (consuming withdraw; each nullifier consumes a claim once)
+#[allow(clippy::too_many_arguments)]
 pub fn build_consume_claim_ix(
     program_id: &Pubkey,
     claim_pda: &Pubkey,
@@ -167,6 +169,7 @@ pub fn build_consume_claim_ix(
     shield_pool_program: &Pubkey,
     miner_authority: &Pubkey,
     batch_hash: &[u8; 32],
+    nullifier: &[u8; 32],
 ) -> Instruction {
     let mut data = Vec::new();
 
@@ -176,6 +179,7 @@ pub fn build_consume_claim_ix(
     // Arguments
     data.extend_from_slice(miner_authority.as_ref());
     data.extend_from_slice(batch_hash);
+    data.extend_from_slice(nullifier);
 
     Instruction {
         program_id: *program_id,
@@ -387,6 +391,7 @@ mod tests {
         let shield_pool = Pubkey::new_unique();
         let authority = Pubkey::new_unique();
         let batch_hash = [0x77; 32];
+        let nullifier = [0x88; 32];
 
         let ix = build_consume_claim_ix(
             &program_id,
@@ -396,6 +401,7 @@ mod tests {
             &shield_pool,
             &authority,
             &batch_hash,
+            &nullifier,
         );
 
         // Check program ID
@@ -404,9 +410,10 @@ mod tests {
         // Check account count
         assert_eq!(ix.accounts.len(), 5);
 
-        // Check data: 1 + 32 + 32 = 65 bytes
-        assert_eq!(ix.data.len(), 65);
+        // Check data: 1 + 32 + 32 + 32 = 97 bytes
+        assert_eq!(ix.data.len(), 97);
         assert_eq!(ix.data[0], 4); // Discriminator
+        assert_eq!(&ix.data[65..97], &nullifier);
     }
 
     #[test]
@@ -16,12 +16,14 @@
 //!   # Other commands
 //!   cloak-miner --network devnet --keypair <PATH> register
 //!   cloak-miner --network devnet --keypair <PATH> status
+//!   cloak-miner bench --duration 10
 
 use std::{
+    collections::VecDeque,
     path::PathBuf,
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     time::{Duration, Instant},
 };
@@ -29,11 +31,12 @@ use std::{
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use cloak_miner::{
-    build_register_miner_ix,
+    backlog::{select_targets, BacklogJob, BacklogResponse, WILDCARD_BATCH_HASH},
+    benchmark, build_register_miner_ix,
     constants::{Network, API_URL},
-    derive_miner_pda, derive_registry_pda, fetch_registry, ClaimManager,
+    derive_miner_pda, derive_registry_pda, fetch_registry, send_and_confirm_with_timeout,
+    ClaimManager,
 };
-use serde::Deserialize;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
@@ -43,13 +46,8 @@ use solana_sdk::{
 };
 use tracing::{error, info, warn};
 
-#[derive(Deserialize)]
-struct BacklogResponse {
-    #[allow(dead_code)]
-    pending_count: usize,
-    #[allow(dead_code)]
-    queued_jobs: Vec<String>,
-}
+/// How long the startup relay health check waits for a response
+const RELAY_HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Parser)]
 #[command(name = "cloak-miner")]
@@ -66,9 +64,17 @@ struct Cli {
     #[arg(long, env = "SOLANA_RPC_URL")]
     rpc_url: Option<String>,
 
-    /// Path to miner keypair file
+    /// Path to miner keypair file (required for all commands except bench)
     #[arg(long, env = "MINER_KEYPAIR_PATH")]
-    keypair: PathBuf,
+    keypair: Option<PathBuf>,
+
+    /// Relay API URL used for backlog and health checks
+    #[arg(long, env = "RELAY_URL", default_value = API_URL)]
+    relay_url: String,
+
+    /// Seconds to wait for a submitted transaction to confirm
+    #[arg(long, env = "MINER_CONFIRM_TIMEOUT", default_value = "60")]
+    confirm_timeout: u64,
 }
 
 #[derive(Subcommand)]
@@ -89,10 +95,29 @@ enum Commands {
         /// Target number of active claims to maintain
         #[arg(long, default_value = "5")]
         target_claims: usize,
+
+        /// Mine claims bound to pending jobs' batch hashes instead of wildcards
+        #[arg(long)]
+        targeted: bool,
+
+        /// Number of recent mining rounds the recent hash rate averages over
+        #[arg(long, default_value = "10")]
+        hash_rate_window: usize,
     },
 
     /// Check miner status and active claims
     Status,
+
+    /// Measure local hash rate without touching the network
+    Bench {
+        /// Benchmark duration in seconds
+        #[arg(long, default_value = "10")]
+        duration: u64,
+
+        /// Number of hashing threads (defaults to available cores)
+        #[arg(long)]
+        threads: Option<usize>,
+    },
 }
 
 #[tokio::main]
@@ -107,54 +132,124 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
-    // Parse network
-    let network = Network::from_str(&cli.network).map_err(|e| anyhow::anyhow!("{}", e))?;
-
-    // Get RPC URL (use explicit or network default)
-    let rpc_url = cli
-        .rpc_url
-        .unwrap_or_else(|| network.default_rpc_url().to_string());
-
-    // Get program ID for network
-    let program_id = network
-        .scramble_program_id()
-        .map_err(|e| anyhow::anyhow!("{}", e))?;
-
-    // Load keypair
-    let keypair = read_keypair_file(&cli.keypair)
-        .map_err(|e| anyhow::anyhow!("Failed to load keypair from {:?}: {}", cli.keypair, e))?;
-
-    info!("Network: {:?}", network);
-    info!("Miner pubkey: {}", keypair.pubkey());
-    info!("RPC URL: {}", rpc_url);
-    info!("Program ID: {}", program_id);
-
     match cli.command {
-        Commands::Register => register_miner(&rpc_url, &program_id, keypair).await,
+        Commands::Bench { duration, threads } => {
+            run_bench(duration, threads);
+            Ok(())
+        }
+        Commands::Register => {
+            let ctx = MinerContext::load(&cli)?;
+            register_miner(
+                &ctx.rpc_url,
+                &ctx.program_id,
+                ctx.keypair,
+                ctx.confirm_timeout,
+            )
+            .await
+        }
         Commands::Mine {
             timeout,
             interval,
             target_claims,
+            targeted,
+            hash_rate_window,
         } => {
+            let ctx = MinerContext::load(&cli)?;
             mine_continuously(
-                &rpc_url,
-                &program_id,
-                keypair,
+                &ctx.rpc_url,
+                &ctx.program_id,
+                ctx.keypair,
                 timeout,
                 interval,
                 target_claims,
+                targeted,
+                hash_rate_window,
+                ctx.confirm_timeout,
+                &cli.relay_url,
             )
             .await
         }
-        Commands::Status => check_status(&rpc_url, &program_id, keypair).await,
+        Commands::Status => {
+            let ctx = MinerContext::load(&cli)?;
+            check_status(&ctx.rpc_url, &ctx.program_id, ctx.keypair).await
+        }
     }
 }
 
+/// Network settings and keypair shared by the commands that talk to the cluster
+struct MinerContext {
+    rpc_url: String,
+    program_id: solana_sdk::pubkey::Pubkey,
+    keypair: Keypair,
+    confirm_timeout: Duration,
+}
+
+impl MinerContext {
+    fn load(cli: &Cli) -> Result<Self> {
+        // Parse network
+        let network = Network::from_str(&cli.network).map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        // Get RPC URL (use explicit or network default)
+        let rpc_url = cli
+            .rpc_url
+            .clone()
+            .unwrap_or_else(|| network.default_rpc_url().to_string());
+
+        // Get program ID for network
+        let program_id = network
+            .scramble_program_id()
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+        // Load keypair
+        let keypair_path = cli
+            .keypair
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("--keypair (or MINER_KEYPAIR_PATH) is required"))?;
+        let keypair = read_keypair_file(keypair_path).map_err(|e| {
+            anyhow::anyhow!("Failed to load keypair from {:?}: {}", keypair_path, e)
+        })?;
+
+        info!("Network: {:?}", network);
+        info!("Miner pubkey: {}", keypair.pubkey());
+        info!("RPC URL: {}", rpc_url);
+        info!("Program ID: {}", program_id);
+
+        Ok(Self {
+            rpc_url,
+            program_id,
+            keypair,
+            confirm_timeout: Duration::from_secs(cli.confirm_timeout),
+        })
+    }
+}
+
+/// Benchmark the mining hash loop and print per-thread and aggregate rates
+fn run_bench(duration_secs: u64, threads: Option<usize>) {
+    let threads = threads.unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    });
+
+    println!(
+        "Benchmarking {} thread(s) for {}s...",
+        threads, duration_secs
+    );
+    let result = benchmark(Duration::from_secs(duration_secs), threads);
+
+    for (i, rate) in result.per_thread_hash_rate.iter().enumerate() {
+        println!("  Thread {}: {:.0} H/s", i, rate);
+    }
+    println!("Total hashes: {}", result.total_hashes);
+    println!("Aggregate: {:.0} H/s", result.aggregate_hash_rate());
+}
+
 /// Register miner (one-time setup)
 async fn register_miner(
     rpc_url: &str,
     program_id: &solana_sdk::pubkey::Pubkey,
     keypair: Keypair,
+    confirm_timeout: Duration,
 ) -> Result<()> {
     info!("Registering miner...");
 
@@ -181,8 +276,7 @@ async fn register_miner(
         recent_blockhash,
     );
 
-    let signature = client
-        .send_and_confirm_transaction(&tx)
+    let signature = send_and_confirm_with_timeout(&client, &tx, confirm_timeout)
         .context("Failed to register miner")?;
 
     info!("✓ Miner registered successfully!");
@@ -201,10 +295,15 @@ struct MinerStats {
     successful_mining_rounds: AtomicU64,
     failed_mining_rounds: AtomicU64,
     start_time: Instant,
+    /// (hash attempts, mining time ms) of the last `hash_rate_window` rounds
+    recent_rounds: Mutex<VecDeque<(u64, u64)>>,
+    hash_rate_window: usize,
 }
 
 impl MinerStats {
-    fn new() -> Self {
+    /// A window of 0 is treated as 1 so the recent rate tracks the last round
+    fn new(hash_rate_window: usize) -> Self {
+        let hash_rate_window = hash_rate_window.max(1);
         Self {
             total_claims_mined: AtomicU64::new(0),
             total_mining_time: AtomicU64::new(0),
@@ -212,6 +311,8 @@ impl MinerStats {
             successful_mining_rounds: AtomicU64::new(0),
             failed_mining_rounds: AtomicU64::new(0),
             start_time: Instant::now(),
+            recent_rounds: Mutex::new(VecDeque::with_capacity(hash_rate_window)),
+            hash_rate_window,
         }
     }
 
@@ -223,6 +324,12 @@ impl MinerStats {
             .fetch_add(hash_attempts, Ordering::Relaxed);
         self.successful_mining_rounds
             .fetch_add(1, Ordering::Relaxed);
+
+        let mut recent = self.recent_rounds.lock().unwrap();
+        if recent.len() == self.hash_rate_window {
+            recent.pop_front();
+        }
+        recent.push_back((hash_attempts, mining_time_ms));
     }
 
     fn record_failed_mining(&self) {
@@ -238,6 +345,23 @@ impl MinerStats {
         }
     }
 
+    /// Hash rate over the last `hash_rate_window` successful rounds, so
+    /// throttling or a difficulty change shows up instead of being averaged
+    /// away over the whole session
+    fn get_recent_hash_rate(&self) -> f64 {
+        let recent = self.recent_rounds.lock().unwrap();
+        let (attempts, time_ms) = recent
+            .iter()
+            .fold((0u64, 0u64), |(attempts, time_ms), &(a, t)| {
+                (attempts + a, time_ms + t)
+            });
+        if time_ms > 0 {
+            attempts as f64 / (time_ms as f64 / 1000.0)
+        } else {
+            0.0
+        }
+    }
+
     fn get_success_rate(&self) -> f64 {
         let total = self.successful_mining_rounds.load(Ordering::Relaxed)
             + self.failed_mining_rounds.load(Ordering::Relaxed);
@@ -252,12 +376,18 @@ impl MinerStats {
         let uptime = self.start_time.elapsed();
         let claims_mined = self.total_claims_mined.load(Ordering::Relaxed);
         let avg_hash_rate = self.get_average_hash_rate();
+        let recent_hash_rate = self.get_recent_hash_rate();
         let success_rate = self.get_success_rate();
 
         println!("Miner Statistics:");
         println!("  Uptime: {:.1}s", uptime.as_secs_f64());
         println!("  Claims mined: {}", claims_mined);
         println!("  Average hash rate: {:.0} H/s", avg_hash_rate);
+        println!(
+            "  Recent hash rate: {:.0} H/s (last {} rounds)",
+            recent_hash_rate,
+            self.recent_rounds.lock().unwrap().len()
+        );
         println!("  Success rate: {:.1}%", success_rate);
         println!(
             "  Claims per hour: {:.1}",
@@ -266,9 +396,57 @@ impl MinerStats {
     }
 }
 
+/// Ping the relay's health endpoint, warning (without failing) if unreachable
+///
+/// Returns whether the relay responded successfully.
+async fn check_relay_health(relay_url: &str) -> bool {
+    let url = format!("{}/health", relay_url);
+    let client = match reqwest::Client::builder()
+        .timeout(RELAY_HEALTH_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Could not build HTTP client for relay health check: {}", e);
+            return false;
+        }
+    };
+
+    match client.get(&url).send().await {
+        Ok(response) if response.status().is_success() => {
+            info!("✓ Relay reachable at {}", relay_url);
+            true
+        }
+        Ok(response) => {
+            warn!(
+                "⚠️  Relay health check at {} returned {}; mining will assume demand",
+                url,
+                response.status()
+            );
+            false
+        }
+        Err(e) => {
+            warn!(
+                "⚠️  Relay unreachable at {}: {}; mining will assume demand",
+                url, e
+            );
+            false
+        }
+    }
+}
+
 /// Check if there's pending demand from the relay
-async fn check_relay_demand(relay_url: &str) -> Result<(bool, usize)> {
-    let url = format!("{}/backlog", relay_url);
+///
+/// With `include_hashes` the relay also reports each pending job's batch hash.
+async fn check_relay_demand(
+    relay_url: &str,
+    include_hashes: bool,
+) -> Result<(bool, usize, Vec<BacklogJob>)> {
+    let url = if include_hashes {
+        format!("{}/backlog?include_hashes=true", relay_url)
+    } else {
+        format!("{}/backlog", relay_url)
+    };
     match reqwest::get(&url).await {
         Ok(response) => {
             let status = response.status();
@@ -282,13 +460,17 @@ async fn check_relay_demand(relay_url: &str) -> Result<(bool, usize)> {
                     "Relay returned non-success status {}: {}",
                     status, response_text
                 );
-                return Ok((true, 0)); // Assume demand to avoid blocking mining
+                return Ok((true, 0, Vec::new())); // Assume demand to avoid blocking mining
             }
 
             match serde_json::from_str::<BacklogResponse>(&response_text) {
                 Ok(backlog) => {
                     let has_demand = backlog.pending_count > 0;
-                    Ok((has_demand, backlog.pending_count))
+                    Ok((
+                        has_demand,
+                        backlog.pending_count,
+                        backlog.jobs.unwrap_or_default(),
+                    ))
                 }
                 Err(e) => {
                     warn!("Failed to parse backlog response from {}: {}", url, e);
@@ -296,19 +478,20 @@ async fn check_relay_demand(relay_url: &str) -> Result<(bool, usize)> {
                         "Response body (first 200 chars): {}",
                         &response_text.chars().take(200).collect::<String>()
                     );
-                    Ok((true, 0)) // Assume demand to avoid blocking mining
+                    Ok((true, 0, Vec::new())) // Assume demand to avoid blocking mining
                 }
             }
         }
         Err(e) => {
             warn!("Failed to connect to relay at {}: {}", url, e);
             warn!("Continuing with mining (assuming demand)...");
-            Ok((true, 0)) // Assume demand if relay unreachable
+            Ok((true, 0, Vec::new())) // Assume demand if relay unreachable
         }
     }
 }
 
 /// Mine claims continuously
+#[allow(clippy::too_many_arguments)]
 async fn mine_continuously(
     rpc_url: &str,
     program_id: &solana_sdk::pubkey::Pubkey,
@@ -316,12 +499,13 @@ async fn mine_continuously(
     timeout_secs: u64,
     interval_secs: u64,
     target_claims: usize,
+    targeted: bool,
+    hash_rate_window: usize,
+    confirm_timeout: Duration,
+    relay_url: &str,
 ) -> Result<()> {
     let miner_pubkey = keypair.pubkey();
 
-    // Use production API URL
-    let relay_url = API_URL;
-
     println!("Cloak Miner Starting");
     println!("Miner: {}", miner_pubkey);
     println!("Network: {}", rpc_url);
@@ -329,6 +513,12 @@ async fn mine_continuously(
     println!("Timeout: {}s per attempt", timeout_secs);
     println!("Interval: {}s between attempts", interval_secs);
     println!("Target claims: {}", target_claims);
+    if targeted {
+        println!("Targeted mining: claims bound to pending jobs, wildcard fallback");
+    }
+
+    // Surface an unreachable relay now rather than in the first mining round
+    check_relay_health(relay_url).await;
 
     // Set up signal handler for graceful shutdown
     let running = Arc::new(AtomicBool::new(true));
@@ -346,13 +536,14 @@ async fn mine_continuously(
         &program_id.to_string(),
         timeout_secs,
     )
-    .context("Failed to initialize ClaimManager")?;
+    .context("Failed to initialize ClaimManager")?
+    .with_confirm_timeout(confirm_timeout);
 
     println!("ClaimManager initialized");
     println!("Continuous mining mode enabled\n");
 
     // Initialize miner statistics
-    let stats = Arc::new(MinerStats::new());
+    let stats = Arc::new(MinerStats::new(hash_rate_window));
 
     // Fetch registry to display difficulty
     let client = RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
@@ -417,14 +608,17 @@ async fn mine_continuously(
 
         // Check for relay demand
         let min_buffer = 2; // Always keep at least 2 claims ready for incoming requests
-        let (has_demand, pending_count) =
-            match check_relay_demand(relay_url).await.unwrap_or((true, 0)) {
-                (demand, count) => {
+        let (has_demand, pending_count, pending_jobs) =
+            match check_relay_demand(relay_url, targeted)
+                .await
+                .unwrap_or((true, 0, Vec::new()))
+            {
+                (demand, count, jobs) => {
                     info!(
                         "📊 Relay check: {} pending jobs, has_demand={}",
                         count, demand
                     );
-                    (demand, count)
+                    (demand, count, jobs)
                 }
             };
 
@@ -471,18 +665,25 @@ async fn mine_continuously(
         let claims_needed = target_claims.saturating_sub(current_claims);
         println!("Mining {} new claim(s)...", claims_needed);
 
-        for i in 0..claims_needed {
+        // Wildcard claims ([0; 32]) can be used by any withdraw request; in
+        // targeted mode pending jobs' own batch hashes are mined first
+        let batch_hashes = if targeted {
+            select_targets(&pending_jobs, claims_needed, |hash| {
+                manager.has_usable_claim(hash)
+            })
+        } else {
+            vec![WILDCARD_BATCH_HASH; claims_needed]
+        };
+
+        for (i, batch_hash) in batch_hashes.into_iter().enumerate() {
             if !running.load(Ordering::SeqCst) {
                 break;
             }
 
-            // Use wildcard batch_hash ([0; 32]) for continuous mining
-            // This allows the claim to be used by any withdraw request
             // Force mining new claims by calling mine_and_reveal directly (bypassing cache)
-            let wildcard_batch_hash = [0u8; 32];
             let mining_start = Instant::now();
 
-            match manager.mine_and_reveal(wildcard_batch_hash).await {
+            match manager.mine_and_reveal(batch_hash).await {
                 Ok((claim_pda, solution)) => {
                     let mining_time = mining_start.elapsed();
                     let mining_time_ms = mining_time.as_millis() as u64;
@@ -567,3 +768,38 @@ async fn check_status(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_hash_rate_tracks_only_window() {
+        let stats = MinerStats::new(3);
+        assert_eq!(stats.get_recent_hash_rate(), 0.0);
+
+        // A fast start: 1_000_000 H/s
+        for _ in 0..5 {
+            stats.record_successful_mining(1_000, 1_000_000);
+        }
+        assert_eq!(stats.get_recent_hash_rate(), 1_000_000.0);
+
+        // Throttled to 100_000 H/s; once it fills the window the early rounds
+        // no longer count, while the lifetime average still includes them
+        for _ in 0..3 {
+            stats.record_successful_mining(2_000, 200_000);
+        }
+        assert_eq!(stats.get_recent_hash_rate(), 100_000.0);
+        assert_eq!(stats.get_average_hash_rate(), 5_600_000.0 / 11.0);
+
+        // Mixed window: (200k + 200k + 4M) attempts over (2s + 2s + 4s)
+        stats.record_successful_mining(4_000, 4_000_000);
+        assert_eq!(stats.get_recent_hash_rate(), 4_400_000.0 / 8.0);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_relay_health_warns_without_failing() {
+        // Nothing listens on port 1
+        assert!(!check_relay_health("http://127.0.0.1:1").await);
+    }
+}
@@ -18,20 +18,15 @@ use solana_sdk::{
 };
 
 use super::{
+    batch::compute_single_job_hash,
     engine::MiningEngine,
     instructions::{build_mine_and_reveal_instructions, derive_claim_pda, derive_registry_pda},
-    rpc::{fetch_recent_slot_hash, fetch_registry, get_current_slot},
+    rpc::{
+        fetch_recent_slot_hash, fetch_registry, get_current_slot, send_and_confirm_with_timeout,
+        DEFAULT_CONFIRM_TIMEOUT,
+    },
 };
 
-/// Helper to compute batch hash from job ID (k=1 for MVP)
-fn compute_batch_hash(job_id: &str) -> [u8; 32] {
-    use blake3::Hasher;
-
-    let mut hasher = Hasher::new();
-    hasher.update(job_id.as_bytes());
-    *hasher.finalize().as_bytes()
-}
-
 /// Active claim state
 #[derive(Debug, Clone)]
 pub struct ClaimState {
@@ -61,6 +56,8 @@ pub struct ClaimManager {
     program_id: Pubkey,
     /// Mining timeout
     mining_timeout: Duration,
+    /// How long to wait for a submitted transaction to confirm
+    confirm_timeout: Duration,
     /// Active claims ((batch_hash, slot) -> ClaimState)
     active_claims: HashMap<ClaimKey, ClaimState>,
 }
@@ -81,10 +78,17 @@ impl ClaimManager {
             miner_keypair,
             program_id,
             mining_timeout: Duration::from_secs(mining_timeout_seconds),
+            confirm_timeout: DEFAULT_CONFIRM_TIMEOUT,
             active_claims: HashMap::new(),
         })
     }
 
+    /// Override how long to wait for submitted transactions to confirm
+    pub fn with_confirm_timeout(mut self, confirm_timeout: Duration) -> Self {
+        self.confirm_timeout = confirm_timeout;
+        self
+    }
+
     /// Get or mine a claim for a single job
     ///
     /// Returns the claim PDA and mining solution that can be consumed.
@@ -94,7 +98,7 @@ impl ClaimManager {
         job_id: &str,
     ) -> Result<(Pubkey, super::engine::MiningSolution)> {
         // Compute batch_hash from job_id to match what the relay expects
-        let batch_hash = compute_batch_hash(job_id);
+        let batch_hash = compute_single_job_hash(job_id);
 
         tracing::debug!(
             "Mining claim for job '{}' with batch_hash: {:x?}...",
@@ -246,7 +250,8 @@ impl ClaimManager {
             self.rpc_client.get_latest_blockhash()?,
         );
 
-        let sig = self.rpc_client.send_and_confirm_transaction(&combined_tx)?;
+        let sig =
+            send_and_confirm_with_timeout(&self.rpc_client, &combined_tx, self.confirm_timeout)?;
         tracing::info!("Mine+reveal transaction confirmed: {}", sig);
 
         // 5. Get current slot and calculate expiry
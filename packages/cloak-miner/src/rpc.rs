@@ -4,10 +4,33 @@
 //! - ScrambleRegistry state (difficulty, windows, parameters)
 //! - SlotHashes sysvar (recent slot + hash)
 //! - Current slot
+//!
+//! and to send transactions with a bounded confirmation wait.
+
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::{pubkey::Pubkey, sysvar};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signature,
+    sysvar,
+    transaction::{self, Transaction},
+};
+
+/// Default time to wait for a sent transaction to confirm
+pub const DEFAULT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Delay between signature status polls
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A sent transaction was not confirmed before the timeout. It may still land.
+#[derive(Debug, thiserror::Error)]
+#[error("Transaction {signature} not confirmed within {timeout:?}")]
+pub struct ConfirmTimeout {
+    pub signature: Signature,
+    pub timeout: Duration,
+}
 
 /// ScrambleRegistry state (matches on-chain struct)
 #[derive(Debug, Clone)]
@@ -44,143 +67,31 @@ pub fn fetch_registry(client: &RpcClient, registry_pubkey: &Pubkey) -> Result<Re
 
 /// Deserialize ScrambleRegistry account data
 ///
-/// Layout matches programs/scramble-registry/src/state/registry.rs:
-/// - discriminator: [u8; 8]
-/// - admin: Pubkey (32)
-/// - current_difficulty: [u8; 32]
-/// - last_retarget_slot: u64
-/// - solutions_observed: u64
-/// - target_interval_slots: u64
-/// - fee_share_bps: u16
-/// - reveal_window: u64
-/// - claim_window: u64
-/// - max_k: u16
-/// - min_difficulty: [u8; 32]
-/// - max_difficulty: [u8; 32]
-/// - total_claims: u64
-/// - active_claims: u64
+/// Pinocchio programs don't use discriminators; the layout is shared with
+/// the program through `cloak_layouts::registry`.
 fn deserialize_registry(data: &[u8]) -> Result<RegistryState> {
-    // Pinocchio programs don't use discriminators - expect exactly 188 bytes
-    if data.len() != 188 {
-        return Err(anyhow!(
-            "Registry data has wrong size: {} bytes (expected 188)",
-            data.len()
-        ));
-    }
-
-    let mut offset = 0;
-
-    // No discriminator in Pinocchio programs
-    // offset += 0;
-
-    // Admin (32 bytes)
-    let admin = Pubkey::try_from(&data[offset..offset + 32])
-        .map_err(|e| anyhow!("Failed to parse admin pubkey: {}", e))?;
-    offset += 32;
-
-    // Current difficulty (32 bytes)
-    let current_difficulty: [u8; 32] = data[offset..offset + 32]
-        .try_into()
-        .map_err(|_| anyhow!("Failed to parse current_difficulty"))?;
-    offset += 32;
-
-    // last_retarget_slot (8 bytes LE)
-    let last_retarget_slot = u64::from_le_bytes(
-        data[offset..offset + 8]
-            .try_into()
-            .map_err(|_| anyhow!("Failed to parse last_retarget_slot"))?,
-    );
-    offset += 8;
-
-    // solutions_observed (8 bytes LE)
-    let solutions_observed = u64::from_le_bytes(
-        data[offset..offset + 8]
-            .try_into()
-            .map_err(|_| anyhow!("Failed to parse solutions_observed"))?,
-    );
-    offset += 8;
-
-    // target_interval_slots (8 bytes LE)
-    let target_interval_slots = u64::from_le_bytes(
-        data[offset..offset + 8]
-            .try_into()
-            .map_err(|_| anyhow!("Failed to parse target_interval_slots"))?,
-    );
-    offset += 8;
-
-    // fee_share_bps (2 bytes LE)
-    let fee_share_bps = u16::from_le_bytes(
-        data[offset..offset + 2]
-            .try_into()
-            .map_err(|_| anyhow!("Failed to parse fee_share_bps"))?,
-    );
-    offset += 2;
-
-    // reveal_window (8 bytes LE)
-    let reveal_window = u64::from_le_bytes(
-        data[offset..offset + 8]
-            .try_into()
-            .map_err(|_| anyhow!("Failed to parse reveal_window"))?,
-    );
-    offset += 8;
-
-    // claim_window (8 bytes LE)
-    let claim_window = u64::from_le_bytes(
-        data[offset..offset + 8]
-            .try_into()
-            .map_err(|_| anyhow!("Failed to parse claim_window"))?,
-    );
-    offset += 8;
-
-    // max_k (2 bytes LE)
-    let max_k = u16::from_le_bytes(
-        data[offset..offset + 2]
-            .try_into()
-            .map_err(|_| anyhow!("Failed to parse max_k"))?,
-    );
-    offset += 2;
-
-    // min_difficulty (32 bytes)
-    let min_difficulty: [u8; 32] = data[offset..offset + 32]
-        .try_into()
-        .map_err(|_| anyhow!("Failed to parse min_difficulty"))?;
-    offset += 32;
-
-    // max_difficulty (32 bytes)
-    let max_difficulty: [u8; 32] = data[offset..offset + 32]
-        .try_into()
-        .map_err(|_| anyhow!("Failed to parse max_difficulty"))?;
-    offset += 32;
-
-    // total_claims (8 bytes LE)
-    let total_claims = u64::from_le_bytes(
-        data[offset..offset + 8]
-            .try_into()
-            .map_err(|_| anyhow!("Failed to parse total_claims"))?,
-    );
-    offset += 8;
-
-    // active_claims (8 bytes LE)
-    let active_claims = u64::from_le_bytes(
-        data[offset..offset + 8]
-            .try_into()
-            .map_err(|_| anyhow!("Failed to parse active_claims"))?,
-    );
+    let view = cloak_layouts::decode_registry(data).ok_or_else(|| {
+        anyhow!(
+            "Registry data has wrong size: {} bytes (expected {})",
+            data.len(),
+            cloak_layouts::registry::SIZE
+        )
+    })?;
 
     Ok(RegistryState {
-        admin,
-        current_difficulty,
-        last_retarget_slot,
-        solutions_observed,
-        target_interval_slots,
-        fee_share_bps,
-        reveal_window,
-        claim_window,
-        max_k,
-        min_difficulty,
-        max_difficulty,
-        total_claims,
-        active_claims,
+        admin: Pubkey::new_from_array(view.admin),
+        current_difficulty: view.current_difficulty,
+        last_retarget_slot: view.last_retarget_slot,
+        solutions_observed: view.solutions_observed,
+        target_interval_slots: view.target_interval_slots,
+        fee_share_bps: view.fee_share_bps,
+        reveal_window: view.reveal_window,
+        claim_window: view.claim_window,
+        max_k: view.max_k,
+        min_difficulty: view.min_difficulty,
+        max_difficulty: view.max_difficulty,
+        total_claims: view.total_claims,
+        active_claims: view.active_claims,
     })
 }
 
@@ -255,18 +166,65 @@ pub fn get_current_slot(client: &RpcClient) -> Result<u64> {
         .map_err(|e| anyhow!("Failed to get current slot: {}", e))
 }
 
+/// Send `tx` once, then poll its signature status until it confirms (at the
+/// client's commitment) or `timeout` elapses.
+///
+/// Unlike `send_and_confirm_transaction` this never blocks past `timeout`;
+/// a timeout is returned as a [`ConfirmTimeout`] error.
+pub fn send_and_confirm_with_timeout(
+    client: &RpcClient,
+    tx: &Transaction,
+    timeout: Duration,
+) -> Result<Signature> {
+    let signature = client
+        .send_transaction(tx)
+        .map_err(|e| anyhow!("Failed to send transaction: {}", e))?;
+
+    poll_confirmation(signature, timeout, CONFIRM_POLL_INTERVAL, || {
+        client
+            .get_signature_status(&signature)
+            .map_err(|e| anyhow!("Failed to get signature status: {}", e))
+    })?;
+
+    Ok(signature)
+}
+
+/// Poll `status` until it reports the transaction's result or `timeout` elapses
+fn poll_confirmation<F>(
+    signature: Signature,
+    timeout: Duration,
+    interval: Duration,
+    mut status: F,
+) -> Result<()>
+where
+    F: FnMut() -> Result<Option<transaction::Result<()>>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        match status() {
+            Ok(Some(Ok(()))) => return Ok(()),
+            Ok(Some(Err(e))) => return Err(anyhow!("Transaction {} failed: {}", signature, e)),
+            Ok(None) => {}
+            // A flaky status query shouldn't abandon a transaction that may land
+            Err(e) => tracing::warn!("{}", e),
+        }
+
+        if Instant::now() >= deadline {
+            return Err(ConfirmTimeout { signature, timeout }.into());
+        }
+        std::thread::sleep(interval);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_deserialize_registry() {
-        // Build mock registry data (196 bytes minimum)
+        // Build mock registry data (188 bytes, no discriminator)
         let mut data = Vec::new();
 
-        // Discriminator (8 bytes)
-        data.extend_from_slice(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0]);
-
         // Admin pubkey (32 bytes)
         let admin = Pubkey::new_unique();
         data.extend_from_slice(admin.as_ref());
@@ -364,4 +322,48 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("No slot hashes"));
     }
+
+    #[test]
+    fn test_poll_confirmation_confirms_on_third_poll() {
+        let mut polls = 0;
+        let result = poll_confirmation(
+            Signature::default(),
+            Duration::from_secs(5),
+            Duration::ZERO,
+            || {
+                polls += 1;
+                Ok((polls == 3).then_some(Ok(())))
+            },
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(polls, 3);
+    }
+
+    #[test]
+    fn test_poll_confirmation_times_out() {
+        let result = poll_confirmation(
+            Signature::default(),
+            Duration::from_millis(20),
+            Duration::from_millis(5),
+            || Ok(None),
+        );
+
+        let err = result.unwrap_err();
+        let timeout = err.downcast_ref::<ConfirmTimeout>().expect("typed timeout");
+        assert_eq!(timeout.timeout, Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_poll_confirmation_surfaces_transaction_error() {
+        let result = poll_confirmation(
+            Signature::default(),
+            Duration::from_secs(5),
+            Duration::ZERO,
+            || Ok(Some(Err(transaction::TransactionError::AccountNotFound))),
+        );
+
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("failed"), "{}", message);
+    }
 }
@@ -0,0 +1,10 @@
+#![no_main]
+
+use cloak_proof_extract::extract_groth16_260;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(frag) = extract_groth16_260(data) {
+        assert!(frag.iter().any(|&b| b != 0));
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+
+use cloak_proof_extract::parse_public_inputs_104;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    match parse_public_inputs_104(data) {
+        Ok(pi) => {
+            assert_eq!(data.len(), 104);
+            assert_eq!(pi.amount.to_le_bytes(), data[96..104]);
+        }
+        Err(_) => assert_ne!(data.len(), 104),
+    }
+});
@@ -0,0 +1,69 @@
+//! Operator tool for inspecting SP1 proof artifacts
+//!
+//! ```bash
+//! cargo run -p cloak-proof-extract --features sp1 -- verify proof.bin public.bin [vkey_hash]
+//! ```
+//!
+//! Prints the Groth16 fragment and the 104-byte public inputs, then verifies
+//! the proof locally against them. The vkey hash defaults to the one recorded
+//! in a proof envelope; legacy raw bundles need it passed explicitly.
+
+use std::{fs, process::ExitCode};
+
+use cloak_proof_extract::{inspect_bundle, ProofEnvelope};
+use sp1_verifier::{Groth16Verifier, GROTH16_VK_BYTES};
+
+const USAGE: &str = "usage: cloak-proof-extract verify <bundle> <public> [vkey_hash]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (bundle_path, public_path, vkey_hash) = match args.as_slice() {
+        [command, bundle, public] if command == "verify" => (bundle, public, None),
+        [command, bundle, public, vkey_hash] if command == "verify" => {
+            (bundle, public, Some(vkey_hash.as_str()))
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match verify(bundle_path, public_path, vkey_hash) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn verify(bundle_path: &str, public_path: &str, vkey_hash: Option<&str>) -> Result<(), String> {
+    let bundle = fs::read(bundle_path).map_err(|e| format!("read {}: {}", bundle_path, e))?;
+    let public = fs::read(public_path).map_err(|e| format!("read {}: {}", public_path, e))?;
+
+    let (fragment, public_inputs) =
+        inspect_bundle(&bundle, &public).map_err(|e| format!("{}: {}", bundle_path, e))?;
+    println!("groth16       {}", to_hex(&fragment));
+    println!("root          {}", to_hex(&public_inputs.root));
+    println!("nf            {}", to_hex(&public_inputs.nf));
+    println!("outputs_hash  {}", to_hex(&public_inputs.outputs_hash));
+    println!(
+        "amount        {} ({})",
+        to_hex(&public_inputs.amount.to_le_bytes()),
+        public_inputs.amount
+    );
+
+    let envelope = ProofEnvelope::decode(&bundle).map_err(|e| e.to_string())?;
+    let vkey_hash = vkey_hash
+        .or(envelope.map(|envelope| envelope.vkey_hash))
+        .ok_or("raw bundles need a vkey_hash argument to verify")?;
+    Groth16Verifier::verify(&fragment, &public, vkey_hash, *GROTH16_VK_BYTES)
+        .map_err(|e| format!("verification failed: {}", e))?;
+    println!("verified      vkey {}", vkey_hash);
+
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
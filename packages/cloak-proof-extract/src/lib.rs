@@ -8,6 +8,8 @@ use core::convert::TryInto;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
     InvalidFormat,
+    /// Proof envelope with a version this build doesn't understand
+    UnsupportedVersion(u8),
 }
 
 impl Error {
@@ -22,6 +24,7 @@ impl core::fmt::Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Error::InvalidFormat => write!(f, "invalid proof/public inputs format"),
+            Error::UnsupportedVersion(v) => write!(f, "unsupported proof envelope version {}", v),
         }
     }
 }
@@ -79,6 +82,108 @@ pub fn extract_groth16_260(sp1_proof_bundle: &[u8]) -> Result<[u8; 260], Error>
     Err(Error::invalid())
 }
 
+/// Magic prefix of a versioned proof envelope. Legacy artifacts are raw
+/// bincode SP1 bundles, which never start with these bytes.
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"CLKP";
+
+/// Envelope version written by this build
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Versioned wrapper around an SP1 proof bundle
+///
+/// Layout (v1): magic(4) || version(1) || vkey_hash_len:u16_le || vkey_hash (utf-8)
+/// || bundle_len:u64_le || sp1_bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofEnvelope<'a> {
+    pub version: u8,
+    pub sp1_bundle: &'a [u8],
+    pub vkey_hash: &'a str,
+}
+
+impl<'a> ProofEnvelope<'a> {
+    /// Wrap an SP1 bundle in a current-version envelope
+    pub fn new(sp1_bundle: &'a [u8], vkey_hash: &'a str) -> Self {
+        Self {
+            version: ENVELOPE_VERSION,
+            sp1_bundle,
+            vkey_hash,
+        }
+    }
+
+    /// Decode an envelope. Returns `Ok(None)` for bytes without the envelope
+    /// magic, i.e. legacy raw bundles.
+    pub fn decode(bytes: &'a [u8]) -> Result<Option<Self>, Error> {
+        let Some(rest) = bytes.strip_prefix(&ENVELOPE_MAGIC[..]) else {
+            return Ok(None);
+        };
+        let (&version, rest) = rest.split_first().ok_or(Error::InvalidFormat)?;
+        if version != ENVELOPE_VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let (hash_len, rest) = split_le::<2>(rest)?;
+        let hash_len = u16::from_le_bytes(hash_len) as usize;
+        if rest.len() < hash_len {
+            return Err(Error::invalid());
+        }
+        let (vkey_hash, rest) = rest.split_at(hash_len);
+        let vkey_hash = core::str::from_utf8(vkey_hash).map_err(|_| Error::invalid())?;
+
+        let (bundle_len, sp1_bundle) = split_le::<8>(rest)?;
+        if u64::from_le_bytes(bundle_len) != sp1_bundle.len() as u64 {
+            return Err(Error::invalid());
+        }
+
+        Ok(Some(Self {
+            version,
+            sp1_bundle,
+            vkey_hash,
+        }))
+    }
+
+    /// Serialize to the envelope layout
+    #[cfg(any(feature = "alloc", feature = "std", test))]
+    pub fn encode(&self) -> alloc::vec::Vec<u8> {
+        let mut out = alloc::vec::Vec::with_capacity(
+            ENVELOPE_MAGIC.len() + 1 + 2 + self.vkey_hash.len() + 8 + self.sp1_bundle.len(),
+        );
+        out.extend_from_slice(&ENVELOPE_MAGIC);
+        out.push(self.version);
+        out.extend_from_slice(&(self.vkey_hash.len() as u16).to_le_bytes());
+        out.extend_from_slice(self.vkey_hash.as_bytes());
+        out.extend_from_slice(&(self.sp1_bundle.len() as u64).to_le_bytes());
+        out.extend_from_slice(self.sp1_bundle);
+        out
+    }
+}
+
+fn split_le<const N: usize>(bytes: &[u8]) -> Result<([u8; N], &[u8]), Error> {
+    if bytes.len() < N {
+        return Err(Error::invalid());
+    }
+    let (head, rest) = bytes.split_at(N);
+    Ok((head.try_into().map_err(|_| Error::invalid())?, rest))
+}
+
+/// The SP1 bundle carried by `bytes`: the envelope payload, or `bytes`
+/// itself for a legacy raw bundle.
+pub fn sp1_bundle(bytes: &[u8]) -> Result<&[u8], Error> {
+    Ok(ProofEnvelope::decode(bytes)?.map_or(bytes, |envelope| envelope.sp1_bundle))
+}
+
+/// Extract the 260-byte Groth16 fragment from a proof envelope, dispatching on
+/// its version. Legacy raw bundles fall back to [`extract_groth16_260`].
+pub fn extract_from_envelope(bytes: &[u8]) -> Result<[u8; 260], Error> {
+    match ProofEnvelope::decode(bytes)? {
+        // v1 wraps the bincode SP1 bundle unchanged
+        #[cfg(feature = "sp1")]
+        Some(envelope) => sp1_helpers::extract_groth16_260_sp1(envelope.sp1_bundle),
+        #[cfg(not(feature = "sp1"))]
+        Some(envelope) => extract_groth16_260(envelope.sp1_bundle),
+        None => extract_groth16_260(bytes),
+    }
+}
+
 /// 104-byte public inputs structure
 #[cfg_attr(feature = "hex", derive(serde::Serialize, serde::Deserialize))]
 pub struct PublicInputs {
@@ -109,6 +214,18 @@ pub fn parse_public_inputs_104(bytes: &[u8]) -> Result<PublicInputs, Error> {
     })
 }
 
+/// Groth16 fragment of a proof artifact (raw bundle or envelope) and its
+/// parsed 104-byte public inputs, as reported by `cloak-proof-extract verify`
+pub fn inspect_bundle(
+    bundle: &[u8],
+    public_inputs: &[u8],
+) -> Result<([u8; 260], PublicInputs), Error> {
+    Ok((
+        extract_from_envelope(bundle)?,
+        parse_public_inputs_104(public_inputs)?,
+    ))
+}
+
 /// Optional SP1-backed helpers (requires feature = "sp1")
 #[cfg(feature = "sp1")]
 mod sp1_helpers {
@@ -117,10 +234,11 @@ mod sp1_helpers {
 
     use super::*;
 
-    /// Deserialize an SP1 proof bundle via bincode and return the 260-byte Groth16 proof bytes.
+    /// Deserialize an SP1 proof bundle (raw or enveloped) via bincode and
+    /// return the 260-byte Groth16 proof bytes.
     pub fn extract_groth16_260_sp1(sp1_proof_bundle: &[u8]) -> Result<[u8; 260], Error> {
-        let proof: SP1ProofWithPublicValues =
-            bincode::deserialize(sp1_proof_bundle).map_err(|_| Error::InvalidFormat)?;
+        let proof: SP1ProofWithPublicValues = bincode::deserialize(sp1_bundle(sp1_proof_bundle)?)
+            .map_err(|_| Error::InvalidFormat)?;
         let bytes = proof.bytes();
         if bytes.len() != 260 {
             return Err(Error::InvalidFormat);
@@ -132,8 +250,8 @@ mod sp1_helpers {
 
     /// Deserialize SP1 bundle and extract raw 104-byte public inputs committed by the guest.
     pub fn extract_public_inputs_104_sp1(sp1_proof_bundle: &[u8]) -> Result<[u8; 104], Error> {
-        let proof: SP1ProofWithPublicValues =
-            bincode::deserialize(sp1_proof_bundle).map_err(|_| Error::InvalidFormat)?;
+        let proof: SP1ProofWithPublicValues = bincode::deserialize(sp1_bundle(sp1_proof_bundle)?)
+            .map_err(|_| Error::InvalidFormat)?;
         let v = proof.public_values.to_vec();
         if v.len() < 104 {
             return Err(Error::InvalidFormat);
@@ -188,6 +306,8 @@ mod hex32_serde {
 mod tests {
     use std::{fs, path::PathBuf};
 
+    use proptest::prelude::*;
+
     use super::*;
 
     fn locate(path_candidates: &[&str]) -> Option<PathBuf> {
@@ -237,4 +357,150 @@ mod tests {
         // not all zeros
         assert!(frag.iter().any(|&b| b != 0));
     }
+
+    #[test]
+    fn test_inspect_fixture_bundle() {
+        let proof_path = locate(&[
+            "../zk-guest-sp1/out/proof.bin",
+            "../../packages/zk-guest-sp1/out/proof.bin",
+        ])
+        .expect("proof.bin not found in expected locations");
+        let bundle = fs::read(proof_path).expect("read proof.bin");
+
+        let mut public = [0u8; 104];
+        public[0..32].fill(0x11);
+        public[32..64].fill(0x22);
+        public[64..96].fill(0x33);
+        public[96..104].copy_from_slice(&1_000_000_000u64.to_le_bytes());
+
+        let (fragment, pi) = inspect_bundle(&bundle, &public).expect("inspect fixture");
+        assert_eq!(fragment, extract_from_envelope(&bundle).unwrap());
+        assert!(fragment.iter().any(|&b| b != 0));
+        assert_eq!(pi.root, [0x11; 32]);
+        assert_eq!(pi.nf, [0x22; 32]);
+        assert_eq!(pi.outputs_hash, [0x33; 32]);
+        assert_eq!(pi.amount, 1_000_000_000);
+
+        // The same bundle wrapped in an envelope yields the same fragment
+        let enveloped = ProofEnvelope::new(&bundle, "0x00abcdef").encode();
+        assert_eq!(inspect_bundle(&enveloped, &public).unwrap().0, fragment);
+
+        // Public inputs must be exactly 104 bytes
+        assert_eq!(
+            inspect_bundle(&bundle, &public[..103]).map(|_| ()),
+            Err(Error::InvalidFormat)
+        );
+    }
+
+    fn fake_bundle() -> Vec<u8> {
+        let mut bundle = vec![0u8; 16];
+        bundle.extend_from_slice(&260u64.to_le_bytes());
+        bundle.extend((0..260).map(|i| (i % 251) as u8 + 1));
+        bundle
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let bundle = fake_bundle();
+        let bytes = ProofEnvelope::new(&bundle, "0x00abcdef").encode();
+
+        let envelope = ProofEnvelope::decode(&bytes)
+            .unwrap()
+            .expect("is an envelope");
+        assert_eq!(envelope.version, ENVELOPE_VERSION);
+        assert_eq!(envelope.sp1_bundle, &bundle[..]);
+        assert_eq!(envelope.vkey_hash, "0x00abcdef");
+        assert_eq!(sp1_bundle(&bytes).unwrap(), &bundle[..]);
+
+        #[cfg(not(feature = "sp1"))]
+        assert_eq!(
+            extract_from_envelope(&bytes).unwrap(),
+            extract_groth16_260(&bundle).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_envelope_legacy_fallback() {
+        let bundle = fake_bundle();
+
+        assert_eq!(ProofEnvelope::decode(&bundle).unwrap(), None);
+        assert_eq!(sp1_bundle(&bundle).unwrap(), &bundle[..]);
+        assert_eq!(
+            extract_from_envelope(&bundle).unwrap(),
+            extract_groth16_260(&bundle).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_envelope_rejects_unknown_version_and_truncation() {
+        let bundle = fake_bundle();
+        let mut bytes = ProofEnvelope::new(&bundle, "vk").encode();
+
+        assert_eq!(
+            ProofEnvelope::decode(&bytes[..bytes.len() - 1]),
+            Err(Error::InvalidFormat)
+        );
+
+        bytes[ENVELOPE_MAGIC.len()] = 9;
+        assert_eq!(
+            extract_from_envelope(&bytes),
+            Err(Error::UnsupportedVersion(9))
+        );
+    }
+
+    /// Lengths around the thresholds the parsers branch on: the 104-byte public
+    /// inputs, the 8 + 260 byte length-prefixed scan and the known 0x2b0 offset.
+    fn boundary_len() -> impl Strategy<Value = usize> {
+        prop_oneof![
+            0usize..16,
+            96usize..112,
+            260usize..276,
+            0x2b0usize + 252..0x2b0 + 268,
+            0usize..2048,
+        ]
+    }
+
+    fn any_bytes() -> impl Strategy<Value = Vec<u8>> {
+        boundary_len().prop_flat_map(|len| prop::collection::vec(any::<u8>(), len))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(512))]
+
+        #[test]
+        fn prop_parse_public_inputs_never_panics(bytes in any_bytes()) {
+            let parsed = parse_public_inputs_104(&bytes);
+            prop_assert_eq!(parsed.is_ok(), bytes.len() == 104);
+        }
+
+        #[test]
+        fn prop_parse_public_inputs_roundtrip(bytes in prop::collection::vec(any::<u8>(), 104)) {
+            let pi = parse_public_inputs_104(&bytes).expect("104 bytes always parse");
+            prop_assert_eq!(&pi.root[..], &bytes[0..32]);
+            prop_assert_eq!(&pi.nf[..], &bytes[32..64]);
+            prop_assert_eq!(&pi.outputs_hash[..], &bytes[64..96]);
+            prop_assert_eq!(&pi.amount.to_le_bytes()[..], &bytes[96..104]);
+        }
+
+        #[test]
+        fn prop_extract_groth16_never_panics(bytes in any_bytes()) {
+            if let Ok(frag) = extract_groth16_260(&bytes) {
+                prop_assert!(frag.iter().any(|&b| b != 0));
+            }
+        }
+
+        #[test]
+        fn prop_extract_groth16_finds_prefixed_fragment(
+            lead in 0usize..64,
+            frag in prop::collection::vec(1u8..=255, 260),
+        ) {
+            // Below the known offset, so only the length-prefix scan can match
+            let mut bundle = vec![0u8; lead];
+            bundle.extend_from_slice(&260u64.to_le_bytes());
+            bundle.extend_from_slice(&frag);
+
+            let extracted = extract_groth16_260(&bundle).expect("prefixed fragment is found");
+            prop_assert_eq!(&extracted[..], &frag[..]);
+        }
+    }
 }
@@ -0,0 +1,65 @@
+use std::{fmt::Display, future::Future, time::Duration};
+
+use tracing::warn;
+
+/// Upper bound on the delay between startup connection attempts
+pub const MAX_CONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Run `connect` up to `max_attempts` times, sleeping `backoff` after the first
+/// failure and doubling it, up to `MAX_CONNECT_BACKOFF`, after each later one.
+/// Returns the last error once attempts run out.
+pub async fn retry_connect<T, E, F, Fut>(
+    max_attempts: u32,
+    mut backoff: Duration,
+    mut connect: F,
+) -> Result<T, E>
+where
+    E: Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let max_attempts = max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match connect().await {
+            Ok(connection) => return Ok(connection),
+            Err(e) if attempt < max_attempts => {
+                warn!(
+                    "Connection attempt {}/{} failed: {}; retrying in {:?}",
+                    attempt, max_attempts, e, backoff
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_CONNECT_BACKOFF);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_connect_retries_until_database_is_up() {
+        let attempts = AtomicU32::new(0);
+        let connect = || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) + 1 {
+                attempt @ 1..=2 => Err(format!("connection refused (attempt {})", attempt)),
+                attempt => Ok(attempt),
+            }
+        };
+
+        let connected_on = retry_connect(5, Duration::ZERO, connect).await.unwrap();
+        assert_eq!(connected_on, 3);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // Gives up with the last error once attempts run out
+        attempts.store(0, Ordering::SeqCst);
+        let err = retry_connect(2, Duration::ZERO, connect).await.unwrap_err();
+        assert_eq!(err, "connection refused (attempt 2)");
+    }
+}
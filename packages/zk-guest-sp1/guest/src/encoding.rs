@@ -1,6 +1,17 @@
 use anyhow::{anyhow, Result};
 use blake3::Hasher;
 use serde::{Deserialize, Serialize};
+// Note-level hash (commitments, pk_spend, nullifiers). Outputs hashes and the
+// Merkle tree stay BLAKE3 because the program and indexer recompute them.
+#[cfg(not(feature = "commitment-keccak"))]
+use blake3::Hasher as NoteHasher;
+#[cfg(feature = "commitment-keccak")]
+use sha3::{Digest, Keccak256 as NoteHasher};
+
+#[cfg(all(feature = "commitment-keccak", feature = "domain-separation"))]
+compile_error!(
+    "`domain-separation` uses BLAKE3 derive_key and cannot be combined with `commitment-keccak`"
+);
 
 /// BLAKE3 hash function returning 32 bytes
 pub fn hash_blake3(data: &[u8]) -> [u8; 32] {
@@ -19,18 +30,20 @@ pub fn serialize_u32_le(value: u32) -> [u8; 4] {
     value.to_le_bytes()
 }
 
-/// Parse hex string to 32-byte array
-pub fn parse_hex32(hex_str: &str) -> Result<[u8; 32]> {
-    let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
-    let bytes = hex::decode(hex_str).map_err(|e| anyhow!("Invalid hex string: {}", e))?;
+/// Note-level hasher for a `cloak_layouts::domains` context
+#[cfg(not(feature = "commitment-keccak"))]
+fn note_hasher(context: &str) -> NoteHasher {
+    cloak_layouts::domains::hasher(context)
+}
 
-    if bytes.len() != 32 {
-        return Err(anyhow!("Expected 32 bytes, got {}", bytes.len()));
-    }
+#[cfg(feature = "commitment-keccak")]
+fn note_hasher(_context: &str) -> NoteHasher {
+    NoteHasher::new()
+}
 
-    let mut result = [0u8; 32];
-    result.copy_from_slice(&bytes);
-    Ok(result)
+/// Parse hex string (optionally `0x`-prefixed) to 32-byte array
+pub fn parse_hex32(hex_str: &str) -> Result<[u8; 32]> {
+    cloak_layouts::parse_hex32(hex_str).map_err(|e| anyhow!("Invalid hex string: {}", e))
 }
 
 /// Parse address from base58 or hex to 32-byte array
@@ -59,39 +72,48 @@ pub fn parse_address(address_str: &str) -> Result<[u8; 32]> {
 }
 
 /// Compute commitment: C = H(amount:u64 || r:32 || pk_spend:32) using BLAKE3
+/// (Keccak-256 with `commitment-keccak`)
 pub fn compute_commitment(amount: u64, r: &[u8; 32], pk_spend: &[u8; 32]) -> [u8; 32] {
-    let mut hasher = Hasher::new();
-    hasher.update(&serialize_u64_le(amount));
+    let mut hasher = note_hasher(cloak_layouts::domains::COMMITMENT);
+    hasher.update(serialize_u64_le(amount).as_slice());
     hasher.update(r);
     hasher.update(pk_spend);
-    *hasher.finalize().as_bytes()
+    hasher.finalize().into()
 }
 
 /// Compute pk_spend: pk_spend = H(sk_spend:32)
 pub fn compute_pk_spend(sk_spend: &[u8; 32]) -> [u8; 32] {
-    hash_blake3(sk_spend)
+    let mut hasher = note_hasher(cloak_layouts::domains::PK_SPEND);
+    hasher.update(sk_spend);
+    hasher.finalize().into()
 }
 
 /// Compute nullifier: nf = H(sk_spend:32 || leaf_index:u32) using BLAKE3
+/// (Keccak-256 with `commitment-keccak`)
 pub fn compute_nullifier(sk_spend: &[u8; 32], leaf_index: u32) -> [u8; 32] {
-    let mut hasher = Hasher::new();
+    let mut hasher = note_hasher(cloak_layouts::domains::NULLIFIER);
     hasher.update(sk_spend);
-    hasher.update(&serialize_u32_le(leaf_index));
-    *hasher.finalize().as_bytes()
+    hasher.update(serialize_u32_le(leaf_index).as_slice());
+    hasher.finalize().into()
 }
 
 /// Compute outputs hash: H(output[0] || output[1] || ... || output[n-1]) using BLAKE3
-/// where output = address:32 || amount:u64
+/// where output = address:32 || amount:u64, in `cloak_layouts::hash_order`
 pub fn compute_outputs_hash(outputs: &[Output]) -> [u8; 32] {
-    let mut hasher = Hasher::new();
-    for output in outputs {
-        hasher.update(&output.address);
-        hasher.update(&serialize_u64_le(output.amount));
+    let mut ordered: Vec<&Output> = outputs.iter().collect();
+    cloak_layouts::hash_order(&mut ordered, |output| (output.address, output.amount));
+    let mut hasher = cloak_layouts::domains::hasher(cloak_layouts::domains::OUTPUTS_HASH);
+    for output in ordered {
+        hasher.update(&cloak_layouts::encode_output(
+            &output.address,
+            output.amount,
+        ));
     }
     *hasher.finalize().as_bytes()
 }
 
-/// Swap-specific parameters for computing outputs_hash in swap mode
+/// Swap-specific parameters for computing outputs_hash in swap mode.
+/// In JSON the addresses are hex (base58 is also accepted when parsing).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwapParams {
     /// Output token mint address (e.g., USDC)
@@ -106,15 +128,17 @@ pub struct SwapParams {
     pub min_output_amount: u64,
 }
 
-
 /// Compute swap-mode outputs hash: H(output_mint || recipient_ata || min_output_amount || public_amount)
+/// over the 80-byte `cloak_layouts::encode_swap_outputs` preimage, amounts little-endian.
 /// This is used for swap withdrawals where we withdraw SOL and swap it for another token
 pub fn compute_swap_outputs_hash(swap_params: &SwapParams, public_amount: u64) -> [u8; 32] {
-    let mut hasher = Hasher::new();
-    hasher.update(&swap_params.output_mint);
-    hasher.update(&swap_params.recipient_ata);
-    hasher.update(&serialize_u64_le(swap_params.min_output_amount));
-    hasher.update(&serialize_u64_le(public_amount));
+    let mut hasher = cloak_layouts::domains::hasher(cloak_layouts::domains::OUTPUTS_HASH);
+    hasher.update(&cloak_layouts::encode_swap_outputs(
+        &swap_params.output_mint,
+        &swap_params.recipient_ata,
+        swap_params.min_output_amount,
+        public_amount,
+    ));
     *hasher.finalize().as_bytes()
 }
 
@@ -123,40 +147,72 @@ pub fn calculate_fee(amount: u64) -> u64 {
     // Fee structure:
     // - For SOL withdrawals: Fixed (0.0025 SOL) + Variable (0.5%)
     // - For SPL swaps: Variable fee (0.5%) is deducted from withdrawn SOL, fixed fee paid separately
-    // Since the circuit doesn't distinguish, we use the full fee (fixed + variable) for all cases
-    let fixed_fee = 2_500_000; // 0.0025 SOL
-    let variable_fee = (amount * 5) / 1_000; // 0.5% = 5/1000
-    fixed_fee + variable_fee
+    // Since the circuit doesn't distinguish, we use the full fee (fixed + variable) for all cases.
+    // The native schedule is at most u64::MAX / 200 + 0.0025 SOL, so it never saturates
+    checked_calculate_fee(amount).unwrap_or(u64::MAX)
 }
 
+/// Overflow-checked variant of [`calculate_fee`], returning `None` when the
+/// fee cannot be represented for `amount`
+pub fn checked_calculate_fee(amount: u64) -> Option<u64> {
+    cloak_layouts::calculate_fee(amount, cloak_layouts::FeeSchedule::NATIVE).ok()
+}
+
+/// Two-to-one hash used to combine Merkle tree nodes
+pub trait MerkleHasher {
+    fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32];
+}
+
+/// Default Merkle hasher: parent = BLAKE3(left || right)
+pub struct Blake3Hasher;
+
+impl MerkleHasher for Blake3Hasher {
+    fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = Hasher::new();
+        hasher.update(left);
+        hasher.update(right);
+        *hasher.finalize().as_bytes()
+    }
+}
+
+pub use cloak_layouts::MERKLE_TREE_DEPTH;
+
 /// Merkle path verification using BLAKE3
 /// Rule: if bit==0 => parent=H(curr||sib) else parent=H(sib||curr)
+/// The path must be exactly `depth` levels long.
 pub fn verify_merkle_path(
     leaf: &[u8; 32],
     path_elements: &[[u8; 32]],
     path_indices: &[u8],
     root: &[u8; 32],
+    depth: usize,
 ) -> bool {
-    if path_elements.len() != path_indices.len() {
+    verify_merkle_path_with::<Blake3Hasher>(leaf, path_elements, path_indices, root, depth)
+}
+
+/// Merkle path verification over an arbitrary [`MerkleHasher`]
+pub fn verify_merkle_path_with<H: MerkleHasher>(
+    leaf: &[u8; 32],
+    path_elements: &[[u8; 32]],
+    path_indices: &[u8],
+    root: &[u8; 32],
+    depth: usize,
+) -> bool {
+    // Reject depth confusion (and cycle inflation) before hashing anything
+    if path_elements.len() != depth || path_indices.len() != depth {
         return false;
     }
 
     let mut current = *leaf;
 
     for (element, &index) in path_elements.iter().zip(path_indices.iter()) {
-        let mut hasher = Hasher::new();
-        if index == 0 {
+        current = match index {
             // current is left, element is right
-            hasher.update(&current);
-            hasher.update(element);
-        } else if index == 1 {
+            0 => H::hash_pair(&current, element),
             // element is left, current is right
-            hasher.update(element);
-            hasher.update(&current);
-        } else {
-            return false; // Invalid index
-        }
-        current = *hasher.finalize().as_bytes();
+            1 => H::hash_pair(element, &current),
+            _ => return false, // Invalid index
+        };
     }
 
     current == *root
@@ -268,6 +324,103 @@ mod tests {
         assert_eq!(commitment, commitment2);
     }
 
+    #[cfg(not(any(feature = "commitment-keccak", feature = "domain-separation")))]
+    #[test]
+    fn test_commitment_uses_blake3_by_default() {
+        let pk_spend = [0x33u8; 32];
+        let preimage = [&1_000_000u64.to_le_bytes()[..], &[0x42u8; 32], &pk_spend].concat();
+
+        let commitment = compute_commitment(1_000_000, &[0x42u8; 32], &pk_spend);
+        assert_eq!(commitment, hash_blake3(&preimage));
+        assert_eq!(
+            commitment,
+            compute_commitment(1_000_000, &[0x42u8; 32], &pk_spend)
+        );
+    }
+
+    #[cfg(feature = "commitment-keccak")]
+    #[test]
+    fn test_commitment_uses_keccak_with_feature() {
+        let pk_spend = [0x33u8; 32];
+        let preimage = [&1_000_000u64.to_le_bytes()[..], &[0x42u8; 32], &pk_spend].concat();
+
+        let commitment = compute_commitment(1_000_000, &[0x42u8; 32], &pk_spend);
+        let keccak: [u8; 32] = sha3::Keccak256::digest(&preimage).into();
+        assert_eq!(commitment, keccak);
+        assert_ne!(commitment, hash_blake3(&preimage));
+        assert_eq!(
+            commitment,
+            compute_commitment(1_000_000, &[0x42u8; 32], &pk_spend)
+        );
+    }
+
+    #[test]
+    fn test_hash_domains_separate_identical_input() {
+        use cloak_layouts::domains::{COMMITMENT, NULLIFIER, OUTPUTS_HASH};
+
+        let input = [0x5Au8; 40];
+        let digest = |context: &str| -> [u8; 32] {
+            let mut hasher = Hasher::new_derive_key(context);
+            hasher.update(&input);
+            hasher.finalize().into()
+        };
+
+        let commitment = digest(COMMITMENT);
+        let nullifier = digest(NULLIFIER);
+        let outputs_hash = digest(OUTPUTS_HASH);
+        assert_ne!(commitment, nullifier);
+        assert_ne!(commitment, outputs_hash);
+        assert_ne!(nullifier, outputs_hash);
+        assert_ne!(commitment, hash_blake3(&input));
+    }
+
+    #[cfg(feature = "domain-separation")]
+    #[test]
+    fn test_domain_separation_applied_to_note_and_outputs_hashes() {
+        let sk_spend = [0x11u8; 32];
+        let nullifier_preimage = [&sk_spend[..], &42u32.to_le_bytes()].concat();
+        assert_ne!(
+            compute_nullifier(&sk_spend, 42),
+            hash_blake3(&nullifier_preimage)
+        );
+
+        let output = Output {
+            address: [0x01u8; 32],
+            amount: 100,
+        };
+        assert_ne!(
+            compute_outputs_hash(&[output.clone()]),
+            hash_blake3(&cloak_layouts::encode_output(
+                &output.address,
+                output.amount
+            ))
+        );
+    }
+
+    #[cfg(feature = "sorted-outputs")]
+    #[test]
+    fn test_sorted_outputs_hash_ignores_order() {
+        let a = Output {
+            address: [0x02u8; 32],
+            amount: 100,
+        };
+        let b = Output {
+            address: [0x01u8; 32],
+            amount: 200,
+        };
+        let c = Output {
+            address: [0x01u8; 32],
+            amount: 50,
+        };
+
+        let canonical = compute_outputs_hash(&[c.clone(), b.clone(), a.clone()]);
+        assert_eq!(
+            compute_outputs_hash(&[a.clone(), b.clone(), c.clone()]),
+            canonical
+        );
+        assert_eq!(compute_outputs_hash(&[b, a, c]), canonical);
+    }
+
     #[test]
     fn test_nullifier_matches_docs() {
         let sk_spend = [0x11u8; 32];
@@ -281,6 +434,8 @@ mod tests {
         assert_eq!(nullifier, nullifier2);
     }
 
+    // `sorted-outputs` hashes in canonical order instead
+    #[cfg(not(feature = "sorted-outputs"))]
     #[test]
     fn test_outputs_hash_order_sensitive() {
         let output1 = Output {
@@ -317,7 +472,8 @@ mod tests {
             &leaf,
             &path_elements,
             &path_indices,
-            &root
+            &root,
+            2
         ));
 
         // Should fail with swapped sibling
@@ -326,7 +482,101 @@ mod tests {
             &leaf,
             &path_elements_swapped,
             &path_indices,
-            &root
+            &root,
+            2
+        ));
+    }
+
+    #[test]
+    fn test_merkle_verify_rejects_wrong_depth() {
+        let leaf = [0x01u8; 32];
+        let sibling1 = [0x02u8; 32];
+        let sibling2 = [0x03u8; 32];
+
+        let level1 = hash_blake3(&[&leaf[..], &sibling1[..]].concat());
+        let root = hash_blake3(&[&level1[..], &sibling2[..]].concat());
+        let path_elements = [sibling1, sibling2];
+        let path_indices = [0, 0];
+
+        // Correct depth is accepted
+        assert!(verify_merkle_path(
+            &leaf,
+            &path_elements,
+            &path_indices,
+            &root,
+            2
+        ));
+
+        // A valid path checked against a different depth is rejected
+        assert!(!verify_merkle_path(
+            &leaf,
+            &path_elements,
+            &path_indices,
+            &root,
+            3
+        ));
+        assert!(!verify_merkle_path(
+            &leaf,
+            &path_elements,
+            &path_indices,
+            &root,
+            MERKLE_TREE_DEPTH
+        ));
+
+        // A truncated path cannot verify an intermediate node as the root
+        assert!(!verify_merkle_path(
+            &leaf,
+            &path_elements[..1],
+            &path_indices[..1],
+            &level1,
+            2
+        ));
+    }
+
+    #[test]
+    fn test_merkle_verify_with_mock_hasher() {
+        // Non-commutative toy hasher so left/right ordering is still exercised
+        struct MockHasher;
+        impl MerkleHasher for MockHasher {
+            fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+                let mut out = [0u8; 32];
+                for i in 0..32 {
+                    out[i] = left[i].wrapping_mul(2).wrapping_add(right[i]);
+                }
+                out
+            }
+        }
+
+        let leaf = [1u8; 32];
+        let sibling1 = [2u8; 32];
+        let sibling2 = [3u8; 32];
+
+        // level1 = 1*2 + 2 = 4, root = 3*2 + 4 = 10 (sibling2 on the left)
+        let root = [10u8; 32];
+        let path_elements = [sibling1, sibling2];
+
+        assert!(verify_merkle_path_with::<MockHasher>(
+            &leaf,
+            &path_elements,
+            &[0, 1],
+            &root,
+            2
+        ));
+        assert!(!verify_merkle_path_with::<MockHasher>(
+            &leaf,
+            &path_elements,
+            &[0, 0],
+            &root,
+            2
+        ));
+
+        // The BLAKE3 default must not accept the mock root
+        assert!(!verify_merkle_path(
+            &leaf,
+            &path_elements,
+            &[0, 1],
+            &root,
+            2
         ));
     }
 
@@ -382,6 +632,59 @@ mod tests {
         );
     }
 
+    // Pinned so clients hashing swap parameters themselves can check their bytes
+    #[cfg(not(feature = "domain-separation"))]
+    #[test]
+    fn test_swap_outputs_hash_vector() {
+        let swap_params = SwapParams {
+            output_mint: [0xAAu8; 32],
+            recipient_ata: [0xBBu8; 32],
+            min_output_amount: 1_000_000,
+        };
+        let preimage = [
+            &[0xAAu8; 32][..],
+            &[0xBBu8; 32],
+            &1_000_000u64.to_le_bytes(),
+            &3_000_000_000u64.to_le_bytes(),
+        ]
+        .concat();
+
+        let hash = compute_swap_outputs_hash(&swap_params, 3_000_000_000);
+        assert_eq!(hash, hash_blake3(&preimage));
+        assert_eq!(
+            hex::encode(hash),
+            "b0a859d97e2cade0e9c97d2a03dc99b196489bcb90772864e1975e4405d988f9"
+        );
+    }
+
+    #[cfg(feature = "domain-separation")]
+    #[test]
+    fn test_swap_outputs_hash_vector_domain_separated() {
+        let swap_params = SwapParams {
+            output_mint: [0xAAu8; 32],
+            recipient_ata: [0xBBu8; 32],
+            min_output_amount: 1_000_000,
+        };
+        assert_eq!(
+            hex::encode(compute_swap_outputs_hash(&swap_params, 3_000_000_000)),
+            "de9b24c184912505178f03f723cf8a239a5f2ecb7dff935e549b2244d736d32b"
+        );
+    }
+
+    #[test]
+    fn test_swap_params_json_round_trip() {
+        let json = format!(
+            r#"{{"output_mint":"{}","recipient_ata":"{}","min_output_amount":1000000}}"#,
+            "aa".repeat(32),
+            "bb".repeat(32)
+        );
+        let swap_params: SwapParams = serde_json::from_str(&json).unwrap();
+        assert_eq!(swap_params.output_mint, [0xAAu8; 32]);
+        assert_eq!(swap_params.recipient_ata, [0xBBu8; 32]);
+        assert_eq!(swap_params.min_output_amount, 1_000_000);
+        assert_eq!(serde_json::to_string(&swap_params).unwrap(), json);
+    }
+
     #[test]
     fn test_swap_vs_regular_outputs_hash() {
         // Verify that swap mode and regular mode produce different hashes
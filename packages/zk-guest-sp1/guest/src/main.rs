@@ -5,6 +5,16 @@ use serde::{Deserialize, Serialize};
 use sp1_zkvm::io;
 
 mod encoding;
+#[cfg(all(
+    test,
+    not(any(
+        feature = "commitment-keccak",
+        feature = "domain-separation",
+        feature = "proof-freshness",
+        feature = "sorted-outputs"
+    ))
+))]
+mod vectors;
 
 use encoding::{SwapParams, *};
 
@@ -28,8 +38,18 @@ struct PublicInputs {
     #[serde(with = "hex_string")]
     pub outputs_hash: [u8; 32],
     pub amount: u64,
+    /// Slot the proof is generated at; the program rejects it once too old
+    #[cfg(feature = "proof-freshness")]
+    pub generated_at: u64,
 }
 
+/// root(32) || nf(32) || outputs_hash(32) || amount(8)
+#[cfg(not(feature = "proof-freshness"))]
+const PUBLIC_INPUTS_LEN: usize = 104;
+/// root(32) || nf(32) || outputs_hash(32) || amount(8) || generated_at(8)
+#[cfg(feature = "proof-freshness")]
+const PUBLIC_INPUTS_LEN: usize = 112;
+
 #[derive(Debug, Serialize, Deserialize)]
 struct CircuitInputs {
     pub private: PrivateInputs,
@@ -75,16 +95,20 @@ pub fn main() {
     // Verify all circuit constraints
     verify_circuit_constraints(&inputs).expect("Circuit constraint verification failed");
 
-    // Commit public inputs as a single 104-byte canonical blob
-    // Format: root(32) || nf(32) || outputs_hash(32) || amount(8)
+    // Commit public inputs as a single canonical blob
     // This matches the format expected by the Solana verifier (sp1-solana crate)
-    let mut public_inputs_blob = [0u8; 104];
-    public_inputs_blob[0..32].copy_from_slice(&inputs.public.root);
-    public_inputs_blob[32..64].copy_from_slice(&inputs.public.nf);
-    public_inputs_blob[64..96].copy_from_slice(&inputs.public.outputs_hash);
-    public_inputs_blob[96..104].copy_from_slice(&inputs.public.amount.to_le_bytes());
+    sp1_zkvm::io::commit_slice(&encode_public_inputs(&inputs.public));
+}
 
-    sp1_zkvm::io::commit_slice(&public_inputs_blob);
+fn encode_public_inputs(public: &PublicInputs) -> [u8; PUBLIC_INPUTS_LEN] {
+    let mut blob = [0u8; PUBLIC_INPUTS_LEN];
+    blob[0..32].copy_from_slice(&public.root);
+    blob[32..64].copy_from_slice(&public.nf);
+    blob[64..96].copy_from_slice(&public.outputs_hash);
+    blob[96..104].copy_from_slice(&public.amount.to_le_bytes());
+    #[cfg(feature = "proof-freshness")]
+    blob[104..112].copy_from_slice(&public.generated_at.to_le_bytes());
+    blob
 }
 
 fn verify_circuit_constraints(inputs: &CircuitInputs) -> Result<()> {
@@ -92,18 +116,30 @@ fn verify_circuit_constraints(inputs: &CircuitInputs) -> Result<()> {
     let public = &inputs.public;
     let outputs = &inputs.outputs;
 
+    // Early guards: bound the amount and fee before doing any hashing so bad
+    // inputs fail fast with an explicit reason
+    if private.amount == 0 {
+        return Err(anyhow!("Amount must be greater than zero"));
+    }
+    let fee = checked_calculate_fee(private.amount)
+        .ok_or_else(|| anyhow!("Fee calculation overflows for amount {}", private.amount))?;
+    if fee > private.amount {
+        return Err(anyhow!("Fee {} exceeds amount {}", fee, private.amount));
+    }
+
     // Constraint 1: pk_spend = H(sk_spend)
     let pk_spend = compute_pk_spend(&private.sk_spend);
 
     // Constraint 2: C = H(amount || r || pk_spend)
     let commitment = compute_commitment(private.amount, &private.r, &pk_spend);
 
-    // Constraint 3: MerkleVerify(C, merkle_path) == root
+    // Constraint 3: MerkleVerify(C, merkle_path) == root, at the protocol tree depth
     let merkle_valid = verify_merkle_path(
         &commitment,
         &private.merkle_path.path_elements,
         &private.merkle_path.path_indices,
         &public.root,
+        MERKLE_TREE_DEPTH,
     );
     if !merkle_valid {
         return Err(anyhow!("Merkle path verification failed"));
@@ -119,7 +155,6 @@ fn verify_circuit_constraints(inputs: &CircuitInputs) -> Result<()> {
     // For swap mode: outputs should be empty (all goes to swap)
     // For regular mode: outputs + fee = amount
     let outputs_sum: u64 = outputs.iter().map(|o| o.amount).sum();
-    let fee = calculate_fee(private.amount);
 
     if inputs.swap_params.is_some() {
         // Swap mode: verify swap constraints
@@ -169,6 +204,17 @@ fn verify_circuit_constraints(inputs: &CircuitInputs) -> Result<()> {
         }
     }
 
+    // Constraint 5a (`distinct-recipients`): no two outputs pay the same
+    // address, the same policy the relay enforces before submitting
+    #[cfg(feature = "distinct-recipients")]
+    if let Some((first, second)) = find_duplicate_recipient(outputs) {
+        return Err(anyhow!(
+            "Duplicate recipient at outputs {} and {}",
+            first,
+            second
+        ));
+    }
+
     // Constraint 6: H(serialize(outputs)) == outputs_hash
     // For swap mode: outputs_hash = H(output_mint || recipient_ata || min_output_amount || public_amount)
     // For regular mode: outputs_hash = H(output[0] || output[1] || ... || output[n-1])
@@ -192,6 +238,18 @@ fn verify_circuit_constraints(inputs: &CircuitInputs) -> Result<()> {
     Ok(())
 }
 
+/// Indices of the first output that repeats an earlier output's address and
+/// of that earlier output. Quadratic, but withdraws carry a handful of outputs.
+#[cfg(feature = "distinct-recipients")]
+fn find_duplicate_recipient(outputs: &[Output]) -> Option<(usize, usize)> {
+    outputs.iter().enumerate().find_map(|(second, output)| {
+        outputs[..second]
+            .iter()
+            .position(|earlier| earlier.address == output.address)
+            .map(|first| (first, second))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,25 +257,28 @@ mod tests {
     fn create_test_inputs() -> CircuitInputs {
         let sk_spend = [0x11u8; 32];
         let r = [0x22u8; 32];
-        let amount = 1000000u64;
+        let amount = 10_000_000u64;
         let leaf_index = 42u32;
 
         let pk_spend = compute_pk_spend(&sk_spend);
         let commitment = compute_commitment(amount, &r, &pk_spend);
         let nullifier = compute_nullifier(&sk_spend, leaf_index);
 
-        // Create a simple merkle path (single level for testing)
+        // Create a full-depth merkle path with the commitment always on the left
         let sibling = [0x33u8; 32];
-        let root = hash_blake3(&[&commitment[..], &sibling[..]].concat());
+        let root = (0..MERKLE_TREE_DEPTH).fold(commitment, |node, _| {
+            hash_blake3(&[&node[..], &sibling[..]].concat())
+        });
 
         let outputs = vec![
             Output {
                 address: [0x01u8; 32],
-                amount: 400000,
+                amount: 4_000_000,
             },
             Output {
                 address: [0x02u8; 32],
-                amount: 594000, // 1000000 - 6000 (fee) = 994000, so 400000 + 594000 = 994000
+                // 10_000_000 - 2_550_000 (fee) = 7_450_000, so 4_000_000 + 3_450_000 = 7_450_000
+                amount: 3_450_000,
             },
         ];
 
@@ -230,8 +291,8 @@ mod tests {
                 sk_spend,
                 leaf_index,
                 merkle_path: MerklePath {
-                    path_elements: vec![sibling],
-                    path_indices: vec![0], // commitment is left, sibling is right
+                    path_elements: vec![sibling; MERKLE_TREE_DEPTH],
+                    path_indices: vec![0; MERKLE_TREE_DEPTH], // commitment is left, sibling is right
                 },
             },
             public: PublicInputs {
@@ -239,6 +300,8 @@ mod tests {
                 nf: nullifier,
                 outputs_hash,
                 amount,
+                #[cfg(feature = "proof-freshness")]
+                generated_at: 1_000,
             },
             outputs,
             swap_params: None, // Regular mode (not swap)
@@ -259,6 +322,16 @@ mod tests {
         assert!(verify_circuit_constraints(&inputs).is_err());
     }
 
+    #[test]
+    fn test_wrong_depth_merkle_path() {
+        let mut inputs = create_test_inputs();
+        // Drop the top level: the path no longer matches the protocol depth
+        inputs.private.merkle_path.path_elements.pop();
+        inputs.private.merkle_path.path_indices.pop();
+        let err = verify_circuit_constraints(&inputs).unwrap_err();
+        assert!(err.to_string().contains("Merkle path"));
+    }
+
     #[test]
     fn test_invalid_outputs_hash() {
         let mut inputs = create_test_inputs();
@@ -269,7 +342,88 @@ mod tests {
 
     #[test]
     fn test_conservation_failure() {
+        let mut inputs = create_test_inputs();
+        // Overspend by one lamport but keep the outputs hash consistent
+        inputs.outputs[1].amount += 1;
+        inputs.public.outputs_hash = compute_outputs_hash(&inputs.outputs);
+        let err = verify_circuit_constraints(&inputs).unwrap_err();
+        assert!(err.to_string().contains("conservation"));
+    }
+
+    #[test]
+    fn test_zero_amount_rejected() {
+        let mut inputs = create_test_inputs();
+        inputs.private.amount = 0;
+        inputs.public.amount = 0;
+        let err = verify_circuit_constraints(&inputs).unwrap_err();
+        assert!(err.to_string().contains("greater than zero"));
+    }
+
+    #[test]
+    fn test_fee_exceeding_amount_rejected() {
+        let mut inputs = create_test_inputs();
+        // Below the fixed fee, so the fee alone exceeds the amount
+        inputs.private.amount = 1_000;
+        inputs.public.amount = 1_000;
+        let err = verify_circuit_constraints(&inputs).unwrap_err();
+        assert!(err.to_string().contains("exceeds amount"));
+    }
+
+    #[test]
+    fn test_fee_does_not_overflow_at_max_amount() {
+        let mut inputs = create_test_inputs();
+        inputs.private.amount = u64::MAX;
+        inputs.public.amount = u64::MAX;
+        // The fee is computed in u128, so these inputs fail a later constraint
+        let err = verify_circuit_constraints(&inputs).unwrap_err();
+        assert!(!err.to_string().contains("overflows"), "{}", err);
+        assert_eq!(
+            checked_calculate_fee(u64::MAX),
+            Some(u64::MAX / 200 + 2_500_000)
+        );
+    }
+
+    #[test]
+    fn test_public_inputs_layout() {
         let inputs = create_test_inputs();
-        assert!(verify_circuit_constraints(&inputs).is_err());
+        let blob = encode_public_inputs(&inputs.public);
+        assert_eq!(&blob[0..32], &inputs.public.root);
+        assert_eq!(&blob[32..64], &inputs.public.nf);
+        assert_eq!(&blob[64..96], &inputs.public.outputs_hash);
+        assert_eq!(&blob[96..104], &inputs.public.amount.to_le_bytes());
+    }
+
+    #[cfg(feature = "distinct-recipients")]
+    #[test]
+    fn test_duplicate_recipients_rejected() {
+        let mut inputs = create_test_inputs();
+        // Same split, but both outputs pay the first address
+        inputs.outputs[1].address = inputs.outputs[0].address;
+        inputs.public.outputs_hash = compute_outputs_hash(&inputs.outputs);
+        let err = verify_circuit_constraints(&inputs).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Duplicate recipient at outputs 0 and 1"),
+            "{}",
+            err
+        );
+    }
+
+    #[cfg(not(feature = "distinct-recipients"))]
+    #[test]
+    fn test_duplicate_recipients_allowed_by_default() {
+        let mut inputs = create_test_inputs();
+        inputs.outputs[1].address = inputs.outputs[0].address;
+        inputs.public.outputs_hash = compute_outputs_hash(&inputs.outputs);
+        assert!(verify_circuit_constraints(&inputs).is_ok());
+    }
+
+    #[cfg(feature = "proof-freshness")]
+    #[test]
+    fn test_generated_at_committed() {
+        let inputs = create_test_inputs();
+        let blob = encode_public_inputs(&inputs.public);
+        assert_eq!(blob.len(), 112);
+        assert_eq!(&blob[104..112], &1_000u64.to_le_bytes());
     }
 }
@@ -15,7 +15,27 @@ fn main() {
         .unwrap()
         .join(".artifacts/zk-guest-sp1-guest");
 
-    if prebuilt_elf.exists() && !force_build {
+    // The pre-built ELF uses plain BLAKE3 hashes, 104-byte public inputs and
+    // the default constraints, so a host with a hashing, layout or constraint
+    // feature needs a fresh guest built with the same features
+    let mut guest_features = Vec::new();
+    if cfg!(feature = "commitment-keccak") {
+        guest_features.push("commitment-keccak".to_string());
+    }
+    if cfg!(feature = "domain-separation") {
+        guest_features.push("domain-separation".to_string());
+    }
+    if cfg!(feature = "proof-freshness") {
+        guest_features.push("proof-freshness".to_string());
+    }
+    if cfg!(feature = "sorted-outputs") {
+        guest_features.push("sorted-outputs".to_string());
+    }
+    if cfg!(feature = "distinct-recipients") {
+        guest_features.push("distinct-recipients".to_string());
+    }
+
+    if prebuilt_elf.exists() && !force_build && guest_features.is_empty() {
         println!("cargo:warning=Using pre-built ELF from .artifacts directory");
         println!("cargo:rerun-if-changed=../.artifacts/zk-guest-sp1-guest");
 
@@ -61,7 +81,17 @@ fn main() {
             );
         }
         println!("cargo:rerun-if-changed=../guest");
-        sp1_build::build_program("../guest");
+        if !guest_features.is_empty() {
+            sp1_build::build_program_with_args(
+                "../guest",
+                sp1_build::BuildArgs {
+                    features: guest_features,
+                    ..Default::default()
+                },
+            );
+        } else {
+            sp1_build::build_program("../guest");
+        }
     }
 
     #[cfg(not(feature = "build-guest"))]
@@ -2,7 +2,8 @@ use std::fs;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use sp1_sdk::{include_elf, ProverClient, SP1ProofWithPublicValues, SP1Stdin};
+use sp1_sdk::{include_elf, ProverClient, SP1ProofWithPublicValues};
+use zk_guest_sp1_host::build_circuit_stdin;
 
 const ELF: &[u8] = include_elf!("zk-guest-sp1-guest");
 
@@ -53,20 +54,9 @@ fn main() -> Result<()> {
             let (pk, _vk) = client.setup(ELF);
             println!("✅ Proving key generated");
 
-            // Create combined input
-            let combined_input = format!(
-                r#"{{
-                    "private": {},
-                    "public": {},
-                    "outputs": {}
-                }}"#,
-                private_json, public_json, outputs_json
-            );
-
             // Generate proof
             println!("📝 Preparing circuit inputs...");
-            let mut stdin = SP1Stdin::new();
-            stdin.write(&combined_input);
+            let stdin = build_circuit_stdin(&private_json, &public_json, &outputs_json, None);
 
             println!("🔨 Generating Groth16 proof (this may take 10-15 minutes)...");
 
@@ -1,6 +1,7 @@
 use std::fs;
 
 use anyhow::Result;
+use cloak_layouts::MERKLE_TREE_DEPTH;
 
 mod encoding {
     pub use zk_guest_sp1_host::encoding::*;
@@ -106,9 +107,11 @@ fn main() -> Result<()> {
     let commitment = compute_commitment(amount, &r, &pk_spend);
     let nullifier = compute_nullifier(&sk_spend, leaf_index);
 
-    // Create a simple merkle path (single level for testing)
+    // Full-depth merkle path with the same sibling at every level
     let sibling = [0x33u8; 32];
-    let root = hash_blake3(&[&commitment[..], &sibling[..]].concat());
+    let root = (0..MERKLE_TREE_DEPTH).fold(commitment, |node, _| {
+        hash_blake3(&[&node[..], &sibling[..]].concat())
+    });
 
     let outputs = vec![
         ExampleOutput {
@@ -137,8 +140,8 @@ fn main() -> Result<()> {
         sk_spend,
         leaf_index,
         merkle_path: ExampleMerklePath {
-            path_elements: vec![sibling],
-            path_indices: vec![0], // commitment is left, sibling is right
+            path_elements: vec![sibling; MERKLE_TREE_DEPTH],
+            path_indices: vec![0; MERKLE_TREE_DEPTH], // commitment is left, sibling is right
         },
     };
 
@@ -1,6 +1,18 @@
 use anyhow::{anyhow, Result};
 use blake3::Hasher;
+pub use cloak_layouts::FeeError;
+use cloak_layouts::FeeSchedule;
 use serde::{Deserialize, Serialize};
+// Note-level hash, must match the guest's `commitment-keccak` setting
+#[cfg(not(feature = "commitment-keccak"))]
+use blake3::Hasher as NoteHasher;
+#[cfg(feature = "commitment-keccak")]
+use sha3::{Digest, Keccak256 as NoteHasher};
+
+#[cfg(all(feature = "commitment-keccak", feature = "domain-separation"))]
+compile_error!(
+    "`domain-separation` uses BLAKE3 derive_key and cannot be combined with `commitment-keccak`"
+);
 
 /// BLAKE3-256 hash function returning 32 bytes
 pub fn hash_blake3(data: &[u8]) -> [u8; 32] {
@@ -17,10 +29,21 @@ pub fn serialize_u32_le(value: u32) -> [u8; 4] {
     value.to_le_bytes()
 }
 
+/// Note-level hasher for a `cloak_layouts::domains` context
+#[cfg(not(feature = "commitment-keccak"))]
+fn note_hasher(context: &str) -> NoteHasher {
+    cloak_layouts::domains::hasher(context)
+}
+
+#[cfg(feature = "commitment-keccak")]
+fn note_hasher(_context: &str) -> NoteHasher {
+    NoteHasher::new()
+}
+
 /// Compute commitment: C = H(amount:u64 || r:32 || pk_spend:32)
 pub fn compute_commitment(amount: u64, r: &[u8; 32], pk_spend: &[u8; 32]) -> [u8; 32] {
-    let mut hasher = Hasher::new();
-    hasher.update(&serialize_u64_le(amount));
+    let mut hasher = note_hasher(cloak_layouts::domains::COMMITMENT);
+    hasher.update(serialize_u64_le(amount).as_slice());
     hasher.update(r);
     hasher.update(pk_spend);
     hasher.finalize().into()
@@ -28,33 +51,41 @@ pub fn compute_commitment(amount: u64, r: &[u8; 32], pk_spend: &[u8; 32]) -> [u8
 
 /// Compute pk_spend: pk_spend = H(sk_spend:32)
 pub fn compute_pk_spend(sk_spend: &[u8; 32]) -> [u8; 32] {
-    hash_blake3(sk_spend)
+    let mut hasher = note_hasher(cloak_layouts::domains::PK_SPEND);
+    hasher.update(sk_spend);
+    hasher.finalize().into()
 }
 
 /// Compute nullifier: nf = H(sk_spend:32 || leaf_index:u32)
 pub fn compute_nullifier(sk_spend: &[u8; 32], leaf_index: u32) -> [u8; 32] {
-    let mut hasher = Hasher::new();
+    let mut hasher = note_hasher(cloak_layouts::domains::NULLIFIER);
     hasher.update(sk_spend);
-    hasher.update(&serialize_u32_le(leaf_index));
+    hasher.update(serialize_u32_le(leaf_index).as_slice());
     hasher.finalize().into()
 }
 
 /// Compute outputs hash: H(output[0] || output[1] || ... || output[n-1])
-/// where output = address:32 || amount:u64
+/// where output = address:32 || amount:u64, in `cloak_layouts::hash_order`
 pub fn compute_outputs_hash(outputs: &[Output]) -> [u8; 32] {
-    let mut hasher = Hasher::new();
-    for output in outputs {
-        hasher.update(&output.address);
-        hasher.update(&serialize_u64_le(output.amount));
+    let mut ordered: Vec<&Output> = outputs.iter().collect();
+    cloak_layouts::hash_order(&mut ordered, |output| (output.address, output.amount));
+    let mut hasher = cloak_layouts::domains::hasher(cloak_layouts::domains::OUTPUTS_HASH);
+    for output in ordered {
+        hasher.update(&cloak_layouts::encode_output(
+            &output.address,
+            output.amount,
+        ));
     }
     hasher.finalize().into()
 }
 
 /// Calculate fee: fee = fixed_fee + (amount * variable_rate) / 1000
-pub fn calculate_fee(amount: u64, fee_bps: u16) -> u64 {
-    let fixed_fee = 2_500_000; // 0.0025 SOL
-    let variable_fee = (amount * fee_bps as u64) / 1_000; // 0.5% = 5/1000
-    fixed_fee + variable_fee
+pub fn calculate_fee(amount: u64, fee_bps: u16) -> Result<u64, FeeError> {
+    let schedule = FeeSchedule {
+        variable_per_mille: u64::from(fee_bps),
+        ..FeeSchedule::NATIVE
+    };
+    cloak_layouts::calculate_fee(amount, schedule)
 }
 
 /// Merkle path verification
@@ -90,18 +121,9 @@ pub fn verify_merkle_path(
     current == *root
 }
 
-/// Parse hex string to 32-byte array
+/// Parse hex string (optionally `0x`-prefixed) to 32-byte array
 pub fn parse_hex32(hex_str: &str) -> Result<[u8; 32]> {
-    let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
-    let bytes = hex::decode(hex_str).map_err(|e| anyhow!("Invalid hex string: {}", e))?;
-
-    if bytes.len() != 32 {
-        return Err(anyhow!("Expected 32 bytes, got {}", bytes.len()));
-    }
-
-    let mut result = [0u8; 32];
-    result.copy_from_slice(&bytes);
-    Ok(result)
+    cloak_layouts::parse_hex32(hex_str).map_err(|e| anyhow!("Invalid hex string: {}", e))
 }
 
 /// Parse base58 or hex string to 32-byte address
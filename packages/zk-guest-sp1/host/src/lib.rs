@@ -1,19 +1,66 @@
 pub mod encoding;
+pub mod validation;
+
+use std::{collections::BTreeMap, fs, path::Path, sync::OnceLock, time::Instant};
 
 use anyhow::Result;
-use sp1_sdk::{include_elf, Prover, ProverClient, SP1Stdin};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{
+    include_elf, CpuProver, ExecutionReport, HashableKey, Prover, ProverClient, SP1ProvingKey,
+    SP1Stdin, SP1VerifyingKey,
+};
 
 pub const ELF: &[u8] = include_elf!("zk-guest-sp1-guest");
 
 /// SP1 proof generation result
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofResult {
+    /// Versioned envelope (`cloak_proof_extract::ProofEnvelope`) around the
+    /// bincode SP1 proof bundle
     pub proof_bytes: Vec<u8>,
     pub public_inputs: Vec<u8>,
     pub generation_time_ms: u64,
     pub total_cycles: u64,
     pub total_syscalls: u64,
     pub execution_report: String, // Full execution report as formatted string
+    pub syscall_breakdown: SyscallBreakdown,
+}
+
+/// Structured view of the SP1 execution report, for programmatic
+/// performance regression tracking
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyscallBreakdown {
+    /// Syscall name -> number of invocations (zero counts omitted)
+    pub syscall_counts: BTreeMap<String, u64>,
+    /// `cycle-tracker` region name -> cycles spent, if the guest defines any
+    pub cycle_tracker: BTreeMap<String, u64>,
+}
+
+impl SyscallBreakdown {
+    /// Extract the breakdown from the report before it is formatted to a string
+    pub fn from_report(report: &ExecutionReport) -> Self {
+        let syscall_counts = report
+            .syscall_counts
+            .iter()
+            .filter(|(_, &count)| count > 0)
+            .map(|(code, &count)| (format!("{:?}", code), count))
+            .collect();
+        let cycle_tracker = report
+            .cycle_tracker
+            .iter()
+            .map(|(name, &cycles)| (name.clone(), cycles))
+            .collect();
+
+        Self {
+            syscall_counts,
+            cycle_tracker,
+        }
+    }
+
+    /// Sum of all syscall invocations; equals `ProofResult::total_syscalls`
+    pub fn total_syscalls(&self) -> u64 {
+        self.syscall_counts.values().sum()
+    }
 }
 
 /// Generate an SP1 proof directly from input data
@@ -25,69 +72,235 @@ pub fn generate_proof(
     public_inputs: &str,
     outputs: &str,
 ) -> Result<ProofResult> {
-    let start_time = std::time::Instant::now();
+    // Reject malformed input before spending any execution cycles
+    validation::validate_inputs(private_inputs, public_inputs, outputs)?;
+
+    ProverSetup::new().prove(private_inputs, public_inputs, outputs)
+}
+
+/// Generate an SP1 proof, reusing a previously generated one from `cache_dir`
+/// when the same inputs were already proven against the same guest ELF.
+///
+/// Entries are content-addressed by [`proof_cache_key`], which covers the
+/// combined input JSON and the vkey hash, so rebuilding the guest invalidates
+/// every existing entry.
+pub fn generate_proof_cached(
+    private_inputs: &str,
+    public_inputs: &str,
+    outputs: &str,
+    cache_dir: &Path,
+) -> Result<ProofResult> {
+    validation::validate_inputs(private_inputs, public_inputs, outputs)?;
 
-    // Convert to owned strings for thread safety
-    let private_inputs = private_inputs.to_string();
-    let public_inputs = public_inputs.to_string();
-    let outputs = outputs.to_string();
+    // Key setup is only needed for the first vkey hash and for cache misses;
+    // keys built for the former are reused if the first lookup misses
+    let (vkey_hash, setup) = match GUEST_VKEY_HASH.get() {
+        Some(vkey_hash) => (vkey_hash, None),
+        None => {
+            let setup = ProverSetup::new();
+            (
+                GUEST_VKEY_HASH.get_or_init(|| setup.vk.bytes32()),
+                Some(setup),
+            )
+        }
+    };
+    let key = proof_cache_key(private_inputs, public_inputs, outputs, vkey_hash);
 
-    let job =
-        move || -> Result<(sp1_sdk::SP1ProofWithPublicValues, u64, u64, String), anyhow::Error> {
-            let client = ProverClient::builder().cpu().build();
-            let (pk, _vk) = client.setup(ELF);
+    if let Some(cached) = load_cached_proof(cache_dir, &key)? {
+        return Ok(cached);
+    }
 
-            let combined_input = format!(
-                r#"{{
-                "private": {},
-                "public": {},
-                "outputs": {}
-            }}"#,
-                private_inputs, public_inputs, outputs
-            );
+    let setup = setup.unwrap_or_else(ProverSetup::new);
+    let result = setup.prove(private_inputs, public_inputs, outputs)?;
+    store_cached_proof(cache_dir, &key, &result)?;
+    Ok(result)
+}
+
+/// The guest ELF's vkey hash, memoized so cache hits skip key setup
+static GUEST_VKEY_HASH: OnceLock<String> = OnceLock::new();
+
+/// A CPU prover with the guest ELF's keys, set up once per proof
+struct ProverSetup {
+    client: CpuProver,
+    pk: SP1ProvingKey,
+    vk: SP1VerifyingKey,
+    /// Key setup counts towards `generation_time_ms`
+    started: Instant,
+}
+
+impl ProverSetup {
+    fn new() -> Self {
+        let started = Instant::now();
+        let client = ProverClient::builder().cpu().build();
+        let (pk, vk) = client.setup(ELF);
+        Self {
+            client,
+            pk,
+            vk,
+            started,
+        }
+    }
+
+    fn prove(
+        self,
+        private_inputs: &str,
+        public_inputs: &str,
+        outputs: &str,
+    ) -> Result<ProofResult> {
+        let Self {
+            client,
+            pk,
+            vk,
+            started,
+        } = self;
 
-            let mut stdin = SP1Stdin::new();
-            stdin.write(&combined_input);
+        // Convert to owned strings for thread safety
+        let private_inputs = private_inputs.to_string();
+        let public_inputs = public_inputs.to_string();
+        let outputs = outputs.to_string();
+
+        let job = move || -> Result<ProverArtifacts, anyhow::Error> {
+            let stdin = build_circuit_stdin(&private_inputs, &public_inputs, &outputs, None);
 
             // First, execute to get full execution report
             let (_, report) = client.execute(ELF, &stdin).run()?;
             let total_cycles = report.total_instruction_count();
             let total_syscalls = report.total_syscall_count();
+            let syscall_breakdown = SyscallBreakdown::from_report(&report);
             let execution_report = format!("{}", report); // Full formatted report
 
             // Then generate the proof
             let proof = client.prove(&pk, &stdin).groth16().run()?;
 
-            Ok((proof, total_cycles, total_syscalls, execution_report))
+            Ok((
+                proof,
+                vk.bytes32(),
+                total_cycles,
+                total_syscalls,
+                execution_report,
+                syscall_breakdown,
+            ))
         };
 
-    let (proof_result, total_cycles, total_syscalls, execution_report) = run_prover_job(job)?;
+        let (
+            proof_result,
+            vkey_hash,
+            total_cycles,
+            total_syscalls,
+            execution_report,
+            syscall_breakdown,
+        ) = run_prover_job(job)?;
+
+        // Serialize the full SP1ProofWithPublicValues bundle (needed by relay to extract proof)
+        // and wrap it in a versioned envelope so consumers don't depend on SP1's bincode layout.
+        // The relay reads it back with cloak_proof_extract::extract_from_envelope.
+        let sp1_bundle = bincode::serialize(&proof_result)?;
+        let proof_bundle =
+            cloak_proof_extract::ProofEnvelope::new(&sp1_bundle, &vkey_hash).encode();
+        let public_inputs_bytes = proof_result.public_values.to_vec();
 
-    // Serialize the full SP1ProofWithPublicValues bundle (needed by relay to extract proof)
-    // The relay will use cloak_proof_extract::extract_groth16_260 to get the 260-byte proof
-    let proof_bundle = bincode::serialize(&proof_result)?;
-    let public_inputs_bytes = proof_result.public_values.to_vec();
+        let generation_time = started.elapsed();
+
+        Ok(ProofResult {
+            proof_bytes: proof_bundle,
+            public_inputs: public_inputs_bytes,
+            generation_time_ms: generation_time.as_millis() as u64,
+            total_cycles,
+            total_syscalls,
+            execution_report,
+            syscall_breakdown,
+        })
+    }
+}
+
+/// Content-address key for a proof: BLAKE3(vkey_hash || combined input JSON), hex encoded
+pub fn proof_cache_key(
+    private_inputs: &str,
+    public_inputs: &str,
+    outputs: &str,
+    vkey_hash: &str,
+) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(vkey_hash.as_bytes());
+    hasher.update(combined_input_json(private_inputs, public_inputs, outputs, None).as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
 
-    let generation_time = start_time.elapsed();
+/// Load a cached proof for `key`, returning `None` on a cache miss
+pub fn load_cached_proof(cache_dir: &Path, key: &str) -> Result<Option<ProofResult>> {
+    let path = cache_dir.join(format!("{}.bin", key));
+    if !path.exists() {
+        return Ok(None);
+    }
+    let bytes = fs::read(&path)?;
+    Ok(Some(bincode::deserialize(&bytes)?))
+}
 
-    Ok(ProofResult {
-        proof_bytes: proof_bundle,
-        public_inputs: public_inputs_bytes,
-        generation_time_ms: generation_time.as_millis() as u64,
-        total_cycles,
-        total_syscalls,
-        execution_report,
-    })
+/// Persist a proof under `key`, creating `cache_dir` if needed
+pub fn store_cached_proof(cache_dir: &Path, key: &str, result: &ProofResult) -> Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    // Write to a temp file first so a crash never leaves a truncated entry behind
+    let tmp_path = cache_dir.join(format!("{}.bin.tmp", key));
+    fs::write(&tmp_path, bincode::serialize(result)?)?;
+    fs::rename(&tmp_path, cache_dir.join(format!("{}.bin", key)))?;
+    Ok(())
 }
 
+/// Guest stdin for one proof
+///
+/// Every entry point feeds the guest through here so it always receives the
+/// same `CircuitInputs` JSON. Each argument is the raw JSON of that section;
+/// `swap_params` is only set for swap-mode withdrawals.
+pub fn build_circuit_stdin(
+    private_inputs: &str,
+    public_inputs: &str,
+    outputs: &str,
+    swap_params: Option<&str>,
+) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&combined_input_json(
+        private_inputs,
+        public_inputs,
+        outputs,
+        swap_params,
+    ));
+    stdin
+}
+
+fn combined_input_json(
+    private_inputs: &str,
+    public_inputs: &str,
+    outputs: &str,
+    swap_params: Option<&str>,
+) -> String {
+    // Omitted rather than `null` so non-swap inputs (and their cache keys) are unchanged
+    let swap_params = swap_params
+        .map(|params| format!(",\n                \"swap_params\": {}", params))
+        .unwrap_or_default();
+    format!(
+        r#"{{
+                "private": {},
+                "public": {},
+                "outputs": {}{}
+            }}"#,
+        private_inputs, public_inputs, outputs, swap_params
+    )
+}
+
+/// Proof bundle plus execution stats produced by a prover job
+type ProverArtifacts = (
+    sp1_sdk::SP1ProofWithPublicValues,
+    String,
+    u64,
+    u64,
+    String,
+    SyscallBreakdown,
+);
+
 #[cfg(not(target_arch = "wasm32"))]
-fn run_prover_job<F>(
-    job: F,
-) -> Result<(sp1_sdk::SP1ProofWithPublicValues, u64, u64, String), anyhow::Error>
+fn run_prover_job<F>(job: F) -> Result<ProverArtifacts, anyhow::Error>
 where
-    F: FnOnce() -> Result<(sp1_sdk::SP1ProofWithPublicValues, u64, u64, String), anyhow::Error>
-        + Send
-        + 'static,
+    F: FnOnce() -> Result<ProverArtifacts, anyhow::Error> + Send + 'static,
 {
     match std::thread::spawn(job).join() {
         Ok(Ok(artifacts)) => Ok(artifacts),
@@ -99,11 +312,9 @@ where
 }
 
 #[cfg(target_arch = "wasm32")]
-fn run_prover_job<F>(
-    job: F,
-) -> Result<(sp1_sdk::SP1ProofWithPublicValues, u64, u64, String), anyhow::Error>
+fn run_prover_job<F>(job: F) -> Result<ProverArtifacts, anyhow::Error>
 where
-    F: FnOnce() -> Result<(sp1_sdk::SP1ProofWithPublicValues, u64, u64, String), anyhow::Error>,
+    F: FnOnce() -> Result<ProverArtifacts, anyhow::Error>,
 {
     job().map_err(Into::into)
 }
@@ -3,10 +3,9 @@ use std::{fs, path::PathBuf};
 use anyhow::{anyhow, Result};
 use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
-use sp1_sdk::{ProverClient, SP1Stdin};
+use sp1_sdk::ProverClient;
 
-mod encoding;
-use encoding::*;
+use zk_guest_sp1_host::{build_circuit_stdin, encoding::parse_hex32, validation::validate_inputs};
 
 // We'll load the ELF at runtime since the build path can vary
 // const GUEST_ELF: &[u8] = include_bytes!("../target/elf-compilation/zk-guest-sp1-guest");
@@ -50,17 +49,6 @@ enum Commands {
     },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct PrivateInputs {
-    pub amount: u64,
-    #[serde(with = "hex_string")]
-    pub r: [u8; 32],
-    #[serde(with = "hex_string")]
-    pub sk_spend: [u8; 32],
-    pub leaf_index: u32,
-    pub merkle_path: MerklePath,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 struct PublicInputs {
     #[serde(with = "hex_string")]
@@ -71,13 +59,8 @@ struct PublicInputs {
     #[serde(with = "hex_string")]
     pub outputs_hash: [u8; 32],
     pub amount: u64,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct CircuitInputs {
-    pub private: PrivateInputs,
-    pub public: PublicInputs,
-    pub outputs: Vec<Output>,
+    #[cfg(feature = "proof-freshness")]
+    pub generated_at: u64,
 }
 
 // Custom serde module for hex strings
@@ -141,25 +124,13 @@ fn prove_command(
     let outputs_json =
         fs::read_to_string(&outputs_path).map_err(|e| anyhow!("Failed to read outputs: {}", e))?;
 
-    let private_inputs: PrivateInputs = serde_json::from_str(&private_json)?;
-    let public_inputs: PublicInputs = serde_json::from_str(&public_json)?;
-    let outputs: Vec<Output> = serde_json::from_str(&outputs_json)?;
-
-    // Create combined input for guest
-    let circuit_inputs = CircuitInputs {
-        private: private_inputs,
-        public: public_inputs,
-        outputs,
-    };
-
-    let input_json = serde_json::to_string(&circuit_inputs)?;
+    validate_inputs(&private_json, &public_json, &outputs_json)?;
 
     println!("Generating proof...");
 
     // Setup prover
     let client = ProverClient::from_env();
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&input_json);
+    let stdin = build_circuit_stdin(&private_json, &public_json, &outputs_json, None);
 
     // Generate proof
     let guest_elf = std::fs::read(
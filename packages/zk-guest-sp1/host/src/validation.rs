@@ -0,0 +1,247 @@
+//! Pre-proving validation of the guest input JSON
+//!
+//! Mirrors the guest's `CircuitInputs` shape so malformed input is rejected
+//! with a clear error before any SP1 execution cycles are spent.
+
+use anyhow::{anyhow, Context, Result};
+use cloak_layouts::MERKLE_TREE_DEPTH;
+use serde::{Deserialize, Deserializer};
+
+use crate::encoding::{parse_address, parse_hex32, MerklePath, Output};
+
+/// Guest private inputs (hex-encoded 32-byte fields)
+// Fields are only parsed, never read back
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct GuestPrivateInputs {
+    amount: u64,
+    #[serde(deserialize_with = "hex32")]
+    r: [u8; 32],
+    #[serde(deserialize_with = "hex32")]
+    sk_spend: [u8; 32],
+    leaf_index: u32,
+    merkle_path: MerklePath,
+}
+
+/// Guest public inputs (hex-encoded 32-byte fields)
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct GuestPublicInputs {
+    #[serde(deserialize_with = "hex32")]
+    root: [u8; 32],
+    #[serde(deserialize_with = "hex32")]
+    nf: [u8; 32],
+    #[serde(deserialize_with = "hex32")]
+    outputs_hash: [u8; 32],
+    amount: u64,
+    #[cfg(feature = "proof-freshness")]
+    generated_at: u64,
+}
+
+/// Guest swap-mode parameters (hex or base58 addresses)
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct GuestSwapParams {
+    #[serde(deserialize_with = "address")]
+    output_mint: [u8; 32],
+    #[serde(deserialize_with = "address")]
+    recipient_ata: [u8; 32],
+    min_output_amount: u64,
+}
+
+/// The combined JSON the guest reads from stdin
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct GuestCircuitInputs {
+    private: GuestPrivateInputs,
+    public: GuestPublicInputs,
+    outputs: Vec<Output>,
+    swap_params: Option<GuestSwapParams>,
+}
+
+fn hex32<'de, D>(deserializer: D) -> Result<[u8; 32], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse_hex32(&s).map_err(serde::de::Error::custom)
+}
+
+fn address<'de, D>(deserializer: D) -> Result<[u8; 32], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    parse_address(&s).map_err(serde::de::Error::custom)
+}
+
+/// Check that `private`/`public`/`outputs` parse into the guest's input shape:
+/// all required fields present, 32-byte hex values of the right length and a
+/// well-formed Merkle path of `MERKLE_TREE_DEPTH` levels.
+pub fn validate_inputs(private_inputs: &str, public_inputs: &str, outputs: &str) -> Result<()> {
+    let private: GuestPrivateInputs =
+        serde_json::from_str(private_inputs).context("Invalid private inputs")?;
+    serde_json::from_str::<GuestPublicInputs>(public_inputs).context("Invalid public inputs")?;
+    serde_json::from_str::<Vec<Output>>(outputs).context("Invalid outputs")?;
+
+    let path = &private.merkle_path;
+    if path.path_elements.len() != path.path_indices.len() {
+        return Err(anyhow!(
+            "Invalid private inputs: merkle_path has {} path_elements but {} path_indices",
+            path.path_elements.len(),
+            path.path_indices.len()
+        ));
+    }
+    if path.path_elements.len() != MERKLE_TREE_DEPTH {
+        return Err(anyhow!(
+            "Invalid private inputs: merkle_path has {} levels, expected {}",
+            path.path_elements.len(),
+            MERKLE_TREE_DEPTH
+        ));
+    }
+    if let Some(index) = path.path_indices.iter().find(|&&i| i > 1) {
+        return Err(anyhow!(
+            "Invalid private inputs: merkle_path index {} is not 0 or 1",
+            index
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE: &str = include_str!("../../examples/private.example.json");
+    const PUBLIC: &str = include_str!("../../examples/public.example.json");
+    const OUTPUTS: &str = include_str!("../../examples/outputs.example.json");
+
+    /// The JSON string `build_circuit_stdin` hands the guest
+    fn stdin_json(stdin: &sp1_sdk::SP1Stdin) -> String {
+        assert_eq!(stdin.buffer.len(), 1);
+        bincode::deserialize(&stdin.buffer[0]).unwrap()
+    }
+
+    fn private_with(edit: impl FnOnce(&mut serde_json::Value)) -> String {
+        let mut value: serde_json::Value = serde_json::from_str(PRIVATE).unwrap();
+        edit(&mut value);
+        value.to_string()
+    }
+
+    #[test]
+    fn test_example_inputs_are_valid() {
+        validate_inputs(PRIVATE, PUBLIC, OUTPUTS).expect("examples should validate");
+    }
+
+    #[test]
+    fn test_missing_merkle_path_fails_fast() {
+        let private = private_with(|v| {
+            v.as_object_mut().unwrap().remove("merkle_path");
+        });
+
+        let err = validate_inputs(&private, PUBLIC, OUTPUTS).unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("Invalid private inputs"), "{}", message);
+        assert!(
+            message.contains("missing field `merkle_path`"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_short_hex_rejected() {
+        let private = private_with(|v| v["r"] = "2222".into());
+
+        let message = format!(
+            "{:#}",
+            validate_inputs(&private, PUBLIC, OUTPUTS).unwrap_err()
+        );
+        assert!(
+            message.contains("expected 64 hex digits (optionally 0x-prefixed), got 4"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_mismatched_merkle_path_rejected() {
+        let private =
+            private_with(|v| v["merkle_path"]["path_indices"] = serde_json::json!([0, 1]));
+
+        let message = format!(
+            "{:#}",
+            validate_inputs(&private, PUBLIC, OUTPUTS).unwrap_err()
+        );
+        assert!(
+            message.contains(&format!(
+                "{} path_elements but 2 path_indices",
+                MERKLE_TREE_DEPTH
+            )),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_wrong_depth_merkle_path_rejected() {
+        let private = private_with(|v| {
+            let path = &mut v["merkle_path"];
+            path["path_elements"] = serde_json::json!(vec![hex::encode([1u8; 32]); 2]);
+            path["path_indices"] = serde_json::json!([0, 1]);
+        });
+
+        let message = format!(
+            "{:#}",
+            validate_inputs(&private, PUBLIC, OUTPUTS).unwrap_err()
+        );
+        assert!(
+            message.contains(&format!("2 levels, expected {}", MERKLE_TREE_DEPTH)),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_missing_outputs_hash_rejected() {
+        let mut public: serde_json::Value = serde_json::from_str(PUBLIC).unwrap();
+        public.as_object_mut().unwrap().remove("outputs_hash");
+
+        let message = format!(
+            "{:#}",
+            validate_inputs(PRIVATE, &public.to_string(), OUTPUTS).unwrap_err()
+        );
+        assert!(message.contains("Invalid public inputs"), "{}", message);
+        assert!(
+            message.contains("missing field `outputs_hash`"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_circuit_stdin_matches_guest_shape() {
+        let json = stdin_json(&crate::build_circuit_stdin(PRIVATE, PUBLIC, OUTPUTS, None));
+        let inputs: GuestCircuitInputs = serde_json::from_str(&json).expect("guest shape");
+        assert!(inputs.swap_params.is_none());
+        assert_eq!(inputs.outputs.len(), 2);
+
+        let swap_params = serde_json::json!({
+            "output_mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "recipient_ata": hex::encode([7u8; 32]),
+            "min_output_amount": 1_000
+        })
+        .to_string();
+        let json = stdin_json(&crate::build_circuit_stdin(
+            PRIVATE,
+            PUBLIC,
+            OUTPUTS,
+            Some(&swap_params),
+        ));
+        let inputs: GuestCircuitInputs = serde_json::from_str(&json).expect("guest shape");
+        let swap = inputs.swap_params.expect("swap params");
+        assert_eq!(swap.recipient_ata, [7u8; 32]);
+        assert_eq!(swap.min_output_amount, 1_000);
+    }
+}
@@ -4,7 +4,10 @@ use anyhow::Result;
 use blake3::Hasher;
 use serde::{Deserialize, Serialize};
 use sp1_sdk::{ProverClient, SP1Stdin};
-use zk_guest_sp1_host::encoding::*;
+use zk_guest_sp1_host::{
+    encoding::*, load_cached_proof, proof_cache_key, store_cached_proof, ProofResult,
+    SyscallBreakdown,
+};
 
 // Custom serialization for hex strings
 mod hex_string {
@@ -270,6 +273,37 @@ fn test_valid_proof_generation_and_verification() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_syscall_breakdown_matches_total_syscalls() -> Result<()> {
+    // Skip execution in debug mode as SP1 SDK requires release mode
+    if cfg!(debug_assertions) {
+        println!(
+            "⚠️  Skipping execution report test in debug mode (SP1 SDK requires release mode)"
+        );
+        return Ok(());
+    }
+
+    let inputs = load_test_inputs()?;
+    let guest_inputs = convert_to_guest_inputs(&inputs);
+    let input_json = serde_json::to_string(&guest_inputs)?;
+
+    let client = ProverClient::from_env();
+    let guest_elf = find_guest_elf()?;
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&input_json);
+
+    // Execution alone is enough to get the report
+    let (_, report) = client.execute(&guest_elf, &stdin).run()?;
+    let breakdown = SyscallBreakdown::from_report(&report);
+
+    assert_eq!(breakdown.total_syscalls(), report.total_syscall_count());
+    assert!(breakdown.syscall_counts.values().all(|&count| count > 0));
+
+    println!("✅ Syscall breakdown: {:?}", breakdown.syscall_counts);
+    Ok(())
+}
+
 #[test]
 fn test_invalid_merkle_path_fails() -> Result<()> {
     // Test the constraint logic without SP1 proving (which requires release mode)
@@ -310,7 +344,7 @@ fn test_conservation_failure() -> Result<()> {
 
     // Use a larger amount to avoid overflow with the fixed fee
     let test_amount = 10_000_000; // 10M lamports (0.01 SOL)
-    let fee = calculate_fee(test_amount, inputs.public.fee_bps);
+    let fee = calculate_fee(test_amount, inputs.public.fee_bps).unwrap();
     let expected_outputs_sum = test_amount - fee;
 
     let invalid_outputs = vec![Output {
@@ -353,6 +387,49 @@ fn test_invalid_outputs_hash_fails() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_proof_cache_hits_identical_inputs_and_misses_changed_outputs() -> Result<()> {
+    // Exercise the cache layer without SP1 proving
+    let cache_dir = std::env::temp_dir().join(format!("cloak-proof-cache-{}", std::process::id()));
+    let vkey_hash = "0x00aa";
+    let private = r#"{"amount":1000}"#;
+    let public = r#"{"amount":1000}"#;
+    let outputs = r#"[{"address":"11","amount":900}]"#;
+
+    let key = proof_cache_key(private, public, outputs, vkey_hash);
+    assert!(load_cached_proof(&cache_dir, &key)?.is_none());
+
+    let result = ProofResult {
+        proof_bytes: vec![1, 2, 3],
+        public_inputs: vec![4; 104],
+        generation_time_ms: 42,
+        total_cycles: 7,
+        total_syscalls: 1,
+        execution_report: "report".to_string(),
+        syscall_breakdown: SyscallBreakdown::default(),
+    };
+    store_cached_proof(&cache_dir, &key, &result)?;
+
+    // Identical inputs produce the same key and hit the cache
+    let same_key = proof_cache_key(private, public, outputs, vkey_hash);
+    let cached = load_cached_proof(&cache_dir, &same_key)?.expect("cache hit");
+    assert_eq!(cached.proof_bytes, result.proof_bytes);
+    assert_eq!(cached.public_inputs, result.public_inputs);
+
+    // A changed output misses
+    let changed_outputs = r#"[{"address":"11","amount":899}]"#;
+    let changed_key = proof_cache_key(private, public, changed_outputs, vkey_hash);
+    assert_ne!(changed_key, key);
+    assert!(load_cached_proof(&cache_dir, &changed_key)?.is_none());
+
+    // A different guest ELF (vkey) misses as well
+    let other_vkey = proof_cache_key(private, public, outputs, "0x00bb");
+    assert!(load_cached_proof(&cache_dir, &other_vkey)?.is_none());
+
+    fs::remove_dir_all(&cache_dir)?;
+    Ok(())
+}
+
 // Unit tests for encoding functions
 #[cfg(test)]
 mod encoding_tests {
@@ -390,6 +467,8 @@ mod encoding_tests {
         assert_eq!(nullifier, nullifier2);
     }
 
+    // `sorted-outputs` hashes in canonical order instead
+    #[cfg(not(feature = "sorted-outputs"))]
     #[test]
     fn test_outputs_hash_order_sensitive() {
         let outputs1 = vec![
@@ -454,9 +533,14 @@ mod encoding_tests {
     #[test]
     fn test_fee_calculation() {
         // Fixed fee: 2,500,000 lamports (0.0025 SOL) + variable fee
-        assert_eq!(calculate_fee(1000000, 60), 2_500_000 + 60_000); // 0.6% variable + fixed
-        assert_eq!(calculate_fee(1000000, 100), 2_500_000 + 100_000); // 1% variable + fixed
-        assert_eq!(calculate_fee(1000000, 0), 2_500_000); // 0% variable + fixed
+        assert_eq!(calculate_fee(1000000, 60), Ok(2_500_000 + 60_000)); // 0.6% variable + fixed
+        assert_eq!(calculate_fee(1000000, 100), Ok(2_500_000 + 100_000)); // 1% variable + fixed
+        assert_eq!(calculate_fee(1000000, 0), Ok(2_500_000)); // 0% variable + fixed
+
+        // No intermediate overflow near u64::MAX; only a rate above 100% can
+        // push the fee itself past u64
+        assert_eq!(calculate_fee(u64::MAX, 5), Ok(u64::MAX / 200 + 2_500_000));
+        assert_eq!(calculate_fee(u64::MAX, 2_000), Err(FeeError::Overflow));
     }
 
     #[test]
@@ -16,6 +16,23 @@ pub const MAX_FEE_SHARE_BPS: u16 = 5000;
 /// Maximum batch size to prevent DoS
 pub const MAX_BATCH_SIZE: u16 = 20;
 
+/// Bounds for admin-updated reveal_window/claim_window (slots)
+pub const MIN_WINDOW_SLOTS: u64 = 1;
+pub const MAX_WINDOW_SLOTS: u64 = 10_000;
+
+/// Miner consume-rate EMA scale (10_000 = every recent event was a consume)
+pub const CONSUME_RATE_SCALE: u64 = 10_000;
+
+/// Miner consume-rate EMA smoothing: alpha = 1/2^shift (1/8)
+pub const CONSUME_RATE_EMA_SHIFT: u32 = 3;
+
+/// Bytes of each consuming withdraw's nullifier recorded in a claim's reserved space
+pub const NULLIFIER_FINGERPRINT_LEN: usize = 4;
+
+/// Consumes a claim can bind: 18 fingerprints fill 72 of the 75 reserved bytes.
+/// Older claims mined with a larger max_consumes stop after this many.
+pub const MAX_BOUND_CONSUMES: u16 = 18;
+
 /// SlotHashes sysvar pubkey
 pub const SLOT_HASHES_SYSVAR: [u8; 32] = [
     0x06, 0xa7, 0xd5, 0x17, 0x19, 0x2c, 0x56, 0x8e, 0xe0, 0x8a, 0x84, 0x5f, 0x73, 0xd2, 0x97, 0x88,
@@ -55,6 +55,12 @@ pub enum ScrambleError {
     BatchHashMismatch = 24,
     /// Invalid instruction discriminator
     InvalidTag = 25,
+    /// Reveal/claim window outside allowed bounds
+    InvalidWindow = 26,
+    /// Claim already consumed by a withdraw with this nullifier
+    NullifierAlreadyConsumed = 27,
+    /// max_consumes exceeds the consumes a claim can bind to nullifiers
+    TooManyConsumes = 28,
 }
 
 impl From<ScrambleError> for ProgramError {
@@ -16,8 +16,8 @@ pub fn process_consume_claim_instruction(
     instruction_data: &[u8],
 ) -> ProgramResult {
     // Parse instruction data
-    // Layout: expected_miner_authority(32) + expected_batch_hash(32) = 64 bytes
-    if instruction_data.len() < 64 {
+    // Layout: expected_miner_authority(32) + expected_batch_hash(32) + nullifier(32) = 96 bytes
+    if instruction_data.len() < 96 {
         return Err(ScrambleError::InvalidTag.into());
     }
 
@@ -27,6 +27,9 @@ pub fn process_consume_claim_instruction(
     let expected_batch_hash: [u8; 32] = instruction_data[32..64]
         .try_into()
         .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let nullifier: [u8; 32] = instruction_data[64..96]
+        .try_into()
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
     // Parse accounts
     let [claim_account, miner_account, registry_account, shield_pool_program, _clock_sysvar, ..] =
         accounts
@@ -75,9 +78,15 @@ pub fn process_consume_claim_instruction(
         return Err(ScrambleError::InvalidClaimStatus.into());
     }
 
+    // Anti-replay: one consume per withdraw nullifier
+    if claim.is_consumed_by(&nullifier) {
+        return Err(ScrambleError::NullifierAlreadyConsumed.into());
+    }
+
     // Consume one unit
     let was_fully_consumed = claim.consumed_count() == claim.max_consumes();
 
+    claim.bind_consumer(&nullifier)?;
     claim.consume()?;
 
     let is_now_fully_consumed = claim.consumed_count() == claim.max_consumes();
@@ -10,6 +10,7 @@ use pinocchio::{
 use pinocchio_system::instructions::CreateAccount;
 
 use crate::{
+    constants::MAX_BOUND_CONSUMES,
     error::ScrambleError,
     state::{Claim, Miner, ScrambleRegistry},
     utils::{u256_lt, verify_pow},
@@ -119,6 +120,11 @@ pub fn process_mine_claim_instruction(
         return Err(ScrambleError::BatchSizeExceedsMaxK.into());
     }
 
+    // Every consume must fit a nullifier fingerprint in the claim
+    if max_consumes > MAX_BOUND_CONSUMES {
+        return Err(ScrambleError::TooManyConsumes.into());
+    }
+
     // 6. Verify max_consumes > 0
     if max_consumes == 0 {
         return Err(ScrambleError::InvalidBatchSize.into());
@@ -2,6 +2,7 @@ pub mod consume_claim;
 pub mod initialize;
 pub mod mine_claim;
 pub mod reveal_claim;
+pub mod update_windows;
 
 use pinocchio::program_error::ProgramError;
 
@@ -13,6 +14,7 @@ pub enum ScrambleRegistryInstruction {
     MineClaim = 2,
     RevealClaim = 3,
     ConsumeClaim = 4,
+    UpdateWindows = 5,
 }
 
 impl TryFrom<&u8> for ScrambleRegistryInstruction {
@@ -25,6 +27,7 @@ impl TryFrom<&u8> for ScrambleRegistryInstruction {
             2 => Ok(Self::MineClaim),
             3 => Ok(Self::RevealClaim),
             4 => Ok(Self::ConsumeClaim),
+            5 => Ok(Self::UpdateWindows),
             _ => Err(ScrambleError::InvalidTag.into()),
         }
     }
@@ -0,0 +1,60 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    constants::{MAX_WINDOW_SLOTS, MIN_WINDOW_SLOTS},
+    error::ScrambleError,
+    state::ScrambleRegistry,
+};
+
+#[inline(always)]
+pub fn process_update_windows_instruction(
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Parse instruction data
+    // Layout: reveal_window(8) + claim_window(8) = 16 bytes
+    if instruction_data.len() < 16 {
+        return Err(ScrambleError::InvalidTag.into());
+    }
+
+    let reveal_window = u64::from_le_bytes(
+        instruction_data[0..8]
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+    );
+    let claim_window = u64::from_le_bytes(
+        instruction_data[8..16]
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?,
+    );
+    // Parse accounts
+    let [registry_account, admin_authority, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Verify signer
+    if !admin_authority.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if registry_account.owner() != &crate::ID {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+
+    // Load registry and verify admin
+    let mut registry = ScrambleRegistry::from_account_info(registry_account)?;
+    if registry.admin() != admin_authority.key() {
+        return Err(ScrambleError::InvalidAdminAuthority.into());
+    }
+
+    // Validate bounds
+    let window_bounds = MIN_WINDOW_SLOTS..=MAX_WINDOW_SLOTS;
+    if !window_bounds.contains(&reveal_window) || !window_bounds.contains(&claim_window) {
+        return Err(ScrambleError::InvalidWindow.into());
+    }
+
+    // Existing claims keep the expiry computed when they were revealed
+    registry.set_windows(reveal_window, claim_window);
+
+    Ok(())
+}
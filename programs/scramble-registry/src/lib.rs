@@ -4,6 +4,7 @@ use instructions::{
     initialize::{process_initialize_registry_instruction, process_register_miner_instruction},
     mine_claim::process_mine_claim_instruction,
     reveal_claim::process_reveal_claim_instruction,
+    update_windows::process_update_windows_instruction,
 };
 use pinocchio::{
     account_info::AccountInfo, default_allocator, default_panic_handler, program_entrypoint,
@@ -59,5 +60,8 @@ pub fn process_instruction(
         ScrambleRegistryInstruction::ConsumeClaim => {
             process_consume_claim_instruction(accounts, instruction_data)
         }
+        ScrambleRegistryInstruction::UpdateWindows => {
+            process_update_windows_instruction(accounts, instruction_data)
+        }
     }
 }
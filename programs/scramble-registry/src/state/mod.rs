@@ -1,5 +1,13 @@
+use cloak_layouts::{claim, miner, registry};
 use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
 
+use crate::{
+    constants::{
+        CONSUME_RATE_EMA_SHIFT, CONSUME_RATE_SCALE, MAX_BOUND_CONSUMES, NULLIFIER_FINGERPRINT_LEN,
+    },
+    error::ScrambleError,
+};
+
 /// ClaimStatus - Status of a PoW claim
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -34,13 +42,19 @@ impl ClaimStatus {
 pub struct ScrambleRegistry(*mut u8);
 
 impl ScrambleRegistry {
-    pub const SIZE: usize = 32 + 32 + 8 + 8 + 8 + 2 + 8 + 8 + 2 + 32 + 32 + 8 + 8;
+    pub const SIZE: usize = registry::SIZE;
 
     #[inline(always)]
     pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
         unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
     }
 
+    /// Test-only view over raw account data; must not outlive `data`
+    #[cfg(test)]
+    pub(crate) fn from_data_unchecked(data: &mut [u8]) -> Self {
+        Self(data.as_mut_ptr())
+    }
+
     #[inline(always)]
     pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
         if account_info.data_len() != Self::SIZE {
@@ -56,62 +70,62 @@ impl ScrambleRegistry {
 
     #[inline(always)]
     pub fn current_difficulty(&self) -> &[u8; 32] {
-        unsafe { &*(self.0.add(32) as *const [u8; 32]) }
+        unsafe { &*(self.0.add(registry::CURRENT_DIFFICULTY) as *const [u8; 32]) }
     }
 
     #[inline(always)]
     pub fn last_retarget_slot(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(64) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(registry::LAST_RETARGET_SLOT) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn solutions_observed(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(72) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(registry::SOLUTIONS_OBSERVED) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn target_interval_slots(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(80) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(registry::TARGET_INTERVAL_SLOTS) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn fee_share_bps(&self) -> u16 {
-        unsafe { u16::from_le(*(self.0.add(88) as *const u16)) }
+        unsafe { u16::from_le(*(self.0.add(registry::FEE_SHARE_BPS) as *const u16)) }
     }
 
     #[inline(always)]
     pub fn reveal_window(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(90) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(registry::REVEAL_WINDOW) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn claim_window(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(98) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(registry::CLAIM_WINDOW) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn max_k(&self) -> u16 {
-        unsafe { u16::from_le(*(self.0.add(106) as *const u16)) }
+        unsafe { u16::from_le(*(self.0.add(registry::MAX_K) as *const u16)) }
     }
 
     #[inline(always)]
     pub fn min_difficulty(&self) -> &[u8; 32] {
-        unsafe { &*(self.0.add(108) as *const [u8; 32]) }
+        unsafe { &*(self.0.add(registry::MIN_DIFFICULTY) as *const [u8; 32]) }
     }
 
     #[inline(always)]
     pub fn max_difficulty(&self) -> &[u8; 32] {
-        unsafe { &*(self.0.add(140) as *const [u8; 32]) }
+        unsafe { &*(self.0.add(registry::MAX_DIFFICULTY) as *const [u8; 32]) }
     }
 
     #[inline(always)]
     pub fn total_claims(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(172) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(registry::TOTAL_CLAIMS) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn active_claims(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(180) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(registry::ACTIVE_CLAIMS) as *const u64)) }
     }
 
     #[inline(always)]
@@ -131,29 +145,50 @@ impl ScrambleRegistry {
             // admin
             core::ptr::copy_nonoverlapping(admin.as_ref().as_ptr(), self.0, 32);
             // current_difficulty
-            core::ptr::copy_nonoverlapping(initial_difficulty.as_ptr(), self.0.add(32), 32);
+            core::ptr::copy_nonoverlapping(
+                initial_difficulty.as_ptr(),
+                self.0.add(registry::CURRENT_DIFFICULTY),
+                32,
+            );
             // last_retarget_slot
-            *(self.0.add(64) as *mut u64) = 0u64.to_le();
+            *(self.0.add(registry::LAST_RETARGET_SLOT) as *mut u64) = 0u64.to_le();
             // solutions_observed
-            *(self.0.add(72) as *mut u64) = 0u64.to_le();
+            *(self.0.add(registry::SOLUTIONS_OBSERVED) as *mut u64) = 0u64.to_le();
             // target_interval_slots
-            *(self.0.add(80) as *mut u64) = target_interval_slots.to_le();
+            *(self.0.add(registry::TARGET_INTERVAL_SLOTS) as *mut u64) =
+                target_interval_slots.to_le();
             // fee_share_bps
-            *(self.0.add(88) as *mut u16) = fee_share_bps.to_le();
+            *(self.0.add(registry::FEE_SHARE_BPS) as *mut u16) = fee_share_bps.to_le();
             // reveal_window
-            *(self.0.add(90) as *mut u64) = reveal_window.to_le();
+            *(self.0.add(registry::REVEAL_WINDOW) as *mut u64) = reveal_window.to_le();
             // claim_window
-            *(self.0.add(98) as *mut u64) = claim_window.to_le();
+            *(self.0.add(registry::CLAIM_WINDOW) as *mut u64) = claim_window.to_le();
             // max_k
-            *(self.0.add(106) as *mut u16) = max_k.to_le();
+            *(self.0.add(registry::MAX_K) as *mut u16) = max_k.to_le();
             // min_difficulty
-            core::ptr::copy_nonoverlapping(min_difficulty.as_ptr(), self.0.add(108), 32);
+            core::ptr::copy_nonoverlapping(
+                min_difficulty.as_ptr(),
+                self.0.add(registry::MIN_DIFFICULTY),
+                32,
+            );
             // max_difficulty
-            core::ptr::copy_nonoverlapping(max_difficulty.as_ptr(), self.0.add(140), 32);
+            core::ptr::copy_nonoverlapping(
+                max_difficulty.as_ptr(),
+                self.0.add(registry::MAX_DIFFICULTY),
+                32,
+            );
             // total_claims
-            *(self.0.add(172) as *mut u64) = 0u64.to_le();
+            *(self.0.add(registry::TOTAL_CLAIMS) as *mut u64) = 0u64.to_le();
             // active_claims
-            *(self.0.add(180) as *mut u64) = 0u64.to_le();
+            *(self.0.add(registry::ACTIVE_CLAIMS) as *mut u64) = 0u64.to_le();
+        }
+    }
+
+    #[inline(always)]
+    pub fn set_windows(&mut self, reveal_window: u64, claim_window: u64) {
+        unsafe {
+            *(self.0.add(registry::REVEAL_WINDOW) as *mut u64) = reveal_window.to_le();
+            *(self.0.add(registry::CLAIM_WINDOW) as *mut u64) = claim_window.to_le();
         }
     }
 
@@ -161,10 +196,11 @@ impl ScrambleRegistry {
     pub fn record_solution(&mut self) {
         unsafe {
             let current = self.solutions_observed();
-            *(self.0.add(72) as *mut u64) = current.saturating_add(1).to_le();
+            *(self.0.add(registry::SOLUTIONS_OBSERVED) as *mut u64) =
+                current.saturating_add(1).to_le();
 
             let total = self.total_claims();
-            *(self.0.add(172) as *mut u64) = total.saturating_add(1).to_le();
+            *(self.0.add(registry::TOTAL_CLAIMS) as *mut u64) = total.saturating_add(1).to_le();
         }
     }
 
@@ -172,7 +208,7 @@ impl ScrambleRegistry {
     pub fn increment_active(&mut self) {
         unsafe {
             let active = self.active_claims();
-            *(self.0.add(180) as *mut u64) = active.saturating_add(1).to_le();
+            *(self.0.add(registry::ACTIVE_CLAIMS) as *mut u64) = active.saturating_add(1).to_le();
         }
     }
 
@@ -180,7 +216,7 @@ impl ScrambleRegistry {
     pub fn decrement_active(&mut self) {
         unsafe {
             let active = self.active_claims();
-            *(self.0.add(180) as *mut u64) = active.saturating_sub(1).to_le();
+            *(self.0.add(registry::ACTIVE_CLAIMS) as *mut u64) = active.saturating_sub(1).to_le();
         }
     }
 }
@@ -188,20 +224,42 @@ impl ScrambleRegistry {
 /// Miner: PDA per authority (anti-key-grinding)
 ///
 /// Layout: [authority: 32][total_mined: 8][total_consumed: 8][registered_at_slot: 8]
-/// Total: 56 bytes
-pub struct Miner(*mut u8);
+///         [consume_rate_bps: 8]
+/// Total: 64 bytes
+/// Miners registered before the consume rate (56 bytes) are accepted with a
+/// zero rate that never updates
+pub struct Miner {
+    data: *mut u8,
+    len: usize,
+}
 
 impl Miner {
-    pub const SIZE: usize = 32 + 8 + 8 + 8;
+    pub const SIZE: usize = miner::SIZE;
+    pub const LEGACY_SIZE: usize = miner::LEGACY_SIZE;
 
     #[inline(always)]
     pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
-        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
+        unsafe {
+            let data = account_info.borrow_mut_data_unchecked();
+            Self {
+                data: data.as_mut_ptr(),
+                len: data.len(),
+            }
+        }
+    }
+
+    /// Test-only view over raw account data; must not outlive `data`
+    #[cfg(test)]
+    pub(crate) fn from_data_unchecked(data: &mut [u8]) -> Self {
+        Self {
+            data: data.as_mut_ptr(),
+            len: data.len(),
+        }
     }
 
     #[inline(always)]
     pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
-        if account_info.data_len() != Self::SIZE {
+        if !miner::is_miner_size(account_info.data_len()) {
             return Err(ProgramError::InvalidAccountData);
         }
         Ok(Self::from_account_info_unchecked(account_info))
@@ -209,35 +267,47 @@ impl Miner {
 
     #[inline(always)]
     pub fn authority(&self) -> &Pubkey {
-        unsafe { &*(self.0 as *const Pubkey) }
+        unsafe { &*(self.data as *const Pubkey) }
     }
 
     #[inline(always)]
     pub fn total_mined(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(32) as *const u64)) }
+        unsafe { u64::from_le(*(self.data.add(miner::TOTAL_MINED) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn total_consumed(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(40) as *const u64)) }
+        unsafe { u64::from_le(*(self.data.add(miner::TOTAL_CONSUMED) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn registered_at_slot(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(48) as *const u64)) }
+        unsafe { u64::from_le(*(self.data.add(miner::REGISTERED_AT_SLOT) as *const u64)) }
+    }
+
+    /// EMA of recent activity where each consume counts as CONSUME_RATE_SCALE
+    /// and each mine as 0, so miners whose claims keep getting used score high
+    #[inline(always)]
+    pub fn consume_rate_bps(&self) -> u64 {
+        if self.len < Self::SIZE {
+            return 0;
+        }
+        unsafe { u64::from_le(*(self.data.add(miner::CONSUME_RATE_BPS) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn initialize(&mut self, authority: &Pubkey, current_slot: u64) {
         unsafe {
             // authority
-            core::ptr::copy_nonoverlapping(authority.as_ref().as_ptr(), self.0, 32);
+            core::ptr::copy_nonoverlapping(authority.as_ref().as_ptr(), self.data, 32);
             // total_mined
-            *(self.0.add(32) as *mut u64) = 0u64.to_le();
+            *(self.data.add(miner::TOTAL_MINED) as *mut u64) = 0u64.to_le();
             // total_consumed
-            *(self.0.add(40) as *mut u64) = 0u64.to_le();
+            *(self.data.add(miner::TOTAL_CONSUMED) as *mut u64) = 0u64.to_le();
             // registered_at_slot
-            *(self.0.add(48) as *mut u64) = current_slot.to_le();
+            *(self.data.add(miner::REGISTERED_AT_SLOT) as *mut u64) = current_slot.to_le();
+            // consume_rate_bps
+            *(self.data.add(miner::CONSUME_RATE_BPS) as *mut u64) = 0u64.to_le();
         }
     }
 
@@ -245,15 +315,37 @@ impl Miner {
     pub fn record_mine(&mut self) {
         unsafe {
             let mined = self.total_mined();
-            *(self.0.add(32) as *mut u64) = mined.saturating_add(1).to_le();
+            *(self.data.add(miner::TOTAL_MINED) as *mut u64) = mined.saturating_add(1).to_le();
         }
+        self.update_consume_rate(0);
     }
 
     #[inline(always)]
     pub fn record_consume(&mut self) {
         unsafe {
             let consumed = self.total_consumed();
-            *(self.0.add(40) as *mut u64) = consumed.saturating_add(1).to_le();
+            *(self.data.add(miner::TOTAL_CONSUMED) as *mut u64) =
+                consumed.saturating_add(1).to_le();
+        }
+        self.update_consume_rate(CONSUME_RATE_SCALE);
+    }
+
+    /// Move the EMA 1/2^CONSUME_RATE_EMA_SHIFT of the way towards `sample`,
+    /// rounding away from the current value so it can reach both bounds
+    #[inline(always)]
+    fn update_consume_rate(&mut self, sample: u64) {
+        if self.len < Self::SIZE {
+            return;
+        }
+        let rate = self.consume_rate_bps().min(CONSUME_RATE_SCALE);
+        let round = (1u64 << CONSUME_RATE_EMA_SHIFT) - 1;
+        let updated = if sample >= rate {
+            rate + ((sample - rate + round) >> CONSUME_RATE_EMA_SHIFT)
+        } else {
+            rate - ((rate - sample + round) >> CONSUME_RATE_EMA_SHIFT)
+        };
+        unsafe {
+            *(self.data.add(miner::CONSUME_RATE_BPS) as *mut u64) = updated.to_le();
         }
     }
 }
@@ -262,18 +354,25 @@ impl Miner {
 ///
 /// Layout: [miner_authority: 32][batch_hash: 32][slot: 8][slot_hash: 32][nonce: 16]
 ///         [proof_hash: 32][mined_at_slot: 8][revealed_at_slot: 8][consumed_count: 2]
-///         [max_consumes: 2][expires_at_slot: 8][status: 1][_reserved: 75]
+///         [max_consumes: 2][expires_at_slot: 8][status: 1]
+///         [consumer_fingerprints: 18 * 4][_reserved: 3]
 /// Total: 256 bytes (aligned)
 pub struct Claim(*mut u8);
 
 impl Claim {
-    pub const SIZE: usize = 256;
+    pub const SIZE: usize = claim::SIZE;
 
     #[inline(always)]
     pub fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
         unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
     }
 
+    /// Test-only view over raw account data; must not outlive `data`
+    #[cfg(test)]
+    pub(crate) fn from_data_unchecked(data: &mut [u8]) -> Self {
+        Self(data.as_mut_ptr())
+    }
+
     #[inline(always)]
     pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
         if account_info.data_len() != Self::SIZE {
@@ -289,63 +388,63 @@ impl Claim {
 
     #[inline(always)]
     pub fn batch_hash(&self) -> &[u8; 32] {
-        unsafe { &*(self.0.add(32) as *const [u8; 32]) }
+        unsafe { &*(self.0.add(claim::BATCH_HASH) as *const [u8; 32]) }
     }
 
     #[inline(always)]
     pub fn slot(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(64) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(claim::SLOT) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn slot_hash(&self) -> &[u8; 32] {
-        unsafe { &*(self.0.add(72) as *const [u8; 32]) }
+        unsafe { &*(self.0.add(claim::SLOT_HASH) as *const [u8; 32]) }
     }
 
     #[inline(always)]
     pub fn nonce(&self) -> u128 {
-        unsafe { u128::from_le(*(self.0.add(104) as *const u128)) }
+        unsafe { u128::from_le(*(self.0.add(claim::NONCE) as *const u128)) }
     }
 
     #[inline(always)]
     pub fn proof_hash(&self) -> &[u8; 32] {
-        unsafe { &*(self.0.add(120) as *const [u8; 32]) }
+        unsafe { &*(self.0.add(claim::PROOF_HASH) as *const [u8; 32]) }
     }
 
     #[inline(always)]
     pub fn mined_at_slot(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(152) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(claim::MINED_AT_SLOT) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn revealed_at_slot(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(160) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(claim::REVEALED_AT_SLOT) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn consumed_count(&self) -> u16 {
-        unsafe { u16::from_le(*(self.0.add(168) as *const u16)) }
+        unsafe { u16::from_le(*(self.0.add(claim::CONSUMED_COUNT) as *const u16)) }
     }
 
     #[inline(always)]
     pub fn max_consumes(&self) -> u16 {
-        unsafe { u16::from_le(*(self.0.add(170) as *const u16)) }
+        unsafe { u16::from_le(*(self.0.add(claim::MAX_CONSUMES) as *const u16)) }
     }
 
     #[inline(always)]
     pub fn expires_at_slot(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(172) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(claim::EXPIRES_AT_SLOT) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn status(&self) -> ClaimStatus {
-        unsafe { ClaimStatus::from_u8(*self.0.add(180)).unwrap_or(ClaimStatus::Expired) }
+        unsafe { ClaimStatus::from_u8(*self.0.add(claim::STATUS)).unwrap_or(ClaimStatus::Expired) }
     }
 
     #[inline(always)]
     fn set_status(&mut self, status: ClaimStatus) {
         unsafe {
-            *self.0.add(180) = status as u8;
+            *self.0.add(claim::STATUS) = status as u8;
         }
     }
 
@@ -366,37 +465,42 @@ impl Claim {
             // miner_authority
             core::ptr::copy_nonoverlapping(miner_authority.as_ref().as_ptr(), self.0, 32);
             // batch_hash
-            core::ptr::copy_nonoverlapping(batch_hash.as_ptr(), self.0.add(32), 32);
+            core::ptr::copy_nonoverlapping(batch_hash.as_ptr(), self.0.add(claim::BATCH_HASH), 32);
             // slot
-            *(self.0.add(64) as *mut u64) = slot.to_le();
+            *(self.0.add(claim::SLOT) as *mut u64) = slot.to_le();
             // slot_hash
-            core::ptr::copy_nonoverlapping(slot_hash.as_ptr(), self.0.add(72), 32);
+            core::ptr::copy_nonoverlapping(slot_hash.as_ptr(), self.0.add(claim::SLOT_HASH), 32);
             // nonce
-            *(self.0.add(104) as *mut u128) = nonce.to_le();
+            *(self.0.add(claim::NONCE) as *mut u128) = nonce.to_le();
             // proof_hash
-            core::ptr::copy_nonoverlapping(proof_hash.as_ptr(), self.0.add(120), 32);
+            core::ptr::copy_nonoverlapping(proof_hash.as_ptr(), self.0.add(claim::PROOF_HASH), 32);
             // mined_at_slot
-            *(self.0.add(152) as *mut u64) = current_slot.to_le();
+            *(self.0.add(claim::MINED_AT_SLOT) as *mut u64) = current_slot.to_le();
             // revealed_at_slot
-            *(self.0.add(160) as *mut u64) = 0u64.to_le();
+            *(self.0.add(claim::REVEALED_AT_SLOT) as *mut u64) = 0u64.to_le();
             // consumed_count
-            *(self.0.add(168) as *mut u16) = 0u16.to_le();
+            *(self.0.add(claim::CONSUMED_COUNT) as *mut u16) = 0u16.to_le();
             // max_consumes
-            *(self.0.add(170) as *mut u16) = max_consumes.to_le();
+            *(self.0.add(claim::MAX_CONSUMES) as *mut u16) = max_consumes.to_le();
             // expires_at_slot
-            *(self.0.add(172) as *mut u64) = 0u64.to_le();
+            *(self.0.add(claim::EXPIRES_AT_SLOT) as *mut u64) = 0u64.to_le();
             // status
-            *self.0.add(180) = ClaimStatus::Mined as u8;
+            *self.0.add(claim::STATUS) = ClaimStatus::Mined as u8;
             // Zero out reserved space
-            core::ptr::write_bytes(self.0.add(181), 0, 75);
+            core::ptr::write_bytes(
+                self.0.add(claim::CONSUMER_FINGERPRINTS),
+                0,
+                claim::SIZE - claim::CONSUMER_FINGERPRINTS,
+            );
         }
     }
 
     #[inline(always)]
     pub fn reveal(&mut self, current_slot: u64, claim_window: u64) {
         unsafe {
-            *(self.0.add(160) as *mut u64) = current_slot.to_le();
-            *(self.0.add(172) as *mut u64) = current_slot.saturating_add(claim_window).to_le();
+            *(self.0.add(claim::REVEALED_AT_SLOT) as *mut u64) = current_slot.to_le();
+            *(self.0.add(claim::EXPIRES_AT_SLOT) as *mut u64) =
+                current_slot.saturating_add(claim_window).to_le();
         }
         self.set_status(ClaimStatus::Revealed);
     }
@@ -411,7 +515,7 @@ impl Claim {
         }
 
         unsafe {
-            *(self.0.add(168) as *mut u16) = consumed.saturating_add(1).to_le();
+            *(self.0.add(claim::CONSUMED_COUNT) as *mut u16) = consumed.saturating_add(1).to_le();
         }
 
         if consumed + 1 == max {
@@ -442,6 +546,43 @@ impl Claim {
             && self.consumed_count() < self.max_consumes()
     }
 
+    /// Whether a withdraw with this nullifier already consumed the claim
+    #[inline(always)]
+    pub fn is_consumed_by(&self, nullifier: &[u8; 32]) -> bool {
+        let bound = self.consumed_count().min(MAX_BOUND_CONSUMES) as usize;
+        (0..bound).any(|i| unsafe {
+            let slot = self
+                .0
+                .add(claim::CONSUMER_FINGERPRINTS + i * NULLIFIER_FINGERPRINT_LEN);
+            core::slice::from_raw_parts(slot, NULLIFIER_FINGERPRINT_LEN)
+                == &nullifier[..NULLIFIER_FINGERPRINT_LEN]
+        })
+    }
+
+    /// Record the consuming withdraw's nullifier in the next fingerprint slot.
+    /// Must be called before `consume`, which advances consumed_count.
+    ///
+    /// Claims mined before `MAX_BOUND_CONSUMES` was enforced may allow more
+    /// consumes than there are slots; past the last slot they fail with
+    /// `TooManyConsumes` instead of consuming unbound.
+    #[inline(always)]
+    pub fn bind_consumer(&mut self, nullifier: &[u8; 32]) -> Result<(), ProgramError> {
+        let index = self.consumed_count();
+        if index >= MAX_BOUND_CONSUMES {
+            return Err(ScrambleError::TooManyConsumes.into());
+        }
+
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                nullifier.as_ptr(),
+                self.0
+                    .add(claim::CONSUMER_FINGERPRINTS + index as usize * NULLIFIER_FINGERPRINT_LEN),
+                NULLIFIER_FINGERPRINT_LEN,
+            );
+        }
+        Ok(())
+    }
+
     /// Check if this claim is a wildcard (can be used for any batch)
     /// Wildcard claims have batch_hash = [0; 32]
     #[inline(always)]
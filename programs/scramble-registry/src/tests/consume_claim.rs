@@ -1,3 +1,222 @@
+use mollusk_svm::{
+    result::{InstructionResult, ProgramResult},
+    Mollusk,
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use super::setup;
+use crate::{
+    constants::{CONSUME_RATE_SCALE, MAX_BOUND_CONSUMES},
+    error::ScrambleError,
+    instructions::ScrambleRegistryInstruction,
+    state::{Claim, ClaimStatus, Miner, ScrambleRegistry},
+};
+
+const SHIELD_POOL_ID: [u8; 32] =
+    five8_const::decode_32_const("c1oak6tetxYnNfvXKFkpn1d98FxtK7B68vBQLYQpWKp");
+
+fn program_account(program_id: &Pubkey, mollusk: &Mollusk, data: Vec<u8>) -> Account {
+    Account {
+        lamports: mollusk.sysvars.rent.minimum_balance(data.len()),
+        data,
+        owner: *program_id,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Revealed wildcard claim with no expiry and `max_consumes` uses
+fn revealed_claim_data(miner_authority: &Pubkey, max_consumes: u16) -> Vec<u8> {
+    let mut data = vec![0u8; Claim::SIZE];
+    data[0..32].copy_from_slice(miner_authority.as_ref());
+    data[170..172].copy_from_slice(&max_consumes.to_le_bytes());
+    data[180] = ClaimStatus::Revealed as u8;
+    data
+}
+
+/// Claim, miner, registry, shield-pool and clock accounts for ConsumeClaim
+fn consume_accounts(
+    program_id: &Pubkey,
+    mollusk: &Mollusk,
+    miner_authority: &Pubkey,
+    max_consumes: u16,
+) -> Vec<(Pubkey, Account)> {
+    let mut miner_data = vec![0u8; Miner::SIZE];
+    miner_data[0..32].copy_from_slice(miner_authority.as_ref());
+
+    vec![
+        (
+            Pubkey::new_unique(),
+            program_account(
+                program_id,
+                mollusk,
+                revealed_claim_data(miner_authority, max_consumes),
+            ),
+        ),
+        (
+            Pubkey::new_unique(),
+            program_account(program_id, mollusk, miner_data),
+        ),
+        (
+            Pubkey::new_unique(),
+            program_account(program_id, mollusk, vec![0u8; ScrambleRegistry::SIZE]),
+        ),
+        (
+            Pubkey::new_from_array(SHIELD_POOL_ID),
+            Account {
+                lamports: 1,
+                data: vec![],
+                owner: solana_sdk::bpf_loader_upgradeable::id(),
+                executable: true,
+                rent_epoch: 0,
+            },
+        ),
+        mollusk.sysvars.keyed_account_for_clock_sysvar(),
+    ]
+}
+
+fn consume_claim(
+    program_id: Pubkey,
+    mollusk: &Mollusk,
+    miner_authority: &Pubkey,
+    nullifier: [u8; 32],
+    accounts: &[(Pubkey, Account)],
+) -> InstructionResult {
+    let instruction_data = [
+        vec![ScrambleRegistryInstruction::ConsumeClaim as u8],
+        miner_authority.to_bytes().to_vec(),
+        [0u8; 32].to_vec(),
+        nullifier.to_vec(),
+    ]
+    .concat();
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &instruction_data,
+        vec![
+            AccountMeta::new(accounts[0].0, false),
+            AccountMeta::new(accounts[1].0, false),
+            AccountMeta::new(accounts[2].0, false),
+            AccountMeta::new_readonly(accounts[3].0, false),
+            AccountMeta::new_readonly(accounts[4].0, false),
+        ],
+    );
+
+    mollusk.process_instruction(&instruction, accounts)
+}
+
+#[test]
+fn test_consume_rate_rises_with_consecutive_consumes() {
+    let (program_id, mollusk) = setup();
+    let miner_authority = Pubkey::new_unique();
+
+    let mut accounts = consume_accounts(&program_id, &mollusk, &miner_authority, 10);
+
+    let mut rates = Vec::new();
+    for i in 0..4u8 {
+        let result = consume_claim(program_id, &mollusk, &miner_authority, [i; 32], &accounts);
+        assert!(
+            !result.program_result.is_err(),
+            "ConsumeClaim should succeed, got: {:?}",
+            result.program_result
+        );
+        accounts = result.resulting_accounts;
+
+        let miner = &accounts[1].1.data;
+        rates.push(u64::from_le_bytes(miner[56..64].try_into().unwrap()));
+    }
+
+    // Each consume moves the EMA 1/8 of the remaining way towards the scale
+    assert_eq!(rates[0], CONSUME_RATE_SCALE / 8);
+    assert!(
+        rates.windows(2).all(|w| w[0] < w[1]),
+        "consume rate should rise with each consume: {:?}",
+        rates
+    );
+    assert!(rates[3] < CONSUME_RATE_SCALE);
+
+    let total_consumed = u64::from_le_bytes(accounts[1].1.data[40..48].try_into().unwrap());
+    assert_eq!(total_consumed, 4);
+}
+
+fn assert_custom_error(result: &InstructionResult, err: ScrambleError) {
+    assert!(
+        matches!(
+            result.program_result,
+            ProgramResult::Failure(ProgramError::Custom(code)) if code == err as u32
+        ),
+        "expected {:?}, got: {:?}",
+        err,
+        result.program_result
+    );
+}
+
+#[test]
+fn test_consume_claim_rejects_same_nullifier_twice() {
+    let (program_id, mollusk) = setup();
+    let miner_authority = Pubkey::new_unique();
+    let accounts = consume_accounts(&program_id, &mollusk, &miner_authority, 3);
+
+    let first = consume_claim(program_id, &mollusk, &miner_authority, [7; 32], &accounts);
+    assert!(
+        !first.program_result.is_err(),
+        "first consume should succeed, got: {:?}",
+        first.program_result
+    );
+
+    let second = consume_claim(
+        program_id,
+        &mollusk,
+        &miner_authority,
+        [7; 32],
+        &first.resulting_accounts,
+    );
+    assert_custom_error(&second, ScrambleError::NullifierAlreadyConsumed);
+    let consumed_count = &second.resulting_accounts[0].1.data[168..170];
+    assert_eq!(u16::from_le_bytes(consumed_count.try_into().unwrap()), 1);
+}
+
+#[test]
+fn test_consume_claim_distinct_nullifiers_up_to_max_consumes() {
+    let (program_id, mollusk) = setup();
+    let miner_authority = Pubkey::new_unique();
+    let mut accounts = consume_accounts(&program_id, &mollusk, &miner_authority, 3);
+
+    for i in 1..=3u8 {
+        let result = consume_claim(program_id, &mollusk, &miner_authority, [i; 32], &accounts);
+        assert!(
+            !result.program_result.is_err(),
+            "consume {} should succeed, got: {:?}",
+            i,
+            result.program_result
+        );
+        accounts = result.resulting_accounts;
+    }
+    assert_eq!(accounts[0].1.data[180], ClaimStatus::Consumed as u8);
+
+    // A fresh nullifier still cannot exceed max_consumes
+    let result = consume_claim(program_id, &mollusk, &miner_authority, [4; 32], &accounts);
+    assert_custom_error(&result, ScrambleError::InvalidClaimStatus);
+}
+
+#[test]
+fn test_consume_claim_stops_at_bound_consumes_for_older_claims() {
+    let (program_id, mollusk) = setup();
+    let miner_authority = Pubkey::new_unique();
+
+    // Mined before the cap: room for 20 consumes, 18 already bound
+    let mut accounts = consume_accounts(&program_id, &mollusk, &miner_authority, 20);
+    accounts[0].1.data[168..170].copy_from_slice(&MAX_BOUND_CONSUMES.to_le_bytes());
+
+    let result = consume_claim(program_id, &mollusk, &miner_authority, [19; 32], &accounts);
+    assert_custom_error(&result, ScrambleError::TooManyConsumes);
+}
+
 #[test]
 fn test_consume_claim() {
     // TODO: Implement test
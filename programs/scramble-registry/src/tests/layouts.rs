@@ -0,0 +1,116 @@
+use cloak_layouts::{claim, decode_claim, decode_miner, decode_registry};
+
+use crate::state::{Claim, ClaimStatus, Miner, ScrambleRegistry};
+
+#[test]
+fn test_registry_round_trip() {
+    let mut data = vec![0u8; ScrambleRegistry::SIZE];
+    let mut registry = ScrambleRegistry::from_data_unchecked(&mut data);
+    registry.initialize(
+        &[0x11; 32],
+        &[0x22; 32],
+        &[0x33; 32],
+        &[0x44; 32],
+        100,
+        2_500,
+        150,
+        300,
+        20,
+    );
+    registry.set_windows(160, 320);
+    registry.record_solution();
+    registry.increment_active();
+
+    let view = decode_registry(&data).unwrap();
+    assert_eq!(view.admin, [0x11; 32]);
+    assert_eq!(view.current_difficulty, [0x22; 32]);
+    assert_eq!(view.min_difficulty, [0x33; 32]);
+    assert_eq!(view.max_difficulty, [0x44; 32]);
+    assert_eq!(view.last_retarget_slot, 0);
+    assert_eq!(view.solutions_observed, 1);
+    assert_eq!(view.target_interval_slots, 100);
+    assert_eq!(view.fee_share_bps, 2_500);
+    assert_eq!(view.reveal_window, 160);
+    assert_eq!(view.claim_window, 320);
+    assert_eq!(view.max_k, 20);
+    assert_eq!(view.total_claims, 1);
+    assert_eq!(view.active_claims, 1);
+}
+
+#[test]
+fn test_miner_round_trip() {
+    let mut data = vec![0u8; Miner::SIZE];
+    let mut miner = Miner::from_data_unchecked(&mut data);
+    miner.initialize(&[0x55; 32], 42);
+    miner.record_mine();
+    miner.record_consume();
+    miner.record_consume();
+    let consume_rate = miner.consume_rate_bps();
+
+    let view = decode_miner(&data).unwrap();
+    assert_eq!(view.authority, [0x55; 32]);
+    assert_eq!(view.total_mined, 1);
+    assert_eq!(view.total_consumed, 2);
+    assert_eq!(view.registered_at_slot, 42);
+    assert_eq!(view.consume_rate_bps, consume_rate);
+    assert!(view.consume_rate_bps > 0);
+}
+
+#[test]
+fn test_legacy_miner_keeps_counting_without_consume_rate() {
+    let mut data = vec![0u8; Miner::LEGACY_SIZE];
+    data[..32].copy_from_slice(&[0x55; 32]);
+    let mut miner = Miner::from_data_unchecked(&mut data);
+    miner.record_mine();
+    miner.record_consume();
+    assert_eq!(miner.consume_rate_bps(), 0);
+
+    let view = decode_miner(&data).unwrap();
+    assert_eq!(view.authority, [0x55; 32]);
+    assert_eq!(view.total_mined, 1);
+    assert_eq!(view.total_consumed, 1);
+    assert_eq!(view.consume_rate_bps, 0);
+}
+
+#[test]
+fn test_claim_round_trip() {
+    let mut data = vec![0u8; Claim::SIZE];
+    let mut claim = Claim::from_data_unchecked(&mut data);
+    claim.initialize(
+        &[0x66; 32],
+        &[0x77; 32],
+        1_000,
+        &[0x88; 32],
+        0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10,
+        &[0x99; 32],
+        3,
+        1_005,
+    );
+    claim.reveal(1_010, 300);
+    claim.bind_consumer(&[0xAA; 32]).unwrap();
+    claim.consume().unwrap();
+
+    let view = decode_claim(&data).unwrap();
+    assert_eq!(view.miner_authority, [0x66; 32]);
+    assert_eq!(view.batch_hash, [0x77; 32]);
+    assert!(!view.is_wildcard());
+    assert_eq!(view.slot, 1_000);
+    assert_eq!(view.slot_hash, [0x88; 32]);
+    assert_eq!(view.nonce, 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10);
+    assert_eq!(view.proof_hash, [0x99; 32]);
+    assert_eq!(view.mined_at_slot, 1_005);
+    assert_eq!(view.revealed_at_slot, 1_010);
+    assert_eq!(view.consumed_count, 1);
+    assert_eq!(view.max_consumes, 3);
+    assert_eq!(view.expires_at_slot, 1_310);
+    assert_eq!(view.status, claim::STATUS_REVEALED);
+}
+
+#[test]
+fn test_claim_status_bytes_match_layout() {
+    assert_eq!(ClaimStatus::Mined as u8, claim::STATUS_MINED);
+    assert_eq!(ClaimStatus::Revealed as u8, claim::STATUS_REVEALED);
+    assert_eq!(ClaimStatus::Active as u8, claim::STATUS_ACTIVE);
+    assert_eq!(ClaimStatus::Consumed as u8, claim::STATUS_CONSUMED);
+    assert_eq!(ClaimStatus::Expired as u8, claim::STATUS_EXPIRED);
+}
@@ -13,6 +13,12 @@ mod reveal_claim;
 #[cfg(test)]
 mod consume_claim;
 
+#[cfg(test)]
+mod update_windows;
+
+#[cfg(test)]
+mod layouts;
+
 pub fn setup() -> (Pubkey, Mollusk) {
     let program_id = Pubkey::new_from_array(crate::ID);
     let mollusk = Mollusk::new(&program_id, "../../target/deploy/scramble_registry");
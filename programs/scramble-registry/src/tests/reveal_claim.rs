@@ -1,4 +1,153 @@
+use mollusk_svm::{
+    result::{InstructionResult, ProgramResult},
+    Mollusk,
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use super::setup;
+use crate::{
+    error::ScrambleError,
+    instructions::ScrambleRegistryInstruction,
+    state::{Claim, ClaimStatus, ScrambleRegistry},
+};
+
+const REVEAL_WINDOW: u64 = 150;
+const CLAIM_WINDOW: u64 = 300;
+
+fn program_account(program_id: &Pubkey, mollusk: &Mollusk, data: Vec<u8>) -> Account {
+    Account {
+        lamports: mollusk.sysvars.rent.minimum_balance(data.len()),
+        data,
+        owner: *program_id,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Claim, registry, miner authority and clock accounts for a claim mined at slot 0
+fn reveal_accounts(
+    program_id: &Pubkey,
+    mollusk: &Mollusk,
+    miner_authority: &Pubkey,
+    status: ClaimStatus,
+) -> Vec<(Pubkey, Account)> {
+    let mut claim = vec![0u8; Claim::SIZE];
+    claim[0..32].copy_from_slice(miner_authority.as_ref());
+    claim[170..172].copy_from_slice(&1u16.to_le_bytes());
+    claim[180] = status as u8;
+
+    let mut registry = vec![0u8; ScrambleRegistry::SIZE];
+    registry[90..98].copy_from_slice(&REVEAL_WINDOW.to_le_bytes());
+    registry[98..106].copy_from_slice(&CLAIM_WINDOW.to_le_bytes());
+
+    vec![
+        (
+            Pubkey::new_unique(),
+            program_account(program_id, mollusk, claim),
+        ),
+        (
+            Pubkey::new_unique(),
+            program_account(program_id, mollusk, registry),
+        ),
+        (
+            *miner_authority,
+            Account {
+                lamports: 1_000_000_000,
+                data: vec![],
+                owner: solana_sdk::system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+        mollusk.sysvars.keyed_account_for_clock_sysvar(),
+    ]
+}
+
+fn reveal_claim(
+    program_id: Pubkey,
+    mollusk: &Mollusk,
+    accounts: &[(Pubkey, Account)],
+) -> InstructionResult {
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &[ScrambleRegistryInstruction::RevealClaim as u8],
+        vec![
+            AccountMeta::new(accounts[0].0, false),
+            AccountMeta::new_readonly(accounts[1].0, false),
+            AccountMeta::new_readonly(accounts[2].0, true),
+            AccountMeta::new_readonly(accounts[3].0, false),
+        ],
+    );
+
+    mollusk.process_instruction(&instruction, accounts)
+}
+
+fn expires_at_slot(claim: &Account) -> u64 {
+    u64::from_le_bytes(claim.data[172..180].try_into().unwrap())
+}
+
+fn assert_invalid_claim_status(result: &InstructionResult) {
+    assert!(
+        matches!(
+            result.program_result,
+            ProgramResult::Failure(ProgramError::Custom(code))
+                if code == ScrambleError::InvalidClaimStatus as u32
+        ),
+        "expected InvalidClaimStatus, got: {:?}",
+        result.program_result
+    );
+}
+
+#[test]
+fn test_reveal_claim_twice_fails() {
+    let (program_id, mut mollusk) = setup();
+    let miner_authority = Pubkey::new_unique();
+
+    mollusk.warp_to_slot(10);
+    let accounts = reveal_accounts(&program_id, &mollusk, &miner_authority, ClaimStatus::Mined);
+    let first = reveal_claim(program_id, &mollusk, &accounts);
+    assert!(
+        !first.program_result.is_err(),
+        "first reveal should succeed, got: {:?}",
+        first.program_result
+    );
+    let claim = &first.resulting_accounts[0].1;
+    assert_eq!(claim.data[180], ClaimStatus::Revealed as u8);
+    assert_eq!(expires_at_slot(claim), 10 + CLAIM_WINDOW);
+
+    // A later re-reveal must not push the expiry out
+    mollusk.warp_to_slot(100);
+    let mut accounts = first.resulting_accounts;
+    accounts[3] = mollusk.sysvars.keyed_account_for_clock_sysvar();
+    let second = reveal_claim(program_id, &mollusk, &accounts);
+
+    assert_invalid_claim_status(&second);
+    assert_eq!(
+        expires_at_slot(&second.resulting_accounts[0].1),
+        10 + CLAIM_WINDOW
+    );
+}
+
 #[test]
-fn test_reveal_claim() {
-    // TODO: Implement test
+fn test_reveal_claim_rejects_non_mined_status() {
+    let (program_id, mollusk) = setup();
+    let miner_authority = Pubkey::new_unique();
+
+    for status in [
+        ClaimStatus::Revealed,
+        ClaimStatus::Active,
+        ClaimStatus::Consumed,
+        ClaimStatus::Expired,
+    ] {
+        let accounts = reveal_accounts(&program_id, &mollusk, &miner_authority, status);
+        let result = reveal_claim(program_id, &mollusk, &accounts);
+
+        assert_invalid_claim_status(&result);
+        assert_eq!(result.resulting_accounts[0].1.data[180], status as u8);
+    }
 }
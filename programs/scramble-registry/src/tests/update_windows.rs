@@ -0,0 +1,160 @@
+use mollusk_svm::{result::ProgramResult, Mollusk};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    constants::MAX_WINDOW_SLOTS, error::ScrambleError, instructions::ScrambleRegistryInstruction,
+    state::ScrambleRegistry,
+};
+
+use super::setup;
+
+const REVEAL_WINDOW: u64 = 150;
+const CLAIM_WINDOW: u64 = 300;
+
+/// Registry account data with `admin` and the default windows, other fields zeroed
+fn registry_data(admin: &Pubkey) -> Vec<u8> {
+    let mut data = vec![0u8; ScrambleRegistry::SIZE];
+    data[0..32].copy_from_slice(admin.as_ref());
+    data[90..98].copy_from_slice(&REVEAL_WINDOW.to_le_bytes());
+    data[98..106].copy_from_slice(&CLAIM_WINDOW.to_le_bytes());
+    data
+}
+
+/// Run UpdateWindows signed by `signer` against a registry administered by `admin`
+fn update_windows(
+    program_id: Pubkey,
+    mollusk: &Mollusk,
+    admin: Pubkey,
+    signer: Pubkey,
+    reveal_window: u64,
+    claim_window: u64,
+) -> (mollusk_svm::result::InstructionResult, Pubkey) {
+    let (registry, _bump) = Pubkey::find_program_address(&[b"registry"], &program_id);
+
+    let instruction_data = [
+        vec![ScrambleRegistryInstruction::UpdateWindows as u8],
+        reveal_window.to_le_bytes().to_vec(),
+        claim_window.to_le_bytes().to_vec(),
+    ]
+    .concat();
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &instruction_data,
+        vec![
+            AccountMeta::new(registry, false),
+            AccountMeta::new_readonly(signer, true),
+        ],
+    );
+
+    let accounts = vec![
+        (
+            registry,
+            Account {
+                lamports: mollusk.sysvars.rent.minimum_balance(ScrambleRegistry::SIZE),
+                data: registry_data(&admin),
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+        (
+            signer,
+            Account {
+                lamports: 1_000_000_000,
+                data: vec![],
+                owner: solana_sdk::system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+    ];
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    (result, registry)
+}
+
+fn windows_of(result: &mollusk_svm::result::InstructionResult, registry: &Pubkey) -> (u64, u64) {
+    let data = &result
+        .resulting_accounts
+        .iter()
+        .find(|(pk, _)| pk == registry)
+        .expect("registry not found")
+        .1
+        .data;
+    (
+        u64::from_le_bytes(data[90..98].try_into().unwrap()),
+        u64::from_le_bytes(data[98..106].try_into().unwrap()),
+    )
+}
+
+fn assert_custom_error(result: &mollusk_svm::result::InstructionResult, err: ScrambleError) {
+    assert!(
+        matches!(
+            result.program_result,
+            ProgramResult::Failure(ProgramError::Custom(code)) if code == err as u32
+        ),
+        "expected {:?}, got: {:?}",
+        err,
+        result.program_result
+    );
+}
+
+#[test]
+fn test_update_windows_by_admin() {
+    let (program_id, mollusk) = setup();
+    let admin = Pubkey::new_unique();
+
+    let (result, registry) = update_windows(program_id, &mollusk, admin, admin, 50, 2_000);
+
+    assert!(
+        !result.program_result.is_err(),
+        "UpdateWindows should succeed for the admin, got: {:?}",
+        result.program_result
+    );
+    assert_eq!(windows_of(&result, &registry), (50, 2_000));
+}
+
+#[test]
+fn test_update_windows_rejects_non_admin() {
+    let (program_id, mollusk) = setup();
+    let admin = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+
+    let (result, registry) = update_windows(program_id, &mollusk, admin, other, 50, 2_000);
+
+    assert_custom_error(&result, ScrambleError::InvalidAdminAuthority);
+    assert_eq!(
+        windows_of(&result, &registry),
+        (REVEAL_WINDOW, CLAIM_WINDOW)
+    );
+}
+
+#[test]
+fn test_update_windows_rejects_out_of_bounds() {
+    let (program_id, mollusk) = setup();
+    let admin = Pubkey::new_unique();
+
+    for (reveal_window, claim_window) in [(0, CLAIM_WINDOW), (REVEAL_WINDOW, MAX_WINDOW_SLOTS + 1)]
+    {
+        let (result, registry) = update_windows(
+            program_id,
+            &mollusk,
+            admin,
+            admin,
+            reveal_window,
+            claim_window,
+        );
+
+        assert_custom_error(&result, ScrambleError::InvalidWindow);
+        assert_eq!(
+            windows_of(&result, &registry),
+            (REVEAL_WINDOW, CLAIM_WINDOW)
+        );
+    }
+}
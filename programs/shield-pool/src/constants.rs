@@ -9,10 +9,17 @@ pub const ADMIN_AUTHORITY: [u8; 32] =
 
 // Layout constants for withdraw instruction payloads
 pub const PROOF_LEN: usize = 260;
+#[cfg(not(feature = "proof-freshness"))]
 pub const PUB_LEN: usize = 104; // root (32) || nullifier (32) || outputs_hash (32) || amount (8)
-pub const SP1_PUB_LEN: usize = 104; // SP1 verifier expects the full 104-byte public inputs slice
+#[cfg(feature = "proof-freshness")]
+pub const PUB_LEN: usize = 112; // ... || amount (8) || generated_at slot (8)
+pub const SP1_PUB_LEN: usize = PUB_LEN; // SP1 verifier expects the full public inputs slice
 pub const DUPLICATE_NULLIFIER_LEN: usize = 32;
 pub const NUM_OUTPUTS_LEN: usize = 1;
 pub const RECIPIENT_ADDR_LEN: usize = 32;
 pub const RECIPIENT_AMOUNT_LEN: usize = 8;
 pub const POW_BATCH_HASH_LEN: usize = 32;
+
+/// Oldest proof, in slots since its `generated_at`, a withdraw accepts (~1h)
+#[cfg(feature = "proof-freshness")]
+pub const MAX_PROOF_AGE_SLOTS: u64 = 9_000;
@@ -12,6 +12,7 @@ pub enum ShieldPoolError {
     InvalidProofSize = 0x1011,
     InvalidPublicInputs = 0x1012,
     VKeyMismatch = 0x1013,
+    ProofExpired = 0x1014,
 
     // Nullifier errors
     DoubleSpend = 0x1020,
@@ -29,6 +30,9 @@ pub enum ShieldPoolError {
     CommitmentAlreadyExists = 0x1035,
     CommitmentLogFull = 0x1036,
     SwapTimeoutNotExpired = 0x1037,
+    SwapAlreadyExecuted = 0x1038,
+    WithdrawExceedsPoolCap = 0x1039,
+    CommitmentQueueFull = 0x103A,
 
     // Math errors
     MathOverflow = 0x1040,
@@ -49,6 +53,7 @@ pub enum ShieldPoolError {
     CommitmentsNotWritable = 0x105B,
     InvalidAdminAuthority = 0x105C,
     InvalidAccountAddress = 0x105D,
+    NoPendingAuthority = 0x105E,
 
     // Instruction errors
     BadIxLength = 0x1060,
@@ -83,3 +88,68 @@ impl From<ShieldPoolError> for u32 {
         e as u32
     }
 }
+
+impl TryFrom<u32> for ShieldPoolError {
+    type Error = ProgramError;
+
+    fn try_from(code: u32) -> Result<Self, ProgramError> {
+        match code {
+            0x1000 => Ok(Self::InvalidRoot),
+            0x1001 => Ok(Self::RootNotFound),
+            0x1002 => Ok(Self::RootsRingFull),
+            0x1010 => Ok(Self::ProofInvalid),
+            0x1011 => Ok(Self::InvalidProofSize),
+            0x1012 => Ok(Self::InvalidPublicInputs),
+            0x1013 => Ok(Self::VKeyMismatch),
+            0x1014 => Ok(Self::ProofExpired),
+            0x1020 => Ok(Self::DoubleSpend),
+            0x1021 => Ok(Self::NullifierShardFull),
+            0x1022 => Ok(Self::InvalidNullifier),
+            0x1023 => Ok(Self::NullifierAlreadyUsed),
+            0x1024 => Ok(Self::NullifierMismatch),
+            0x1030 => Ok(Self::OutputsMismatch),
+            0x1031 => Ok(Self::Conservation),
+            0x1032 => Ok(Self::InvalidOutputsHash),
+            0x1033 => Ok(Self::InvalidAmount),
+            0x1034 => Ok(Self::InvalidRecipient),
+            0x1035 => Ok(Self::CommitmentAlreadyExists),
+            0x1036 => Ok(Self::CommitmentLogFull),
+            0x1037 => Ok(Self::SwapTimeoutNotExpired),
+            0x1038 => Ok(Self::SwapAlreadyExecuted),
+            0x1039 => Ok(Self::WithdrawExceedsPoolCap),
+            0x103A => Ok(Self::CommitmentQueueFull),
+            0x1040 => Ok(Self::MathOverflow),
+            0x1041 => Ok(Self::DivisionByZero),
+            0x1050 => Ok(Self::BadAccounts),
+            0x1051 => Ok(Self::PoolOwnerNotProgramId),
+            0x1052 => Ok(Self::TreasuryOwnerNotProgramId),
+            0x1053 => Ok(Self::RootsRingOwnerNotProgramId),
+            0x1054 => Ok(Self::NullifierShardOwnerNotProgramId),
+            0x1055 => Ok(Self::PoolNotWritable),
+            0x1056 => Ok(Self::TreasuryNotWritable),
+            0x1057 => Ok(Self::RecipientNotWritable),
+            0x1058 => Ok(Self::InsufficientLamports),
+            0x1059 => Ok(Self::InvalidAccountOwner),
+            0x105A => Ok(Self::InvalidAccountSize),
+            0x105B => Ok(Self::CommitmentsNotWritable),
+            0x105C => Ok(Self::InvalidAdminAuthority),
+            0x105D => Ok(Self::InvalidAccountAddress),
+            0x105E => Ok(Self::NoPendingAuthority),
+            0x1060 => Ok(Self::BadIxLength),
+            0x1061 => Ok(Self::InvalidInstructionData),
+            0x1062 => Ok(Self::MissingAccounts),
+            0x1063 => Ok(Self::InvalidTag),
+            0x1064 => Ok(Self::InvalidMinerAccount),
+            0x1065 => Ok(Self::InvalidClaimAccount),
+            0x1066 => Ok(Self::ConsumClaimFailed),
+            0x1070 => Ok(Self::InvalidG1Length),
+            0x1071 => Ok(Self::InvalidG2Length),
+            0x1072 => Ok(Self::InvalidPublicInputsLength),
+            0x1073 => Ok(Self::PublicInputGreaterThanFieldSize),
+            0x1074 => Ok(Self::PreparingInputsG1MulFailed),
+            0x1075 => Ok(Self::PreparingInputsG1AdditionFailed),
+            0x1076 => Ok(Self::ProofVerificationFailed),
+            _ => Err(ProgramError::Custom(code)),
+        }
+    }
+}
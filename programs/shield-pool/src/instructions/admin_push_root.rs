@@ -1,24 +1,22 @@
 use pinocchio::{account_info::AccountInfo, ProgramResult};
 
-use crate::{constants::ADMIN_AUTHORITY, error::ShieldPoolError, state::RootsRing};
+use crate::{error::ShieldPoolError, instructions::authorize_root_push, state::RootsRing};
 
 pub fn process_admin_push_root_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    // Parse accounts - expecting: [admin (signer), roots_ring (writable)]
-    let [admin_info, roots_ring_info] = accounts else {
+    // Parse accounts - expecting: [admin (signer), roots_ring (writable), pool]
+    let [admin_info, roots_ring_info, pool_info, ..] = accounts else {
         return Err(ShieldPoolError::MissingAccounts.into());
     };
-
-    // Verify admin authorization
-    if !admin_info.is_signer()
-        || admin_info.key() != &ADMIN_AUTHORITY
-        || !roots_ring_info.is_writable()
-    {
+    if !roots_ring_info.is_writable() {
         return Err(ShieldPoolError::BadAccounts.into());
     }
 
+    // Verify admin authorization against the pool's current authority
+    authorize_root_push(admin_info, roots_ring_info, pool_info)?;
+
     // Parse instruction data
     let admin_data = unsafe { *((instruction_data.as_ptr()).add(0) as *const [u8; 32]) };
 
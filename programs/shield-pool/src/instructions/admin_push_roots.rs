@@ -0,0 +1,44 @@
+use pinocchio::{account_info::AccountInfo, ProgramResult};
+
+use crate::{error::ShieldPoolError, instructions::authorize_root_push, state::RootsRing};
+
+/// Push several roots into `RootsRing` in order, e.g. after an indexer backfill.
+///
+/// Instruction data: `[count: u8][roots: count * 32]`. `count` is capped at the
+/// ring size so a batch never evicts its own earlier roots.
+pub fn process_admin_push_roots_instruction(
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Parse accounts - expecting: [admin (signer), roots_ring (writable), pool]
+    let [admin_info, roots_ring_info, pool_info, ..] = accounts else {
+        return Err(ShieldPoolError::MissingAccounts.into());
+    };
+    if !roots_ring_info.is_writable() {
+        return Err(ShieldPoolError::BadAccounts.into());
+    }
+
+    // Verify admin authorization against the pool's current authority
+    authorize_root_push(admin_info, roots_ring_info, pool_info)?;
+
+    // Parse instruction data
+    let (&count, roots) = instruction_data
+        .split_first()
+        .ok_or(ShieldPoolError::BadIxLength)?;
+    let count = count as usize;
+    if count == 0 || count > RootsRing::MAX_ROOTS {
+        return Err(ShieldPoolError::InvalidInstructionData.into());
+    }
+    if roots.len() != count * 32 {
+        return Err(ShieldPoolError::BadIxLength.into());
+    }
+
+    // Load and update RootsRing
+    let mut roots_ring = RootsRing::from_account_info(roots_ring_info)?;
+    for root in roots.chunks_exact(32) {
+        let root: &[u8; 32] = root.try_into().map_err(|_| ShieldPoolError::BadIxLength)?;
+        roots_ring.push_root(root)?;
+    }
+
+    Ok(())
+}
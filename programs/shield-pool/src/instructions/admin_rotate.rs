@@ -0,0 +1,63 @@
+use pinocchio::{account_info::AccountInfo, pubkey::Pubkey, ProgramResult};
+
+use crate::{
+    error::ShieldPoolError,
+    instructions::{grow_pool, load_pool_as_admin, load_pool_checked},
+    state::Pool,
+};
+
+/// First step of rotating a pool's admin: the current authority names its
+/// successor, who must then sign `AdminRotateAccept`.
+///
+/// Accounts: `[admin (signer), pool (writable), system_program (optional)]`
+/// Instruction data: `[new_authority: 32]`. Proposing all zeros cancels a
+/// pending rotation.
+///
+/// Pools created before the authority fields are grown to the current layout
+/// first, with the admin paying the extra rent; the admin must then also be
+/// writable and the system program passed.
+pub fn process_admin_rotate_propose_instruction(
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let [admin_info, pool_info, ..] = accounts else {
+        return Err(ShieldPoolError::MissingAccounts.into());
+    };
+    if !pool_info.is_writable() {
+        return Err(ShieldPoolError::PoolNotWritable.into());
+    }
+
+    let new_authority: [u8; 32] = instruction_data
+        .try_into()
+        .map_err(|_| ShieldPoolError::BadIxLength)?;
+
+    load_pool_as_admin(admin_info, pool_info)?;
+    grow_pool(admin_info, pool_info)?;
+    let mut pool = Pool::from_account_info(pool_info)?;
+    pool.set_pending_authority(&Pubkey::from(new_authority))
+}
+
+/// Second step of rotating a pool's admin: the proposed authority signs to
+/// take over, which also clears the pending slot.
+///
+/// Accounts: `[new_admin (signer), pool (writable)]`
+pub fn process_admin_rotate_accept_instruction(accounts: &[AccountInfo]) -> ProgramResult {
+    let [new_admin_info, pool_info] = accounts else {
+        return Err(ShieldPoolError::MissingAccounts.into());
+    };
+    if !pool_info.is_writable() {
+        return Err(ShieldPoolError::PoolNotWritable.into());
+    }
+
+    let mut pool = load_pool_checked(pool_info)?;
+    let pending = pool.pending_authority();
+    if pending == Pubkey::default() {
+        return Err(ShieldPoolError::NoPendingAuthority.into());
+    }
+    if !new_admin_info.is_signer() || new_admin_info.key() != &pending {
+        return Err(ShieldPoolError::InvalidAdminAuthority.into());
+    }
+
+    pool.set_authority(new_admin_info.key())?;
+    pool.set_pending_authority(&Pubkey::default())
+}
@@ -0,0 +1,40 @@
+use pinocchio::{account_info::AccountInfo, ProgramResult};
+
+use crate::{
+    error::ShieldPoolError,
+    instructions::{grow_pool, load_pool_as_admin},
+    state::Pool,
+};
+
+/// Replace a pool's `pool::FLAG_*` bits, e.g. to turn on no-overwrite for a
+/// pool created without it.
+///
+/// Accounts: `[admin (signer), pool (writable), system_program (optional)]`
+/// Instruction data: `[flags: u8]`
+///
+/// Pools created before the flags byte are grown to the current layout first,
+/// with the admin paying the extra rent; the admin must then also be writable
+/// and the system program passed.
+pub fn process_admin_set_flags_instruction(
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let [admin_info, pool_info, ..] = accounts else {
+        return Err(ShieldPoolError::MissingAccounts.into());
+    };
+    if !pool_info.is_writable() {
+        return Err(ShieldPoolError::PoolNotWritable.into());
+    }
+
+    let [flags] = instruction_data else {
+        return Err(ShieldPoolError::BadIxLength.into());
+    };
+    if flags & !cloak_layouts::pool::KNOWN_FLAGS != 0 {
+        return Err(ShieldPoolError::InvalidInstructionData.into());
+    }
+
+    load_pool_as_admin(admin_info, pool_info)?;
+    grow_pool(admin_info, pool_info)?;
+    let mut pool = Pool::from_account_info(pool_info)?;
+    pool.set_flags(*flags)
+}
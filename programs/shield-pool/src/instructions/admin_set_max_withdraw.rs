@@ -0,0 +1,42 @@
+use pinocchio::{account_info::AccountInfo, ProgramResult};
+
+use crate::{
+    error::ShieldPoolError,
+    instructions::{grow_pool, load_pool_as_admin},
+    state::Pool,
+};
+
+/// Change a pool's largest single withdraw, in basis points of its balance
+/// (0 = uncapped).
+///
+/// Accounts: `[admin (signer), pool (writable), system_program (optional)]`
+/// Instruction data: `[max_withdraw_bps: u16 LE]`
+///
+/// Mint-only pools have no room for the cap and are grown to the current
+/// layout first, with the admin paying the extra rent; the admin must then
+/// also be writable and the system program passed.
+pub fn process_admin_set_max_withdraw_instruction(
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let [admin_info, pool_info, ..] = accounts else {
+        return Err(ShieldPoolError::MissingAccounts.into());
+    };
+    if !pool_info.is_writable() {
+        return Err(ShieldPoolError::PoolNotWritable.into());
+    }
+
+    let max_withdraw_bps = instruction_data
+        .try_into()
+        .map(u16::from_le_bytes)
+        .map_err(|_| ShieldPoolError::BadIxLength)?;
+    if max_withdraw_bps > 10_000 {
+        return Err(ShieldPoolError::InvalidInstructionData.into());
+    }
+
+    load_pool_as_admin(admin_info, pool_info)?;
+    grow_pool(admin_info, pool_info)?;
+    let mut pool = Pool::from_account_info(pool_info)?;
+    pool.set_max_withdraw_bps(max_withdraw_bps);
+    Ok(())
+}
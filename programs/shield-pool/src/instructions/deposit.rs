@@ -96,6 +96,12 @@ fn process_native_deposit(
         return Err(ShieldPoolError::CommitmentAlreadyExists.into());
     }
 
+    // No-overwrite pools keep every commitment on-chain: once the queue is
+    // full, deposits fail until an admin provisions more room
+    if pool_state.no_overwrite() && commitment_queue.is_full() {
+        return Err(ShieldPoolError::CommitmentQueueFull.into());
+    }
+
     commitment_queue.append(commit_bytes)?;
 
     Transfer {
@@ -148,6 +154,12 @@ fn process_token_deposit(
         return Err(ShieldPoolError::CommitmentAlreadyExists.into());
     }
 
+    // No-overwrite pools keep every commitment on-chain: once the queue is
+    // full, deposits fail until an admin provisions more room
+    if pool_state.no_overwrite() && commitment_queue.is_full() {
+        return Err(ShieldPoolError::CommitmentQueueFull.into());
+    }
+
     commitment_queue.append(commit_bytes)?;
 
     // Transfer SPL tokens from user to pool
@@ -0,0 +1,38 @@
+use pinocchio::{
+    account_info::AccountInfo, cpi::set_return_data, log::sol_log_data, ProgramResult,
+};
+
+use super::deposit::process_deposit_instruction;
+use crate::error::ShieldPoolError;
+
+/// Tag of the `Program data:` event carrying a deposit's memo hash
+pub const DEPOSIT_MEMO_EVENT: &[u8] = b"deposit_memo";
+
+/// amount (8) || commitment (32) || memo_hash (32)
+const DEPOSIT_WITH_MEMO_DATA_LEN: usize = 72;
+
+/// Deposit that also anchors a hash of the client's encrypted note on-chain.
+///
+/// Takes the same accounts as `Deposit`. The memo hash is emitted as a
+/// `Program data:` event of `[DEPOSIT_MEMO_EVENT, commitment, memo_hash]`, so a
+/// client can check the encrypted output the indexer serves against the hash it
+/// signed, without growing the `CommitmentQueue` layout. It is also set as the
+/// instruction's return data for programs that deposit via CPI.
+#[inline(always)]
+pub fn process_deposit_with_memo_instruction(
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() != DEPOSIT_WITH_MEMO_DATA_LEN {
+        return Err(ShieldPoolError::InvalidInstructionData.into());
+    }
+
+    let (deposit_data, memo_hash) = instruction_data.split_at(40);
+    process_deposit_instruction(accounts, deposit_data)?;
+
+    let commitment = &deposit_data[8..40];
+    sol_log_data(&[DEPOSIT_MEMO_EVENT, commitment, memo_hash]);
+    set_return_data(memo_hash);
+
+    Ok(())
+}
@@ -45,9 +45,29 @@ pub fn process_initialize_instruction(
         Pubkey::default() // Native SOL
     };
 
+    // Optional max withdraw cap in basis points of the pool balance (u16 LE
+    // after the mint); absent or 0 leaves withdraws uncapped
+    let max_withdraw_bps = if instruction_data.len() >= 34 {
+        u16::from_le_bytes([instruction_data[32], instruction_data[33]])
+    } else {
+        0
+    };
+    if max_withdraw_bps > 10_000 {
+        return Err(ShieldPoolError::InvalidInstructionData.into());
+    }
+
+    // Optional `pool::FLAG_*` byte after the cap; absent leaves every flag
+    // off, e.g. deposits overwrite the oldest commitments once the queue is full
+    let flags = instruction_data.get(34).copied().unwrap_or(0);
+    if flags & !cloak_layouts::pool::KNOWN_FLAGS != 0 {
+        return Err(ShieldPoolError::InvalidInstructionData.into());
+    }
+
     let program_id = Pubkey::from(ID);
     let rent = Rent::get()?;
 
+    // Re-running Initialize must not reset an existing pool's authority
+    let pool_is_new = pool.lamports() == 0;
     create_pda_account(
         &admin,
         &pool,
@@ -58,10 +78,13 @@ pub fn process_initialize_instruction(
         &rent,
     )?;
 
-    // Initialize pool state with mint
-    {
+    // Initialize pool state with mint, withdraw cap, admin and flags
+    if pool_is_new {
         let mut pool_state = Pool::from_account_info(&pool)?;
         pool_state.set_mint(&mint);
+        pool_state.set_max_withdraw_bps(max_withdraw_bps);
+        pool_state.set_authority(admin.key())?;
+        pool_state.set_flags(flags)?;
     }
 
     create_pda_account(
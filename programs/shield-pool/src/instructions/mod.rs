@@ -1,17 +1,32 @@
 pub mod admin_push_root;
+pub mod admin_push_roots;
+pub mod admin_rotate;
+pub mod admin_set_flags;
+pub mod admin_set_max_withdraw;
 pub mod deposit;
+pub mod deposit_with_memo;
 pub mod execute_swap;
 pub mod execute_swap_via_orca;
 pub mod initialize;
 pub mod prepare_swap_sol;
 pub mod release_swap_funds;
+pub mod timeout_refund_swap;
 pub mod withdraw;
 pub mod withdraw_swap;
 
-use pinocchio::program_error::ProgramError;
+use cloak_layouts::pool;
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
 
-use crate::error::ShieldPoolError;
+use crate::{error::ShieldPoolError, state::Pool, ID};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ShieldPoolInstruction {
     Deposit = 0,
     AdminPushRoot = 1,
@@ -22,6 +37,13 @@ pub enum ShieldPoolInstruction {
     ReleaseSwapFunds = 6,
     ExecuteSwapViaOrca = 7,
     PrepareSwapSol = 8,
+    AdminPushRoots = 9,
+    DepositWithMemo = 10,
+    TimeoutRefundSwap = 11,
+    AdminRotatePropose = 12,
+    AdminRotateAccept = 13,
+    AdminSetFlags = 14,
+    AdminSetMaxWithdraw = 15,
 }
 
 impl TryFrom<&u8> for ShieldPoolInstruction {
@@ -38,7 +60,116 @@ impl TryFrom<&u8> for ShieldPoolInstruction {
             6 => Ok(Self::ReleaseSwapFunds),
             7 => Ok(Self::ExecuteSwapViaOrca),
             8 => Ok(Self::PrepareSwapSol),
+            9 => Ok(Self::AdminPushRoots),
+            10 => Ok(Self::DepositWithMemo),
+            11 => Ok(Self::TimeoutRefundSwap),
+            12 => Ok(Self::AdminRotatePropose),
+            13 => Ok(Self::AdminRotateAccept),
+            14 => Ok(Self::AdminSetFlags),
+            15 => Ok(Self::AdminSetMaxWithdraw),
             _ => Err(ShieldPoolError::InvalidTag.into()),
         }
     }
 }
+
+/// Reject a proof whose committed `generated_at` slot is more than
+/// `MAX_PROOF_AGE_SLOTS` behind the current slot, or ahead of it
+#[cfg(feature = "proof-freshness")]
+pub(crate) fn check_proof_fresh(public_inputs: &[u8]) -> Result<(), ProgramError> {
+    use pinocchio::sysvars::{clock::Clock, Sysvar};
+
+    let generated_at = public_inputs
+        .get(104..112)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ShieldPoolError::InvalidPublicInputs)?;
+    let current_slot = Clock::get()?.slot;
+
+    match current_slot.checked_sub(generated_at) {
+        Some(age) if age <= crate::constants::MAX_PROOF_AGE_SLOTS => Ok(()),
+        _ => Err(ShieldPoolError::ProofExpired.into()),
+    }
+}
+
+#[cfg(not(feature = "proof-freshness"))]
+pub(crate) fn check_proof_fresh(_public_inputs: &[u8]) -> Result<(), ProgramError> {
+    Ok(())
+}
+
+/// Load `pool_info`, checking it is the pool PDA of its own mint
+pub(crate) fn load_pool_checked(pool_info: &AccountInfo) -> Result<Pool, ProgramError> {
+    let pool = Pool::from_account_info(pool_info)?;
+    let (expected, _) = find_program_address(&[pool::SEED_PREFIX, pool.mint().as_ref()], &ID);
+    if pool_info.key() != &expected {
+        return Err(ShieldPoolError::InvalidAccountAddress.into());
+    }
+    Ok(pool)
+}
+
+/// Load `pool_info` and check `admin_info` signed as that pool's authority
+pub(crate) fn load_pool_as_admin(
+    admin_info: &AccountInfo,
+    pool_info: &AccountInfo,
+) -> Result<Pool, ProgramError> {
+    let pool = load_pool_checked(pool_info)?;
+    if !admin_info.is_signer() || admin_info.key() != &pool.authority() {
+        return Err(ShieldPoolError::InvalidAdminAuthority.into());
+    }
+    Ok(pool)
+}
+
+/// Check `admin_info` may push roots into `roots_ring_info`: the signer must
+/// be the pool's current authority, so a rotation revokes the old key.
+pub(crate) fn authorize_root_push(
+    admin_info: &AccountInfo,
+    roots_ring_info: &AccountInfo,
+    pool_info: &AccountInfo,
+) -> Result<(), ProgramError> {
+    let pool = load_pool_as_admin(admin_info, pool_info)?;
+    verify_roots_ring_for_pool(roots_ring_info, &pool)
+}
+
+/// Grow a pool created under an older layout to `Pool::SIZE`, with `payer`
+/// topping up its rent. The added fields start zeroed, which reads the same
+/// as the older layout: no stored authority, no pending authority, no flags.
+///
+/// `payer` must be a writable signer and the system program must be in the
+/// transaction whenever the pool is short of rent for the new size.
+pub(crate) fn grow_pool(payer: &AccountInfo, pool_info: &AccountInfo) -> ProgramResult {
+    let old_len = pool_info.data_len();
+    if old_len >= Pool::SIZE {
+        return Ok(());
+    }
+
+    let shortfall = Rent::get()?
+        .minimum_balance(Pool::SIZE)
+        .saturating_sub(pool_info.lamports());
+    if shortfall > 0 {
+        if !payer.is_signer() || !payer.is_writable() {
+            return Err(ShieldPoolError::BadAccounts.into());
+        }
+        Transfer {
+            from: payer,
+            to: pool_info,
+            lamports: shortfall,
+        }
+        .invoke()?;
+    }
+
+    pool_info.resize(Pool::SIZE)?;
+    let data = unsafe { pool_info.borrow_mut_data_unchecked() };
+    data[old_len..].fill(0);
+    Ok(())
+}
+
+/// Check `roots_ring_info` is the roots ring PDA of `pool`'s mint
+pub(crate) fn verify_roots_ring_for_pool(
+    roots_ring_info: &AccountInfo,
+    pool: &Pool,
+) -> Result<(), ProgramError> {
+    let (expected, _) = find_program_address(&[b"roots_ring", pool.mint().as_ref()], &ID);
+    if roots_ring_info.key() != &expected {
+        return Err(ShieldPoolError::InvalidAccountAddress.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,128 @@
+/// TimeoutRefundSwap instruction - Refunds the recipient if the relay never completed the swap
+///
+/// This instruction can only be called AFTER the timeout_slot has passed.
+/// Anyone may crank it, but the locked SOL always goes back to the wallet that
+/// owns the SwapState's recipient ATA, so the caller cannot redirect funds.
+///
+/// Flow:
+/// 1. Verify SwapState PDA exists and matches nullifier
+/// 2. Verify the recipient wallet owns the stored recipient ATA
+/// 3. Verify current slot > timeout_slot
+/// 4. Verify the swap was not executed (sol_amount is still held by the PDA)
+/// 5. Close SwapState PDA and return all lamports to the recipient
+///
+/// Instruction data layout:
+/// [nullifier (32)] - Used to derive SwapState PDA
+/// Total: 32 bytes
+///
+/// Account layout:
+/// 0. swap_state_pda (writable) - Will be closed
+/// 1. recipient (writable) - Wallet owning the recipient ATA, receives the refund
+use crate::error::ShieldPoolError;
+use crate::state::SwapState;
+use crate::ID;
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+const NULLIFIER_LEN: usize = 32;
+
+// SPL Token program ID
+const TOKEN_PROGRAM_ID: [u8; 32] =
+    five8_const::decode_32_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+// Associated Token Account program ID
+const ASSOCIATED_TOKEN_PROGRAM_ID: [u8; 32] =
+    five8_const::decode_32_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+pub fn process_timeout_refund_swap(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    // Parse accounts
+    let [swap_state_info, recipient_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !swap_state_info.is_writable() {
+        return Err(ShieldPoolError::BadAccounts.into());
+    }
+    if !recipient_info.is_writable() {
+        return Err(ShieldPoolError::RecipientNotWritable.into());
+    }
+
+    // Parse instruction data
+    if data.len() != NULLIFIER_LEN {
+        return Err(ShieldPoolError::InvalidInstructionData.into());
+    }
+
+    let nullifier: [u8; 32] = data[..NULLIFIER_LEN]
+        .try_into()
+        .map_err(|_| ShieldPoolError::InvalidInstructionData)?;
+
+    // Load SwapState
+    let swap_state = SwapState::from_account_info(swap_state_info)?;
+
+    // Verify nullifier matches
+    if swap_state.nullifier() != nullifier {
+        return Err(ShieldPoolError::NullifierMismatch.into());
+    }
+
+    // Verify SwapState PDA derivation
+    let (expected_swap_state_pubkey, _bump) =
+        pinocchio::pubkey::find_program_address(&[SwapState::SEED_PREFIX, &nullifier], &ID);
+
+    if swap_state_info.key() != &expected_swap_state_pubkey {
+        return Err(ShieldPoolError::InvalidAccountAddress.into());
+    }
+
+    // Verify the recipient is the wallet behind the stored recipient ATA.
+    // Derived rather than read, so this works even if the ATA was never created.
+    let output_mint = swap_state.output_mint();
+    let (expected_recipient_ata, _bump) = pinocchio::pubkey::find_program_address(
+        &[recipient_info.key(), &TOKEN_PROGRAM_ID, &output_mint],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    );
+
+    if expected_recipient_ata != swap_state.recipient_ata() {
+        return Err(ShieldPoolError::InvalidRecipient.into());
+    }
+
+    // Verify timeout has passed
+    let clock = Clock::get()?;
+    let current_slot = clock.slot;
+    let timeout_slot = swap_state.timeout_slot();
+
+    if current_slot <= timeout_slot {
+        return Err(ShieldPoolError::SwapTimeoutNotExpired.into());
+    }
+
+    // Verify the swap was not executed: once PrepareSwapSol or ReleaseSwapFunds
+    // has moved the SOL out, the PDA only holds its rent-exempt minimum
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(SwapState::SIZE);
+    let locked_lamports = rent_exempt_minimum
+        .checked_add(swap_state.sol_amount())
+        .ok_or(ShieldPoolError::MathOverflow)?;
+    let swap_state_lamports = swap_state_info.lamports();
+
+    if swap_state_lamports < locked_lamports {
+        return Err(ShieldPoolError::SwapAlreadyExecuted.into());
+    }
+
+    // Close SwapState PDA - transfer all lamports to recipient
+    let recipient_lamports = recipient_info
+        .lamports()
+        .checked_add(swap_state_lamports)
+        .ok_or(ShieldPoolError::MathOverflow)?;
+
+    unsafe {
+        *swap_state_info.borrow_mut_lamports_unchecked() = 0;
+        *recipient_info.borrow_mut_lamports_unchecked() = recipient_lamports;
+    }
+
+    // Zero out the SwapState data (mark as closed)
+    let data = unsafe { swap_state_info.borrow_mut_data_unchecked() };
+    data.fill(0);
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use core::convert::TryInto;
 
+use cloak_layouts::{calculate_fee, outputs::MAX_OUTPUTS, FeeSchedule};
 use pinocchio::{
     account_info::AccountInfo,
     cpi::invoke_signed,
@@ -21,7 +22,6 @@ use crate::{
 
 const MIN_TAIL_LEN: usize = PUB_LEN + DUPLICATE_NULLIFIER_LEN + NUM_OUTPUTS_LEN;
 const PER_OUTPUT_LEN: usize = RECIPIENT_ADDR_LEN + RECIPIENT_AMOUNT_LEN;
-const MAX_OUTPUTS: usize = 5;
 
 struct ParsedWithdraw<'a> {
     proof: &'a [u8],
@@ -411,6 +411,7 @@ fn process_withdraw_unified<'a>(
         GROTH16_VK_5_0_0_BYTES,
     )
     .map_err(|_| ShieldPoolError::ProofInvalid)?;
+    super::check_proof_fresh(&parsed.public_inputs)?;
 
     {
         let roots_ring = crate::state::RootsRing::from_account_info(roots_ring_info)?;
@@ -428,7 +429,7 @@ fn process_withdraw_unified<'a>(
     }
 
     // Validate outputs hash by hashing all recipients
-    let mut hasher = blake3::Hasher::new();
+    let mut hasher = cloak_layouts::domains::hasher(cloak_layouts::domains::OUTPUTS_HASH);
     let mut total_recipient_amount = 0u64;
     for i in 0..num_recipients {
         let (address, amount) = parsed.recipients[i];
@@ -454,11 +455,13 @@ fn process_withdraw_unified<'a>(
     // Fee validation:
     // - For native SOL: fixed fee (0.0025 SOL) + variable fee (0.5%) both from withdrawal amount
     // - For SPL tokens: only variable fee (0.5%) from withdrawal amount, fixed fee paid in SOL separately
-    let expected_fee = if is_native_asset {
-        2_500_000u64 + (parsed.public_amount * 5) / 1_000
+    let fee_schedule = if is_native_asset {
+        FeeSchedule::NATIVE
     } else {
-        (parsed.public_amount * 5) / 1_000
+        FeeSchedule::VARIABLE_ONLY
     };
+    let expected_fee = calculate_fee(parsed.public_amount, fee_schedule)
+        .map_err(|_| ShieldPoolError::MathOverflow)?;
     let total_fee = parsed.public_amount - total_recipient_amount;
     if total_fee != expected_fee {
         return Err(ShieldPoolError::Conservation.into());
@@ -543,10 +546,11 @@ fn process_withdraw_unified<'a>(
                 .map_err(|_| ShieldPoolError::InvalidMinerAccount)?
         };
 
-        let mut consume_ix_data = [0u8; 65];
+        let mut consume_ix_data = [0u8; 97];
         consume_ix_data[0] = 4;
         consume_ix_data[1..33].copy_from_slice(&miner_authority);
         consume_ix_data[33..65].copy_from_slice(&batch_hash);
+        consume_ix_data[65..97].copy_from_slice(&parsed.nullifier);
 
         let account_metas = [
             AccountMeta::writable(ctx.claim_pda_info.key()),
@@ -597,6 +601,24 @@ fn process_withdraw_unified<'a>(
             return Err(ShieldPoolError::BadAccounts.into());
         }
 
+        // Token account layout: [mint(32)][owner(32)][amount(8)][...]
+        let pool_token_balance = {
+            let data = spl_context.pool_token_account.try_borrow_data()?;
+            if data.len() < 72 {
+                return Err(ShieldPoolError::InvalidAccountSize.into());
+            }
+            u64::from_le_bytes(
+                data[64..72]
+                    .try_into()
+                    .map_err(|_| ShieldPoolError::InvalidAccountSize)?,
+            )
+        };
+        check_withdraw_cap(
+            parsed.public_amount,
+            pool_token_balance,
+            pool_state.max_withdraw_bps(),
+        )?;
+
         let pool_bump_seed = [pool_bump];
         let pool_seeds = [
             Seed::from(b"pool".as_ref()),
@@ -653,6 +675,11 @@ fn process_withdraw_unified<'a>(
     if pool_info.lamports() < parsed.public_amount {
         return Err(ShieldPoolError::InsufficientLamports.into());
     }
+    check_withdraw_cap(
+        parsed.public_amount,
+        pool_info.lamports(),
+        pool_state.max_withdraw_bps(),
+    )?;
 
     let pool_lamports = pool_info.lamports();
     let treasury_lamports = treasury_info.lamports();
@@ -685,3 +712,21 @@ fn process_withdraw_unified<'a>(
 
     Ok(())
 }
+
+/// Reject a withdraw above `max_withdraw_bps` of the pool's balance before it,
+/// bounding how much a single transaction can drain (0 = uncapped)
+#[inline(always)]
+pub(crate) fn check_withdraw_cap(
+    amount: u64,
+    pool_balance: u64,
+    max_withdraw_bps: u16,
+) -> ProgramResult {
+    if max_withdraw_bps == 0 {
+        return Ok(());
+    }
+    let cap = (pool_balance as u128 * max_withdraw_bps as u128) / 10_000;
+    if amount as u128 > cap {
+        return Err(ShieldPoolError::WithdrawExceedsPoolCap.into());
+    }
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use core::convert::TryInto;
 
+use cloak_layouts::{calculate_fee, encode_swap_outputs, FeeSchedule};
 use pinocchio::{
     account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, sysvars::Sysvar,
     ProgramResult,
@@ -15,9 +16,9 @@ use sp1_solana::{verify_proof, GROTH16_VK_5_0_0_BYTES};
 /// 4. Withdraws SOL from pool → SwapState PDA (native SOL, held by PDA)
 ///
 /// Instruction data layout:
-/// [proof (260)][public_inputs (104)][duplicate_nullifier (32)]
+/// [proof (260)][public_inputs (104, or 112 with proof-freshness)][duplicate_nullifier (32)]
 /// [output_mint (32)][recipient_ata (32)][min_output_amount (8)]
-/// Total: 468 bytes (no batch_hash for swaps)
+/// Total: 468 bytes, or 476 with proof-freshness (no batch_hash for swaps)
 ///
 /// Account layout:
 /// 0. pool_pda (writable)
@@ -70,7 +71,7 @@ fn parse_withdraw_swap_data(data: &[u8]) -> Result<ParsedWithdrawSwap, ShieldPoo
     let proof = &data[offset..offset + PROOF_LEN];
     offset += PROOF_LEN;
 
-    // Parse public inputs (104 bytes)
+    // Parse public inputs
     let public_inputs_slice = &data[offset..offset + PUB_LEN];
     let mut public_inputs = [0u8; PUB_LEN];
     public_inputs.copy_from_slice(public_inputs_slice);
@@ -176,11 +177,13 @@ pub fn process_withdraw_swap_instruction(accounts: &[AccountInfo], data: &[u8])
 
     // Compute expected outputs_hash for swap mode
     // outputs_hash = H(output_mint || recipient_ata || min_output_amount || public_amount)
-    let mut hasher = blake3::Hasher::new();
-    hasher.update(parsed.output_mint.as_ref());
-    hasher.update(parsed.recipient_ata.as_ref());
-    hasher.update(&parsed.min_output_amount.to_le_bytes());
-    hasher.update(&parsed.public_amount.to_le_bytes());
+    let mut hasher = cloak_layouts::domains::hasher(cloak_layouts::domains::OUTPUTS_HASH);
+    hasher.update(&encode_swap_outputs(
+        &parsed.output_mint,
+        &parsed.recipient_ata,
+        parsed.min_output_amount,
+        parsed.public_amount,
+    ));
     let expected_outputs_hash = hasher.finalize();
 
     if &parsed.outputs_hash != expected_outputs_hash.as_bytes() {
@@ -195,6 +198,7 @@ pub fn process_withdraw_swap_instruction(accounts: &[AccountInfo], data: &[u8])
         GROTH16_VK_5_0_0_BYTES,
     )
     .map_err(|_| ShieldPoolError::ProofInvalid)?;
+    super::check_proof_fresh(&parsed.public_inputs)?;
 
     // Mark nullifier as used
     nullifier_shard.add_nullifier(&parsed.nullifier)?;
@@ -250,13 +254,20 @@ pub fn process_withdraw_swap_instruction(accounts: &[AccountInfo], data: &[u8])
     // 1. Pool → Treasury: full public_amount (100M)
     // 2. Treasury → SwapState: public_amount - fee (99.5M)
     // 3. Treasury keeps: fee (0.5M)
-    let variable_fee = (parsed.public_amount * 5) / 1_000;
+    let variable_fee = calculate_fee(parsed.public_amount, FeeSchedule::VARIABLE_ONLY)
+        .map_err(|_| ShieldPoolError::MathOverflow)?;
     let amount_to_transfer = parsed.public_amount - variable_fee;
 
     let pool_lamports = pool_info.lamports();
     let treasury_lamports = treasury_info.lamports();
     let swap_state_lamports = swap_state_info.lamports();
 
+    super::withdraw::check_withdraw_cap(
+        parsed.public_amount,
+        pool_lamports,
+        pool.max_withdraw_bps(),
+    )?;
+
     unsafe {
         // Pool → Treasury (full amount)
         *pool_info.borrow_mut_lamports_unchecked() = pool_lamports - parsed.public_amount;
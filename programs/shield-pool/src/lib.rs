@@ -8,7 +8,7 @@ use pinocchio::{
 use crate::instructions::ShieldPoolInstruction;
 
 mod constants;
-mod error;
+pub mod error;
 pub mod instructions;
 pub mod state;
 
@@ -69,5 +69,29 @@ pub fn process_instruction(
         ShieldPoolInstruction::PrepareSwapSol => {
             prepare_swap_sol::process_prepare_swap_sol(program_id, accounts)
         }
+        ShieldPoolInstruction::AdminPushRoots => {
+            admin_push_roots::process_admin_push_roots_instruction(accounts, instruction_data)
+        }
+        ShieldPoolInstruction::DepositWithMemo => {
+            deposit_with_memo::process_deposit_with_memo_instruction(accounts, instruction_data)
+        }
+        ShieldPoolInstruction::TimeoutRefundSwap => {
+            timeout_refund_swap::process_timeout_refund_swap(accounts, instruction_data)
+        }
+        ShieldPoolInstruction::AdminRotatePropose => {
+            admin_rotate::process_admin_rotate_propose_instruction(accounts, instruction_data)
+        }
+        ShieldPoolInstruction::AdminRotateAccept => {
+            admin_rotate::process_admin_rotate_accept_instruction(accounts)
+        }
+        ShieldPoolInstruction::AdminSetFlags => {
+            admin_set_flags::process_admin_set_flags_instruction(accounts, instruction_data)
+        }
+        ShieldPoolInstruction::AdminSetMaxWithdraw => {
+            admin_set_max_withdraw::process_admin_set_max_withdraw_instruction(
+                accounts,
+                instruction_data,
+            )
+        }
     }
 }
@@ -1,22 +1,38 @@
+use cloak_layouts::{commitment_queue, pool, roots_ring, swap_state};
 use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
 
-use crate::{error::ShieldPoolError, ID};
+use crate::{constants::ADMIN_AUTHORITY, error::ShieldPoolError, ID};
 
-/// Pool: Stores the token mint for this shield pool
-/// Layout: [mint: 32 bytes]
+/// Pool: Stores the token mint for this shield pool, its withdraw cap, admin
+/// and flags
+/// Layout: [mint: 32 bytes][max_withdraw_bps: u16][authority: 32][pending_authority: 32][flags: u8]
 /// If mint == Pubkey::default() (all zeros), pool handles native SOL
 /// Otherwise, pool handles the specified SPL token
-pub struct Pool(*mut u8);
+/// Legacy mint-only pools (32 bytes) are accepted with the cap disabled and
+/// mint-and-cap pools (34 bytes) with their cap; both are administered by
+/// ADMIN_AUTHORITY until an admin instruction grows them to `SIZE`. Pools
+/// without the flags byte (98 bytes) have no flags set
+pub struct Pool {
+    data: *mut u8,
+    len: usize,
+}
 
 impl Pool {
-    pub const SIZE: usize = 32; // Just the mint pubkey
+    pub const SIZE: usize = pool::SIZE; // 99 bytes
+    pub const PRE_FLAGS_SIZE: usize = pool::PRE_FLAGS_SIZE; // 98 bytes, no flags
+    pub const PRE_AUTHORITY_SIZE: usize = pool::PRE_AUTHORITY_SIZE; // 34 bytes, mint and cap
+    pub const LEGACY_SIZE: usize = pool::LEGACY_SIZE; // 32 bytes, mint only
 
     #[inline(always)]
     pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
         if account_info.owner() != &ID {
             return Err(ShieldPoolError::InvalidAccountOwner.into());
         }
-        if account_info.data_len() != Self::SIZE {
+        let len = account_info.data_len();
+        if !matches!(
+            len,
+            Self::SIZE | Self::PRE_FLAGS_SIZE | Self::PRE_AUTHORITY_SIZE | Self::LEGACY_SIZE
+        ) {
             return Err(ShieldPoolError::InvalidAccountSize.into());
         }
         Ok(Self::from_account_info_unchecked(account_info))
@@ -24,14 +40,29 @@ impl Pool {
 
     #[inline(always)]
     fn from_account_info_unchecked(account_info: &AccountInfo) -> Self {
-        unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
+        unsafe {
+            let data = account_info.borrow_mut_data_unchecked();
+            Self {
+                data: data.as_mut_ptr(),
+                len: data.len(),
+            }
+        }
+    }
+
+    /// Test-only view over raw account data; must not outlive `data`
+    #[cfg(test)]
+    pub(crate) fn from_data_unchecked(data: &mut [u8]) -> Self {
+        Self {
+            data: data.as_mut_ptr(),
+            len: data.len(),
+        }
     }
 
     #[inline(always)]
     pub fn mint(&self) -> Pubkey {
         unsafe {
             let mut mint_bytes = [0u8; 32];
-            core::ptr::copy_nonoverlapping(self.0, mint_bytes.as_mut_ptr(), 32);
+            core::ptr::copy_nonoverlapping(self.data.add(pool::MINT), mint_bytes.as_mut_ptr(), 32);
             Pubkey::from(mint_bytes)
         }
     }
@@ -39,7 +70,7 @@ impl Pool {
     #[inline(always)]
     pub fn set_mint(&mut self, mint: &Pubkey) {
         unsafe {
-            core::ptr::copy_nonoverlapping(mint.as_ref().as_ptr(), self.0, 32);
+            core::ptr::copy_nonoverlapping(mint.as_ref().as_ptr(), self.data.add(pool::MINT), 32);
         }
     }
 
@@ -47,6 +78,122 @@ impl Pool {
     pub fn is_native(&self) -> bool {
         self.mint() == Pubkey::default()
     }
+
+    /// Largest single withdraw, in basis points of the pool balance (0 = uncapped)
+    #[inline(always)]
+    pub fn max_withdraw_bps(&self) -> u16 {
+        if self.len < Self::PRE_AUTHORITY_SIZE {
+            return 0;
+        }
+        unsafe {
+            let mut bps = [0u8; 2];
+            core::ptr::copy_nonoverlapping(
+                self.data.add(pool::MAX_WITHDRAW_BPS),
+                bps.as_mut_ptr(),
+                2,
+            );
+            u16::from_le_bytes(bps)
+        }
+    }
+
+    /// No-op on legacy pools, which have no room for the cap
+    #[inline(always)]
+    pub fn set_max_withdraw_bps(&mut self, bps: u16) {
+        if self.len < Self::PRE_AUTHORITY_SIZE {
+            return;
+        }
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                bps.to_le_bytes().as_ptr(),
+                self.data.add(pool::MAX_WITHDRAW_BPS),
+                2,
+            );
+        }
+    }
+
+    /// Admin allowed to push roots and rotate authority; falls back to
+    /// ADMIN_AUTHORITY for pools too old to store one and pools that never did
+    #[inline(always)]
+    pub fn authority(&self) -> Pubkey {
+        if self.len < Self::PRE_FLAGS_SIZE {
+            return ADMIN_AUTHORITY;
+        }
+        let authority = self.read_pubkey(pool::AUTHORITY);
+        if authority == Pubkey::default() {
+            ADMIN_AUTHORITY
+        } else {
+            authority
+        }
+    }
+
+    #[inline(always)]
+    pub fn set_authority(&mut self, authority: &Pubkey) -> Result<(), ProgramError> {
+        self.write_pubkey(pool::AUTHORITY, authority)
+    }
+
+    /// Authority proposed by the current admin, all zeros when none is pending
+    #[inline(always)]
+    pub fn pending_authority(&self) -> Pubkey {
+        if self.len < Self::PRE_FLAGS_SIZE {
+            return Pubkey::default();
+        }
+        self.read_pubkey(pool::PENDING_AUTHORITY)
+    }
+
+    #[inline(always)]
+    pub fn set_pending_authority(&mut self, pending: &Pubkey) -> Result<(), ProgramError> {
+        self.write_pubkey(pool::PENDING_AUTHORITY, pending)
+    }
+
+    /// `pool::FLAG_*` bits; none are set on pools without the flags byte
+    #[inline(always)]
+    pub fn flags(&self) -> u8 {
+        if self.len < Self::SIZE {
+            return 0;
+        }
+        unsafe { *self.data.add(pool::FLAGS) }
+    }
+
+    /// Older pools have no room for flags
+    #[inline(always)]
+    pub fn set_flags(&mut self, flags: u8) -> Result<(), ProgramError> {
+        if self.len < Self::SIZE {
+            return Err(ShieldPoolError::InvalidAccountSize.into());
+        }
+        unsafe {
+            *self.data.add(pool::FLAGS) = flags;
+        }
+        Ok(())
+    }
+
+    /// Whether deposits must fail on a full commitment queue rather than
+    /// overwrite its oldest commitments
+    #[inline(always)]
+    pub fn no_overwrite(&self) -> bool {
+        self.flags() & pool::FLAG_NO_OVERWRITE != 0
+    }
+
+    #[inline(always)]
+    fn read_pubkey(&self, offset: usize) -> Pubkey {
+        unsafe {
+            let mut bytes = [0u8; 32];
+            core::ptr::copy_nonoverlapping(self.data.add(offset), bytes.as_mut_ptr(), 32);
+            Pubkey::from(bytes)
+        }
+    }
+
+    /// Older pools have no room for authority fields until grown with
+    /// `grow_pool`
+    #[inline(always)]
+    fn write_pubkey(&mut self, offset: usize, key: &Pubkey) -> Result<(), ProgramError> {
+        if self.len < Self::PRE_FLAGS_SIZE {
+            return Err(ShieldPoolError::InvalidAccountSize.into());
+        }
+        unsafe {
+            core::ptr::copy_nonoverlapping(key.as_ref().as_ptr(), self.data.add(offset), 32);
+        }
+        Ok(())
+    }
 }
 
 /// CommitmentQueue: Fixed-size ring buffer storing recent deposit commitments.
@@ -55,9 +202,9 @@ impl Pool {
 pub struct CommitmentQueue(*mut u8);
 
 impl CommitmentQueue {
-    pub const HEADER_SIZE: usize = 16; // 8 bytes count + 8 bytes reserved
-    pub const CAPACITY: usize = 256;
-    pub const SIZE: usize = Self::HEADER_SIZE + Self::CAPACITY * 32; // 16 + 8192 = 8208 bytes
+    pub const HEADER_SIZE: usize = commitment_queue::HEADER_SIZE; // 8 bytes count + 8 bytes reserved
+    pub const CAPACITY: usize = commitment_queue::CAPACITY;
+    pub const SIZE: usize = commitment_queue::SIZE; // 16 + 8192 = 8208 bytes
 
     #[inline(always)]
     pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
@@ -75,6 +222,23 @@ impl CommitmentQueue {
         unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
     }
 
+    /// Test-only view over raw account data; must not outlive `data`
+    #[cfg(test)]
+    pub(crate) fn from_data_unchecked(data: &mut [u8]) -> Self {
+        Self(data.as_mut_ptr())
+    }
+
+    /// Decode the commitments still held in a queue account, oldest first.
+    ///
+    /// Walks the ring with the same `live_window` as `contains`, so off-chain
+    /// tooling (relay, indexer, tests) sees exactly what the program checks against.
+    #[cfg(not(target_os = "solana"))]
+    pub fn snapshot(data: &[u8]) -> Result<Vec<[u8; 32]>, ProgramError> {
+        let queue = cloak_layouts::decode_commitment_queue(data)
+            .ok_or(ShieldPoolError::InvalidAccountSize)?;
+        Ok(queue.commitments().collect())
+    }
+
     #[inline(always)]
     pub fn total_commits(&self) -> u64 {
         unsafe { u64::from_le(*(self.0 as *const u64)) }
@@ -89,7 +253,7 @@ impl CommitmentQueue {
 
     #[inline(always)]
     fn slot_offset(slot: usize) -> usize {
-        Self::HEADER_SIZE + slot * 32
+        commitment_queue::slot_offset(slot)
     }
 
     #[inline(always)]
@@ -108,13 +272,11 @@ impl CommitmentQueue {
 
     #[inline(always)]
     pub fn contains(&self, commitment: &[u8; 32]) -> bool {
-        let total = self.total_commits();
-        let count = core::cmp::min(total, Self::CAPACITY as u64);
+        let (start_index, count) = commitment_queue::live_window(self.total_commits());
         if count == 0 {
             return false;
         }
 
-        let start_index = total.saturating_sub(count);
         let mut buffer = [0u8; 32];
         for offset in 0..count {
             let index = start_index + offset;
@@ -129,6 +291,13 @@ impl CommitmentQueue {
         false
     }
 
+    /// Every slot holds a live commitment, so the next append overwrites the
+    /// oldest one
+    #[inline(always)]
+    pub fn is_full(&self) -> bool {
+        self.total_commits() >= Self::CAPACITY as u64
+    }
+
     #[inline(always)]
     pub fn append(&mut self, commitment: &[u8; 32]) -> Result<u64, ProgramError> {
         let total = self.total_commits();
@@ -150,8 +319,8 @@ impl CommitmentQueue {
 pub struct RootsRing(*mut u8);
 
 impl RootsRing {
-    pub const SIZE: usize = 8 + 64 * 32; // 2056 bytes
-    pub const MAX_ROOTS: usize = 64;
+    pub const SIZE: usize = roots_ring::SIZE; // 2056 bytes
+    pub const MAX_ROOTS: usize = roots_ring::MAX_ROOTS;
 
     #[inline(always)]
     pub fn from_account_info_unchecked(
@@ -185,8 +354,7 @@ impl RootsRing {
             *self.0 = new_head;
 
             // Store root at new position
-            let root_offset = 8 + (new_head as usize) * 32;
-            let root_ptr = self.0.add(root_offset);
+            let root_ptr = self.0.add(roots_ring::root_offset(new_head as usize));
             core::ptr::copy_nonoverlapping(root.as_ptr(), root_ptr, 32);
         }
 
@@ -198,8 +366,7 @@ impl RootsRing {
         unsafe {
             // Unrolled first 8 comparisons for common cases
             for i in 0..8 {
-                let root_offset = 8 + i * 32;
-                let root_ptr = self.0.add(root_offset) as *const [u8; 32];
+                let root_ptr = self.0.add(roots_ring::root_offset(i)) as *const [u8; 32];
                 if &*root_ptr == target_root {
                     return true;
                 }
@@ -207,8 +374,7 @@ impl RootsRing {
 
             // Continue with regular loop for remaining roots
             for i in 8..Self::MAX_ROOTS {
-                let root_offset = 8 + i * 32;
-                let root_ptr = self.0.add(root_offset) as *const [u8; 32];
+                let root_ptr = self.0.add(roots_ring::root_offset(i)) as *const [u8; 32];
                 if &*root_ptr == target_root {
                     return true;
                 }
@@ -248,6 +414,8 @@ impl NullifierShard {
         unsafe { u32::from_le(*(self.0 as *const u32)) }
     }
 
+    /// Uses short-circuiting `==` to save compute units; timing is not
+    /// observable on-chain. Off-chain checks in the relay use constant-time equality.
     #[inline(always)]
     pub fn contains_nullifier(&self, nf: &[u8; 32]) -> bool {
         let count = self.count() as usize;
@@ -308,9 +476,9 @@ impl NullifierShard {
 pub struct SwapState(*mut u8);
 
 impl SwapState {
-    pub const SIZE: usize = 32 + 8 + 32 + 32 + 8 + 8 + 8 + 1; // 129 bytes
+    pub const SIZE: usize = swap_state::SIZE; // 129 bytes
 
-    pub const SEED_PREFIX: &'static [u8] = b"swap_state";
+    pub const SEED_PREFIX: &'static [u8] = swap_state::SEED_PREFIX;
 
     #[inline(always)]
     pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
@@ -328,6 +496,12 @@ impl SwapState {
         unsafe { Self(account_info.borrow_mut_data_unchecked().as_mut_ptr()) }
     }
 
+    /// Test-only view over raw account data; must not outlive `data`
+    #[cfg(test)]
+    pub(crate) fn from_data_unchecked(data: &mut [u8]) -> Self {
+        Self(data.as_mut_ptr())
+    }
+
     // Getters
     #[inline(always)]
     pub fn nullifier(&self) -> [u8; 32] {
@@ -340,14 +514,18 @@ impl SwapState {
 
     #[inline(always)]
     pub fn sol_amount(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(32) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(swap_state::SOL_AMOUNT) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn output_mint(&self) -> Pubkey {
         unsafe {
             let mut bytes = [0u8; 32];
-            core::ptr::copy_nonoverlapping(self.0.add(40), bytes.as_mut_ptr(), 32);
+            core::ptr::copy_nonoverlapping(
+                self.0.add(swap_state::OUTPUT_MINT),
+                bytes.as_mut_ptr(),
+                32,
+            );
             Pubkey::from(bytes)
         }
     }
@@ -356,29 +534,33 @@ impl SwapState {
     pub fn recipient_ata(&self) -> Pubkey {
         unsafe {
             let mut bytes = [0u8; 32];
-            core::ptr::copy_nonoverlapping(self.0.add(72), bytes.as_mut_ptr(), 32);
+            core::ptr::copy_nonoverlapping(
+                self.0.add(swap_state::RECIPIENT_ATA),
+                bytes.as_mut_ptr(),
+                32,
+            );
             Pubkey::from(bytes)
         }
     }
 
     #[inline(always)]
     pub fn min_output_amount(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(104) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(swap_state::MIN_OUTPUT_AMOUNT) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn created_slot(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(112) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(swap_state::CREATED_SLOT) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn timeout_slot(&self) -> u64 {
-        unsafe { u64::from_le(*(self.0.add(120) as *const u64)) }
+        unsafe { u64::from_le(*(self.0.add(swap_state::TIMEOUT_SLOT) as *const u64)) }
     }
 
     #[inline(always)]
     pub fn bump(&self) -> u8 {
-        unsafe { *self.0.add(128) }
+        unsafe { *self.0.add(swap_state::BUMP) }
     }
 
     // Setters
@@ -392,49 +574,57 @@ impl SwapState {
     #[inline(always)]
     pub fn set_sol_amount(&mut self, amount: u64) {
         unsafe {
-            *(self.0.add(32) as *mut u64) = amount.to_le();
+            *(self.0.add(swap_state::SOL_AMOUNT) as *mut u64) = amount.to_le();
         }
     }
 
     #[inline(always)]
     pub fn set_output_mint(&mut self, mint: &Pubkey) {
         unsafe {
-            core::ptr::copy_nonoverlapping(mint.as_ref().as_ptr(), self.0.add(40), 32);
+            core::ptr::copy_nonoverlapping(
+                mint.as_ref().as_ptr(),
+                self.0.add(swap_state::OUTPUT_MINT),
+                32,
+            );
         }
     }
 
     #[inline(always)]
     pub fn set_recipient_ata(&mut self, ata: &Pubkey) {
         unsafe {
-            core::ptr::copy_nonoverlapping(ata.as_ref().as_ptr(), self.0.add(72), 32);
+            core::ptr::copy_nonoverlapping(
+                ata.as_ref().as_ptr(),
+                self.0.add(swap_state::RECIPIENT_ATA),
+                32,
+            );
         }
     }
 
     #[inline(always)]
     pub fn set_min_output_amount(&mut self, amount: u64) {
         unsafe {
-            *(self.0.add(104) as *mut u64) = amount.to_le();
+            *(self.0.add(swap_state::MIN_OUTPUT_AMOUNT) as *mut u64) = amount.to_le();
         }
     }
 
     #[inline(always)]
     pub fn set_created_slot(&mut self, slot: u64) {
         unsafe {
-            *(self.0.add(112) as *mut u64) = slot.to_le();
+            *(self.0.add(swap_state::CREATED_SLOT) as *mut u64) = slot.to_le();
         }
     }
 
     #[inline(always)]
     pub fn set_timeout_slot(&mut self, slot: u64) {
         unsafe {
-            *(self.0.add(120) as *mut u64) = slot.to_le();
+            *(self.0.add(swap_state::TIMEOUT_SLOT) as *mut u64) = slot.to_le();
         }
     }
 
     #[inline(always)]
     pub fn set_bump(&mut self, bump: u8) {
         unsafe {
-            *self.0.add(128) = bump;
+            *self.0.add(swap_state::BUMP) = bump;
         }
     }
 
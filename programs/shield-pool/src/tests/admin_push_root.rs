@@ -1,10 +1,17 @@
+use mollusk_svm::result::ProgramResult;
 use solana_sdk::{
     account::Account,
     instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
     pubkey::Pubkey,
 };
 
-use crate::{instructions::ShieldPoolInstruction, state::RootsRing, tests::setup};
+use crate::{
+    error::ShieldPoolError,
+    instructions::ShieldPoolInstruction,
+    state::RootsRing,
+    tests::{native_pool, setup},
+};
 
 #[test]
 fn test_admin_push_root_instruction() {
@@ -19,6 +26,7 @@ fn test_admin_push_root_instruction() {
     // Create a roots ring PDA with mint
     let (roots_ring_pda, _) =
         Pubkey::find_program_address(&[b"roots_ring", mint.as_ref()], &program_id);
+    let (pool_pda, pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
 
     // Create test root to push
     let new_root = [0x42u8; 32]; // Test root
@@ -36,6 +44,7 @@ fn test_admin_push_root_instruction() {
         vec![
             AccountMeta::new(admin_pubkey, true),    // admin is signer
             AccountMeta::new(roots_ring_pda, false), // roots_ring is writable (but not signer)
+            AccountMeta::new_readonly(pool_pda, false),
         ],
     );
 
@@ -65,6 +74,7 @@ fn test_admin_push_root_instruction() {
                 rent_epoch: 0,
             },
         ),
+        (pool_pda, pool_account),
     ];
 
     let result = mollusk.process_and_validate_instruction(&instruction, &accounts, &[]);
@@ -110,6 +120,7 @@ fn test_admin_push_root_unauthorized() {
 
     let (roots_ring_pda, _) =
         Pubkey::find_program_address(&[b"roots_ring", mint.as_ref()], &program_id);
+    let (pool_pda, pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
     let new_root = [0x42u8; 32];
 
     let instruction_data = [
@@ -124,6 +135,7 @@ fn test_admin_push_root_unauthorized() {
         vec![
             AccountMeta::new(unauthorized_admin, true), // unauthorized admin
             AccountMeta::new(roots_ring_pda, false),
+            AccountMeta::new_readonly(pool_pda, false),
         ],
     );
 
@@ -148,6 +160,7 @@ fn test_admin_push_root_unauthorized() {
                 rent_epoch: 0,
             },
         ),
+        (pool_pda, pool_account),
     ];
 
     let result = mollusk.process_and_validate_instruction(&instruction, &accounts, &[]);
@@ -172,6 +185,7 @@ fn test_admin_push_root_multiple_roots() {
 
     let (roots_ring_pda, _) =
         Pubkey::find_program_address(&[b"roots_ring", mint.as_ref()], &program_id);
+    let (pool_pda, pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
 
     // Push first root
     let root1 = [0x42u8; 32];
@@ -187,6 +201,7 @@ fn test_admin_push_root_multiple_roots() {
         vec![
             AccountMeta::new(admin_pubkey, true),
             AccountMeta::new(roots_ring_pda, false),
+            AccountMeta::new_readonly(pool_pda, false),
         ],
     );
 
@@ -211,6 +226,7 @@ fn test_admin_push_root_multiple_roots() {
                 rent_epoch: 0,
             },
         ),
+        (pool_pda, pool_account),
     ];
 
     let result1 = mollusk.process_and_validate_instruction(&instruction1, &accounts, &[]);
@@ -236,6 +252,7 @@ fn test_admin_push_root_multiple_roots() {
         vec![
             AccountMeta::new(admin_pubkey, true),
             AccountMeta::new(roots_ring_pda, false),
+            AccountMeta::new_readonly(pool_pda, false),
         ],
     );
 
@@ -269,3 +286,208 @@ fn test_admin_push_root_multiple_roots() {
     println!("   - Root 1: {}", hex::encode(root1));
     println!("   - Root 2: {}", hex::encode(root2));
 }
+
+#[test]
+fn test_admin_push_roots_batch() {
+    let (program_id, mollusk) = setup();
+
+    let admin_pubkey = Pubkey::new_from_array(five8_const::decode_32_const(
+        "mgfSqUe1qaaUjeEzuLUyDUx5Rk4fkgePB5NtLnS3Vxa",
+    ));
+    let mint = Pubkey::default(); // Native SOL
+
+    let (roots_ring_pda, _) =
+        Pubkey::find_program_address(&[b"roots_ring", mint.as_ref()], &program_id);
+    let (pool_pda, pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
+
+    let roots = [[0x42u8; 32], [0x43u8; 32], [0x44u8; 32]];
+    let mut instruction_data = vec![
+        ShieldPoolInstruction::AdminPushRoots as u8,
+        roots.len() as u8,
+    ];
+    for root in &roots {
+        instruction_data.extend_from_slice(root);
+    }
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &instruction_data,
+        vec![
+            AccountMeta::new(admin_pubkey, true),
+            AccountMeta::new(roots_ring_pda, false),
+            AccountMeta::new_readonly(pool_pda, false),
+        ],
+    );
+
+    let accounts: Vec<(Pubkey, Account)> = vec![
+        (
+            admin_pubkey,
+            Account {
+                lamports: mollusk.sysvars.rent.minimum_balance(0),
+                data: vec![],
+                owner: solana_sdk::system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+        (
+            roots_ring_pda,
+            Account {
+                lamports: mollusk.sysvars.rent.minimum_balance(RootsRing::SIZE),
+                data: vec![0u8; RootsRing::SIZE],
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+        (pool_pda, pool_account),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(&instruction, &accounts, &[]);
+    assert!(
+        !result.program_result.is_err(),
+        "AdminPushRoots failed: {:?}",
+        result.program_result
+    );
+
+    let updated_account = result
+        .resulting_accounts
+        .iter()
+        .find(|(pk, _)| *pk == roots_ring_pda)
+        .map(|(_, acc)| acc)
+        .expect("roots_ring account not found after");
+
+    // Pushed in order: head advances once per root
+    assert_eq!(updated_account.data[0], 3, "Head should be 3 after 3 roots");
+
+    // Same scan as RootsRing::contains_root
+    let contains_root = |root: &[u8; 32]| {
+        updated_account.data[8..]
+            .chunks_exact(32)
+            .any(|stored| stored == root)
+    };
+    for root in &roots {
+        assert!(contains_root(root), "Root {} missing", hex::encode(root));
+    }
+}
+
+#[test]
+fn test_admin_push_roots_rejects_more_than_ring_size() {
+    let (program_id, mollusk) = setup();
+
+    let admin_pubkey = Pubkey::new_from_array(five8_const::decode_32_const(
+        "mgfSqUe1qaaUjeEzuLUyDUx5Rk4fkgePB5NtLnS3Vxa",
+    ));
+    let (roots_ring_pda, _) =
+        Pubkey::find_program_address(&[b"roots_ring", Pubkey::default().as_ref()], &program_id);
+    let (pool_pda, pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
+
+    let count = RootsRing::MAX_ROOTS + 1;
+    let mut instruction_data = vec![ShieldPoolInstruction::AdminPushRoots as u8, count as u8];
+    instruction_data.resize(2 + count * 32, 0x42);
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &instruction_data,
+        vec![
+            AccountMeta::new(admin_pubkey, true),
+            AccountMeta::new(roots_ring_pda, false),
+            AccountMeta::new_readonly(pool_pda, false),
+        ],
+    );
+
+    let accounts: Vec<(Pubkey, Account)> = vec![
+        (
+            admin_pubkey,
+            Account {
+                lamports: mollusk.sysvars.rent.minimum_balance(0),
+                data: vec![],
+                owner: solana_sdk::system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+        (
+            roots_ring_pda,
+            Account {
+                lamports: mollusk.sysvars.rent.minimum_balance(RootsRing::SIZE),
+                data: vec![0u8; RootsRing::SIZE],
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+        (pool_pda, pool_account),
+    ];
+
+    let result = mollusk.process_and_validate_instruction(&instruction, &accounts, &[]);
+    assert!(
+        result.program_result.is_err(),
+        "AdminPushRoots should reject more roots than the ring holds"
+    );
+}
+
+#[test]
+fn test_admin_push_root_requires_pool_account() {
+    let (program_id, mollusk) = setup();
+
+    let admin_pubkey = Pubkey::new_from_array(five8_const::decode_32_const(
+        "mgfSqUe1qaaUjeEzuLUyDUx5Rk4fkgePB5NtLnS3Vxa",
+    ));
+    let (roots_ring_pda, _) =
+        Pubkey::find_program_address(&[b"roots_ring", Pubkey::default().as_ref()], &program_id);
+
+    // Clients that predate the pool account only send [admin, roots_ring];
+    // the pool's authority can't be checked without it, so even the built-in
+    // admin is turned away
+    let push_root = |admin: Pubkey| {
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[
+                &[ShieldPoolInstruction::AdminPushRoot as u8][..],
+                &[0x42u8; 32],
+            ]
+            .concat(),
+            vec![
+                AccountMeta::new(admin, true),
+                AccountMeta::new(roots_ring_pda, false),
+            ],
+        );
+        let accounts: Vec<(Pubkey, Account)> = vec![
+            (
+                admin,
+                Account {
+                    lamports: mollusk.sysvars.rent.minimum_balance(0),
+                    data: vec![],
+                    owner: solana_sdk::system_program::id(),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            ),
+            (
+                roots_ring_pda,
+                Account {
+                    lamports: mollusk.sysvars.rent.minimum_balance(RootsRing::SIZE),
+                    data: vec![0u8; RootsRing::SIZE],
+                    owner: program_id,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            ),
+        ];
+        mollusk.process_instruction(&instruction, &accounts)
+    };
+
+    for admin in [admin_pubkey, Pubkey::new_unique()] {
+        let result = push_root(admin);
+        assert!(
+            matches!(
+                result.program_result,
+                ProgramResult::Failure(ProgramError::Custom(code))
+                    if code == ShieldPoolError::MissingAccounts as u32
+            ),
+            "AdminPushRoot without the pool should fail: {:?}",
+            result.program_result
+        );
+    }
+}
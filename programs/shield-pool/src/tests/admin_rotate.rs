@@ -0,0 +1,301 @@
+use cloak_layouts::decode_pool;
+use mollusk_svm::{
+    result::{InstructionResult, ProgramResult},
+    Mollusk,
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::ShieldPoolError,
+    instructions::ShieldPoolInstruction,
+    state::{Pool, RootsRing},
+    tests::{admin_authority, native_pool, setup, signer_account, system_program_account},
+};
+
+fn rotate_instruction(
+    program_id: Pubkey,
+    tag: ShieldPoolInstruction,
+    signer: &Pubkey,
+    pool_pda: &Pubkey,
+    data: &[u8],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        program_id,
+        &[&[tag as u8], data].concat(),
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(*pool_pda, false),
+        ],
+    )
+}
+
+/// Native pool administered by ADMIN_AUTHORITY with `pending` already proposed
+fn pool_with_pending(
+    program_id: &Pubkey,
+    mollusk: &Mollusk,
+    pending: &Pubkey,
+) -> (Pubkey, Account) {
+    let (pool_pda, mut pool_account) = native_pool(program_id, mollusk, &Pubkey::default());
+    pool_account.data[cloak_layouts::pool::PENDING_AUTHORITY..cloak_layouts::pool::FLAGS]
+        .copy_from_slice(pending.as_ref());
+    (pool_pda, pool_account)
+}
+
+fn resulting_pool(result: &InstructionResult, pool_pda: &Pubkey) -> cloak_layouts::PoolView {
+    let account = result
+        .resulting_accounts
+        .iter()
+        .find(|(pk, _)| pk == pool_pda)
+        .map(|(_, acc)| acc)
+        .expect("pool account not found after");
+    decode_pool(&account.data).expect("pool account has wrong size")
+}
+
+fn assert_custom_error(result: &InstructionResult, err: ShieldPoolError) {
+    assert!(
+        matches!(
+            result.program_result,
+            ProgramResult::Failure(ProgramError::Custom(code)) if code == err as u32
+        ),
+        "expected {:?}, got: {:?}",
+        err,
+        result.program_result
+    );
+}
+
+#[test]
+fn test_admin_rotate_propose() {
+    let (program_id, mollusk) = setup();
+    let admin = admin_authority();
+    let new_authority = Pubkey::new_unique();
+    let (pool_pda, pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
+
+    let instruction = rotate_instruction(
+        program_id,
+        ShieldPoolInstruction::AdminRotatePropose,
+        &admin,
+        &pool_pda,
+        new_authority.as_ref(),
+    );
+    let accounts = vec![(admin, signer_account(&mollusk)), (pool_pda, pool_account)];
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(
+        !result.program_result.is_err(),
+        "AdminRotatePropose failed: {:?}",
+        result.program_result
+    );
+
+    // Proposing alone does not hand over control
+    let pool = resulting_pool(&result, &pool_pda);
+    assert_eq!(pool.pending_authority, new_authority.to_bytes());
+    assert_eq!(pool.authority, [0u8; 32]);
+
+    // Only the current authority may propose
+    let outsider = Pubkey::new_unique();
+    let instruction = rotate_instruction(
+        program_id,
+        ShieldPoolInstruction::AdminRotatePropose,
+        &outsider,
+        &pool_pda,
+        outsider.as_ref(),
+    );
+    let (_, pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
+    let accounts = vec![
+        (outsider, signer_account(&mollusk)),
+        (pool_pda, pool_account),
+    ];
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_custom_error(&result, ShieldPoolError::InvalidAdminAuthority);
+}
+
+#[test]
+fn test_admin_rotate_accept_by_new_authority() {
+    let (program_id, mollusk) = setup();
+    let new_authority = Pubkey::new_unique();
+    let (pool_pda, pool_account) = pool_with_pending(&program_id, &mollusk, &new_authority);
+
+    let instruction = rotate_instruction(
+        program_id,
+        ShieldPoolInstruction::AdminRotateAccept,
+        &new_authority,
+        &pool_pda,
+        &[],
+    );
+    let accounts = vec![
+        (new_authority, signer_account(&mollusk)),
+        (pool_pda, pool_account),
+    ];
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(
+        !result.program_result.is_err(),
+        "AdminRotateAccept failed: {:?}",
+        result.program_result
+    );
+
+    let pool = resulting_pool(&result, &pool_pda);
+    assert_eq!(pool.authority, new_authority.to_bytes());
+    assert_eq!(pool.pending_authority, [0u8; 32]);
+
+    // Root pushes now follow the rotated authority, not ADMIN_AUTHORITY
+    let (roots_ring_pda, _) =
+        Pubkey::find_program_address(&[b"roots_ring", Pubkey::default().as_ref()], &program_id);
+    let rotated_pool = result
+        .resulting_accounts
+        .iter()
+        .find(|(pk, _)| *pk == pool_pda)
+        .map(|(_, acc)| acc.clone())
+        .unwrap();
+    let push_root = |admin: Pubkey, with_pool: bool| {
+        let mut metas = vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new(roots_ring_pda, false),
+        ];
+        let mut accounts = vec![
+            (admin, signer_account(&mollusk)),
+            (
+                roots_ring_pda,
+                Account {
+                    lamports: mollusk.sysvars.rent.minimum_balance(RootsRing::SIZE),
+                    data: vec![0u8; RootsRing::SIZE],
+                    owner: program_id,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            ),
+        ];
+        if with_pool {
+            metas.push(AccountMeta::new_readonly(pool_pda, false));
+            accounts.push((pool_pda, rotated_pool.clone()));
+        }
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[
+                &[ShieldPoolInstruction::AdminPushRoot as u8][..],
+                &[0x42u8; 32],
+            ]
+            .concat(),
+            metas,
+        );
+        mollusk.process_instruction(&instruction, &accounts)
+    };
+
+    assert!(!push_root(new_authority, true).program_result.is_err());
+    assert_custom_error(
+        &push_root(admin_authority(), true),
+        ShieldPoolError::InvalidAdminAuthority,
+    );
+    // Leaving out the pool account doesn't bring the old key back
+    assert_custom_error(
+        &push_root(admin_authority(), false),
+        ShieldPoolError::MissingAccounts,
+    );
+}
+
+#[test]
+fn test_admin_rotate_accept_rejects_wrong_signer() {
+    let (program_id, mollusk) = setup();
+    let new_authority = Pubkey::new_unique();
+    let (pool_pda, pool_account) = pool_with_pending(&program_id, &mollusk, &new_authority);
+
+    // Neither a stranger nor the outgoing admin can complete the rotation
+    for signer in [Pubkey::new_unique(), admin_authority()] {
+        let instruction = rotate_instruction(
+            program_id,
+            ShieldPoolInstruction::AdminRotateAccept,
+            &signer,
+            &pool_pda,
+            &[],
+        );
+        let accounts = vec![
+            (signer, signer_account(&mollusk)),
+            (pool_pda, pool_account.clone()),
+        ];
+
+        let result = mollusk.process_instruction(&instruction, &accounts);
+        assert_custom_error(&result, ShieldPoolError::InvalidAdminAuthority);
+    }
+
+    // Nothing to accept once no rotation is pending
+    let (pool_pda, pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
+    let instruction = rotate_instruction(
+        program_id,
+        ShieldPoolInstruction::AdminRotateAccept,
+        &new_authority,
+        &pool_pda,
+        &[],
+    );
+    let accounts = vec![
+        (new_authority, signer_account(&mollusk)),
+        (pool_pda, pool_account),
+    ];
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert_custom_error(&result, ShieldPoolError::NoPendingAuthority);
+}
+
+#[test]
+fn test_admin_rotate_propose_grows_older_pools() {
+    let (program_id, mollusk) = setup();
+    let admin = admin_authority();
+    let new_authority = Pubkey::new_unique();
+
+    // Mint-only and mint-and-cap pools have no room for the authority fields
+    for old_size in [Pool::LEGACY_SIZE, Pool::PRE_AUTHORITY_SIZE] {
+        let (pool_pda, mut pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
+        pool_account.data.truncate(old_size);
+        pool_account.lamports = mollusk.sysvars.rent.minimum_balance(old_size);
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[
+                &[ShieldPoolInstruction::AdminRotatePropose as u8][..],
+                new_authority.as_ref(),
+            ]
+            .concat(),
+            vec![
+                AccountMeta::new(admin, true),
+                AccountMeta::new(pool_pda, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+        );
+        let accounts = vec![
+            (
+                admin,
+                Account {
+                    lamports: 1_000_000_000,
+                    ..signer_account(&mollusk)
+                },
+            ),
+            (pool_pda, pool_account),
+            system_program_account(),
+        ];
+
+        let result = mollusk.process_instruction(&instruction, &accounts);
+        assert!(
+            !result.program_result.is_err(),
+            "AdminRotatePropose on a {}-byte pool failed: {:?}",
+            old_size,
+            result.program_result
+        );
+
+        let grown = result
+            .resulting_accounts
+            .iter()
+            .find(|(pk, _)| *pk == pool_pda)
+            .map(|(_, acc)| acc)
+            .unwrap();
+        assert_eq!(grown.data.len(), Pool::SIZE);
+        assert!(grown.lamports >= mollusk.sysvars.rent.minimum_balance(Pool::SIZE));
+
+        let pool = resulting_pool(&result, &pool_pda);
+        assert_eq!(pool.pending_authority, new_authority.to_bytes());
+        assert_eq!(pool.authority, [0u8; 32]);
+        assert_eq!(pool.flags, 0);
+    }
+}
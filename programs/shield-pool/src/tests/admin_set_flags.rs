@@ -0,0 +1,124 @@
+use cloak_layouts::{decode_pool, pool::FLAG_NO_OVERWRITE};
+use mollusk_svm::{
+    result::{InstructionResult, ProgramResult},
+    Mollusk,
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::ShieldPoolError,
+    instructions::ShieldPoolInstruction,
+    state::Pool,
+    tests::{admin_authority, native_pool, setup, signer_account, system_program_account},
+};
+
+fn set_flags(
+    program_id: Pubkey,
+    mollusk: &Mollusk,
+    signer: Pubkey,
+    pool: (Pubkey, Account),
+    flags: u8,
+) -> InstructionResult {
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &[ShieldPoolInstruction::AdminSetFlags as u8, flags],
+        vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(pool.0, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    );
+    let accounts = vec![
+        (
+            signer,
+            Account {
+                lamports: 1_000_000_000,
+                ..signer_account(mollusk)
+            },
+        ),
+        pool,
+        system_program_account(),
+    ];
+    mollusk.process_instruction(&instruction, &accounts)
+}
+
+fn resulting_flags(result: &InstructionResult, pool_pda: &Pubkey) -> u8 {
+    let account = result
+        .resulting_accounts
+        .iter()
+        .find(|(pk, _)| pk == pool_pda)
+        .map(|(_, acc)| acc)
+        .expect("pool account not found after");
+    assert_eq!(account.data.len(), Pool::SIZE);
+    decode_pool(&account.data).unwrap().flags
+}
+
+#[test]
+fn test_admin_set_flags() {
+    let (program_id, mollusk) = setup();
+    let pool = native_pool(&program_id, &mollusk, &Pubkey::default());
+    let pool_pda = pool.0;
+
+    let result = set_flags(
+        program_id,
+        &mollusk,
+        admin_authority(),
+        pool,
+        FLAG_NO_OVERWRITE,
+    );
+    assert!(
+        !result.program_result.is_err(),
+        "AdminSetFlags failed: {:?}",
+        result.program_result
+    );
+    assert_eq!(resulting_flags(&result, &pool_pda), FLAG_NO_OVERWRITE);
+}
+
+#[test]
+fn test_admin_set_flags_grows_pre_flags_pool() {
+    let (program_id, mollusk) = setup();
+    let (pool_pda, mut pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
+    pool_account.data.truncate(Pool::PRE_FLAGS_SIZE);
+    pool_account.lamports = mollusk.sysvars.rent.minimum_balance(Pool::PRE_FLAGS_SIZE);
+
+    let result = set_flags(
+        program_id,
+        &mollusk,
+        admin_authority(),
+        (pool_pda, pool_account),
+        FLAG_NO_OVERWRITE,
+    );
+    assert!(
+        !result.program_result.is_err(),
+        "AdminSetFlags on a pre-flags pool failed: {:?}",
+        result.program_result
+    );
+    assert_eq!(resulting_flags(&result, &pool_pda), FLAG_NO_OVERWRITE);
+}
+
+#[test]
+fn test_admin_set_flags_rejects_outsider_and_unknown_flags() {
+    let (program_id, mollusk) = setup();
+
+    let outsider = Pubkey::new_unique();
+    let pool = native_pool(&program_id, &mollusk, &Pubkey::default());
+    let result = set_flags(program_id, &mollusk, outsider, pool, FLAG_NO_OVERWRITE);
+    assert!(matches!(
+        result.program_result,
+        ProgramResult::Failure(ProgramError::Custom(code))
+            if code == ShieldPoolError::InvalidAdminAuthority as u32
+    ));
+
+    let pool = native_pool(&program_id, &mollusk, &Pubkey::default());
+    let result = set_flags(program_id, &mollusk, admin_authority(), pool, 0x80);
+    assert!(matches!(
+        result.program_result,
+        ProgramResult::Failure(ProgramError::Custom(code))
+            if code == ShieldPoolError::InvalidInstructionData as u32
+    ));
+}
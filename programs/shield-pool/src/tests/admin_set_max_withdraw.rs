@@ -0,0 +1,112 @@
+use cloak_layouts::decode_pool;
+use mollusk_svm::{
+    result::{InstructionResult, ProgramResult},
+    Mollusk,
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::ShieldPoolError,
+    instructions::ShieldPoolInstruction,
+    state::Pool,
+    tests::{admin_authority, native_pool, setup, signer_account, system_program_account},
+};
+
+pub fn set_max_withdraw(
+    program_id: Pubkey,
+    mollusk: &Mollusk,
+    signer: Pubkey,
+    pool: (Pubkey, Account),
+    max_withdraw_bps: u16,
+) -> InstructionResult {
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &[
+            &[ShieldPoolInstruction::AdminSetMaxWithdraw as u8][..],
+            &max_withdraw_bps.to_le_bytes(),
+        ]
+        .concat(),
+        vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(pool.0, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    );
+    let accounts = vec![
+        (
+            signer,
+            Account {
+                lamports: 1_000_000_000,
+                ..signer_account(mollusk)
+            },
+        ),
+        pool,
+        system_program_account(),
+    ];
+    mollusk.process_instruction(&instruction, &accounts)
+}
+
+fn resulting_max_withdraw_bps(result: &InstructionResult, pool_pda: &Pubkey) -> u16 {
+    let account = result
+        .resulting_accounts
+        .iter()
+        .find(|(pk, _)| pk == pool_pda)
+        .map(|(_, acc)| acc)
+        .expect("pool account not found after");
+    assert_eq!(account.data.len(), Pool::SIZE);
+    decode_pool(&account.data).unwrap().max_withdraw_bps
+}
+
+#[test]
+fn test_admin_set_max_withdraw() {
+    let (program_id, mollusk) = setup();
+
+    // A current pool, then a mint-only pool that must grow to hold the cap
+    for size in [Pool::SIZE, Pool::LEGACY_SIZE] {
+        let (pool_pda, mut pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
+        pool_account.data.truncate(size);
+        pool_account.lamports = mollusk.sysvars.rent.minimum_balance(size);
+
+        let result = set_max_withdraw(
+            program_id,
+            &mollusk,
+            admin_authority(),
+            (pool_pda, pool_account),
+            2_500,
+        );
+        assert!(
+            !result.program_result.is_err(),
+            "AdminSetMaxWithdraw on a {}-byte pool failed: {:?}",
+            size,
+            result.program_result
+        );
+        assert_eq!(resulting_max_withdraw_bps(&result, &pool_pda), 2_500);
+    }
+}
+
+#[test]
+fn test_admin_set_max_withdraw_rejects_outsider_and_over_100_percent() {
+    let (program_id, mollusk) = setup();
+
+    let outsider = Pubkey::new_unique();
+    let pool = native_pool(&program_id, &mollusk, &Pubkey::default());
+    let result = set_max_withdraw(program_id, &mollusk, outsider, pool, 2_500);
+    assert!(matches!(
+        result.program_result,
+        ProgramResult::Failure(ProgramError::Custom(code))
+            if code == ShieldPoolError::InvalidAdminAuthority as u32
+    ));
+
+    let pool = native_pool(&program_id, &mollusk, &Pubkey::default());
+    let result = set_max_withdraw(program_id, &mollusk, admin_authority(), pool, 10_001);
+    assert!(matches!(
+        result.program_result,
+        ProgramResult::Failure(ProgramError::Custom(code))
+            if code == ShieldPoolError::InvalidInstructionData as u32
+    ));
+}
@@ -0,0 +1,42 @@
+use crate::state::CommitmentQueue;
+
+fn commitment(index: u64) -> [u8; 32] {
+    let mut commitment = [0xCCu8; 32];
+    commitment[..8].copy_from_slice(&index.to_le_bytes());
+    commitment
+}
+
+#[test]
+fn test_snapshot_follows_insertion_order_across_wraparound() {
+    let mut data = vec![0u8; CommitmentQueue::SIZE];
+    let extra = 3;
+    let appended = (CommitmentQueue::CAPACITY + extra) as u64;
+
+    let mut queue = CommitmentQueue::from_data_unchecked(&mut data);
+    for index in 0..appended {
+        queue.append(&commitment(index)).unwrap();
+    }
+    assert!(
+        !queue.contains(&commitment(0)),
+        "oldest entries are evicted"
+    );
+
+    let snapshot = CommitmentQueue::snapshot(&data).unwrap();
+    let expected: Vec<[u8; 32]> = (extra as u64..appended).map(commitment).collect();
+    assert_eq!(snapshot.len(), CommitmentQueue::CAPACITY);
+    assert_eq!(snapshot, expected);
+}
+
+#[test]
+fn test_snapshot_of_partial_queue() {
+    let mut data = vec![0u8; CommitmentQueue::SIZE];
+    let mut queue = CommitmentQueue::from_data_unchecked(&mut data);
+    queue.append(&commitment(7)).unwrap();
+    queue.append(&commitment(8)).unwrap();
+
+    assert_eq!(
+        CommitmentQueue::snapshot(&data).unwrap(),
+        vec![commitment(7), commitment(8)]
+    );
+    assert!(CommitmentQueue::snapshot(&data[..16]).is_err());
+}
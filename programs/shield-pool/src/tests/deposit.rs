@@ -1,11 +1,18 @@
 use blake3::Hasher;
+use mollusk_svm::result::ProgramResult;
 use solana_sdk::{
     account::Account,
     instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
     pubkey::Pubkey,
 };
 
-use crate::{instructions::ShieldPoolInstruction, state::CommitmentQueue, tests::setup};
+use crate::{
+    error::ShieldPoolError,
+    instructions::ShieldPoolInstruction,
+    state::{CommitmentQueue, Pool},
+    tests::{native_pool, setup, signer_account, system_program_account},
+};
 
 #[test]
 fn test_deposit_instruction() {
@@ -322,3 +329,195 @@ fn test_deposit_duplicate_commitment() {
         "Duplicate commitment should be rejected",
     );
 }
+
+#[test]
+fn test_deposit_into_full_no_overwrite_queue_rejected() {
+    let (program_id, mollusk) = setup();
+
+    let user = Pubkey::new_from_array([0x11u8; 32]);
+    let mint = Pubkey::default(); // Native SOL
+    let (pool, _) = Pubkey::find_program_address(&[b"pool", mint.as_ref()], &program_id);
+    let (commitments_log, _) =
+        Pubkey::find_program_address(&[b"commitments", mint.as_ref()], &program_id);
+
+    let instruction_data = [
+        vec![ShieldPoolInstruction::Deposit as u8],
+        1_000_000u64.to_le_bytes().to_vec(),
+        [0x42u8; 32].to_vec(),
+    ]
+    .concat();
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &instruction_data,
+        vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new(commitments_log, false),
+        ],
+    );
+
+    let mut accounts = native_deposit_accounts(&mollusk, program_id, user, commitments_log);
+    // Every slot of the queue already holds a live commitment
+    accounts[3].1.data[..8].copy_from_slice(&(CommitmentQueue::CAPACITY as u64).to_le_bytes());
+    accounts[1].1.data[cloak_layouts::pool::FLAGS] = cloak_layouts::pool::FLAG_NO_OVERWRITE;
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(
+        matches!(
+            result.program_result,
+            ProgramResult::Failure(ProgramError::Custom(code))
+                if code == ShieldPoolError::CommitmentQueueFull as u32
+        ),
+        "Deposit into a full no-overwrite queue should be rejected, got: {:?}",
+        result.program_result
+    );
+
+    // Without the flag the same deposit overwrites the oldest slot
+    accounts[1].1.data[cloak_layouts::pool::FLAGS] = 0;
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(
+        !result.program_result.is_err(),
+        "Deposit into a full wrapping queue should succeed, got: {:?}",
+        result.program_result
+    );
+}
+
+/// Funded depositor, native SOL pool, system program and an empty commitments
+/// log, in `Deposit` account order
+fn native_deposit_accounts(
+    mollusk: &mollusk_svm::Mollusk,
+    program_id: Pubkey,
+    user: Pubkey,
+    commitments_log: Pubkey,
+) -> Vec<(Pubkey, Account)> {
+    vec![
+        (
+            user,
+            Account {
+                lamports: 2_000_000_000,
+                ..signer_account(mollusk)
+            },
+        ),
+        native_pool(&program_id, mollusk, &Pubkey::default()),
+        system_program_account(),
+        (
+            commitments_log,
+            Account {
+                lamports: mollusk.sysvars.rent.minimum_balance(CommitmentQueue::SIZE),
+                data: vec![0u8; CommitmentQueue::SIZE],
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+    ]
+}
+
+#[test]
+fn test_deposit_with_memo_instruction() {
+    let (program_id, mollusk) = setup();
+
+    let user = Pubkey::new_from_array([0x11u8; 32]);
+    let mint = Pubkey::default(); // Native SOL
+    let (pool, _) = Pubkey::find_program_address(&[b"pool", mint.as_ref()], &program_id);
+    let (commitments_log, _) =
+        Pubkey::find_program_address(&[b"commitments", mint.as_ref()], &program_id);
+
+    let amount = 1_000_000u64;
+    let leaf_commit = [0x42u8; 32];
+    let memo_hash = blake3::hash(b"encrypted output ciphertext");
+
+    let instruction_data = [
+        vec![ShieldPoolInstruction::DepositWithMemo as u8],
+        amount.to_le_bytes().to_vec(),
+        leaf_commit.to_vec(),
+        memo_hash.as_bytes().to_vec(),
+    ]
+    .concat();
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &instruction_data,
+        vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new(commitments_log, false),
+        ],
+    );
+    let accounts = native_deposit_accounts(&mollusk, program_id, user, commitments_log);
+
+    let result = mollusk.process_and_validate_instruction(&instruction, &accounts, &[]);
+    assert!(
+        !result.program_result.is_err(),
+        "DepositWithMemo should succeed, got: {:?}",
+        result.program_result
+    );
+
+    let commitments_account = result
+        .resulting_accounts
+        .iter()
+        .find(|(pk, _)| *pk == commitments_log)
+        .map(|(_, acc)| acc)
+        .expect("Commitments account not found after deposit");
+    assert_eq!(
+        &commitments_account.data[CommitmentQueue::HEADER_SIZE..CommitmentQueue::HEADER_SIZE + 32],
+        &leaf_commit,
+        "Stored commitment mismatch"
+    );
+
+    let pool_account = result
+        .resulting_accounts
+        .iter()
+        .find(|(pk, _)| *pk == pool)
+        .map(|(_, acc)| acc)
+        .expect("Pool account not found after deposit");
+    assert_eq!(
+        pool_account.lamports,
+        mollusk.sysvars.rent.minimum_balance(Pool::SIZE) + amount
+    );
+
+    assert_eq!(
+        result.return_data,
+        memo_hash.as_bytes(),
+        "DepositWithMemo should return the memo hash"
+    );
+}
+
+#[test]
+fn test_deposit_with_memo_requires_memo_hash() {
+    let (program_id, mollusk) = setup();
+
+    let user = Pubkey::new_from_array([0x11u8; 32]);
+    let mint = Pubkey::default(); // Native SOL
+    let (pool, _) = Pubkey::find_program_address(&[b"pool", mint.as_ref()], &program_id);
+    let (commitments_log, _) =
+        Pubkey::find_program_address(&[b"commitments", mint.as_ref()], &program_id);
+
+    // Plain deposit payload without the trailing memo hash
+    let instruction_data = [
+        vec![ShieldPoolInstruction::DepositWithMemo as u8],
+        1_000_000u64.to_le_bytes().to_vec(),
+        [0x42u8; 32].to_vec(),
+    ]
+    .concat();
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &instruction_data,
+        vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new(commitments_log, false),
+        ],
+    );
+    let accounts = native_deposit_accounts(&mollusk, program_id, user, commitments_log);
+
+    let result = mollusk.process_and_validate_instruction(&instruction, &accounts, &[]);
+    assert!(
+        result.program_result.is_err(),
+        "DepositWithMemo without a memo hash should fail"
+    );
+}
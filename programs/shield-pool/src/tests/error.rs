@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+
+use crate::error::ShieldPoolError::{self, *};
+
+const ALL_ERRORS: [ShieldPoolError; 55] = [
+    InvalidRoot,
+    RootNotFound,
+    RootsRingFull,
+    ProofInvalid,
+    InvalidProofSize,
+    InvalidPublicInputs,
+    VKeyMismatch,
+    ProofExpired,
+    DoubleSpend,
+    NullifierShardFull,
+    InvalidNullifier,
+    NullifierAlreadyUsed,
+    NullifierMismatch,
+    OutputsMismatch,
+    Conservation,
+    InvalidOutputsHash,
+    InvalidAmount,
+    InvalidRecipient,
+    CommitmentAlreadyExists,
+    CommitmentLogFull,
+    SwapTimeoutNotExpired,
+    SwapAlreadyExecuted,
+    WithdrawExceedsPoolCap,
+    CommitmentQueueFull,
+    MathOverflow,
+    DivisionByZero,
+    BadAccounts,
+    PoolOwnerNotProgramId,
+    TreasuryOwnerNotProgramId,
+    RootsRingOwnerNotProgramId,
+    NullifierShardOwnerNotProgramId,
+    PoolNotWritable,
+    TreasuryNotWritable,
+    RecipientNotWritable,
+    InsufficientLamports,
+    InvalidAccountOwner,
+    InvalidAccountSize,
+    CommitmentsNotWritable,
+    InvalidAdminAuthority,
+    InvalidAccountAddress,
+    NoPendingAuthority,
+    BadIxLength,
+    InvalidInstructionData,
+    MissingAccounts,
+    InvalidTag,
+    InvalidMinerAccount,
+    InvalidClaimAccount,
+    ConsumClaimFailed,
+    InvalidG1Length,
+    InvalidG2Length,
+    InvalidPublicInputsLength,
+    PublicInputGreaterThanFieldSize,
+    PreparingInputsG1MulFailed,
+    PreparingInputsG1AdditionFailed,
+    ProofVerificationFailed,
+];
+
+/// Custom error code clients see for each error. Exhaustive, so adding a
+/// variant fails to compile until its code is pinned here.
+fn expected_code(err: ShieldPoolError) -> u32 {
+    match err {
+        InvalidRoot => 0x1000,
+        RootNotFound => 0x1001,
+        RootsRingFull => 0x1002,
+        ProofInvalid => 0x1010,
+        InvalidProofSize => 0x1011,
+        InvalidPublicInputs => 0x1012,
+        VKeyMismatch => 0x1013,
+        ProofExpired => 0x1014,
+        DoubleSpend => 0x1020,
+        NullifierShardFull => 0x1021,
+        InvalidNullifier => 0x1022,
+        NullifierAlreadyUsed => 0x1023,
+        NullifierMismatch => 0x1024,
+        OutputsMismatch => 0x1030,
+        Conservation => 0x1031,
+        InvalidOutputsHash => 0x1032,
+        InvalidAmount => 0x1033,
+        InvalidRecipient => 0x1034,
+        CommitmentAlreadyExists => 0x1035,
+        CommitmentLogFull => 0x1036,
+        SwapTimeoutNotExpired => 0x1037,
+        SwapAlreadyExecuted => 0x1038,
+        WithdrawExceedsPoolCap => 0x1039,
+        CommitmentQueueFull => 0x103A,
+        MathOverflow => 0x1040,
+        DivisionByZero => 0x1041,
+        BadAccounts => 0x1050,
+        PoolOwnerNotProgramId => 0x1051,
+        TreasuryOwnerNotProgramId => 0x1052,
+        RootsRingOwnerNotProgramId => 0x1053,
+        NullifierShardOwnerNotProgramId => 0x1054,
+        PoolNotWritable => 0x1055,
+        TreasuryNotWritable => 0x1056,
+        RecipientNotWritable => 0x1057,
+        InsufficientLamports => 0x1058,
+        InvalidAccountOwner => 0x1059,
+        InvalidAccountSize => 0x105A,
+        CommitmentsNotWritable => 0x105B,
+        InvalidAdminAuthority => 0x105C,
+        InvalidAccountAddress => 0x105D,
+        NoPendingAuthority => 0x105E,
+        BadIxLength => 0x1060,
+        InvalidInstructionData => 0x1061,
+        MissingAccounts => 0x1062,
+        InvalidTag => 0x1063,
+        InvalidMinerAccount => 0x1064,
+        InvalidClaimAccount => 0x1065,
+        ConsumClaimFailed => 0x1066,
+        InvalidG1Length => 0x1070,
+        InvalidG2Length => 0x1071,
+        InvalidPublicInputsLength => 0x1072,
+        PublicInputGreaterThanFieldSize => 0x1073,
+        PreparingInputsG1MulFailed => 0x1074,
+        PreparingInputsG1AdditionFailed => 0x1075,
+        ProofVerificationFailed => 0x1076,
+    }
+}
+
+#[test]
+fn test_error_codes_are_unique_and_round_trip() {
+    let mut seen = HashSet::new();
+    for err in ALL_ERRORS {
+        let code = u32::from(err);
+        assert_eq!(code, expected_code(err), "{:?} changed code", err);
+        assert!(seen.insert(code), "{:?} reuses code {:#x}", err, code);
+        assert_eq!(ShieldPoolError::try_from(code).unwrap(), err);
+    }
+
+    // Every decodable code belongs to a listed error
+    let decodable = (0x1000..=0x10FF)
+        .filter(|&code| ShieldPoolError::try_from(code).is_ok())
+        .count();
+    assert_eq!(decodable, ALL_ERRORS.len());
+}
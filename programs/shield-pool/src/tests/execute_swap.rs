@@ -0,0 +1,146 @@
+use mollusk_svm::{result::ProgramResult, Mollusk};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::ShieldPoolError,
+    instructions::ShieldPoolInstruction,
+    state::SwapState,
+    tests::{_pack_token_account, setup},
+};
+
+const MIN_OUTPUT: u64 = 1_000_000; // 1 USDC (6 decimals)
+const SWAP_STATE_LAMPORTS: u64 = 5_000_000;
+const PAYER_LAMPORTS: u64 = 1_000_000_000;
+
+/// Run ExecuteSwap against a SwapState PDA whose recipient ATA holds `received` tokens
+fn execute_swap(
+    program_id: Pubkey,
+    mollusk: &Mollusk,
+    received: u64,
+) -> (mollusk_svm::result::InstructionResult, Pubkey, Pubkey) {
+    let nullifier = [0x77u8; 32];
+    let (swap_state_pda, bump) =
+        Pubkey::find_program_address(&[SwapState::SEED_PREFIX, &nullifier], &program_id);
+    let output_mint = Pubkey::new_from_array([0xAAu8; 32]);
+    let recipient = Pubkey::new_from_array([0xCCu8; 32]);
+    let recipient_ata = Pubkey::new_from_array([0xBBu8; 32]);
+    let payer = Pubkey::new_from_array([0x11u8; 32]);
+
+    // [nullifier: 32][sol_amount: 8][output_mint: 32][recipient_ata: 32]
+    // [min_output_amount: 8][created_slot: 8][timeout_slot: 8][bump: 1]
+    let swap_state_data = [
+        nullifier.to_vec(),
+        3_000_000_000u64.to_le_bytes().to_vec(),
+        output_mint.to_bytes().to_vec(),
+        recipient_ata.to_bytes().to_vec(),
+        MIN_OUTPUT.to_le_bytes().to_vec(),
+        100u64.to_le_bytes().to_vec(),
+        200u64.to_le_bytes().to_vec(),
+        vec![bump],
+    ]
+    .concat();
+    assert_eq!(swap_state_data.len(), SwapState::SIZE);
+
+    let instruction_data = [
+        vec![ShieldPoolInstruction::ExecuteSwap as u8],
+        nullifier.to_vec(),
+    ]
+    .concat();
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &instruction_data,
+        vec![
+            AccountMeta::new(swap_state_pda, false),
+            AccountMeta::new_readonly(recipient_ata, false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    );
+
+    let accounts: Vec<(Pubkey, Account)> = vec![
+        (
+            swap_state_pda,
+            Account {
+                lamports: SWAP_STATE_LAMPORTS,
+                data: swap_state_data,
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+        (
+            recipient_ata,
+            _pack_token_account(&recipient, &output_mint, received).into(),
+        ),
+        (
+            payer,
+            Account {
+                lamports: PAYER_LAMPORTS,
+                data: vec![],
+                owner: solana_sdk::system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+        mollusk_svm_programs_token::token::keyed_account(),
+    ];
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    (result, swap_state_pda, payer)
+}
+
+fn lamports_of(result: &mollusk_svm::result::InstructionResult, key: &Pubkey) -> u64 {
+    result
+        .resulting_accounts
+        .iter()
+        .find(|(pk, _)| pk == key)
+        .map(|(_, acc)| acc.lamports)
+        .expect("account not found")
+}
+
+#[test]
+fn test_execute_swap_rejects_output_below_minimum() {
+    let (program_id, mollusk) = setup();
+
+    let (result, swap_state_pda, payer) = execute_swap(program_id, &mollusk, MIN_OUTPUT - 1);
+
+    assert!(
+        matches!(
+            result.program_result,
+            ProgramResult::Failure(ProgramError::Custom(code))
+                if code == ShieldPoolError::InvalidAmount as u32
+        ),
+        "ExecuteSwap should reject output below min_output_amount, got: {:?}",
+        result.program_result
+    );
+    // SwapState stays open so the relay can retry or the user can recover funds
+    assert_eq!(lamports_of(&result, &swap_state_pda), SWAP_STATE_LAMPORTS);
+    assert_eq!(lamports_of(&result, &payer), PAYER_LAMPORTS);
+}
+
+#[test]
+fn test_execute_swap_closes_swap_state_at_or_above_minimum() {
+    let (program_id, mollusk) = setup();
+
+    for received in [MIN_OUTPUT, MIN_OUTPUT * 2] {
+        let (result, swap_state_pda, payer) = execute_swap(program_id, &mollusk, received);
+
+        assert!(
+            !result.program_result.is_err(),
+            "ExecuteSwap should succeed with {} tokens received, got: {:?}",
+            received,
+            result.program_result
+        );
+        assert_eq!(lamports_of(&result, &swap_state_pda), 0);
+        assert_eq!(
+            lamports_of(&result, &payer),
+            PAYER_LAMPORTS + SWAP_STATE_LAMPORTS
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashSet;
+
+use crate::instructions::ShieldPoolInstruction::{self, *};
+
+const ALL_INSTRUCTIONS: [ShieldPoolInstruction; 16] = [
+    Deposit,
+    AdminPushRoot,
+    Withdraw,
+    Initialize,
+    WithdrawSwap,
+    ExecuteSwap,
+    ReleaseSwapFunds,
+    ExecuteSwapViaOrca,
+    PrepareSwapSol,
+    AdminPushRoots,
+    DepositWithMemo,
+    TimeoutRefundSwap,
+    AdminRotatePropose,
+    AdminRotateAccept,
+    AdminSetFlags,
+    AdminSetMaxWithdraw,
+];
+
+/// Wire tag clients send for each instruction. Exhaustive, so adding a variant
+/// fails to compile until its tag is pinned here.
+fn expected_tag(ix: ShieldPoolInstruction) -> u8 {
+    match ix {
+        Deposit => 0,
+        AdminPushRoot => 1,
+        Withdraw => 2,
+        Initialize => 3,
+        WithdrawSwap => 4,
+        ExecuteSwap => 5,
+        ReleaseSwapFunds => 6,
+        ExecuteSwapViaOrca => 7,
+        PrepareSwapSol => 8,
+        AdminPushRoots => 9,
+        DepositWithMemo => 10,
+        TimeoutRefundSwap => 11,
+        AdminRotatePropose => 12,
+        AdminRotateAccept => 13,
+        AdminSetFlags => 14,
+        AdminSetMaxWithdraw => 15,
+    }
+}
+
+#[test]
+fn test_instruction_discriminants_are_unique_and_round_trip() {
+    let mut seen = HashSet::new();
+    for ix in ALL_INSTRUCTIONS {
+        let tag = ix as u8;
+        assert_eq!(tag, expected_tag(ix), "{:?} changed discriminant", ix);
+        assert!(seen.insert(tag), "{:?} reuses discriminant {}", ix, tag);
+        assert_eq!(ShieldPoolInstruction::try_from(&tag).unwrap(), ix);
+    }
+
+    // Every decodable tag belongs to a listed instruction
+    let decodable = (0..=u8::MAX)
+        .filter(|tag| ShieldPoolInstruction::try_from(tag).is_ok())
+        .count();
+    assert_eq!(decodable, ALL_INSTRUCTIONS.len());
+}
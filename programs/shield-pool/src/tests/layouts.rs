@@ -0,0 +1,79 @@
+use cloak_layouts::{
+    decode_commitment_queue, decode_pool, decode_swap_state, pool::FLAG_NO_OVERWRITE,
+};
+use pinocchio::pubkey::Pubkey;
+
+use crate::state::{CommitmentQueue, Pool, SwapState};
+
+#[test]
+fn test_swap_state_round_trip() {
+    let mut data = vec![0u8; SwapState::SIZE];
+    let mut swap_state = SwapState::from_data_unchecked(&mut data);
+    swap_state.initialize(
+        &[0x11; 32],
+        3_000_000_000,
+        &[0x22; 32],
+        &[0x33; 32],
+        1_000_000,
+        100,
+        200,
+        254,
+    );
+
+    let view = decode_swap_state(&data).unwrap();
+    assert_eq!(view.nullifier, [0x11; 32]);
+    assert_eq!(view.sol_amount, 3_000_000_000);
+    assert_eq!(view.output_mint, [0x22; 32]);
+    assert_eq!(view.recipient_ata, [0x33; 32]);
+    assert_eq!(view.min_output_amount, 1_000_000);
+    assert_eq!(view.created_slot, 100);
+    assert_eq!(view.timeout_slot, 200);
+    assert_eq!(view.bump, 254);
+}
+
+#[test]
+fn test_commitment_queue_round_trip() {
+    let mut data = vec![0u8; CommitmentQueue::SIZE];
+    let mut queue = CommitmentQueue::from_data_unchecked(&mut data);
+    queue.append(&[0x44; 32]).unwrap();
+    queue.append(&[0x55; 32]).unwrap();
+
+    let view = decode_commitment_queue(&data).unwrap();
+    assert_eq!(view.total_commits, 2);
+    assert_eq!(
+        view.commitments().collect::<Vec<_>>(),
+        vec![[0x44; 32], [0x55; 32]]
+    );
+}
+
+#[test]
+fn test_pool_round_trip() {
+    let mut data = vec![0u8; Pool::SIZE];
+    let mut pool = Pool::from_data_unchecked(&mut data);
+    pool.set_mint(&Pubkey::from([0x66; 32]));
+    pool.set_max_withdraw_bps(2_500);
+    assert_eq!(pool.max_withdraw_bps(), 2_500);
+
+    pool.set_flags(FLAG_NO_OVERWRITE).unwrap();
+    assert!(pool.no_overwrite());
+
+    let view = decode_pool(&data).unwrap();
+    assert_eq!(view.mint, [0x66; 32]);
+    assert_eq!(view.max_withdraw_bps, 2_500);
+    assert!(view.no_overwrite());
+
+    // Pools created before flags keep their cap but have no room for flags
+    let mut pre_flags = vec![0u8; Pool::PRE_FLAGS_SIZE];
+    let mut pool = Pool::from_data_unchecked(&mut pre_flags);
+    pool.set_max_withdraw_bps(2_500);
+    assert_eq!(pool.max_withdraw_bps(), 2_500);
+    assert!(pool.set_flags(FLAG_NO_OVERWRITE).is_err());
+    assert!(!pool.no_overwrite());
+
+    // Legacy mint-only pools have no cap and ignore attempts to set one
+    let mut legacy = vec![0u8; Pool::LEGACY_SIZE];
+    let mut pool = Pool::from_data_unchecked(&mut legacy);
+    pool.set_max_withdraw_bps(2_500);
+    assert_eq!(pool.max_withdraw_bps(), 0);
+    assert!(pool.is_native());
+}
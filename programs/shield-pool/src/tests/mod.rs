@@ -1,6 +1,6 @@
-use mollusk_svm::Mollusk;
+use mollusk_svm::{result::InstructionResult, Mollusk};
 use solana_sdk::{
-    account::{AccountSharedData, WritableAccount},
+    account::{Account, AccountSharedData, WritableAccount},
     program_option::COption,
     program_pack::Pack,
     pubkey::Pubkey,
@@ -13,12 +13,39 @@ mod deposit;
 #[cfg(test)]
 mod admin_push_root;
 
+#[cfg(test)]
+mod admin_rotate;
+
+#[cfg(test)]
+mod admin_set_flags;
+
+#[cfg(test)]
+mod admin_set_max_withdraw;
+
 #[cfg(test)]
 mod withdraw;
 
 #[cfg(test)]
 mod withdraw_swap;
 
+#[cfg(test)]
+mod execute_swap;
+
+#[cfg(test)]
+mod timeout_refund_swap;
+
+#[cfg(test)]
+mod instruction;
+
+#[cfg(test)]
+mod error;
+
+#[cfg(test)]
+mod commitment_queue;
+
+#[cfg(test)]
+mod layouts;
+
 pub fn setup() -> (Pubkey, Mollusk) {
     let program_id = Pubkey::new_from_array(crate::ID);
     let mut mollusk = Mollusk::new(&program_id, "../../target/deploy/shield_pool");
@@ -27,6 +54,86 @@ pub fn setup() -> (Pubkey, Mollusk) {
     (program_id, mollusk)
 }
 
+/// The program's built-in ADMIN_AUTHORITY
+pub fn admin_authority() -> Pubkey {
+    Pubkey::new_from_array(five8_const::decode_32_const(
+        "mgfSqUe1qaaUjeEzuLUyDUx5Rk4fkgePB5NtLnS3Vxa",
+    ))
+}
+
+/// Rent-exempt system account for a signer that pays nothing
+pub fn signer_account(mollusk: &Mollusk) -> Account {
+    Account {
+        lamports: mollusk.sysvars.rent.minimum_balance(0),
+        data: vec![],
+        owner: solana_sdk::system_program::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn system_program_account() -> (Pubkey, Account) {
+    (
+        solana_sdk::system_program::id(),
+        Account {
+            lamports: 0,
+            data: vec![],
+            owner: solana_sdk::native_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        },
+    )
+}
+
+/// Native SOL pool PDA whose stored authority is `authority` (all zeros falls
+/// back to ADMIN_AUTHORITY)
+pub fn native_pool(
+    program_id: &Pubkey,
+    mollusk: &Mollusk,
+    authority: &Pubkey,
+) -> (Pubkey, Account) {
+    let (pool_pda, _) =
+        Pubkey::find_program_address(&[b"pool", Pubkey::default().as_ref()], program_id);
+    let mut data = vec![0u8; crate::state::Pool::SIZE];
+    data[cloak_layouts::pool::AUTHORITY..cloak_layouts::pool::PENDING_AUTHORITY]
+        .copy_from_slice(authority.as_ref());
+
+    let account = Account {
+        lamports: mollusk.sysvars.rent.minimum_balance(data.len()),
+        data,
+        owner: *program_id,
+        executable: false,
+        rent_epoch: 0,
+    };
+    (pool_pda, account)
+}
+
+/// Print the compute units `result` consumed and fail if they exceed the
+/// ceiling, read from the `env_var` environment variable when set
+pub fn assert_compute_units_within(
+    result: &InstructionResult,
+    label: &str,
+    env_var: &str,
+    default_ceiling: u64,
+) {
+    let ceiling = std::env::var(env_var)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default_ceiling);
+    println!(
+        "{} consumed {} CU (ceiling {})",
+        label, result.compute_units_consumed, ceiling
+    );
+    assert!(
+        result.compute_units_consumed <= ceiling,
+        "{} consumed {} CU, over the {} CU ceiling (override with {})",
+        label,
+        result.compute_units_consumed,
+        ceiling,
+        env_var
+    );
+}
+
 pub fn _pack_mint(mint_authority: &Pubkey, supply: u64) -> AccountSharedData {
     let mut account = AccountSharedData::new(0, spl_token::state::Mint::LEN, &spl_token::id());
     spl_token::state::Mint {
@@ -41,7 +148,7 @@ pub fn _pack_mint(mint_authority: &Pubkey, supply: u64) -> AccountSharedData {
 }
 
 pub fn _pack_token_account(owner: &Pubkey, mint: &Pubkey, amount: u64) -> AccountSharedData {
-    let mut account = AccountSharedData::new(0, spl_token::state::Mint::LEN, &spl_token::id());
+    let mut account = AccountSharedData::new(0, spl_token::state::Account::LEN, &spl_token::id());
     spl_token::state::Account {
         mint: *mint,
         owner: *owner,
@@ -0,0 +1,197 @@
+use mollusk_svm::{result::ProgramResult, Mollusk};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::ShieldPoolError, instructions::ShieldPoolInstruction, state::SwapState, tests::setup,
+};
+
+const SOL_AMOUNT: u64 = 3_000_000_000;
+const TIMEOUT_SLOT: u64 = 200;
+const RECIPIENT_LAMPORTS: u64 = 1_000_000;
+
+/// Associated token address of `wallet` for `mint`, as stored in SwapState.recipient_ata
+fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let ata_program = Pubkey::new_from_array(five8_const::decode_32_const(
+        "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    ));
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), spl_token::id().as_ref(), mint.as_ref()],
+        &ata_program,
+    )
+    .0
+}
+
+/// Run TimeoutRefundSwap at `slot` against a SwapState PDA holding `swap_state_lamports`
+fn timeout_refund_swap(
+    program_id: Pubkey,
+    mollusk: &mut Mollusk,
+    slot: u64,
+    swap_state_lamports: u64,
+    recipient: Pubkey,
+) -> (mollusk_svm::result::InstructionResult, Pubkey) {
+    mollusk.warp_to_slot(slot);
+
+    let nullifier = [0x55u8; 32];
+    let (swap_state_pda, bump) =
+        Pubkey::find_program_address(&[SwapState::SEED_PREFIX, &nullifier], &program_id);
+    let output_mint = Pubkey::new_from_array([0xAAu8; 32]);
+    let owner = Pubkey::new_from_array([0xCCu8; 32]);
+    let recipient_ata = associated_token_address(&owner, &output_mint);
+
+    // [nullifier: 32][sol_amount: 8][output_mint: 32][recipient_ata: 32]
+    // [min_output_amount: 8][created_slot: 8][timeout_slot: 8][bump: 1]
+    let swap_state_data = [
+        nullifier.to_vec(),
+        SOL_AMOUNT.to_le_bytes().to_vec(),
+        output_mint.to_bytes().to_vec(),
+        recipient_ata.to_bytes().to_vec(),
+        1_000_000u64.to_le_bytes().to_vec(),
+        100u64.to_le_bytes().to_vec(),
+        TIMEOUT_SLOT.to_le_bytes().to_vec(),
+        vec![bump],
+    ]
+    .concat();
+    assert_eq!(swap_state_data.len(), SwapState::SIZE);
+
+    let instruction_data = [
+        vec![ShieldPoolInstruction::TimeoutRefundSwap as u8],
+        nullifier.to_vec(),
+    ]
+    .concat();
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &instruction_data,
+        vec![
+            AccountMeta::new(swap_state_pda, false),
+            AccountMeta::new(recipient, false),
+        ],
+    );
+
+    let accounts: Vec<(Pubkey, Account)> = vec![
+        (
+            swap_state_pda,
+            Account {
+                lamports: swap_state_lamports,
+                data: swap_state_data,
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+        (
+            recipient,
+            Account {
+                lamports: RECIPIENT_LAMPORTS,
+                data: vec![],
+                owner: solana_sdk::system_program::id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        ),
+    ];
+
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    (result, swap_state_pda)
+}
+
+fn lamports_of(result: &mollusk_svm::result::InstructionResult, key: &Pubkey) -> u64 {
+    result
+        .resulting_accounts
+        .iter()
+        .find(|(pk, _)| pk == key)
+        .map(|(_, acc)| acc.lamports)
+        .expect("account not found")
+}
+
+fn assert_custom_error(result: &mollusk_svm::result::InstructionResult, err: ShieldPoolError) {
+    assert!(
+        matches!(
+            result.program_result,
+            ProgramResult::Failure(ProgramError::Custom(code)) if code == err as u32
+        ),
+        "expected {:?}, got: {:?}",
+        err,
+        result.program_result
+    );
+}
+
+#[test]
+fn test_timeout_refund_swap_rejects_before_timeout() {
+    let (program_id, mut mollusk) = setup();
+    let locked = mollusk.sysvars.rent.minimum_balance(SwapState::SIZE) + SOL_AMOUNT;
+    let recipient = Pubkey::new_from_array([0xCCu8; 32]);
+
+    // timeout_slot itself is still inside the swap window
+    for slot in [TIMEOUT_SLOT - 1, TIMEOUT_SLOT] {
+        let (result, swap_state_pda) =
+            timeout_refund_swap(program_id, &mut mollusk, slot, locked, recipient);
+
+        assert_custom_error(&result, ShieldPoolError::SwapTimeoutNotExpired);
+        assert_eq!(lamports_of(&result, &swap_state_pda), locked);
+        assert_eq!(lamports_of(&result, &recipient), RECIPIENT_LAMPORTS);
+    }
+}
+
+#[test]
+fn test_timeout_refund_swap_refunds_recipient_after_timeout() {
+    let (program_id, mut mollusk) = setup();
+    let locked = mollusk.sysvars.rent.minimum_balance(SwapState::SIZE) + SOL_AMOUNT;
+    let recipient = Pubkey::new_from_array([0xCCu8; 32]);
+
+    let (result, swap_state_pda) = timeout_refund_swap(
+        program_id,
+        &mut mollusk,
+        TIMEOUT_SLOT + 1,
+        locked,
+        recipient,
+    );
+
+    assert!(
+        !result.program_result.is_err(),
+        "TimeoutRefundSwap should succeed after timeout, got: {:?}",
+        result.program_result
+    );
+    assert_eq!(lamports_of(&result, &swap_state_pda), 0);
+    assert_eq!(
+        lamports_of(&result, &recipient),
+        RECIPIENT_LAMPORTS + locked
+    );
+}
+
+#[test]
+fn test_timeout_refund_swap_rejects_other_recipient() {
+    let (program_id, mut mollusk) = setup();
+    let locked = mollusk.sysvars.rent.minimum_balance(SwapState::SIZE) + SOL_AMOUNT;
+    let attacker = Pubkey::new_from_array([0xDDu8; 32]);
+
+    let (result, swap_state_pda) =
+        timeout_refund_swap(program_id, &mut mollusk, TIMEOUT_SLOT + 1, locked, attacker);
+
+    assert_custom_error(&result, ShieldPoolError::InvalidRecipient);
+    assert_eq!(lamports_of(&result, &swap_state_pda), locked);
+}
+
+#[test]
+fn test_timeout_refund_swap_rejects_executed_swap() {
+    let (program_id, mut mollusk) = setup();
+    // PrepareSwapSol already moved sol_amount out, leaving only rent
+    let rent_only = mollusk.sysvars.rent.minimum_balance(SwapState::SIZE);
+    let recipient = Pubkey::new_from_array([0xCCu8; 32]);
+
+    let (result, swap_state_pda) = timeout_refund_swap(
+        program_id,
+        &mut mollusk,
+        TIMEOUT_SLOT + 1,
+        rent_only,
+        recipient,
+    );
+
+    assert_custom_error(&result, ShieldPoolError::SwapAlreadyExecuted);
+    assert_eq!(lamports_of(&result, &swap_state_pda), rent_only);
+}
@@ -1,10 +1,165 @@
+use cloak_layouts::{calculate_fee, parse_hex32, roots_ring, FeeSchedule};
+use mollusk_svm::{result::ProgramResult, Mollusk};
 use solana_sdk::{
     account::Account,
     instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
     pubkey::Pubkey,
 };
+use sp1_sdk::SP1ProofWithPublicValues;
+
+use crate::{
+    error::ShieldPoolError,
+    instructions::{withdraw::check_withdraw_cap, ShieldPoolInstruction},
+    state::{NullifierShard, RootsRing},
+    tests::{
+        admin_authority, admin_set_max_withdraw::set_max_withdraw, assert_compute_units_within,
+        native_pool, setup, system_program_account,
+    },
+};
+
+/// Compute units the relay requests for a withdraw transaction
+/// (`set_compute_unit_limit` in its transaction builder); a withdraw that
+/// needs more cannot land
+const WITHDRAW_CU_CEILING: u64 = 400_000;
+
+/// Where tests that need a proof the program accepts find one:
+/// `proof_live.bin` saved by `cloak-zk prove --proof`, and `outputs_live.json`,
+/// the outputs file it was proven with. Those tests skip when it is missing.
+const LIVE_FIXTURE_DIR: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../packages/zk-guest-sp1/out"
+);
+
+/// A proven native withdraw from the live fixture
+struct LiveWithdraw {
+    proof: [u8; 260],
+    public_inputs: Vec<u8>,
+    outputs: Vec<(Pubkey, u64)>,
+}
 
-use crate::{instructions::ShieldPoolInstruction, state::RootsRing, tests::setup};
+impl LiveWithdraw {
+    fn load() -> Option<Self> {
+        #[derive(serde::Deserialize)]
+        struct Output {
+            address: String,
+            amount: u64,
+        }
+
+        let proof_path = format!("{}/proof_live.bin", LIVE_FIXTURE_DIR);
+        let outputs_path = format!("{}/outputs_live.json", LIVE_FIXTURE_DIR);
+        let proof = match SP1ProofWithPublicValues::load(&proof_path) {
+            Ok(proof) => proof,
+            Err(err) => {
+                println!(
+                    "Skipping: unable to load SP1 proof at {}: {}",
+                    proof_path, err
+                );
+                return None;
+            }
+        };
+        let outputs: Vec<Output> = match std::fs::read_to_string(&outputs_path) {
+            Ok(json) => serde_json::from_str(&json).expect("parse live outputs"),
+            Err(err) => {
+                println!("Skipping: unable to read {}: {}", outputs_path, err);
+                return None;
+            }
+        };
+
+        Some(Self {
+            proof: cloak_proof_extract::extract_groth16_260(&proof.bytes())
+                .expect("extract groth16 proof from SP1 bundle"),
+            public_inputs: proof.public_values.to_vec(),
+            outputs: outputs
+                .into_iter()
+                .map(|output| {
+                    let address = parse_hex32(&output.address).expect("output address");
+                    (Pubkey::new_from_array(address), output.amount)
+                })
+                .collect(),
+        })
+    }
+
+    fn amount(&self) -> u64 {
+        u64::from_le_bytes(self.public_inputs[96..104].try_into().unwrap())
+    }
+
+    /// Withdraw instruction and accounts with the proof's root on-chain and its
+    /// nullifier unspent, drawing from `pool`
+    fn instruction(
+        &self,
+        program_id: Pubkey,
+        mollusk: &Mollusk,
+        pool: (Pubkey, Account),
+    ) -> (Instruction, Vec<(Pubkey, Account)>) {
+        let mint = Pubkey::default();
+        let (treasury_pda, _) =
+            Pubkey::find_program_address(&[b"treasury", mint.as_ref()], &program_id);
+        let (roots_ring_pda, _) =
+            Pubkey::find_program_address(&[b"roots_ring", mint.as_ref()], &program_id);
+        let (nullifier_shard_pda, _) =
+            Pubkey::find_program_address(&[b"nullifier_shard", mint.as_ref()], &program_id);
+
+        let nullifier = &self.public_inputs[32..64];
+        let mut data = vec![ShieldPoolInstruction::Withdraw as u8];
+        data.extend_from_slice(&self.proof);
+        data.extend_from_slice(&self.public_inputs);
+        data.extend_from_slice(nullifier);
+        data.push(self.outputs.len() as u8);
+        for (address, amount) in &self.outputs {
+            data.extend_from_slice(address.as_ref());
+            data.extend_from_slice(&amount.to_le_bytes());
+        }
+
+        let mut roots = vec![0u8; RootsRing::SIZE];
+        roots[roots_ring::root_offset(0)..roots_ring::root_offset(1)]
+            .copy_from_slice(&self.public_inputs[0..32]);
+        let shard_size = NullifierShard::MIN_SIZE + NullifierShard::MAX_NULLIFIERS * 32;
+        let program_account = |data: Vec<u8>| Account {
+            lamports: mollusk.sysvars.rent.minimum_balance(data.len()),
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        let mut metas = vec![
+            AccountMeta::new(pool.0, false),
+            AccountMeta::new(treasury_pda, false),
+            AccountMeta::new(roots_ring_pda, false),
+            AccountMeta::new(nullifier_shard_pda, false),
+        ];
+        let mut accounts = vec![
+            pool,
+            (treasury_pda, program_account(vec![])),
+            (roots_ring_pda, program_account(roots)),
+            (nullifier_shard_pda, program_account(vec![0u8; shard_size])),
+        ];
+        for (address, _) in &self.outputs {
+            metas.push(AccountMeta::new(*address, false));
+            accounts.push((
+                *address,
+                Account {
+                    lamports: 0,
+                    data: vec![],
+                    owner: solana_sdk::system_program::id(),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            ));
+        }
+        metas.push(AccountMeta::new_readonly(
+            solana_sdk::system_program::id(),
+            false,
+        ));
+        accounts.push(system_program_account());
+
+        (
+            Instruction::new_with_bytes(program_id, &data, metas),
+            accounts,
+        )
+    }
+}
 
 #[test]
 fn test_withdraw_instruction() {
@@ -28,15 +183,9 @@ fn test_withdraw_instruction() {
     let nullifier = [0x55u8; 32];
 
     // Calculate fee using the same logic as SP1 guest program
-    let fee = {
-        let fixed_fee = 2_500_000; // 0.0025 SOL
-        let variable_fee = (withdraw_amount * 5) / 1_000; // 0.5% = 5/1000
-        fixed_fee + variable_fee
-    };
+    let fee = calculate_fee(withdraw_amount, FeeSchedule::NATIVE).unwrap();
     let recipient_amount = withdraw_amount - fee;
 
-    use sp1_sdk::SP1ProofWithPublicValues;
-
     let proof_path = "packages/zk-guest-sp1/out/proof_live.bin";
     let sp1_proof_with_public_values = match SP1ProofWithPublicValues::load(proof_path) {
         Ok(proof) => proof,
@@ -259,3 +408,80 @@ fn test_withdraw_instruction() {
 
     println!("✅ Withdraw instruction test completed - instruction structure validated");
 }
+
+#[test]
+fn test_withdraw_compute_units() {
+    let (program_id, mollusk) = setup();
+    let Some(live) = LiveWithdraw::load() else {
+        return;
+    };
+
+    let (pool_pda, mut pool_account) = native_pool(&program_id, &mollusk, &Pubkey::default());
+    pool_account.lamports += live.amount();
+    let (instruction, accounts) = live.instruction(program_id, &mollusk, (pool_pda, pool_account));
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(
+        !result.program_result.is_err(),
+        "Withdraw failed: {:?}",
+        result.program_result
+    );
+
+    // Groth16 verification dominates; catch a change that blows the CU budget
+    assert_compute_units_within(
+        &result,
+        "Withdraw",
+        "SHIELD_POOL_WITHDRAW_CU_CEILING",
+        WITHDRAW_CU_CEILING,
+    );
+}
+
+#[test]
+fn test_withdraw_cap_at_over_and_under() {
+    let pool_balance = 10_000_000_000u64; // 10 SOL
+    let cap_bps = 2_500; // 25% => 2.5 SOL
+
+    assert!(check_withdraw_cap(2_499_999_999, pool_balance, cap_bps).is_ok());
+    assert!(check_withdraw_cap(2_500_000_000, pool_balance, cap_bps).is_ok());
+    assert_eq!(
+        check_withdraw_cap(2_500_000_001, pool_balance, cap_bps),
+        Err(ShieldPoolError::WithdrawExceedsPoolCap.into())
+    );
+
+    // 0 disables the cap, 100% only bounds by the balance itself
+    assert!(check_withdraw_cap(pool_balance, pool_balance, 0).is_ok());
+    assert!(check_withdraw_cap(pool_balance, pool_balance, 10_000).is_ok());
+    assert!(check_withdraw_cap(pool_balance + 1, pool_balance, 10_000).is_err());
+}
+
+#[test]
+fn test_withdraw_over_admin_set_cap_rejected() {
+    let (program_id, mollusk) = setup();
+    let Some(live) = LiveWithdraw::load() else {
+        return;
+    };
+
+    // Cap the pool at 1% through the admin instruction
+    let pool = native_pool(&program_id, &mollusk, &Pubkey::default());
+    let pool_pda = pool.0;
+    let result = set_max_withdraw(program_id, &mollusk, admin_authority(), pool, 100);
+    assert!(!result.program_result.is_err());
+    let mut capped_pool = result
+        .resulting_accounts
+        .into_iter()
+        .find(|(pk, _)| *pk == pool_pda)
+        .unwrap();
+
+    // The withdraw is 2% of the pool's balance, and otherwise valid
+    capped_pool.1.lamports = live.amount() * 50;
+    let (instruction, accounts) = live.instruction(program_id, &mollusk, capped_pool);
+    let result = mollusk.process_instruction(&instruction, &accounts);
+    assert!(
+        matches!(
+            result.program_result,
+            ProgramResult::Failure(ProgramError::Custom(code))
+                if code == ShieldPoolError::WithdrawExceedsPoolCap as u32
+        ),
+        "expected WithdrawExceedsPoolCap, got: {:?}",
+        result.program_result
+    );
+}
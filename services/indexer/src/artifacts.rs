@@ -44,6 +44,26 @@ pub struct ArtifactVerificationDetails {
     pub vk: bool,
 }
 
+/// Outcome of checking a stored proof bundle against its public inputs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofVerification {
+    pub valid: bool,
+    pub details: ProofVerificationDetails,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProofVerificationDetails {
+    /// A 260-byte Groth16 fragment could be extracted from the bundle
+    pub groth16: bool,
+    /// The public inputs parse as the guest's 104-byte blob
+    pub public_inputs: bool,
+    /// The public inputs are the ones committed in the bundle
+    pub public_inputs_match: bool,
+    /// SP1 verification result; `None` when it was not run
+    pub sp1: Option<bool>,
+}
+
 #[derive(Clone)]
 pub struct ArtifactManager {
     base_path: PathBuf,
@@ -294,6 +314,85 @@ impl ArtifactManager {
     }
 }
 
+/// Verify a stored SP1 proof bundle and the public inputs returned with it.
+///
+/// The bundle is checked with `cloak_proof_extract` first; a structurally
+/// sound bundle is then verified against the guest's verifying key.
+pub fn verify_proof_bundle(sp1_proof_bundle: &[u8], public_inputs: &[u8]) -> ProofVerification {
+    // Host output is a versioned envelope; older artifacts hold the raw bundle.
+    // A malformed envelope is left as-is and fails the checks below.
+    let sp1_proof_bundle =
+        cloak_proof_extract::sp1_bundle(sp1_proof_bundle).unwrap_or(sp1_proof_bundle);
+    let details = check_proof_bundle(sp1_proof_bundle, public_inputs);
+    let error = if !details.groth16 {
+        Some("Groth16 proof not found in bundle".to_string())
+    } else if !details.public_inputs {
+        Some(format!(
+            "Public inputs are {} bytes, expected 104",
+            public_inputs.len()
+        ))
+    } else if !details.public_inputs_match {
+        Some("Public inputs do not match the proof bundle".to_string())
+    } else {
+        None
+    };
+
+    let (details, error) = match error {
+        None => {
+            let result = sp1_verify(sp1_proof_bundle);
+            let details = ProofVerificationDetails {
+                sp1: Some(result.is_ok()),
+                ..details
+            };
+            (details, result.err())
+        }
+        error => (details, error),
+    };
+
+    ProofVerification {
+        valid: error.is_none(),
+        details,
+        error,
+    }
+}
+
+/// Structural checks that need no prover: the Groth16 fragment is present,
+/// the public inputs are well formed and the bundle commits to exactly them.
+fn check_proof_bundle(sp1_proof_bundle: &[u8], public_inputs: &[u8]) -> ProofVerificationDetails {
+    // SP1 bundles are bincode, so the committed public values appear as a
+    // u64 LE length prefix followed by the bytes themselves
+    let mut committed = (public_inputs.len() as u64).to_le_bytes().to_vec();
+    committed.extend_from_slice(public_inputs);
+
+    ProofVerificationDetails {
+        groth16: cloak_proof_extract::extract_groth16_260(sp1_proof_bundle).is_ok(),
+        public_inputs: cloak_proof_extract::parse_public_inputs_104(public_inputs).is_ok(),
+        public_inputs_match: sp1_proof_bundle
+            .windows(committed.len())
+            .any(|window| window == committed.as_slice()),
+        sp1: None,
+    }
+}
+
+fn sp1_verify(sp1_proof_bundle: &[u8]) -> std::result::Result<(), String> {
+    use once_cell::sync::Lazy;
+    use sp1_sdk::{CpuProver, Prover, ProverClient, SP1ProofWithPublicValues, SP1VerifyingKey};
+
+    // Key setup is expensive, do it once
+    static VERIFIER: Lazy<(CpuProver, SP1VerifyingKey)> = Lazy::new(|| {
+        let client = ProverClient::builder().cpu().build();
+        let (_, vk) = client.setup(zk_guest_sp1_host::ELF);
+        (client, vk)
+    });
+
+    let proof: SP1ProofWithPublicValues = bincode::deserialize(sp1_proof_bundle)
+        .map_err(|e| format!("Invalid SP1 proof bundle: {}", e))?;
+    let (client, vk) = &*VERIFIER;
+    client
+        .verify(&proof, vk)
+        .map_err(|e| format!("SP1 verification failed: {}", e))
+}
+
 /// Compute SHA-256 hash of data
 fn compute_sha256(data: &[u8]) -> String {
     let mut hasher = Sha256::new();
@@ -312,4 +411,53 @@ fn get_content_type(filename: &str) -> String {
     }
 }
 
-// Tests can be added here when needed
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public_inputs() -> Vec<u8> {
+        (0..104u8).map(|i| i.wrapping_mul(7) | 1).collect()
+    }
+
+    /// Minimal bincode-shaped bundle: a length-prefixed Groth16 proof followed
+    /// by the length-prefixed public values
+    fn bundle(public_inputs: &[u8]) -> Vec<u8> {
+        let mut bundle = vec![0u8; 16];
+        bundle.extend_from_slice(&260u64.to_le_bytes());
+        bundle.extend((0..260u32).map(|i| (i % 251) as u8 + 1));
+        bundle.extend_from_slice(&(public_inputs.len() as u64).to_le_bytes());
+        bundle.extend_from_slice(public_inputs);
+        bundle.extend_from_slice(&104u64.to_le_bytes());
+        bundle
+    }
+
+    #[test]
+    fn test_good_proof_bundle_passes_checks() {
+        let public = public_inputs();
+        let details = check_proof_bundle(&bundle(&public), &public);
+
+        assert!(details.groth16);
+        assert!(details.public_inputs);
+        assert!(details.public_inputs_match);
+    }
+
+    #[test]
+    fn test_tampered_proof_bundle_fails_verification() {
+        let public = public_inputs();
+
+        let mut tampered_public = public.clone();
+        tampered_public[96] ^= 0xff; // amount
+        let result = verify_proof_bundle(&bundle(&public), &tampered_public);
+        assert!(!result.valid);
+        assert!(!result.details.public_inputs_match);
+        assert_eq!(result.details.sp1, None);
+
+        let result = verify_proof_bundle(&bundle(&public)[..200], &public);
+        assert!(!result.valid);
+        assert!(!result.details.groth16);
+
+        let result = verify_proof_bundle(&bundle(&public), &public[..100]);
+        assert!(!result.valid);
+        assert!(!result.details.public_inputs);
+    }
+}
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 use anyhow::{anyhow, Result};
+use cloak_layouts::{MERKLE_TREE_DEPTH, MERKLE_TREE_HEIGHT};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +19,10 @@ pub struct DatabaseConfig {
     pub url: Option<String>,
     pub max_connections: u32,
     pub min_connections: u32,
+    /// Connection attempts at startup before giving up
+    pub connect_max_attempts: u32,
+    /// Delay before the first connection retry, doubled on each further attempt
+    pub connect_backoff_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +82,8 @@ impl Config {
                     .ok(),
                 max_connections: get_env_var_as_number("DB_MAX_CONNECTIONS", 20)?,
                 min_connections: get_env_var_as_number("DB_MIN_CONNECTIONS", 2)?,
+                connect_max_attempts: get_env_var_as_number("DB_CONNECT_MAX_ATTEMPTS", 10)?,
+                connect_backoff_ms: get_env_var_as_number("DB_CONNECT_BACKOFF_MS", 500)?,
             },
             solana: SolanaConfig {
                 rpc_url: get_env_var("SOLANA_RPC_URL", "http://127.0.0.1:8899"),
@@ -92,7 +99,7 @@ impl Config {
                 cors_origins: get_cors_origins(),
             },
             merkle: MerkleConfig {
-                tree_height: get_env_var_as_number("TREE_HEIGHT", 32)?,
+                tree_height: get_env_var_as_number("TREE_HEIGHT", MERKLE_TREE_HEIGHT)?,
                 zero_value: get_env_var(
                     "TREE_ZERO_VALUE",
                     "0000000000000000000000000000000000000000000000000000000000000000",
@@ -113,6 +120,16 @@ impl Config {
             },
         };
 
+        // The guest only accepts paths of exactly MERKLE_TREE_DEPTH siblings
+        if config.merkle.tree_height != MERKLE_TREE_HEIGHT {
+            return Err(anyhow!(
+                "TREE_HEIGHT must be {} (proofs carry {} siblings), got {}",
+                MERKLE_TREE_HEIGHT,
+                MERKLE_TREE_DEPTH,
+                config.merkle.tree_height
+            ));
+        }
+
         Ok(config)
     }
 
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use cloak_retry::retry_connect;
 use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
 
 use crate::{
@@ -13,8 +14,19 @@ pub struct Database {
 }
 
 impl Database {
-    /// Create a new database connection pool
+    /// Create a new database connection pool, retrying with exponential backoff
+    /// so the indexer can start before PostgreSQL is accepting connections
     pub async fn new(config: &Config) -> Result<Self> {
+        retry_connect(
+            config.database.connect_max_attempts,
+            Duration::from_millis(config.database.connect_backoff_ms),
+            || Self::connect(config),
+        )
+        .await
+    }
+
+    /// Single connection attempt
+    async fn connect(config: &Config) -> Result<Self> {
         let database_url = config.database_url();
 
         tracing::info!("Connecting to database");
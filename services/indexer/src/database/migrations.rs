@@ -57,6 +57,11 @@ fn get_migrations() -> Vec<Migration> {
             name: "Add sequence for atomic leaf index allocation",
             sql: include_str!("../migrations/002_add_leaf_index_sequence.sql"),
         },
+        Migration {
+            id: "003_add_pushed_roots",
+            name: "Track roots pushed to the on-chain roots ring",
+            sql: include_str!("../migrations/003_add_pushed_roots.sql"),
+        },
     ]
 }
 
@@ -4,7 +4,7 @@ use sqlx::{Pool, Postgres};
 
 use crate::{
     error::{IndexerError, Result},
-    merkle::TreeStorage,
+    merkle::{PushedRoot, TreeStorage},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -195,6 +195,14 @@ impl PostgresTreeStorage {
                 IndexerError::Database(e)
             })?;
 
+        sqlx::query("DELETE FROM pushed_roots")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to clear pushed_roots table: {}", e);
+                IndexerError::Database(e)
+            })?;
+
         sqlx::query("DELETE FROM indexer_metadata")
             .execute(&self.pool)
             .await
@@ -403,6 +411,48 @@ impl PostgresTreeStorage {
         Ok(row.map(|(value,)| value))
     }
 
+    /// Record a root that was pushed to the on-chain roots ring
+    pub async fn record_pushed_root(&self, root: &str, next_index: u64) -> Result<()> {
+        let start = std::time::Instant::now();
+
+        sqlx::query("INSERT INTO pushed_roots (root, next_index) VALUES ($1, $2)")
+            .bind(root.to_lowercase())
+            .bind(next_index as i64)
+            .execute(&self.pool)
+            .await
+            .map_err(IndexerError::Database)?;
+
+        let duration = start.elapsed();
+        crate::log_database_operation!("INSERT", "pushed_roots", duration.as_millis() as u64);
+
+        tracing::debug!(root = root, next_index = next_index, "Recorded pushed root");
+        Ok(())
+    }
+
+    /// Get the `limit` most recently pushed roots, newest first
+    pub async fn get_recent_pushed_roots(&self, limit: usize) -> Result<Vec<PushedRoot>> {
+        let start = std::time::Instant::now();
+
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT root, next_index FROM pushed_roots ORDER BY id DESC LIMIT $1",
+        )
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(IndexerError::Database)?;
+
+        let duration = start.elapsed();
+        crate::log_database_operation!("SELECT", "pushed_roots", duration.as_millis() as u64);
+
+        Ok(rows
+            .into_iter()
+            .map(|(root, next_index)| PushedRoot {
+                root,
+                next_index: next_index as u64,
+            })
+            .collect())
+    }
+
     /// Log event processing
     pub async fn log_event_processing(
         &self,
@@ -67,87 +67,66 @@ pub struct ErrorResponse {
 
 impl IntoResponse for IndexerError {
     fn into_response(self) -> Response {
-        let (status, error_type, message, details) = match &self {
+        let status = self.status_code();
+        let (error_type, message, details) = match &self {
             IndexerError::Database(e) => {
                 tracing::error!("Database error: {}", e);
                 (
-                    StatusCode::INTERNAL_SERVER_ERROR,
                     "Database error".to_string(),
-                    Some("Internal database error occurred".to_string()),
+                    Some(if status == StatusCode::SERVICE_UNAVAILABLE {
+                        "Database is unavailable".to_string()
+                    } else {
+                        "Internal database error occurred".to_string()
+                    }),
                     None,
                 )
             }
             IndexerError::Config(e) => {
                 tracing::error!("Configuration error: {}", e);
                 (
-                    StatusCode::INTERNAL_SERVER_ERROR,
                     "Configuration error".to_string(),
                     Some("Service configuration error".to_string()),
                     None,
                 )
             }
             IndexerError::Validation { message, details } => (
-                StatusCode::BAD_REQUEST,
                 "Validation error".to_string(),
                 Some(message.clone()),
                 Some(details.clone()),
             ),
-            IndexerError::MerkleTree(msg) => (
-                StatusCode::BAD_REQUEST,
-                "Merkle tree error".to_string(),
-                Some(msg.clone()),
-                None,
-            ),
-            IndexerError::Artifact(msg) => (
-                StatusCode::NOT_FOUND,
-                "Artifact error".to_string(),
-                Some(msg.clone()),
-                None,
-            ),
-            IndexerError::NotFound(msg) => (
-                StatusCode::NOT_FOUND,
-                "Not found".to_string(),
-                Some(msg.clone()),
-                None,
-            ),
+            IndexerError::MerkleTree(msg) => {
+                ("Merkle tree error".to_string(), Some(msg.clone()), None)
+            }
+            IndexerError::Artifact(msg) => ("Artifact error".to_string(), Some(msg.clone()), None),
+            IndexerError::NotFound(msg) => ("Not found".to_string(), Some(msg.clone()), None),
             IndexerError::Internal(msg) => {
                 tracing::error!("Internal error: {}", msg);
                 (
-                    StatusCode::INTERNAL_SERVER_ERROR,
                     "Internal server error".to_string(),
                     Some("An internal error occurred".to_string()),
                     None,
                 )
             }
-            IndexerError::BadRequest(msg) => (
-                StatusCode::BAD_REQUEST,
-                "Bad request".to_string(),
-                Some(msg.clone()),
-                None,
-            ),
+            IndexerError::BadRequest(msg) => ("Bad request".to_string(), Some(msg.clone()), None),
             IndexerError::Io(e) => {
                 tracing::error!("IO error: {}", e);
                 (
-                    StatusCode::INTERNAL_SERVER_ERROR,
                     "Internal server error".to_string(),
                     Some("File system error occurred".to_string()),
                     None,
                 )
             }
             IndexerError::HexDecode(e) => (
-                StatusCode::BAD_REQUEST,
                 "Invalid hex string".to_string(),
                 Some(format!("Hex decoding failed: {}", e)),
                 None,
             ),
             IndexerError::Json(e) => (
-                StatusCode::BAD_REQUEST,
                 "JSON error".to_string(),
                 Some(format!("JSON processing failed: {}", e)),
                 None,
             ),
             IndexerError::Base64Decode(e) => (
-                StatusCode::BAD_REQUEST,
                 "Base64 decode error".to_string(),
                 Some(format!("Base64 decoding failed: {}", e)),
                 None,
@@ -167,6 +146,31 @@ impl IntoResponse for IndexerError {
 }
 
 impl IndexerError {
+    /// HTTP status for this error: client mistakes are 4xx, an unreachable
+    /// database is 503 so callers know to retry, anything else is 500.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            IndexerError::Database(e) => match e {
+                sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+                sqlx::Error::PoolTimedOut
+                | sqlx::Error::PoolClosed
+                | sqlx::Error::Io(_)
+                | sqlx::Error::Tls(_) => StatusCode::SERVICE_UNAVAILABLE,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            IndexerError::Validation { .. }
+            | IndexerError::MerkleTree(_)
+            | IndexerError::BadRequest(_)
+            | IndexerError::HexDecode(_)
+            | IndexerError::Json(_)
+            | IndexerError::Base64Decode(_) => StatusCode::BAD_REQUEST,
+            IndexerError::Artifact(_) | IndexerError::NotFound(_) => StatusCode::NOT_FOUND,
+            IndexerError::Config(_) | IndexerError::Internal(_) | IndexerError::Io(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
     pub fn validation(message: &str, field_errors: Vec<(&str, &str)>) -> Self {
         let details = field_errors
             .into_iter()
@@ -213,9 +217,11 @@ pub fn not_found_with_endpoints() -> Response {
         "POST /api/v1/deposit".to_string(),
         "GET /api/v1/merkle/root".to_string(),
         "GET /api/v1/merkle/proof/:index".to_string(),
+        "GET /api/v1/merkle/valid-root/:leaf".to_string(),
         "GET /api/v1/notes/range".to_string(),
         "GET /api/v1/artifacts/withdraw/:version".to_string(),
         "GET /api/v1/artifacts/files/:version/:filename".to_string(),
+        "GET /api/v1/artifacts/:artifact_id/verify".to_string(),
     ];
 
     let error_response = ErrorResponse {
@@ -229,4 +235,46 @@ pub fn not_found_with_endpoints() -> Response {
     (StatusCode::NOT_FOUND, Json(error_response)).into_response()
 }
 
-// Tests can be added here when needed
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_codes() {
+        let cases = [
+            (
+                IndexerError::validation("Invalid deposit", vec![("leaf_commit", "too short")]),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                IndexerError::bad_request("Invalid deposit"),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                IndexerError::not_found("Leaf index 7 does not exist"),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                IndexerError::Database(sqlx::Error::PoolTimedOut),
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            (
+                IndexerError::Database(sqlx::Error::RowNotFound),
+                StatusCode::NOT_FOUND,
+            ),
+            (
+                IndexerError::Database(sqlx::Error::Protocol("unexpected".into())),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                IndexerError::internal("boom"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ];
+
+        for (error, expected) in cases {
+            let label = error.to_string();
+            assert_eq!(error.into_response().status(), expected, "{}", label);
+        }
+    }
+}
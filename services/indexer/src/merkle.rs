@@ -15,6 +15,20 @@ pub struct MerkleTreeState {
     pub next_index: u64,
 }
 
+/// A root the indexer pushed to the on-chain roots ring
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushedRoot {
+    pub root: String,
+    /// Tree size when the root was computed: it commits to leaves `0..next_index`
+    pub next_index: u64,
+}
+
+/// Pick the most recent root that includes `leaf_index` from `recent`, the
+/// roots still in the on-chain ring ordered newest first
+pub fn select_valid_root(recent: &[PushedRoot], leaf_index: u64) -> Option<&PushedRoot> {
+    recent.iter().find(|pushed| leaf_index < pushed.next_index)
+}
+
 #[derive(Debug, Clone)]
 pub struct TreeNode {
     pub level: u32,
@@ -246,7 +260,7 @@ impl MerkleTree {
         storage: &dyn TreeStorage,
     ) -> Result<MerkleProof> {
         if leaf_index >= self.next_index {
-            return Err(IndexerError::merkle_tree(format!(
+            return Err(IndexerError::not_found(format!(
                 "Leaf index {} does not exist (next_index: {})",
                 leaf_index, self.next_index
             )));
@@ -289,6 +303,103 @@ impl MerkleTree {
         })
     }
 
+    /// Generate a Merkle proof for `leaf_index` against the root the tree had
+    /// when it held `tree_size` leaves, e.g. an older root still in the on-chain ring.
+    /// Leaves are appended in index order, so nodes whose subtree lies entirely
+    /// below `tree_size` are unchanged since then and only the right frontier
+    /// has to be recomputed. Returns the proof and that root.
+    pub async fn generate_proof_at(
+        &self,
+        leaf_index: u64,
+        tree_size: u64,
+        storage: &dyn TreeStorage,
+    ) -> Result<(MerkleProof, String)> {
+        if leaf_index >= tree_size || tree_size > self.next_index {
+            return Err(IndexerError::not_found(format!(
+                "Leaf index {} is not in a tree of size {} (next_index: {})",
+                leaf_index, tree_size, self.next_index
+            )));
+        }
+
+        // frontier[level] is the node at index `tree_size >> level` when its
+        // subtree is only partly filled
+        let mut frontier: Vec<Option<String>> = vec![None; self.height];
+        for level in 1..self.height {
+            if tree_size & ((1u64 << level) - 1) == 0 {
+                continue;
+            }
+            let index = tree_size >> level;
+            let left = self
+                .node_at_size(level - 1, 2 * index, tree_size, &frontier, storage)
+                .await?;
+            let right = self
+                .node_at_size(level - 1, 2 * index + 1, tree_size, &frontier, storage)
+                .await?;
+            frontier[level] = Some(Self::hash_pair(&left, &right)?);
+        }
+
+        let mut path_elements = Vec::new();
+        let mut path_indices = Vec::new();
+        let mut current_index = leaf_index;
+
+        for level in 0..(self.height - 1) {
+            let is_left_child = current_index.is_multiple_of(2);
+            path_indices.push(if is_left_child { 0 } else { 1 });
+
+            let sibling_index = if is_left_child {
+                current_index + 1
+            } else {
+                current_index - 1
+            };
+            path_elements.push(
+                self.node_at_size(level, sibling_index, tree_size, &frontier, storage)
+                    .await?,
+            );
+            current_index /= 2;
+        }
+
+        let root = self
+            .node_at_size(self.height - 1, 0, tree_size, &frontier, storage)
+            .await?;
+
+        Ok((
+            MerkleProof {
+                path_elements,
+                path_indices,
+            },
+            root,
+        ))
+    }
+
+    /// Value of a node as it was when the tree held `tree_size` leaves
+    async fn node_at_size(
+        &self,
+        level: usize,
+        index: u64,
+        tree_size: u64,
+        frontier: &[Option<String>],
+        storage: &dyn TreeStorage,
+    ) -> Result<String> {
+        let first_leaf = index << level;
+        let end_leaf = (index + 1) << level;
+
+        if first_leaf >= tree_size {
+            Ok(self.zero_values[level].clone())
+        } else if end_leaf <= tree_size {
+            Ok(storage
+                .get_node(level as u32, index)
+                .await?
+                .unwrap_or_else(|| self.zero_values[level].clone()))
+        } else {
+            frontier[level].clone().ok_or_else(|| {
+                IndexerError::merkle_tree(format!(
+                    "Missing frontier node at level {} index {}",
+                    level, index
+                ))
+            })
+        }
+    }
+
     /// Verify a Merkle proof
     pub fn verify_proof(
         &self,
@@ -403,6 +514,11 @@ impl MerkleTree {
         tracing::info!("Reset Merkle tree state - next_index set to 0");
     }
 
+    /// Get the next leaf index (number of leaves in the tree)
+    pub fn next_index(&self) -> u64 {
+        self.next_index
+    }
+
     /// Get tree height
     pub fn height(&self) -> usize {
         self.height
@@ -414,4 +530,143 @@ impl MerkleTree {
     }
 }
 
-// Tests can be added here when needed
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use super::*;
+
+    /// Tree storage held in memory
+    #[derive(Default)]
+    struct MemoryStorage {
+        nodes: Mutex<HashMap<(u32, u64), String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TreeStorage for MemoryStorage {
+        async fn store_node(&self, level: u32, index: u64, value: &str) -> Result<()> {
+            self.nodes
+                .lock()
+                .unwrap()
+                .insert((level, index), value.to_string());
+            Ok(())
+        }
+
+        async fn get_node(&self, level: u32, index: u64) -> Result<Option<String>> {
+            Ok(self.nodes.lock().unwrap().get(&(level, index)).cloned())
+        }
+
+        async fn get_max_leaf_index(&self) -> Result<u64> {
+            Ok(0)
+        }
+    }
+
+    fn leaf(index: u64) -> String {
+        hex::encode(blake3::hash(&index.to_le_bytes()).as_bytes())
+    }
+
+    #[tokio::test]
+    async fn test_valid_root_contains_leaf_and_is_on_chain() {
+        const RING: usize = 64;
+        let storage = MemoryStorage::default();
+        let mut tree = MerkleTree::new(32, &"0".repeat(64)).unwrap();
+
+        // Every insert but the last is pushed on-chain
+        let mut pushed = Vec::new();
+        for index in 0..80u64 {
+            let (root, _) = tree
+                .insert_leaf(index, &leaf(index), &storage)
+                .await
+                .unwrap();
+            if index < 79 {
+                pushed.push(PushedRoot {
+                    root,
+                    next_index: tree.next_index(),
+                });
+            }
+        }
+        let recent: Vec<PushedRoot> = pushed.iter().rev().take(RING).cloned().collect();
+
+        for leaf_index in [0, 5, 40, 78] {
+            let valid = select_valid_root(&recent, leaf_index).expect("leaf has an on-chain root");
+            assert!(recent.contains(valid));
+            assert_eq!(valid, &recent[0]);
+
+            let (proof, root) = tree
+                .generate_proof_at(leaf_index, valid.next_index, &storage)
+                .await
+                .unwrap();
+            assert_eq!(root, valid.root);
+            assert!(tree
+                .verify_proof(&leaf(leaf_index), leaf_index, &proof, &valid.root)
+                .unwrap());
+        }
+
+        // The latest leaf's root was never pushed
+        assert!(select_valid_root(&recent, 79).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_proof_matches_guest_depth() {
+        let storage = MemoryStorage::default();
+        let mut tree = MerkleTree::new(cloak_layouts::MERKLE_TREE_HEIGHT, &"0".repeat(64)).unwrap();
+        let mut root = String::new();
+        for index in 0..5u64 {
+            (root, _) = tree
+                .insert_leaf(index, &leaf(index), &storage)
+                .await
+                .unwrap();
+        }
+
+        let (proof, _) = tree.generate_proof_at(3, 5, &storage).await.unwrap();
+        assert_eq!(proof.path_elements.len(), cloak_layouts::MERKLE_TREE_DEPTH);
+        assert_eq!(proof.path_indices.len(), cloak_layouts::MERKLE_TREE_DEPTH);
+
+        // Fold the path the way the guest does: bit 0 puts the current node on the left
+        let mut node = hex::decode(leaf(3)).unwrap();
+        for (sibling, &bit) in proof.path_elements.iter().zip(&proof.path_indices) {
+            let sibling = hex::decode(sibling).unwrap();
+            let (left, right) = if bit == 0 {
+                (&node, &sibling)
+            } else {
+                (&sibling, &node)
+            };
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(left);
+            hasher.update(right);
+            node = hasher.finalize().as_bytes().to_vec();
+        }
+        assert_eq!(hex::encode(node), root);
+    }
+
+    #[tokio::test]
+    async fn test_proof_at_older_tree_size() {
+        let storage = MemoryStorage::default();
+        let mut tree = MerkleTree::new(8, &"0".repeat(64)).unwrap();
+
+        let mut roots = Vec::new();
+        for index in 0..13u64 {
+            let (root, _) = tree
+                .insert_leaf(index, &leaf(index), &storage)
+                .await
+                .unwrap();
+            roots.push(root);
+        }
+
+        for tree_size in 1..=13u64 {
+            for leaf_index in 0..tree_size {
+                let (proof, root) = tree
+                    .generate_proof_at(leaf_index, tree_size, &storage)
+                    .await
+                    .unwrap();
+                assert_eq!(root, roots[tree_size as usize - 1]);
+                assert!(tree
+                    .verify_proof(&leaf(leaf_index), leaf_index, &proof, &root)
+                    .unwrap());
+            }
+        }
+
+        assert!(tree.generate_proof_at(5, 5, &storage).await.is_err());
+        assert!(tree.generate_proof_at(0, 14, &storage).await.is_err());
+    }
+}
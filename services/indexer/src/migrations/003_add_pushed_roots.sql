@@ -0,0 +1,12 @@
+-- Roots pushed to the on-chain roots ring
+-- Lets the indexer tell clients which roots are still accepted on-chain
+
+CREATE TABLE IF NOT EXISTS pushed_roots (
+    id BIGSERIAL PRIMARY KEY,
+    root CHAR(64) NOT NULL,
+    -- Tree size when the root was computed (root covers leaves 0..next_index)
+    next_index BIGINT NOT NULL,
+    pushed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_pushed_roots_next_index ON pushed_roots (next_index);
@@ -7,6 +7,7 @@ use axum::{
     Json,
 };
 use base64::{engine::general_purpose, Engine as _};
+use cloak_layouts::parse_hex32;
 use serde::Deserialize;
 use solana_sdk::transaction::VersionedTransaction;
 use tokio::sync::Mutex;
@@ -14,7 +15,7 @@ use tokio::sync::Mutex;
 use crate::{
     artifacts::ArtifactManager,
     database::PostgresTreeStorage,
-    merkle::{MerkleTree, TreeStorage},
+    merkle::{select_valid_root, MerkleTree, TreeStorage},
     solana::push_root_to_chain,
     sp1_tee_client::Sp1TeeClient,
 };
@@ -67,6 +68,7 @@ pub async fn api_info() -> impl IntoResponse {
         ("deposit", "/api/v1/deposit"),
         ("merkle_root", "/api/v1/merkle/root"),
         ("merkle_proof", "/api/v1/merkle/proof/:index"),
+        ("merkle_valid_root", "/api/v1/merkle/valid-root/:leaf"),
         ("notes_range", "/api/v1/notes/range"),
         ("artifacts", "/api/v1/artifacts/withdraw/:version"),
         ("tee_artifact", "/api/v1/tee/artifact"),
@@ -76,6 +78,7 @@ pub async fn api_info() -> impl IntoResponse {
         ),
         ("tee_request_proof", "/api/v1/tee/request-proof"),
         ("tee_proof_status", "/api/v1/tee/proof-status"),
+        ("artifact_verify", "/api/v1/artifacts/:artifact_id/verify"),
     ]
     .into_iter()
     .map(|(k, v)| (k.to_string(), v.to_string()))
@@ -120,7 +123,7 @@ pub async fn health_check(State(state): State<AppState>) -> impl IntoResponse {
 
 pub async fn deposit(
     State(state): State<AppState>,
-    Json(request): Json<DepositRequest>,
+    Json(mut request): Json<DepositRequest>,
 ) -> impl IntoResponse {
     tracing::info!("📥 Received deposit request");
     tracing::info!(
@@ -150,18 +153,19 @@ pub async fn deposit(
         }
     }
 
-    // Basic validation
-    if request.leaf_commit.len() != 64 {
-        tracing::warn!(
-            leaf_commit_len = request.leaf_commit.len(),
-            "Invalid leaf commit length"
-        );
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "error": "Leaf commit must be 64 characters"
-            })),
-        );
+    // Basic validation; store the commitment in its canonical form
+    // (lowercase, no 0x) so prefixed and bare submissions match
+    match parse_hex32(&request.leaf_commit) {
+        Ok(bytes) => request.leaf_commit = hex::encode(bytes),
+        Err(e) => {
+            tracing::warn!(error = %e, "Invalid leaf commit");
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!("Invalid leaf commit: {}", e)
+                })),
+            );
+        }
     }
 
     if request.encrypted_output.is_empty() {
@@ -334,13 +338,19 @@ pub async fn deposit(
                 // Push new root to on-chain roots ring synchronously to prevent race conditions
                 // The withdrawal proof depends on this root being on-chain before it can be verified
                 tracing::info!("🔗 Pushing root to on-chain roots ring");
-                if let Err(e) = push_root_to_chain(&new_root, &state.config.solana).await {
-                    tracing::error!("❌ Failed to push root to on-chain roots ring: {}", e);
-                    // Continue anyway - withdrawals can still work if root is pushed later
-                    // or if the on-chain program has a grace period for root updates
-                    tracing::warn!("⚠️  Continuing despite root push failure - withdrawals may fail until root is manually pushed");
-                } else {
-                    tracing::info!("✅ Root successfully pushed to on-chain roots ring");
+                match push_root_to_chain(&new_root, &state.config.solana).await {
+                    Ok(pushed) => {
+                        tracing::info!("✅ Root successfully pushed to on-chain roots ring");
+                        if pushed {
+                            record_pushed_root(&state, &new_root, tree.next_index()).await;
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("❌ Failed to push root to on-chain roots ring: {}", e);
+                        // Continue anyway - withdrawals can still work if root is pushed later
+                        // or if the on-chain program has a grace period for root updates
+                        tracing::warn!("⚠️  Continuing despite root push failure - withdrawals may fail until root is manually pushed");
+                    }
                 }
 
                 tracing::info!("🎉 Deposit request completed successfully");
@@ -374,7 +384,7 @@ pub async fn deposit(
 /// This ensures the root is on-chain BEFORE the user sends the transaction
 pub async fn deposit_prepare(
     State(state): State<AppState>,
-    Json(request): Json<DepositPrepareRequest>,
+    Json(mut request): Json<DepositPrepareRequest>,
 ) -> impl IntoResponse {
     tracing::info!("📥 Received deposit prepare request");
     tracing::info!(
@@ -385,13 +395,16 @@ pub async fn deposit_prepare(
     );
 
     // Basic validation
-    if request.leaf_commit.len() != 64 {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "error": "Leaf commit must be 64 characters"
-            })),
-        );
+    match parse_hex32(&request.leaf_commit) {
+        Ok(bytes) => request.leaf_commit = hex::encode(bytes),
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!("Invalid leaf commit: {}", e)
+                })),
+            );
+        }
     }
 
     if request.encrypted_output.is_empty() {
@@ -560,20 +573,26 @@ pub async fn deposit_prepare(
             tracing::info!(
                 "🔗 Pushing root to on-chain roots ring (CRITICAL: must succeed before deposit)"
             );
-            if let Err(e) = push_root_to_chain(&new_root, &state.config.solana).await {
-                tracing::error!("❌ Failed to push root to on-chain roots ring: {}", e);
-                // This is a critical failure - we cannot proceed if root push fails
-                // The deposit transaction should not be sent if root is not on-chain
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({
-                        "error": "Failed to push root to on-chain roots ring",
-                        "details": e.to_string(),
-                        "message": "Deposit cannot proceed - root must be on-chain first"
-                    })),
-                );
-            } else {
-                tracing::info!("✅ Root successfully pushed to on-chain roots ring");
+            match push_root_to_chain(&new_root, &state.config.solana).await {
+                Ok(pushed) => {
+                    tracing::info!("✅ Root successfully pushed to on-chain roots ring");
+                    if pushed {
+                        record_pushed_root(&state, &new_root, tree.next_index()).await;
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("❌ Failed to push root to on-chain roots ring: {}", e);
+                    // This is a critical failure - we cannot proceed if root push fails
+                    // The deposit transaction should not be sent if root is not on-chain
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(serde_json::json!({
+                            "error": "Failed to push root to on-chain roots ring",
+                            "details": e.to_string(),
+                            "message": "Deposit cannot proceed - root must be on-chain first"
+                        })),
+                    );
+                }
             }
 
             tracing::info!("🎉 Deposit prepare completed successfully");
@@ -605,7 +624,7 @@ pub async fn deposit_prepare(
 /// Confirm a prepared deposit: update with transaction signature and slot
 pub async fn deposit_confirm(
     State(state): State<AppState>,
-    Json(request): Json<DepositConfirmRequest>,
+    Json(mut request): Json<DepositConfirmRequest>,
 ) -> impl IntoResponse {
     tracing::info!("📥 Received deposit confirm request");
     tracing::info!(
@@ -616,13 +635,16 @@ pub async fn deposit_confirm(
     );
 
     // Validate inputs
-    if request.prepared_deposit_id.len() != 64 {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "error": "Prepared deposit ID (commitment) must be 64 characters"
-            })),
-        );
+    match parse_hex32(&request.prepared_deposit_id) {
+        Ok(bytes) => request.prepared_deposit_id = hex::encode(bytes),
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!("Invalid prepared deposit ID (commitment): {}", e)
+                })),
+            );
+        }
     }
 
     if request.tx_signature.is_empty() {
@@ -695,7 +717,7 @@ pub async fn get_merkle_root(State(state): State<AppState>) -> impl IntoResponse
         Err(e) => {
             tracing::error!("❌ Failed to get merkle tree state: {}", e);
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
+                e.status_code(),
                 Json(serde_json::json!({
                     "error": "Failed to get merkle tree state",
                     "details": e.to_string()
@@ -803,7 +825,7 @@ pub async fn get_merkle_proof(
                 e
             );
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
+                e.status_code(),
                 Json(serde_json::json!({
                     "error": "Failed to generate merkle proof",
                     "details": e.to_string()
@@ -813,7 +835,7 @@ pub async fn get_merkle_proof(
         (_, Err(e)) => {
             tracing::error!("❌ Failed to get merkle tree state: {}", e);
             (
-                StatusCode::INTERNAL_SERVER_ERROR,
+                e.status_code(),
                 Json(serde_json::json!({
                     "error": "Failed to get merkle tree state",
                     "details": e.to_string()
@@ -823,6 +845,82 @@ pub async fn get_merkle_proof(
     }
 }
 
+/// Most recent root that includes `leaf` and is still in the on-chain roots
+/// ring, with the Merkle path against that root
+pub async fn get_valid_root(
+    State(state): State<AppState>,
+    Path(leaf): Path<u64>,
+) -> impl IntoResponse {
+    tracing::info!("🔍 Finding valid on-chain root for leaf: {}", leaf);
+
+    let recent = match state
+        .storage
+        .get_recent_pushed_roots(shield_pool::state::RootsRing::MAX_ROOTS)
+        .await
+    {
+        Ok(recent) => recent,
+        Err(e) => {
+            tracing::error!("❌ Failed to load pushed roots: {}", e);
+            return (
+                e.status_code(),
+                Json(serde_json::json!({
+                    "error": "Failed to load pushed roots",
+                    "details": e.to_string()
+                })),
+            );
+        }
+    };
+
+    let Some(valid) = select_valid_root(&recent, leaf) else {
+        tracing::warn!(leaf = leaf, "No on-chain root includes leaf");
+        return (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": "No on-chain root includes this leaf yet",
+                "leaf": leaf
+            })),
+        );
+    };
+
+    let tree = state.merkle_tree.lock().await;
+    match tree
+        .generate_proof_at(leaf, valid.next_index, &state.storage)
+        .await
+    {
+        Ok((proof, root)) => {
+            tracing::info!(
+                leaf = leaf,
+                root = root,
+                next_index = valid.next_index,
+                "✅ Valid root found"
+            );
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "root": root,
+                    "nextIndex": valid.next_index,
+                    "pathElements": proof.path_elements,
+                    "pathIndices": proof.path_indices
+                })),
+            )
+        }
+        Err(e) => {
+            tracing::error!(
+                "❌ Failed to generate merkle proof for leaf {}: {}",
+                leaf,
+                e
+            );
+            (
+                e.status_code(),
+                Json(serde_json::json!({
+                    "error": "Failed to generate merkle proof",
+                    "details": e.to_string()
+                })),
+            )
+        }
+    }
+}
+
 pub async fn get_notes_range(
     State(state): State<AppState>,
     Query(params): Query<NotesRangeQuery>,
@@ -880,3 +978,10 @@ pub async fn get_notes_range(
         }
     }
 }
+
+/// Remember a root that reached the on-chain roots ring for `get_valid_root`
+async fn record_pushed_root(state: &AppState, root: &str, next_index: u64) {
+    if let Err(e) = state.storage.record_pushed_root(root, next_index).await {
+        tracing::warn!("⚠️ Failed to record pushed root: {}", e);
+    }
+}
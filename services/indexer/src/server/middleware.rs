@@ -117,7 +117,7 @@ pub fn cors_layer(cors_origins: &[String]) -> CorsLayer {
 /// Request timeout middleware
 /// Uses different timeouts based on endpoint:
 /// - /api/v1/deposit: 120 seconds - Merkle tree insertion can be slow
-/// - /api/v1/merkle/proof/*: 90 seconds - Merkle proof generation can be slow (many DB queries)
+/// - /api/v1/merkle/proof/*, /api/v1/merkle/valid-root/*: 90 seconds - Merkle proof generation can be slow (many DB queries)
 /// - Other endpoints: 20 seconds - faster timeout for regular requests
 pub async fn timeout_middleware(
     request: Request<axum::body::Body>,
@@ -128,7 +128,9 @@ pub async fn timeout_middleware(
     // Use longer timeout for slow endpoints
     let timeout_duration = if path == "/api/v1/deposit" {
         std::time::Duration::from_secs(120) // 120 seconds for deposit (Merkle tree insertion)
-    } else if path.starts_with("/api/v1/merkle/proof/") {
+    } else if path.starts_with("/api/v1/merkle/proof/")
+        || path.starts_with("/api/v1/merkle/valid-root/")
+    {
         std::time::Duration::from_secs(90) // 90 seconds for Merkle proof generation
     } else {
         std::time::Duration::from_secs(20) // 20 seconds for other endpoints
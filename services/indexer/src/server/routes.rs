@@ -23,7 +23,9 @@ use crate::{
             cors_layer, logging_middleware, rate_limit_general, request_size_limit,
             timeout_middleware,
         },
-        tee_artifact_handler::{create_artifact, get_proof_status, request_proof, upload_stdin},
+        tee_artifact_handler::{
+            create_artifact, get_proof_status, request_proof, upload_stdin, verify_artifact,
+        },
     },
     sp1_tee_client::create_tee_client,
 };
@@ -166,12 +168,14 @@ fn create_api_v1_routes() -> Router<AppState> {
         .route("/deposit/confirm", post(deposit_confirm)) // New: confirm deposit
         .route("/merkle/root", get(get_merkle_root))
         .route("/merkle/proof/:index", get(get_merkle_proof))
+        .route("/merkle/valid-root/:leaf", get(get_valid_root))
         .route("/notes/range", get(get_notes_range))
         // TEE artifact-based proof generation endpoints
         .route("/tee/artifact", post(create_artifact))
         .route("/tee/artifact/:artifact_id/upload", post(upload_stdin))
         .route("/tee/request-proof", post(request_proof))
         .route("/tee/proof-status", get(get_proof_status))
+        .route("/artifacts/:artifact_id/verify", get(verify_artifact))
         // Admin endpoints
         .route("/admin/reset", post(reset_database))
         // Apply general rate limiting to all routes
@@ -10,7 +10,7 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::server::final_handlers::AppState;
+use crate::{artifacts::verify_proof_bundle, server::final_handlers::AppState};
 
 /// Request to create a stdin artifact
 #[derive(Debug, Deserialize)]
@@ -174,6 +174,15 @@ impl ArtifactStore {
         let requests = self.proof_requests.read().await;
         requests.get(request_id).cloned()
     }
+
+    /// Hex proof bundle and public inputs of a completed proof for `artifact_id`
+    pub async fn get_ready_proof(&self, artifact_id: &str) -> Option<(String, String)> {
+        let requests = self.proof_requests.read().await;
+        requests
+            .values()
+            .filter(|request| request.artifact_id == artifact_id && request.status == "ready")
+            .find_map(|request| Some((request.proof.clone()?, request.public_inputs.clone()?)))
+    }
 }
 
 // Global artifact store (in production, use a database)
@@ -474,3 +483,65 @@ pub async fn get_proof_status(
         )),
     }
 }
+
+/// GET /artifacts/:artifact_id/verify
+/// Verify the proof generated for an artifact before it is used
+pub async fn verify_artifact(
+    Path(artifact_id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if ARTIFACT_STORE.get_artifact(&artifact_id).await.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": "Artifact not found"
+            })),
+        ));
+    }
+
+    let Some((proof_hex, public_inputs_hex)) = ARTIFACT_STORE.get_ready_proof(&artifact_id).await
+    else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": "No completed proof for artifact"
+            })),
+        ));
+    };
+
+    // A stored value that is not hex is as corrupted as a bad proof
+    let proof = hex::decode(&proof_hex).unwrap_or_default();
+    let public_inputs = hex::decode(&public_inputs_hex).unwrap_or_default();
+
+    // SP1 verification is CPU bound
+    let verification = tokio::task::spawn_blocking(move || {
+        verify_proof_bundle(&proof, &public_inputs)
+    })
+    .await
+    .map_err(|e| {
+        tracing::error!(artifact_id = %artifact_id, error = %e, "Proof verification task failed");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": "Proof verification failed to run"
+            })),
+        )
+    })?;
+
+    if verification.valid {
+        tracing::info!(artifact_id = %artifact_id, "✅ Artifact proof verified");
+    } else {
+        tracing::warn!(
+            artifact_id = %artifact_id,
+            error = ?verification.error,
+            "❌ Artifact proof failed verification"
+        );
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "artifact_id": artifact_id,
+            "verification": verification
+        })),
+    ))
+}
@@ -13,20 +13,22 @@ use solana_sdk::{
 use crate::config::SolanaConfig;
 
 /// Push a merkle root to the on-chain roots ring
-pub async fn push_root_to_chain(root_hash: &str, config: &SolanaConfig) -> Result<()> {
+///
+/// Returns `false` when pushing is disabled by configuration.
+pub async fn push_root_to_chain(root_hash: &str, config: &SolanaConfig) -> Result<bool> {
     // Check if admin keypair is configured
     let admin_keypair_bytes = match &config.admin_keypair {
         Some(bytes) => bytes,
         None => {
             tracing::warn!("Admin keypair not configured, skipping root push");
-            return Ok(());
+            return Ok(false);
         }
     };
 
     // Validate configuration
     if config.shield_pool_program_id.is_empty() {
         tracing::warn!("Shield pool program ID not configured, skipping root push");
-        return Ok(());
+        return Ok(false);
     }
 
     // Create admin keypair from bytes
@@ -65,11 +67,14 @@ pub async fn push_root_to_chain(root_hash: &str, config: &SolanaConfig) -> Resul
         .context("Invalid shield pool program ID")?;
     let (roots_ring_pda, _bump) =
         Pubkey::find_program_address(&[b"roots_ring", mint.as_ref()], &program_id);
+    // The program checks the signer against the authority stored in the pool
+    let (pool_pda, _bump) = Pubkey::find_program_address(&[b"pool", mint.as_ref()], &program_id);
 
     tracing::info!(
         roots_ring_pda = %roots_ring_pda,
+        pool_pda = %pool_pda,
         mint = %mint,
-        "Derived roots_ring PDA from program ID with mint"
+        "Derived roots_ring and pool PDAs from program ID with mint"
     );
     // Create instruction
     let instruction = Instruction {
@@ -77,6 +82,7 @@ pub async fn push_root_to_chain(root_hash: &str, config: &SolanaConfig) -> Resul
         accounts: vec![
             AccountMeta::new(admin_keypair.pubkey(), true),
             AccountMeta::new(roots_ring_pda, false),
+            AccountMeta::new_readonly(pool_pda, false),
         ],
         data: instruction_data,
     };
@@ -102,5 +108,5 @@ pub async fn push_root_to_chain(root_hash: &str, config: &SolanaConfig) -> Resul
         "Root successfully pushed to on-chain roots ring"
     );
 
-    Ok(())
+    Ok(true)
 }
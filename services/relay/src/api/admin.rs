@@ -0,0 +1,189 @@
+use axum::{
+    extract::{Query, State},
+    http::{header::AUTHORIZATION, HeaderMap},
+    response::Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    config::Config as RelayConfig,
+    db::{models::FailedJob, repository::JobRepository},
+    error::Error,
+    solana::SubmitSettings,
+    AppState,
+};
+
+/// Settings in effect after a reload
+#[derive(Debug, Serialize)]
+pub struct ReloadedConfig {
+    pub priority_micro_lamports: u64,
+    pub priority_fee_escalation: f64,
+    pub max_priority_micro_lamports: u64,
+    pub max_retries: u8,
+    pub retry_delay_ms: u64,
+    pub slot_patterns: Vec<u8>,
+    pub max_batch_size: usize,
+}
+
+/// POST /admin/reload-config
+///
+/// Re-reads the environment (and `.env`) and swaps in the hot-reloadable
+/// settings: priority fee and its escalation, retry policy and the scheduler
+/// window. Everything else (RPC endpoints, keys, CORS, database) still requires
+/// a restart.
+pub async fn reload_config(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<ReloadedConfig>, Error> {
+    authorize(state.admin_token.as_deref(), &headers)?;
+
+    // Pick up edits to .env as well as the process environment
+    let config = RelayConfig::reload()
+        .map_err(|e| Error::ValidationError(format!("Failed to reload config: {}", e)))?;
+
+    let submit = SubmitSettings::from_config(&config.solana);
+    state.solana.update_submit_settings(submit);
+
+    {
+        let mut window = state
+            .window_config
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        window.slot_patterns = config.scheduler.slot_patterns.clone();
+        window.max_batch_size = config.scheduler.max_batch_size;
+    }
+
+    info!(
+        "🔄 Config reloaded: priority_micro_lamports={}, max_retries={}, retry_delay_ms={}, slot_patterns={:?}, max_batch_size={}",
+        submit.priority_micro_lamports,
+        submit.max_retries,
+        submit.retry_delay_ms,
+        config.scheduler.slot_patterns,
+        config.scheduler.max_batch_size
+    );
+
+    Ok(Json(ReloadedConfig {
+        priority_micro_lamports: submit.priority_micro_lamports,
+        priority_fee_escalation: submit.priority_fee_escalation,
+        max_priority_micro_lamports: submit.max_priority_micro_lamports,
+        max_retries: submit.max_retries,
+        retry_delay_ms: submit.retry_delay_ms,
+        slot_patterns: config.scheduler.slot_patterns,
+        max_batch_size: config.scheduler.max_batch_size,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FailedJobsQuery {
+    #[serde(default = "default_failed_jobs_limit")]
+    pub limit: i64,
+}
+
+fn default_failed_jobs_limit() -> i64 {
+    50
+}
+
+/// Dead-lettered job, with enough data to resubmit it by hand
+#[derive(Debug, Serialize)]
+pub struct FailedJobResponse {
+    pub job_id: Uuid,
+    pub request_id: Uuid,
+    /// Hex-encoded proof bytes
+    pub proof_bytes: String,
+    /// Hex-encoded public inputs
+    pub public_inputs: String,
+    pub outputs: serde_json::Value,
+    pub error: String,
+    pub retry_count: i32,
+    pub failed_at: DateTime<Utc>,
+}
+
+impl From<FailedJob> for FailedJobResponse {
+    fn from(job: FailedJob) -> Self {
+        Self {
+            job_id: job.job_id,
+            request_id: job.request_id,
+            proof_bytes: hex::encode(job.proof_bytes),
+            public_inputs: hex::encode(job.public_inputs),
+            outputs: job.outputs_json,
+            error: job.error_message,
+            retry_count: job.retry_count,
+            failed_at: job.failed_at,
+        }
+    }
+}
+
+/// GET /admin/failed-jobs?limit=N
+///
+/// Jobs that failed permanently or ran out of retries, most recent first.
+pub async fn get_failed_jobs(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<FailedJobsQuery>,
+) -> Result<Json<Vec<FailedJobResponse>>, Error> {
+    authorize(state.admin_token.as_deref(), &headers)?;
+
+    let jobs = state
+        .job_repo
+        .get_failed_jobs(query.limit.clamp(1, 500))
+        .await?;
+
+    Ok(Json(jobs.into_iter().map(Into::into).collect()))
+}
+
+/// Check the `Authorization: Bearer <token>` header against the configured admin token.
+///
+/// The admin API is disabled (404) when no token is configured.
+fn authorize(expected: Option<&str>, headers: &HeaderMap) -> Result<(), Error> {
+    let expected = expected.ok_or(Error::NotFound)?;
+
+    let provided = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or(Error::Unauthorized)?;
+
+    if bool::from(provided.trim().as_bytes().ct_eq(expected.as_bytes())) {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_admin_token_required() {
+        assert!(authorize(Some("s3cret"), &bearer("s3cret")).is_ok());
+        assert!(matches!(
+            authorize(Some("s3cret"), &bearer("wrong")),
+            Err(Error::Unauthorized)
+        ));
+        assert!(matches!(
+            authorize(Some("s3cret"), &HeaderMap::new()),
+            Err(Error::Unauthorized)
+        ));
+        // No token configured: admin API is disabled entirely
+        assert!(matches!(
+            authorize(None, &bearer("s3cret")),
+            Err(Error::NotFound)
+        ));
+    }
+}
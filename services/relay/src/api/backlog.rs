@@ -1,16 +1,45 @@
-use axum::{extract::State, response::Json};
-use serde::Serialize;
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-use crate::{db::repository::JobRepository, AppState};
+use crate::{
+    claim_manager::compute_batch_hash,
+    db::{models::Job, repository::JobRepository},
+    AppState,
+};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BacklogQuery {
+    /// Include per-job batch hashes and ages
+    #[serde(default)]
+    pub include_hashes: bool,
+    /// Only report jobs that have been waiting at least this long
+    pub older_than_secs: Option<u64>,
+}
 
 #[derive(Debug, Serialize)]
 pub struct BacklogStatus {
     pub pending_count: usize,
     pub queued_jobs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<Vec<BacklogJob>>,
+}
+
+/// Per-job detail for miners doing targeted mining
+#[derive(Debug, Serialize)]
+pub struct BacklogJob {
+    pub job_id: String,
+    /// Hex-encoded batch hash the relay will look up claims for
+    pub batch_hash: String,
+    pub age_secs: u64,
 }
 
 pub async fn get_backlog_status(
     State(state): State<AppState>,
+    Query(query): Query<BacklogQuery>,
 ) -> Result<Json<BacklogStatus>, String> {
     // Get queued jobs
     let jobs = state
@@ -19,11 +48,105 @@ pub async fn get_backlog_status(
         .await
         .map_err(|e| format!("Failed to get backlog: {}", e))?;
 
+    Ok(Json(build_backlog_status(&jobs, &query, Utc::now())))
+}
+
+fn build_backlog_status(jobs: &[Job], query: &BacklogQuery, now: DateTime<Utc>) -> BacklogStatus {
+    let age_secs = |job: &Job| (now - job.created_at).num_seconds().max(0) as u64;
+
+    let jobs: Vec<&Job> = jobs
+        .iter()
+        .filter(|j| query.older_than_secs.is_none_or(|min| age_secs(j) >= min))
+        .collect();
+
     // Extract job IDs
     let job_ids: Vec<String> = jobs.iter().map(|j| j.id.to_string()).collect();
 
-    Ok(Json(BacklogStatus {
+    // Batch hashes are derived from the request ID, same as the relay's claim lookup
+    let details = query.include_hashes.then(|| {
+        jobs.iter()
+            .map(|j| BacklogJob {
+                job_id: j.id.to_string(),
+                batch_hash: hex::encode(compute_batch_hash(&j.request_id.to_string())),
+                age_secs: age_secs(j),
+            })
+            .collect()
+    });
+
+    BacklogStatus {
         pending_count: jobs.len(),
         queued_jobs: job_ids,
-    }))
+        jobs: details,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::db::models::JobStatus;
+
+    fn queued_job(created_at: DateTime<Utc>) -> Job {
+        Job {
+            id: Uuid::new_v4(),
+            request_id: Uuid::new_v4(),
+            status: JobStatus::Queued,
+            proof_bytes: vec![],
+            public_inputs: vec![0u8; 104],
+            outputs_json: serde_json::json!([]),
+            fee_bps: 0,
+            root_hash: vec![0u8; 32],
+            nullifier: vec![0u8; 32],
+            amount: 0,
+            outputs_hash: vec![0u8; 32],
+            priority: 0,
+            tx_id: None,
+            solana_signature: None,
+            error_message: None,
+            retry_count: 0,
+            max_retries: 3,
+            created_at,
+            updated_at: created_at,
+            started_at: None,
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_default_backlog_shape_unchanged() {
+        let now = Utc::now();
+        let jobs = vec![queued_job(now), queued_job(now)];
+
+        let status = build_backlog_status(&jobs, &BacklogQuery::default(), now);
+        let value = serde_json::to_value(&status).unwrap();
+
+        assert_eq!(value["pending_count"], 2);
+        assert!(value.get("jobs").is_none());
+        assert_eq!(value.as_object().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_backlog_enriched_with_hashes_and_age_filter() {
+        let now = Utc::now();
+        let old = queued_job(now - chrono::Duration::seconds(120));
+        let fresh = queued_job(now - chrono::Duration::seconds(5));
+        let jobs = vec![old.clone(), fresh];
+
+        let query = BacklogQuery {
+            include_hashes: true,
+            older_than_secs: Some(60),
+        };
+        let status = build_backlog_status(&jobs, &query, now);
+
+        assert_eq!(status.pending_count, 1);
+        assert_eq!(status.queued_jobs, vec![old.id.to_string()]);
+        let details = status.jobs.expect("details requested");
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].age_secs, 120);
+        assert_eq!(
+            details[0].batch_hash,
+            hex::encode(compute_batch_hash(&old.request_id.to_string()))
+        );
+    }
 }
@@ -1,4 +1,6 @@
+pub mod admin;
 pub mod backlog;
+pub mod params;
 pub mod status;
 pub mod validator_agent;
 pub mod withdraw;
@@ -6,6 +8,11 @@ pub mod withdraw;
 use serde::Serialize;
 use uuid::Uuid;
 
+use crate::{
+    db::models::{JobEvent, SwapReceipt},
+    error::Error,
+};
+
 #[derive(Debug, Serialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
@@ -46,6 +53,46 @@ pub struct StatusResponse {
     pub error: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Status transitions, only present when requested with `?history=true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<Vec<JobEvent>>,
+    /// Transactions confirmed so far for a swap withdraw, and its output once complete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swap: Option<SwapReceiptResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SwapReceiptResponse {
+    /// Hex-encoded nullifier the swap spent
+    pub nullifier: String,
+    pub output_mint: String,
+    pub withdraw_swap_signature: Option<String>,
+    pub prepare_swap_signature: Option<String>,
+    pub sync_native_signature: Option<String>,
+    pub execute_swap_signature: Option<String>,
+    /// Output tokens the recipient received, when the relay could measure it
+    pub output_amount: Option<i64>,
+}
+
+impl From<SwapReceipt> for SwapReceiptResponse {
+    fn from(receipt: SwapReceipt) -> Self {
+        Self {
+            nullifier: hex::encode(receipt.nullifier),
+            output_mint: receipt.output_mint,
+            withdraw_swap_signature: receipt.withdraw_swap_signature,
+            prepare_swap_signature: receipt.prepare_swap_signature,
+            sync_native_signature: receipt.sync_native_signature,
+            execute_swap_signature: receipt.execute_swap_signature,
+            output_amount: receipt.output_amount,
+        }
+    }
+}
+
+/// Parse a 32-byte hex request field (bare or `0x`-prefixed), naming the
+/// field in the error
+pub fn parse_hex32_field(field: &str, value: &str) -> Result<[u8; 32], Error> {
+    cloak_layouts::parse_hex32(value)
+        .map_err(|e| Error::ValidationError(format!("Invalid {}: {}", field, e)))
 }
 
 // Health check endpoint
@@ -0,0 +1,105 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::{Query, State},
+    response::Json,
+};
+use cloak_layouts::{FeeSchedule, MERKLE_TREE_DEPTH};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{error::Error, AppState};
+
+/// Fees and limits withdraws are currently validated against, so clients
+/// don't have to hardcode them
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ProtocolParams {
+    /// Flat part of the withdraw fee, in the pool mint's base units
+    pub fixed_fee: u64,
+    /// Variable part of the withdraw fee, in basis points of the amount
+    pub variable_fee_bps: u64,
+    /// Smallest total withdraw amount, `null` when unbounded
+    pub min_amount: Option<u64>,
+    /// Largest total withdraw amount, `null` when unbounded
+    pub max_amount: Option<u64>,
+    /// Smallest amount any single output may carry
+    pub min_output_amount: u64,
+    pub max_outputs: usize,
+    /// Depth of the commitment tree Merkle paths must cover
+    pub tree_depth: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ParamsQuery {
+    /// Report the fees for this pool mint (base58) instead of the relay's
+    /// configured pool
+    pub mint: Option<String>,
+}
+
+pub async fn get_params(
+    State(state): State<AppState>,
+    Query(query): Query<ParamsQuery>,
+) -> Result<Json<ProtocolParams>, Error> {
+    let fee_schedule = match query.mint.as_deref() {
+        Some(mint) => {
+            let mint = Pubkey::from_str(mint)
+                .map_err(|e| Error::ValidationError(format!("Invalid mint address: {}", e)))?;
+            state.solana.fee_schedule_for(&mint)
+        }
+        None => state.solana.fee_schedule()?,
+    };
+    Ok(Json(build_params(
+        fee_schedule,
+        state.solana.withdraw_amount_bounds(),
+        state.solana.min_output_amount(),
+        state.solana.max_outputs(),
+    )))
+}
+
+fn build_params(
+    fee_schedule: FeeSchedule,
+    (min_amount, max_amount): (Option<u64>, Option<u64>),
+    min_output_amount: u64,
+    max_outputs: usize,
+) -> ProtocolParams {
+    ProtocolParams {
+        fixed_fee: fee_schedule.fixed,
+        variable_fee_bps: fee_schedule.variable_per_mille * 10,
+        min_amount,
+        max_amount,
+        min_output_amount,
+        max_outputs,
+        tree_depth: MERKLE_TREE_DEPTH,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_reflect_configured_values() {
+        // A pool with a 0.001 SOL flat fee and 0.3% variable fee
+        let schedule = FeeSchedule {
+            fixed: 1_000_000,
+            variable_per_mille: 3,
+        };
+        let params = build_params(schedule, (Some(10_000_000), None), 5_000, 4);
+        assert_eq!(
+            params,
+            ProtocolParams {
+                fixed_fee: 1_000_000,
+                variable_fee_bps: 30,
+                min_amount: Some(10_000_000),
+                max_amount: None,
+                min_output_amount: 5_000,
+                max_outputs: 4,
+                tree_depth: 31,
+            }
+        );
+
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(json["variable_fee_bps"], 30);
+        assert!(json["max_amount"].is_null());
+    }
+}
@@ -1,21 +1,30 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
     Json,
 };
+use serde::Deserialize;
 use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::{
     api::{ApiResponse, StatusResponse},
-    db::repository::JobRepository,
+    db::repository::{JobRepository, SwapReceiptRepository},
     error::Error,
     AppState,
 };
 
+#[derive(Debug, Default, Deserialize)]
+pub struct StatusQuery {
+    /// Include the job's status transition timeline
+    #[serde(default)]
+    pub history: bool,
+}
+
 pub async fn get_status(
     State(state): State<AppState>,
     Path(request_id): Path<Uuid>,
+    Query(query): Query<StatusQuery>,
 ) -> Result<impl IntoResponse, Error> {
     info!("Status endpoint called for request: {}", request_id);
 
@@ -24,6 +33,20 @@ pub async fn get_status(
     match state.job_repo.get_job_by_request_id(request_id).await {
         Ok(Some(job)) => {
             info!("Job found in database: {:?}", job.status);
+            let history = if query.history {
+                Some(state.job_repo.get_job_events(job.id).await?)
+            } else {
+                None
+            };
+            let swap = if job.outputs_json.get("swap").is_some() {
+                state
+                    .swap_receipt_repo
+                    .get_swap_receipt(job.id)
+                    .await?
+                    .map(Into::into)
+            } else {
+                None
+            };
             let response = StatusResponse {
                 request_id,
                 status: job.status.to_string(),
@@ -31,6 +54,8 @@ pub async fn get_status(
                 error: job.error_message.clone(),
                 created_at: job.created_at,
                 completed_at: job.completed_at,
+                history,
+                swap,
             };
             Ok(Json(ApiResponse::success(response)))
         }
@@ -1,16 +1,23 @@
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use base64::Engine;
-use blake3::Hasher;
 use serde::{Deserialize, Serialize};
+use shield_pool::instructions::ShieldPoolInstruction;
 use solana_client::{rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
 use solana_sdk::{
-    commitment_config::CommitmentLevel, signature::Signature, transaction::VersionedTransaction,
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::VersionedTransaction,
 };
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::{
+    api::parse_hex32_field,
     db::repository::{JobRepository, NullifierRepository},
     error::Error,
+    planner::{calculate_scheduled_fee, compute_outputs_single},
+    solana::transaction_builder::PUBLIC_INPUTS_LEN,
     AppState,
 };
 
@@ -63,8 +70,42 @@ pub struct JobArtifacts {
     pub tx_bytes_base64: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct JobTransactionResponse {
+    pub job_id: Uuid,
+    /// Unsigned withdraw (bincode `VersionedTransaction`, base64) for the
+    /// agent to sign as the external fee payer
+    pub tx_bytes_base64: String,
+    /// Block height after which the transaction's blockhash has expired;
+    /// fetch it again past this point for a rebuilt one
+    pub last_valid_block_height: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobAccountsResponse {
+    pub job_id: Uuid,
+    pub program_id: String,
+    pub accounts: Vec<AccountMetaResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClaimsRequiredResponse {
+    pub job_id: Uuid,
+    pub claims_required: usize,
+    /// When false the relay submits without claims and `claims_required` is informational
+    pub pow_enabled: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountMetaResponse {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SubmitRequest {
+    pub job_id: Uuid,
     pub tx_bytes_base64: String,
 }
 
@@ -72,26 +113,8 @@ pub struct SubmitRequest {
 pub struct SubmitResponse {
     pub signature: String,
     pub slot: Option<u64>,
-}
-
-#[inline]
-fn parse_hex32(s: &str) -> Result<[u8; 32], Error> {
-    if s.len() != 64 {
-        return Err(Error::BadRequest(
-            "address_hex32 must be 64 hex chars".into(),
-        ));
-    }
-    let bytes = hex::decode(s).map_err(|e| Error::BadRequest(format!("invalid hex: {}", e)))?;
-    let mut out = [0u8; 32];
-    out.copy_from_slice(&bytes);
-    Ok(out)
-}
-
-#[inline]
-fn calculate_fee(amount: u64) -> u64 {
-    let fixed = 2_500_000u64;
-    let variable = (amount.saturating_mul(5)) / 1_000; // 0.5%
-    fixed.saturating_add(variable)
+    /// Whether the transaction reached `confirmed` before the relay stopped waiting
+    pub confirmed: bool,
 }
 
 pub async fn create_withdraw_job(
@@ -99,17 +122,19 @@ pub async fn create_withdraw_job(
     Json(req): Json<WithdrawJobRequest>,
 ) -> Result<impl IntoResponse, Error> {
     // Validate public inputs length and decode
-    if req.public_bin_hex.len() != 208 {
-        return Err(Error::ValidationError(
-            "public_bin_hex must be 208 hex chars".into(),
-        ));
+    if req.public_bin_hex.len() != 2 * PUBLIC_INPUTS_LEN {
+        return Err(Error::ValidationError(format!(
+            "public_bin_hex must be {} hex chars",
+            2 * PUBLIC_INPUTS_LEN
+        )));
     }
     let public = hex::decode(&req.public_bin_hex)
         .map_err(|e| Error::ValidationError(format!("invalid public_bin_hex: {}", e)))?;
-    if public.len() != 104 {
-        return Err(Error::ValidationError(
-            "decoded public inputs must be 104 bytes".into(),
-        ));
+    if public.len() != PUBLIC_INPUTS_LEN {
+        return Err(Error::ValidationError(format!(
+            "decoded public inputs must be {} bytes",
+            PUBLIC_INPUTS_LEN
+        )));
     }
 
     // Parse public inputs
@@ -128,18 +153,14 @@ pub async fn create_withdraw_job(
         return Err(Error::ValidationError("MVP only supports 1 output".into()));
     }
     let out0 = &req.outputs[0];
-    let addr32 = parse_hex32(&out0.address_hex32)?;
+    let addr32 = parse_hex32_field("address_hex32", &out0.address_hex32)?;
     let recipient_amount: u64 = out0
         .amount_u64
         .parse()
         .map_err(|_| Error::ValidationError("amount_u64 must be a decimal u64".into()))?;
 
-    // Compute outputs_hash = BLAKE3(address:32 || amount:u64_le)
-    let mut hasher = Hasher::new();
-    hasher.update(&addr32);
-    hasher.update(&recipient_amount.to_le_bytes());
-    let out_hash = hasher.finalize();
-    let out_hash_bytes = out_hash.as_bytes();
+    // Compute outputs_hash exactly as the guest does
+    let (_, out_hash_bytes) = compute_outputs_single(addr32, recipient_amount);
 
     if out_hash_bytes != outputs_hash_pub {
         return Err(Error::ValidationError(
@@ -148,7 +169,7 @@ pub async fn create_withdraw_job(
     }
 
     // Conservation: sum(outputs) + fee == amount
-    let fee = calculate_fee(amount);
+    let fee = calculate_scheduled_fee(amount, state.solana.fee_schedule()?)?;
     let outputs_sum = recipient_amount;
     if outputs_sum + fee != amount {
         return Err(Error::ValidationError(
@@ -168,7 +189,7 @@ pub async fn create_withdraw_job(
 
     let job = state
         .job_repo
-        .create_job(crate::db::models::CreateJob {
+        .create_job_with_nullifier(crate::db::models::CreateJob {
             request_id,
             proof_bytes: Vec::new(),       // proof to be generated
             public_inputs: public.clone(), // 104 bytes
@@ -178,15 +199,10 @@ pub async fn create_withdraw_job(
             nullifier: nf.to_vec(),
             amount: amount as i64,
             outputs_hash: outputs_hash_pub.to_vec(),
+            priority: 0,
         })
         .await?;
 
-    // Record nullifier
-    state
-        .nullifier_repo
-        .create_nullifier(nf.to_vec(), job.id)
-        .await?;
-
     let resp = JobResponse {
         job_id: job.id,
         status: "queued".to_string(),
@@ -214,12 +230,17 @@ pub async fn get_job(
         public_bin_hex_104: None,
         tx_bytes_base64: None,
     };
-    if job.public_inputs.len() == 104 {
+    if job.public_inputs.len() == PUBLIC_INPUTS_LEN {
         artifacts.public_bin_hex_104 = Some(hex::encode(&job.public_inputs));
     }
     if !job.proof_bytes.is_empty() {
         artifacts.proof_hex = Some(hex::encode(&job.proof_bytes));
     }
+    artifacts.tx_bytes_base64 = state
+        .job_repo
+        .get_pending_transaction(job.id)
+        .await?
+        .map(|pending| base64::engine::general_purpose::STANDARD.encode(pending.tx_bytes));
 
     let resp = JobStatusResponse {
         job_id: job.id,
@@ -232,7 +253,161 @@ pub async fn get_job(
     Ok((StatusCode::OK, Json(value)))
 }
 
-pub async fn submit_tx(Json(req): Json<SubmitRequest>) -> Result<impl IntoResponse, Error> {
+/// Withdraw transaction the relay built for this job in external submission
+/// mode, for an agent to sign and post back to /submit
+pub async fn get_job_transaction(
+    State(state): State<AppState>,
+    axum::extract::Path(job_id): axum::extract::Path<Uuid>,
+) -> Result<impl IntoResponse, Error> {
+    let job = state
+        .job_repo
+        .get_job_by_id(job_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let mut pending = state
+        .job_repo
+        .get_pending_transaction(job_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    // A transaction past its blockhash can never land; hand out a fresh one
+    if state.solana.pending_transaction_expired(&pending).await? {
+        pending = state.solana.build_external_transaction(&job).await?;
+        state
+            .job_repo
+            .set_pending_transaction(job_id, pending.clone())
+            .await?;
+    }
+
+    let resp = JobTransactionResponse {
+        job_id,
+        tx_bytes_base64: base64::engine::general_purpose::STANDARD.encode(&pending.tx_bytes),
+        last_valid_block_height: pending.last_valid_block_height,
+    };
+    let value =
+        serde_json::to_value(resp).map_err(|e| Error::InternalServerError(e.to_string()))?;
+    Ok((StatusCode::OK, Json(value)))
+}
+
+/// List the account metas (in instruction order) the withdraw for this job needs,
+/// so an external validator agent can build the transaction itself
+pub async fn get_job_accounts(
+    State(state): State<AppState>,
+    axum::extract::Path(job_id): axum::extract::Path<Uuid>,
+) -> Result<impl IntoResponse, Error> {
+    let Some(job) = state.job_repo.get_job_by_id(job_id).await? else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "code": "not_found",
+                "message": "Job not found"
+            })),
+        ));
+    };
+
+    let accounts = state
+        .solana
+        .withdraw_account_metas(&job)
+        .await?
+        .into_iter()
+        .map(|meta| AccountMetaResponse {
+            pubkey: meta.pubkey.to_string(),
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+        })
+        .collect();
+
+    let resp = JobAccountsResponse {
+        job_id: job.id,
+        program_id: state.solana.program_id().to_string(),
+        accounts,
+    };
+    let value =
+        serde_json::to_value(resp).map_err(|e| Error::InternalServerError(e.to_string()))?;
+    Ok((StatusCode::OK, Json(value)))
+}
+
+/// Number of PoW claims the withdraw for this job will consume, so miners can
+/// size their mining to actual demand
+pub async fn get_job_claims_required(
+    State(state): State<AppState>,
+    axum::extract::Path(job_id): axum::extract::Path<Uuid>,
+) -> Result<impl IntoResponse, Error> {
+    let Some(job) = state.job_repo.get_job_by_id(job_id).await? else {
+        return Ok((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "code": "not_found",
+                "message": "Job not found"
+            })),
+        ));
+    };
+
+    let resp = ClaimsRequiredResponse {
+        job_id: job.id,
+        claims_required: state.solana.claims_required(&job)?,
+        pow_enabled: state.solana.pow_enabled(),
+    };
+    let value =
+        serde_json::to_value(resp).map_err(|e| Error::InternalServerError(e.to_string()))?;
+    Ok((StatusCode::OK, Json(value)))
+}
+
+/// Ensure `vtx` carries a shield-pool withdraw for this job: the instruction body
+/// must embed the job's public inputs followed by its nullifier, preceded by the
+/// job's proof bytes when the relay has them.
+fn verify_tx_matches_job(
+    vtx: &VersionedTransaction,
+    program_id: &Pubkey,
+    proof_bytes: &[u8],
+    public_inputs: &[u8],
+    nullifier: &[u8],
+) -> Result<(), Error> {
+    if public_inputs.len() != PUBLIC_INPUTS_LEN || nullifier.len() != 32 {
+        return Err(Error::InternalServerError(
+            "job is missing public inputs or nullifier".into(),
+        ));
+    }
+
+    let keys = vtx.message.static_account_keys();
+    let withdraw_ix = vtx
+        .message
+        .instructions()
+        .iter()
+        .find(|ix| {
+            keys.get(ix.program_id_index as usize) == Some(program_id)
+                && ix.data.first() == Some(&(ShieldPoolInstruction::Withdraw as u8))
+        })
+        .ok_or_else(|| {
+            Error::BadRequest("transaction has no shield-pool withdraw instruction".into())
+        })?;
+    let body = &withdraw_ix.data[1..];
+
+    // Layout: [proof][public][nf-dup:32][outputs...]
+    let mut expected = Vec::with_capacity(public_inputs.len() + nullifier.len());
+    expected.extend_from_slice(public_inputs);
+    expected.extend_from_slice(nullifier);
+    let matches = if proof_bytes.is_empty() {
+        body.windows(expected.len())
+            .any(|w| w == expected.as_slice())
+    } else {
+        let proof_len = proof_bytes.len();
+        body.get(..proof_len) == Some(proof_bytes)
+            && body.get(proof_len..proof_len + expected.len()) == Some(expected.as_slice())
+    };
+
+    if !matches {
+        return Err(Error::BadRequest(
+            "submitted transaction does not match the job's proof and nullifier".into(),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn submit_tx(
+    State(state): State<AppState>,
+    Json(req): Json<SubmitRequest>,
+) -> Result<impl IntoResponse, Error> {
     // 1) Decode base64 → bytes
     let raw = base64::engine::general_purpose::STANDARD
         .decode(&req.tx_bytes_base64)
@@ -242,6 +417,20 @@ pub async fn submit_tx(Json(req): Json<SubmitRequest>) -> Result<impl IntoRespon
     let vtx: VersionedTransaction = bincode::deserialize(&raw)
         .map_err(|e| Error::ValidationError(format!("invalid transaction payload: {}", e)))?;
 
+    // 2b) Refuse to broadcast anything that isn't this job's withdraw
+    let job = state
+        .job_repo
+        .get_job_by_id(req.job_id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    verify_tx_matches_job(
+        &vtx,
+        &state.solana.program_id(),
+        &job.proof_bytes,
+        &job.public_inputs,
+        &job.nullifier,
+    )?;
+
     // 3) Choose RPC URL from env
     let rpc_url = std::env::var("RELAY_SOLANA__RPC_URL")
         .or_else(|_| std::env::var("SOLANA_RPC_URL"))
@@ -279,14 +468,22 @@ pub async fn submit_tx(Json(req): Json<SubmitRequest>) -> Result<impl IntoRespon
         out_sig.expect("signature set or returned error")
     };
 
-    // 5) Confirm (best-effort)
+    // 5) Confirm: poll statuses briefly
     let rpc = RpcClient::new(rpc_url.clone());
-    // best-effort confirm: poll statuses briefly
+    let mut confirmed = false;
     let start = std::time::Instant::now();
     while start.elapsed() < std::time::Duration::from_secs(20) {
         if let Ok(sts) = rpc.get_signature_statuses(&[sig]) {
             if let Some(Some(st)) = sts.value.first() {
-                if st.err.is_none() {
+                if st.err.is_some() {
+                    warn!(
+                        "Transaction {} for job {} failed: {:?}",
+                        sig, job.id, st.err
+                    );
+                    break;
+                }
+                if st.satisfies_commitment(CommitmentConfig::confirmed()) {
+                    confirmed = true;
                     break;
                 }
             }
@@ -302,9 +499,127 @@ pub async fn submit_tx(Json(req): Json<SubmitRequest>) -> Result<impl IntoRespon
         }
     }
 
+    // 7) A withdraw the relay built for this agent is done once it confirms;
+    // otherwise the job stays processing and the agent can fetch it again
+    let pending = state.job_repo.get_pending_transaction(job.id).await?;
+    if pending.is_some() && confirmed {
+        let signature = sig.to_string();
+        state
+            .job_repo
+            .update_job_completed(job.id, signature.clone(), signature)
+            .await?;
+        if let Err(e) = state
+            .nullifier_repo
+            .insert_nullifier(job.nullifier.clone(), job.id)
+            .await
+        {
+            warn!("Failed to store nullifier for job {}: {}", job.id, e);
+        }
+    } else if pending.is_some() {
+        warn!(
+            "Transaction {} for job {} not confirmed, leaving the job processing",
+            sig, job.id
+        );
+    }
+
     let resp = SubmitResponse {
         signature: sig.to_string(),
         slot,
+        confirmed,
     };
     Ok((StatusCode::OK, Json(serde_json::to_value(resp).unwrap())))
 }
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::hash::Hash;
+
+    use super::*;
+    use crate::{planner::Output, solana::transaction_builder};
+
+    fn withdraw_vtx(
+        program_id: Pubkey,
+        proof: &[u8],
+        public: [u8; PUBLIC_INPUTS_LEN],
+    ) -> VersionedTransaction {
+        let recipient = Pubkey::new_unique();
+        let outputs = vec![Output {
+            address: recipient.to_bytes(),
+            amount: 1_000_000,
+        }];
+        let (pool, treasury, roots_ring, nullifier_shard) =
+            transaction_builder::derive_shield_pool_pdas(&program_id, &Pubkey::default());
+        let tx = transaction_builder::build_withdraw_transaction(
+            proof.to_vec(),
+            public,
+            &outputs,
+            program_id,
+            pool,
+            roots_ring,
+            nullifier_shard,
+            treasury,
+            &[recipient],
+            Pubkey::new_unique(),
+            Hash::new_unique(),
+            1_000,
+            None,
+            None,
+            None,
+            None,
+            false,
+        )
+        .expect("tx");
+        VersionedTransaction::from(tx)
+    }
+
+    fn job_public() -> [u8; PUBLIC_INPUTS_LEN] {
+        let mut public = [0u8; PUBLIC_INPUTS_LEN];
+        public[0..32].copy_from_slice(&[0x11; 32]);
+        public[32..64].copy_from_slice(&[0x22; 32]);
+        public[64..96].copy_from_slice(&[0x33; 32]);
+        public[96..104].copy_from_slice(&10_000_000u64.to_le_bytes());
+        public
+    }
+
+    #[test]
+    fn test_submit_accepts_matching_transaction() {
+        let program_id = Pubkey::new_unique();
+        let proof = vec![0xAB; 260];
+        let public = job_public();
+        let vtx = withdraw_vtx(program_id, &proof, public);
+
+        assert!(verify_tx_matches_job(&vtx, &program_id, &proof, &public, &public[32..64]).is_ok());
+        // Jobs created via /jobs/withdraw have no proof stored yet
+        assert!(verify_tx_matches_job(&vtx, &program_id, &[], &public, &public[32..64]).is_ok());
+    }
+
+    #[test]
+    fn test_submit_rejects_tampered_transaction() {
+        let program_id = Pubkey::new_unique();
+        let proof = vec![0xAB; 260];
+        let public = job_public();
+
+        // Different proof bytes
+        let vtx = withdraw_vtx(program_id, &[0xCD; 260], public);
+        assert!(matches!(
+            verify_tx_matches_job(&vtx, &program_id, &proof, &public, &public[32..64]),
+            Err(Error::BadRequest(_))
+        ));
+
+        // Different nullifier in the public inputs
+        let mut other_public = public;
+        other_public[32..64].copy_from_slice(&[0x99; 32]);
+        let vtx = withdraw_vtx(program_id, &proof, other_public);
+        assert!(matches!(
+            verify_tx_matches_job(&vtx, &program_id, &proof, &public, &public[32..64]),
+            Err(Error::BadRequest(_))
+        ));
+
+        // Withdraw aimed at another program
+        let vtx = withdraw_vtx(Pubkey::new_unique(), &proof, public);
+        assert!(matches!(
+            verify_tx_matches_job(&vtx, &program_id, &proof, &public, &public[32..64]),
+            Err(Error::BadRequest(_))
+        ));
+    }
+}
@@ -2,6 +2,7 @@ use std::str::FromStr;
 
 use axum::{extract::State, response::IntoResponse, Json};
 use base64::Engine;
+use cloak_layouts::FeeSchedule;
 use cloak_proof_extract::extract_groth16_260_sp1;
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
@@ -9,11 +10,13 @@ use tracing::info;
 use uuid::Uuid;
 
 use crate::{
-    api::{ApiResponse, WithdrawResponse},
+    api::{parse_hex32_field, ApiResponse, WithdrawResponse},
     db::{models::CreateJob, repository::JobRepository},
     error::Error,
-    planner::calculate_fee,
+    planner::{self, calculate_fee, calculate_protocol_fee, compute_outputs_hash},
+    solana::{check_output_amount, check_output_count, check_withdraw_amount},
     swap::SwapConfig,
+    worker::priority::MAX_JOB_PRIORITY,
     AppState,
 };
 
@@ -25,6 +28,9 @@ pub struct WithdrawRequest {
     pub proof_bytes: String, // base64 encoded
     #[serde(skip_serializing_if = "Option::is_none")]
     pub swap: Option<SwapConfig>, // optional swap configuration
+    /// 0 (default) to `MAX_JOB_PRIORITY`; higher-priority withdraws are processed sooner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i16>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -45,6 +51,9 @@ pub struct PublicInputs {
     pub amount: u64,
     pub fee_bps: u16,
     pub outputs_hash: String,
+    /// Slot the proof was generated at, committed by the guest
+    #[cfg(feature = "proof-freshness")]
+    pub generated_at: u64,
 }
 
 pub async fn handle_withdraw(
@@ -63,7 +72,14 @@ pub async fn handle_withdraw(
     };
 
     // Validate the request
-    validate_request(&payload, decimals)?;
+    validate_request(
+        &payload,
+        state.solana.max_outputs(),
+        state.solana.min_output_amount(),
+        state.solana.fee_schedule()?,
+    )?;
+    let (min_amount, max_amount) = state.solana.withdraw_amount_bounds();
+    check_withdraw_amount(payload.public_inputs.amount, min_amount, max_amount)?;
 
     // Validate swap config if present
     if let Some(ref swap_config) = payload.swap {
@@ -102,99 +118,31 @@ pub async fn handle_withdraw(
             ))
         }
     };
+    state.solana.check_proof_vkey(&proof_bytes)?;
 
     // Parse public inputs
-    // Strip "0x" prefix if present
-    let root_str = payload
-        .public_inputs
-        .root
-        .strip_prefix("0x")
-        .unwrap_or(&payload.public_inputs.root);
-    let nf_str = payload
-        .public_inputs
-        .nf
-        .strip_prefix("0x")
-        .unwrap_or(&payload.public_inputs.nf);
-    let outputs_hash_str = payload
-        .public_inputs
-        .outputs_hash
-        .strip_prefix("0x")
-        .unwrap_or(&payload.public_inputs.outputs_hash);
-
-    // Validate hex strings are not empty
-    if root_str.is_empty() {
-        return Err(Error::ValidationError("Root cannot be empty".to_string()));
-    }
-    if nf_str.is_empty() {
-        return Err(Error::ValidationError(
-            "Nullifier cannot be empty".to_string(),
-        ));
-    }
-    if outputs_hash_str.is_empty() {
-        return Err(Error::ValidationError(
-            "Outputs hash cannot be empty".to_string(),
-        ));
-    }
-
-    // Validate hex string lengths before decoding
-    if root_str.len() != 64 {
-        return Err(Error::ValidationError(format!(
-            "Root must be 64 hex characters, got {}",
-            root_str.len()
-        )));
-    }
-    if nf_str.len() != 64 {
-        return Err(Error::ValidationError(format!(
-            "Nullifier must be 64 hex characters, got {}",
-            nf_str.len()
-        )));
-    }
-    if outputs_hash_str.len() != 64 {
-        return Err(Error::ValidationError(format!(
-            "Outputs hash must be 64 hex characters, got {}",
-            outputs_hash_str.len()
-        )));
-    }
+    let root = parse_hex32_field("root", &payload.public_inputs.root)?;
+    let nullifier = parse_hex32_field("nullifier", &payload.public_inputs.nf)?.to_vec();
+    let outputs_hash =
+        parse_hex32_field("outputs hash", &payload.public_inputs.outputs_hash)?.to_vec();
+    let root_hash = root.to_vec();
 
-    let root_hash = hex::decode(root_str)
-        .map_err(|e| Error::ValidationError(format!("Invalid root hex: {}", e)))?;
-    let nullifier = hex::decode(nf_str)
-        .map_err(|e| Error::ValidationError(format!("Invalid nullifier hex: {}", e)))?;
-    let outputs_hash = hex::decode(outputs_hash_str)
-        .map_err(|e| Error::ValidationError(format!("Invalid outputs hash hex: {}", e)))?;
-
-    // Validate decoded lengths (should always be 32 after validation above, but double-check)
-    if root_hash.len() != 32 {
-        return Err(Error::ValidationError(format!(
-            "Root must be 32 bytes, got {}",
-            root_hash.len()
-        )));
-    }
-    if nullifier.len() != 32 {
-        return Err(Error::ValidationError(format!(
-            "Nullifier must be 32 bytes, got {}",
-            nullifier.len()
-        )));
-    }
-    if outputs_hash.len() != 32 {
-        return Err(Error::ValidationError(format!(
-            "Outputs hash must be 32 bytes, got {}",
-            outputs_hash.len()
-        )));
-    }
-
-    // Note: We no longer check for nullifier double-spend at queueing time.
-    // The on-chain program will enforce uniqueness when the transaction executes.
-    // The worker stores the nullifier AFTER successful on-chain execution
-    // (see services/relay/src/worker/processor.rs:113-120)
+    // Fail fast on a root the program would no longer accept
+    state.solana.check_root_fresh(&root).await?;
 
     // Encode public inputs for storage (canonical 104-byte format)
     // Format: root(32) || nf(32) || outputs_hash(32) || amount(8) = 104 bytes
+    // With proof-freshness: || generated_at(8) = 112 bytes
     let mut public_inputs_bytes = Vec::new();
     public_inputs_bytes.extend_from_slice(&root_hash);
     public_inputs_bytes.extend_from_slice(&nullifier);
     public_inputs_bytes.extend_from_slice(&outputs_hash);
     public_inputs_bytes.extend_from_slice(&payload.public_inputs.amount.to_le_bytes());
+    #[cfg(feature = "proof-freshness")]
+    public_inputs_bytes.extend_from_slice(&payload.public_inputs.generated_at.to_le_bytes());
+
+    // Fail fast on a proof the program would reject as too old
+    state.solana.check_proof_fresh(&public_inputs_bytes).await?;
 
     // Create job in database
     let effective_fee_bps = if payload.public_inputs.amount == 0 {
@@ -222,16 +170,16 @@ pub async fn handle_withdraw(
         outputs_json: metadata,
         fee_bps: effective_fee_bps as i16,
         root_hash,
-        nullifier: nullifier.clone(),
+        nullifier,
         amount: payload.public_inputs.amount as i64,
         outputs_hash,
+        priority: payload.priority.unwrap_or(0),
     };
 
-    state.job_repo.create_job(create_job).await?;
-
-    // Note: Nullifier is NOT created here!
-    // It will be created by the worker AFTER the transaction succeeds on-chain
-    // (see services/relay/src/worker/processor.rs:113-120)
+    // Reserve the nullifier with the job so a concurrent withdraw spending the same
+    // note is rejected before either reaches the chain (the on-chain check only sees
+    // confirmed spends)
+    state.job_repo.create_job_with_nullifier(create_job).await?;
 
     info!(
         request_id = %request_id,
@@ -248,29 +196,18 @@ pub async fn handle_withdraw(
     Ok(Json(ApiResponse::success(response)))
 }
 
-fn validate_request(request: &WithdrawRequest, decimals: u8) -> Result<(), Error> {
+fn validate_request(
+    request: &WithdrawRequest,
+    max_outputs: usize,
+    min_output_amount: u64,
+    fee_schedule: FeeSchedule,
+) -> Result<(), Error> {
     // Validate outputs
-    if request.outputs.is_empty() {
-        return Err(Error::ValidationError(
-            "At least one output is required".to_string(),
-        ));
-    }
-
-    if request.outputs.len() > 10 {
-        return Err(Error::ValidationError(format!(
-            "Too many outputs: {} (max 10)",
-            request.outputs.len()
-        )));
-    }
+    check_output_count(request.outputs.len(), max_outputs)?;
 
     // Validate amounts and recipient addresses
     for (i, output) in request.outputs.iter().enumerate() {
-        if output.amount == 0 {
-            return Err(Error::ValidationError(format!(
-                "Output {} amount cannot be zero",
-                i
-            )));
-        }
+        check_output_amount(i, output.amount, min_output_amount)?;
 
         // Validate recipient address using Solana Pubkey validation
         if output.recipient.is_empty() {
@@ -296,18 +233,12 @@ fn validate_request(request: &WithdrawRequest, decimals: u8) -> Result<(), Error
 
     // Calculate expected fee based on mode:
     // - For swap requests, use variable-only fee (matches SPL swap economics)
-    // - For regular SOL withdrawals (no swap), use full fee (fixed + variable)
+    // - For regular withdrawals (no swap), use the pool mint's fee schedule
     //   to stay consistent with the SP1 circuit and validator_agent API.
     let expected_fee = if request.swap.is_some() {
-        // Swap mode: same semantics as planner::calculate_fee (variable 0.5%)
-        calculate_fee(request.public_inputs.amount, decimals)
+        calculate_protocol_fee(request.public_inputs.amount, false)
     } else {
-        // Non-swap withdrawals: fixed 0.0025 SOL + variable 0.5%
-        // This matches zk-guest-sp1/guest/src/encoding.rs::calculate_fee
-        // and services/relay/src/api/validator_agent.rs::calculate_fee.
-        let fixed_fee: u64 = 2_500_000; // 0.0025 SOL in lamports
-        let variable_fee = calculate_fee(request.public_inputs.amount, decimals);
-        fixed_fee.saturating_add(variable_fee)
+        planner::calculate_scheduled_fee(request.public_inputs.amount, fee_schedule)?
     };
     if expected_fee == 0 {
         return Err(Error::ValidationError(
@@ -352,70 +283,18 @@ fn validate_request(request: &WithdrawRequest, decimals: u8) -> Result<(), Error
         )));
     }
 
-    // Validate hex strings (strip 0x prefix if present for validation)
-    let root_str = request
-        .public_inputs
-        .root
-        .strip_prefix("0x")
-        .unwrap_or(&request.public_inputs.root);
-    let nf_str = request
-        .public_inputs
-        .nf
-        .strip_prefix("0x")
-        .unwrap_or(&request.public_inputs.nf);
-    let outputs_hash_str = request
-        .public_inputs
-        .outputs_hash
-        .strip_prefix("0x")
-        .unwrap_or(&request.public_inputs.outputs_hash);
-
-    // Validate hex strings are not empty
-    if root_str.is_empty() {
-        return Err(Error::ValidationError("Root cannot be empty".to_string()));
-    }
-    if nf_str.is_empty() {
-        return Err(Error::ValidationError(
-            "Nullifier cannot be empty".to_string(),
-        ));
-    }
-    if outputs_hash_str.is_empty() {
+    // Validate hex fields
+    parse_hex32_field("root", &request.public_inputs.root)?;
+    parse_hex32_field("nullifier", &request.public_inputs.nf)?;
+    let outputs_hash = parse_hex32_field("outputs hash", &request.public_inputs.outputs_hash)?;
+
+    // The proof commits to the SOL outputs; swap outputs are settled in another token
+    if request.swap.is_none() && request_outputs_hash(&request.outputs)? != outputs_hash {
         return Err(Error::ValidationError(
-            "Outputs hash cannot be empty".to_string(),
+            "outputs_hash mismatch with outputs".to_string(),
         ));
     }
 
-    // Validate hex string lengths
-    if root_str.len() != 64 {
-        return Err(Error::ValidationError(format!(
-            "Root must be 64 hex characters (or 66 with 0x prefix), got {}",
-            root_str.len()
-        )));
-    }
-
-    if nf_str.len() != 64 {
-        return Err(Error::ValidationError(format!(
-            "Nullifier must be 64 hex characters (or 66 with 0x prefix), got {}",
-            nf_str.len()
-        )));
-    }
-
-    if outputs_hash_str.len() != 64 {
-        return Err(Error::ValidationError(format!(
-            "Outputs hash must be 64 hex characters (or 66 with 0x prefix), got {}",
-            outputs_hash_str.len()
-        )));
-    }
-
-    // Validate hex format
-    hex::decode(root_str)
-        .map_err(|_| Error::ValidationError("Root must be valid hex".to_string()))?;
-
-    hex::decode(nf_str)
-        .map_err(|_| Error::ValidationError("Nullifier must be valid hex".to_string()))?;
-
-    hex::decode(outputs_hash_str)
-        .map_err(|_| Error::ValidationError("Outputs hash must be valid hex".to_string()))?;
-
     // Validate proof bytes are valid base64
     if request.proof_bytes.is_empty() {
         return Err(Error::ValidationError(
@@ -423,118 +302,182 @@ fn validate_request(request: &WithdrawRequest, decimals: u8) -> Result<(), Error
         ));
     }
 
+    if let Some(priority) = request.priority {
+        if !(0..=MAX_JOB_PRIORITY).contains(&priority) {
+            return Err(Error::ValidationError(format!(
+                "Priority must be between 0 and {}, got {}",
+                MAX_JOB_PRIORITY, priority
+            )));
+        }
+    }
+
     Ok(())
 }
 
+/// Hash the request's outputs with the same serialization the guest commits to
+fn request_outputs_hash(outputs: &[Output]) -> Result<[u8; 32], Error> {
+    let outputs = outputs
+        .iter()
+        .map(|output| {
+            let address = Pubkey::from_str(&output.recipient)
+                .map_err(|e| Error::ValidationError(format!("Invalid recipient address: {}", e)))?
+                .to_bytes();
+            Ok(planner::Output {
+                address,
+                amount: output.amount,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(compute_outputs_hash(&outputs))
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
-
-    #[test]
-    fn test_validate_request() {
-        let valid_request = WithdrawRequest {
-            outputs: vec![Output {
-                recipient: "11111111111111111111111111111112".to_string(),
-                amount: 97_000_000,
-            }],
-            policy: Policy { fee_bps: 300 },
+    use crate::config::{DEFAULT_MAX_OUTPUTS, DEFAULT_MIN_OUTPUT_AMOUNT};
+
+    fn validate(request: &WithdrawRequest) -> Result<(), Error> {
+        validate_request(
+            request,
+            DEFAULT_MAX_OUTPUTS,
+            DEFAULT_MIN_OUTPUT_AMOUNT,
+            FeeSchedule::NATIVE,
+        )
+    }
+
+    /// A request that passes validation: one output, the native fee and a
+    /// matching outputs hash
+    fn valid_request() -> WithdrawRequest {
+        let outputs = vec![Output {
+            recipient: "11111111111111111111111111111112".to_string(),
+            amount: 97_000_000,
+        }];
+        WithdrawRequest {
             public_inputs: PublicInputs {
                 root: "0".repeat(64),
                 nf: "1".repeat(64),
                 amount: 100_000_000,
                 fee_bps: 300,
-                outputs_hash: "2".repeat(64),
+                outputs_hash: hex::encode(request_outputs_hash(&outputs).unwrap()),
+                #[cfg(feature = "proof-freshness")]
+                generated_at: 0,
             },
+            outputs,
+            policy: Policy { fee_bps: 300 },
             proof_bytes: base64::encode(vec![0u8; 256]),
             swap: None,
-        };
+            priority: None,
+        }
+    }
 
-        assert!(validate_request(&valid_request, 9).is_ok());
+    #[test]
+    fn test_validate_request() {
+        assert!(validate(&valid_request()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_request_outputs_hash_mismatch() {
+        let mut request = valid_request();
+        request.public_inputs.outputs_hash = "2".repeat(64);
+
+        let err = validate(&request).unwrap_err();
+        assert!(err.to_string().contains("outputs_hash mismatch"), "{}", err);
+
+        // Same bytes the guest hashes: address:32 || amount:u64_le
+        let mut preimage = Pubkey::from_str("11111111111111111111111111111112")
+            .unwrap()
+            .to_bytes()
+            .to_vec();
+        preimage.extend_from_slice(&97_000_000u64.to_le_bytes());
+        let mut hasher = cloak_layouts::domains::hasher(cloak_layouts::domains::OUTPUTS_HASH);
+        hasher.update(&preimage);
+        request.public_inputs.outputs_hash = hasher.finalize().to_hex().to_string();
+        assert!(validate(&request).is_ok());
+    }
+
+    #[test]
+    fn test_validate_request_rejects_dust_outputs() {
+        let request = valid_request();
+
+        // Exactly at the threshold is accepted
+        assert!(validate_request(
+            &request,
+            DEFAULT_MAX_OUTPUTS,
+            97_000_000,
+            FeeSchedule::NATIVE
+        )
+        .is_ok());
+
+        let err = validate_request(
+            &request,
+            DEFAULT_MAX_OUTPUTS,
+            97_000_001,
+            FeeSchedule::NATIVE,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("below the minimum"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_request_respects_configured_max_outputs() {
+        let mut request = valid_request();
+        request.outputs = (0..6)
+            .map(|_| Output {
+                recipient: "11111111111111111111111111111112".to_string(),
+                amount: 16_000_000,
+            })
+            .collect();
+
+        let err = validate_request(&request, 5, DEFAULT_MIN_OUTPUT_AMOUNT, FeeSchedule::NATIVE)
+            .unwrap_err();
+        assert!(err.to_string().contains("between 1 and 5"), "{}", err);
     }
 
     #[test]
     fn test_validate_request_empty_outputs() {
-        let invalid_request = WithdrawRequest {
-            outputs: vec![],
-            policy: Policy { fee_bps: 300 },
-            public_inputs: PublicInputs {
-                root: "0".repeat(64),
-                nf: "1".repeat(64),
-                amount: 100_000_000,
-                fee_bps: 300,
-                outputs_hash: "2".repeat(64),
-            },
-            proof_bytes: base64::encode(vec![0u8; 256]),
-            swap: None,
-        };
+        let mut request = valid_request();
+        request.outputs = vec![];
 
-        assert!(validate_request(&invalid_request, 9).is_err());
+        assert!(validate(&request).is_err());
     }
 
     #[test]
     fn test_validate_request_invalid_fee() {
-        let invalid_request = WithdrawRequest {
-            outputs: vec![Output {
-                recipient: "11111111111111111111111111111112".to_string(),
-                amount: 97_000_000,
-            }],
-            policy: Policy { fee_bps: 10001 }, // Too high
-            public_inputs: PublicInputs {
-                root: "0".repeat(64),
-                nf: "1".repeat(64),
-                amount: 100_000_000,
-                fee_bps: 10001,
-                outputs_hash: "2".repeat(64),
-            },
-            proof_bytes: base64::encode(vec![0u8; 256]),
-            swap: None,
-        };
+        let mut request = valid_request();
+        request.policy.fee_bps = 10001; // Too high
+        request.public_inputs.fee_bps = 10001;
 
-        assert!(validate_request(&invalid_request, 9).is_err());
+        assert!(validate(&request).is_err());
     }
 
     #[test]
     fn test_validate_request_invalid_hex() {
-        let invalid_request = WithdrawRequest {
-            outputs: vec![Output {
-                recipient: "11111111111111111111111111111112".to_string(),
-                amount: 97_000_000,
-            }],
-            policy: Policy { fee_bps: 300 },
-            public_inputs: PublicInputs {
-                root: "G".repeat(64), // Invalid hex
-                nf: "1".repeat(64),
-                amount: 100_000_000,
-                fee_bps: 300,
-                outputs_hash: "2".repeat(64),
-            },
-            proof_bytes: base64::encode(vec![0u8; 256]),
-            swap: None,
-        };
+        let mut request = valid_request();
+        request.public_inputs.root = "G".repeat(64); // Invalid hex
 
-        assert!(validate_request(&invalid_request, 9).is_err());
+        assert!(validate(&request).is_err());
     }
 
     #[test]
     fn test_validate_request_empty_proof() {
-        let invalid_request = WithdrawRequest {
-            outputs: vec![Output {
-                recipient: "11111111111111111111111111111112".to_string(),
-                amount: 97_000_000,
-            }],
-            policy: Policy { fee_bps: 300 },
-            public_inputs: PublicInputs {
-                root: "0".repeat(64),
-                nf: "1".repeat(64),
-                amount: 100_000_000,
-                fee_bps: 300,
-                outputs_hash: "2".repeat(64),
-            },
-            proof_bytes: "".to_string(), // Empty base64
-            swap: None,
-        };
+        let mut request = valid_request();
+        request.proof_bytes = "".to_string(); // Empty base64
 
-        assert!(validate_request(&invalid_request, 9).is_err());
+        let err = validate(&request).unwrap_err();
+        assert!(err.to_string().contains("Proof bytes"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_request_priority_range() {
+        let mut request = valid_request();
+        request.priority = Some(MAX_JOB_PRIORITY);
+        assert!(validate(&request).is_ok());
+
+        for priority in [-1, MAX_JOB_PRIORITY + 1] {
+            request.priority = Some(priority);
+            let err = validate(&request).unwrap_err();
+            assert!(err.to_string().contains("Priority"), "{}", err);
+        }
     }
 }
@@ -63,20 +63,15 @@ fn main() -> anyhow::Result<()> {
             println!("   Data length: {} bytes", account.data.len());
             println!();
 
-            if account.data.len() >= 121 {
-                // Parse SwapState data
-                // Layout: [nullifier(32)][sol_amount(8)][output_mint(32)][recipient_ata(32)]
-                //         [min_output_amount(8)][created_slot(8)][bump(1)]
-
-                let stored_nullifier = &account.data[0..32];
-                let sol_amount = u64::from_le_bytes(account.data[32..40].try_into()?);
-                let output_mint_bytes: [u8; 32] = account.data[40..72].try_into()?;
-                let output_mint = Pubkey::new_from_array(output_mint_bytes);
-                let recipient_ata_bytes: [u8; 32] = account.data[72..104].try_into()?;
-                let recipient_ata = Pubkey::new_from_array(recipient_ata_bytes);
-                let min_output_amount = u64::from_le_bytes(account.data[104..112].try_into()?);
-                let created_slot = u64::from_le_bytes(account.data[112..120].try_into()?);
-                let stored_bump = account.data[120];
+            if let Some(swap_state) = cloak_layouts::decode_swap_state(&account.data) {
+                let stored_nullifier = swap_state.nullifier;
+                let sol_amount = swap_state.sol_amount;
+                let output_mint = Pubkey::new_from_array(swap_state.output_mint);
+                let recipient_ata = Pubkey::new_from_array(swap_state.recipient_ata);
+                let min_output_amount = swap_state.min_output_amount;
+                let created_slot = swap_state.created_slot;
+                let timeout_slot = swap_state.timeout_slot;
+                let stored_bump = swap_state.bump;
 
                 println!("📊 SwapState Contents:");
                 println!("   Nullifier: {}", hex::encode(stored_nullifier));
@@ -89,6 +84,7 @@ fn main() -> anyhow::Result<()> {
                 println!("   Recipient ATA: {}", recipient_ata);
                 println!("   Min Output Amount: {}", min_output_amount);
                 println!("   Created Slot: {}", created_slot);
+                println!("   Timeout Slot: {}", timeout_slot);
                 println!("   Bump: {}", stored_bump);
                 println!();
 
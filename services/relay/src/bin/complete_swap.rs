@@ -83,20 +83,18 @@ async fn main() -> Result<()> {
         )
     })?;
 
-    if swap_state_account.data.len() != 121 {
-        anyhow::bail!(
-            "Invalid SwapState account size: {}",
-            swap_state_account.data.len()
-        );
-    }
-
-    // Parse SwapState data
-    let sol_amount = u64::from_le_bytes(swap_state_account.data[32..40].try_into()?);
-    let output_mint_bytes: [u8; 32] = swap_state_account.data[40..72].try_into()?;
-    let output_mint = Pubkey::new_from_array(output_mint_bytes);
-    let recipient_ata_bytes: [u8; 32] = swap_state_account.data[72..104].try_into()?;
-    let recipient_ata = Pubkey::new_from_array(recipient_ata_bytes);
-    let min_output_amount = u64::from_le_bytes(swap_state_account.data[104..112].try_into()?);
+    let swap_state =
+        cloak_layouts::decode_swap_state(&swap_state_account.data).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid SwapState account size: {}",
+                swap_state_account.data.len()
+            )
+        })?;
+
+    let sol_amount = swap_state.sol_amount;
+    let output_mint = Pubkey::new_from_array(swap_state.output_mint);
+    let recipient_ata = Pubkey::new_from_array(swap_state.recipient_ata);
+    let min_output_amount = swap_state.min_output_amount;
 
     println!("📊 Swap Details:");
     println!(
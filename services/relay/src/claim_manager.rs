@@ -9,11 +9,23 @@
 //! - Relay queries on-chain for available claims
 //! - Relay uses claims when building withdraw transactions
 
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use cloak_layouts::claim;
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{account::Account, pubkey::Pubkey};
-use tracing::{debug, error, info};
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    error::Error, shutdown::run_until_shutdown, solana::subscription::AccountNotification,
+};
 
-use crate::error::Error;
+/// How often the background refresher re-counts available claims
+pub const CLAIM_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
 
 /// An available claim discovered on-chain
 #[derive(Debug, Clone)]
@@ -47,6 +59,9 @@ pub struct ClaimFinder {
 
     /// Registry program ID
     registry_program_id: Pubkey,
+
+    /// Usable claims as of the last refresh (`None` until the first one)
+    available_claims: RwLock<Option<usize>>,
 }
 
 impl ClaimFinder {
@@ -54,6 +69,83 @@ impl ClaimFinder {
         Self {
             rpc_client: RpcClient::new(rpc_url),
             registry_program_id,
+            available_claims: RwLock::new(None),
+        }
+    }
+
+    pub fn registry_program_id(&self) -> Pubkey {
+        self.registry_program_id
+    }
+
+    /// Cached number of claims any withdraw could use right now
+    ///
+    /// Kept up to date by [`ClaimFinder::run_refresher`]; `None` until the first
+    /// refresh completes.
+    pub fn available_claim_count(&self) -> Option<usize> {
+        *self
+            .available_claims
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Re-count available claims on-chain and update the cached count
+    pub async fn refresh_available_claims(&self) -> Result<usize, Error> {
+        let accounts = self
+            .rpc_client
+            .get_program_accounts(&self.registry_program_id)
+            .await
+            .map_err(|e| Error::InternalServerError(format!("Failed to query claims: {}", e)))?;
+        let current_slot = self
+            .rpc_client
+            .get_slot()
+            .await
+            .map_err(|e| Error::InternalServerError(format!("Failed to get slot: {}", e)))?;
+
+        Ok(self.record_available_claims(&accounts, current_slot))
+    }
+
+    fn record_available_claims(&self, accounts: &[(Pubkey, Account)], current_slot: u64) -> usize {
+        let count = count_available_claims(accounts, current_slot);
+        *self
+            .available_claims
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = Some(count);
+        count
+    }
+
+    /// Refresh the cached claim count every `interval` until shutdown
+    pub async fn run_refresher(
+        self: Arc<Self>,
+        interval: Duration,
+        shutdown: watch::Receiver<bool>,
+    ) {
+        info!("⛏️  PoW claim refresher started (interval: {:?})", interval);
+
+        let finder = &self;
+        run_until_shutdown(interval, shutdown, move || async move {
+            match finder.refresh_available_claims().await {
+                Ok(0) => warn!("⚠️  No PoW claims available, withdraws will wait for miners"),
+                Ok(count) => debug!("PoW claims available: {}", count),
+                Err(e) => warn!("⚠️  Failed to refresh PoW claim count: {}", e),
+            }
+        })
+        .await;
+
+        info!("🛑 PoW claim refresher stopped");
+    }
+
+    /// Refresh the cached claim count whenever the registry reports an account
+    /// change, until `updates` closes. A burst of notifications coalesces into
+    /// one refresh.
+    pub async fn run_notified_refresher(
+        self: Arc<Self>,
+        mut updates: mpsc::Receiver<AccountNotification>,
+    ) {
+        while updates.recv().await.is_some() {
+            while updates.try_recv().is_ok() {}
+            if let Err(e) = self.refresh_available_claims().await {
+                warn!("⚠️  Failed to refresh PoW claim count: {}", e);
+            }
         }
     }
 
@@ -166,8 +258,7 @@ impl ClaimFinder {
                     }
 
                     // Check if revealed
-                    if claim.status != 1 {
-                        // 1 = Revealed
+                    if claim.status != claim::STATUS_REVEALED {
                         not_revealed += 1;
                         continue;
                     }
@@ -252,12 +343,12 @@ impl ClaimFinder {
         .await
         {
             Ok(Ok(account)) => {
-                // Registry account should be exactly 180 bytes
-                if account.data.len() != 188 {
+                if account.data.len() != cloak_layouts::registry::SIZE {
                     return Err(Error::ValidationError(format!(
-                        "Registry account {} has invalid data size: {} bytes (expected 180)",
+                        "Registry account {} has invalid data size: {} bytes (expected {})",
                         registry_pda,
-                        account.data.len()
+                        account.data.len(),
+                        cloak_layouts::registry::SIZE
                     )));
                 }
             }
@@ -295,12 +386,13 @@ impl ClaimFinder {
         .await
         {
             Ok(Ok(account)) => {
-                // Miner account should be exactly 56 bytes
-                if account.data.len() != 56 {
+                if !cloak_layouts::miner::is_miner_size(account.data.len()) {
                     return Err(Error::ValidationError(format!(
-                        "Miner account {} has invalid data size: {} bytes (expected 56)",
+                        "Miner account {} has invalid data size: {} bytes (expected {} or legacy {})",
                         miner_pda,
-                        account.data.len()
+                        account.data.len(),
+                        cloak_layouts::miner::SIZE,
+                        cloak_layouts::miner::LEGACY_SIZE
                     )));
                 }
             }
@@ -365,53 +457,46 @@ struct ParsedClaim {
     expires_at_slot: u64,
 }
 
-/// Parse a claim account from raw bytes
-///
-/// Claim layout (256 bytes total) - NO DISCRIMINATOR:
-/// - miner_authority: 32 bytes (offset 0)
-/// - batch_hash: 32 bytes (offset 32)
-/// - slot: 8 bytes (offset 64)
-/// - slot_hash: 32 bytes (offset 72)
-/// - nonce: 16 bytes (offset 104)
-/// - proof_hash: 32 bytes (offset 120)
-/// - mined_at_slot: 8 bytes (offset 152)
-/// - revealed_at_slot: 8 bytes (offset 160)
-/// - consumed_count: 2 bytes (offset 168)
-/// - max_consumes: 2 bytes (offset 170)
-/// - expires_at_slot: 8 bytes (offset 172)
-/// - status: 1 byte (offset 180)
-fn parse_claim_account(account: &Account) -> Result<ParsedClaim, Error> {
-    if account.data.len() < 256 {
-        return Err(Error::ValidationError("Account too small".into()));
+impl ParsedClaim {
+    /// Revealed, unexpired wildcard claim with consumes left, i.e. one that
+    /// `find_claim` would hand to any withdraw
+    fn is_available(&self, current_slot: u64) -> bool {
+        self.batch_hash == [0u8; 32]
+            && self.status == claim::STATUS_REVEALED
+            && current_slot <= self.expires_at_slot
+            && self.consumed_count < self.max_consumes
     }
+}
 
-    let data = &account.data;
-
-    // Extract fields using unsafe pointer arithmetic (same as on-chain)
-    unsafe {
-        let miner_authority = Pubkey::new_from_array(*(data.as_ptr() as *const [u8; 32]));
-
-        let batch_hash: [u8; 32] = *(data.as_ptr().add(32) as *const [u8; 32]);
-
-        let slot = u64::from_le_bytes(*(data.as_ptr().add(64) as *const [u8; 8]));
-
-        let consumed_count = u16::from_le_bytes([data[168], data[169]]);
-        let max_consumes = u16::from_le_bytes([data[170], data[171]]);
-
-        let expires_at_slot = u64::from_le_bytes(*(data.as_ptr().add(172) as *const [u8; 8]));
-
-        let status = data[180];
+/// Number of registry accounts holding an available claim
+fn count_available_claims(accounts: &[(Pubkey, Account)], current_slot: u64) -> usize {
+    accounts
+        .iter()
+        .filter(|(_, account)| account.data.len() == claim::SIZE)
+        .filter_map(|(_, account)| parse_claim_account(account).ok())
+        .filter(|claim| claim.is_available(current_slot))
+        .count()
+}
 
-        Ok(ParsedClaim {
-            miner_authority,
-            batch_hash,
-            slot,
-            status,
-            consumed_count,
-            max_consumes,
-            expires_at_slot,
-        })
-    }
+/// Parse a claim account using the shared scramble-registry layout
+fn parse_claim_account(account: &Account) -> Result<ParsedClaim, Error> {
+    let claim = cloak_layouts::decode_claim(&account.data).ok_or_else(|| {
+        Error::ValidationError(format!(
+            "Claim account has invalid data size: {} bytes (expected {})",
+            account.data.len(),
+            claim::SIZE
+        ))
+    })?;
+
+    Ok(ParsedClaim {
+        miner_authority: Pubkey::new_from_array(claim.miner_authority),
+        batch_hash: claim.batch_hash,
+        slot: claim.slot,
+        status: claim.status,
+        consumed_count: claim.consumed_count,
+        max_consumes: claim.max_consumes,
+        expires_at_slot: claim.expires_at_slot,
+    })
 }
 
 /// Helper to compute batch hash from job ID (k=1 for MVP)
@@ -449,33 +534,22 @@ mod tests {
 
     #[test]
     fn test_parse_claim_account() {
-        // Create a mock claim account with proper layout
-        let mut data = vec![0u8; 256];
-
-        // discriminator (8 bytes) - skip
+        // Create a mock claim account with the on-chain layout
+        let mut data = vec![0u8; claim::SIZE];
 
-        // miner_authority (32 bytes at offset 8)
         let miner_authority = Pubkey::new_unique();
-        data[8..40].copy_from_slice(miner_authority.as_ref());
+        data[claim::MINER_AUTHORITY..claim::MINER_AUTHORITY + 32]
+            .copy_from_slice(miner_authority.as_ref());
 
-        // batch_hash (32 bytes at offset 40)
         let batch_hash = [0xAB; 32];
-        data[40..72].copy_from_slice(&batch_hash);
+        data[claim::BATCH_HASH..claim::BATCH_HASH + 32].copy_from_slice(&batch_hash);
 
-        // slot (8 bytes at offset 72)
-        data[72..80].copy_from_slice(&1000u64.to_le_bytes());
-
-        // consumed_count (2 bytes at offset 176)
-        data[176..178].copy_from_slice(&5u16.to_le_bytes());
-
-        // max_consumes (2 bytes at offset 178)
-        data[178..180].copy_from_slice(&10u16.to_le_bytes());
-
-        // expires_at_slot (8 bytes at offset 180)
-        data[180..188].copy_from_slice(&2000u64.to_le_bytes());
-
-        // status (1 byte at offset 188) - 1 = Revealed
-        data[188] = 1;
+        data[claim::SLOT..claim::SLOT + 8].copy_from_slice(&1000u64.to_le_bytes());
+        data[claim::CONSUMED_COUNT..claim::CONSUMED_COUNT + 2].copy_from_slice(&5u16.to_le_bytes());
+        data[claim::MAX_CONSUMES..claim::MAX_CONSUMES + 2].copy_from_slice(&10u16.to_le_bytes());
+        data[claim::EXPIRES_AT_SLOT..claim::EXPIRES_AT_SLOT + 8]
+            .copy_from_slice(&2000u64.to_le_bytes());
+        data[claim::STATUS] = claim::STATUS_REVEALED;
 
         let account = Account {
             lamports: 1_000_000,
@@ -496,5 +570,46 @@ mod tests {
         assert_eq!(parsed.expires_at_slot, 2000);
     }
 
+    fn claim_account(batch_hash: [u8; 32], status: u8, consumed: u16, expires: u64) -> Account {
+        let mut data = vec![0u8; 256];
+        data[32..64].copy_from_slice(&batch_hash);
+        data[168..170].copy_from_slice(&consumed.to_le_bytes());
+        data[170..172].copy_from_slice(&2u16.to_le_bytes());
+        data[172..180].copy_from_slice(&expires.to_le_bytes());
+        data[180] = status;
+
+        Account {
+            lamports: 1_000_000,
+            data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_available_claim_count_updates_after_refresh() {
+        let finder = ClaimFinder::new("http://localhost:8899".to_string(), Pubkey::new_unique());
+        assert_eq!(finder.available_claim_count(), None);
+
+        let mut accounts = vec![
+            (Pubkey::new_unique(), claim_account([0; 32], 1, 0, 500)),
+            // Expired, fully consumed, unrevealed and batch-bound claims don't count
+            (Pubkey::new_unique(), claim_account([0; 32], 1, 0, 99)),
+            (Pubkey::new_unique(), claim_account([0; 32], 1, 2, 500)),
+            (Pubkey::new_unique(), claim_account([0; 32], 0, 0, 500)),
+            (Pubkey::new_unique(), claim_account([0xAB; 32], 1, 0, 500)),
+        ];
+        assert_eq!(finder.record_available_claims(&accounts, 100), 1);
+        assert_eq!(finder.available_claim_count(), Some(1));
+
+        accounts.push((Pubkey::new_unique(), claim_account([0; 32], 1, 1, 500)));
+        finder.record_available_claims(&accounts, 100);
+        assert_eq!(finder.available_claim_count(), Some(2));
+
+        finder.record_available_claims(&accounts, 1_000);
+        assert_eq!(finder.available_claim_count(), Some(0));
+    }
+
     // Note: find_claim() requires live RPC connection, so it's tested in integration tests
 }
@@ -1,4 +1,10 @@
-use std::sync::Arc;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use anyhow::Result;
 use aws_config::BehaviorVersion;
@@ -13,6 +19,51 @@ use tracing_subscriber::{
     EnvFilter, Layer,
 };
 
+/// Log lines buffered for CloudWatch before new ones are dropped
+const LOG_BUFFER_CAPACITY: usize = 10_000;
+/// Maximum events per PutLogEvents call
+const MAX_BATCH_SIZE: usize = 100;
+/// Partial batches are flushed at least this often
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Producer side of the CloudWatch buffer.
+///
+/// Never blocks: when the flusher falls behind (e.g. AWS is slow) new lines
+/// are dropped and counted instead of stalling the thread that logged them.
+#[derive(Clone)]
+struct LogSink {
+    tx: mpsc::Sender<String>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl LogSink {
+    fn new(capacity: usize) -> (Self, mpsc::Receiver<String>, Arc<AtomicU64>) {
+        let (tx, rx) = mpsc::channel(capacity);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let sink = Self {
+            tx,
+            dropped: Arc::clone(&dropped),
+        };
+        (sink, rx, dropped)
+    }
+
+    /// Queue a line for CloudWatch, returning false if it was dropped
+    fn push(&self, message: String) -> bool {
+        match self.tx.try_send(message) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                // Warn locally on the first drop; the flusher reports the total
+                if self.dropped.fetch_add(1, Ordering::Relaxed) == 0 {
+                    eprintln!("⚠️  CloudWatch log buffer full, dropping log lines");
+                }
+                false
+            }
+            // Flusher is gone (shutdown); nothing left to ship to
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+}
+
 /// Get the machine ID to use as log stream name
 fn get_machine_id() -> String {
     // Try to get hostname first
@@ -31,7 +82,8 @@ async fn cloudwatch_log_sender(
     client: Arc<CloudWatchLogsClient>,
     log_group_name: String,
     log_stream_name: String,
-    mut rx: mpsc::UnboundedReceiver<String>,
+    mut rx: mpsc::Receiver<String>,
+    dropped: Arc<AtomicU64>,
 ) {
     // Try to create log group first (ignore error if it already exists)
     if let Err(e) = client
@@ -62,29 +114,49 @@ async fn cloudwatch_log_sender(
 
     let mut sequence_token: Option<String> = None;
     let mut batch = Vec::new();
+    let mut flush_timer = tokio::time::interval(FLUSH_INTERVAL);
+
+    loop {
+        // Send in batches of MAX_BATCH_SIZE or every FLUSH_INTERVAL
+        let flush = tokio::select! {
+            message = rx.recv() => match message {
+                Some(message) => {
+                    batch.push(
+                        InputLogEvent::builder()
+                            .timestamp(chrono::Utc::now().timestamp_millis())
+                            .message(message)
+                            .build()
+                            .expect("Failed to build log event"),
+                    );
+                    batch.len() >= MAX_BATCH_SIZE
+                }
+                None => break,
+            },
+            _ = flush_timer.tick() => !batch.is_empty(),
+        };
 
-    while let Some(message) = rx.recv().await {
-        batch.push(
-            InputLogEvent::builder()
-                .timestamp(chrono::Utc::now().timestamp_millis())
-                .message(message)
-                .build()
-                .expect("Failed to build log event"),
-        );
-
-        // Send in batches of 10 or after 1 second
-        if batch.len() >= 10 {
-            if let Err(e) = send_batch(
-                &client,
-                &log_group_name,
-                &log_stream_name,
-                &mut batch,
-                &mut sequence_token,
-            )
-            .await
-            {
-                eprintln!("Failed to send logs to CloudWatch: {}", e);
-            }
+        if !flush {
+            continue;
+        }
+
+        let lost = dropped.swap(0, Ordering::Relaxed);
+        if lost > 0 {
+            eprintln!(
+                "⚠️  Dropped {} log lines while the CloudWatch buffer was full",
+                lost
+            );
+        }
+
+        if let Err(e) = send_batch(
+            &client,
+            &log_group_name,
+            &log_stream_name,
+            &mut batch,
+            &mut sequence_token,
+        )
+        .await
+        {
+            eprintln!("Failed to send logs to CloudWatch: {}", e);
         }
     }
 
@@ -137,12 +209,12 @@ async fn send_batch(
 
 /// Custom tracing layer that sends logs to CloudWatch
 struct CloudWatchLayer {
-    tx: mpsc::UnboundedSender<String>,
+    sink: LogSink,
 }
 
 impl CloudWatchLayer {
-    fn new(tx: mpsc::UnboundedSender<String>) -> Self {
-        Self { tx }
+    fn new(sink: LogSink) -> Self {
+        Self { sink }
     }
 }
 
@@ -182,8 +254,8 @@ where
         let mut visitor = MessageVisitor(&mut message);
         event.record(&mut visitor);
 
-        // Send to CloudWatch (ignore send errors as we don't want logging to fail the app)
-        let _ = self.tx.send(message);
+        // Send to CloudWatch (dropped if the buffer is full; logging must never block the app)
+        self.sink.push(message);
     }
 }
 
@@ -301,15 +373,15 @@ pub async fn init_logging_with_cloudwatch(
         }
     }
 
-    // Create channel for sending logs
-    let (tx, rx) = mpsc::unbounded_channel();
+    // Create bounded buffer for sending logs
+    let (sink, rx, dropped) = LogSink::new(LOG_BUFFER_CAPACITY);
 
     // Spawn background task to send logs
     let client_clone = cloudwatch_client.clone();
     let group_clone = log_group_name.to_string();
     let stream_clone = log_stream_name.clone();
     tokio::spawn(async move {
-        cloudwatch_log_sender(client_clone, group_clone, stream_clone, rx).await;
+        cloudwatch_log_sender(client_clone, group_clone, stream_clone, rx, dropped).await;
     });
 
     // Create env filter for console output, excluding AWS SDK debug logs
@@ -330,7 +402,7 @@ pub async fn init_logging_with_cloudwatch(
                 .with_timer(SystemTime)
                 .with_span_events(FmtSpan::CLOSE),
         )
-        .with(CloudWatchLayer::new(tx))
+        .with(CloudWatchLayer::new(sink))
         .init();
 
     tracing::info!(
@@ -342,3 +414,27 @@ pub async fn init_logging_with_cloudwatch(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_buffer_drops_instead_of_blocking() {
+        // Nobody drains the receiver, as when the flusher is stuck on the network
+        let (sink, mut rx, dropped) = LogSink::new(3);
+
+        let accepted = (0..10).filter(|i| sink.push(format!("line {}", i))).count();
+
+        assert_eq!(accepted, 3);
+        assert_eq!(dropped.load(Ordering::Relaxed), 7);
+        // The oldest lines are kept, in order
+        assert_eq!(rx.try_recv().unwrap(), "line 0");
+        assert_eq!(rx.try_recv().unwrap(), "line 1");
+        assert_eq!(rx.try_recv().unwrap(), "line 2");
+        assert!(rx.try_recv().is_err());
+
+        // Space frees up once the flusher catches up
+        assert!(sink.push("line 10".to_string()));
+    }
+}
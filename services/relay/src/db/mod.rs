@@ -1,16 +1,30 @@
 pub mod models;
 pub mod repository;
 
+use std::time::Duration;
+
+use cloak_retry::retry_connect;
 use sqlx::{
     migrate::MigrateDatabase,
     postgres::{PgPool, PgPoolOptions},
 };
 use tracing::info;
 
-use crate::error::Error;
+use crate::{config::DatabaseConfig, error::Error};
 
 pub type DatabasePool = PgPool;
 
+/// Connect to PostgreSQL, retrying with exponential backoff so the relay can
+/// start before the database is accepting connections
+pub async fn connect_with_retry(config: &DatabaseConfig) -> Result<DatabasePool, Error> {
+    retry_connect(
+        config.connect_max_attempts,
+        Duration::from_millis(config.connect_backoff_ms),
+        || connect(&config.url, config.max_connections),
+    )
+    .await
+}
+
 /// Connect to PostgreSQL database
 pub async fn connect(database_url: &str, max_connections: u32) -> Result<DatabasePool, Error> {
     info!("Connecting to database");
@@ -48,7 +62,7 @@ pub async fn run_migrations(pool: &DatabasePool) -> Result<(), Error> {
     sqlx::query(
         r#"
         DO $$ BEGIN
-            CREATE TYPE job_status AS ENUM ('queued', 'processing', 'completed', 'failed', 'cancelled');
+            CREATE TYPE job_status AS ENUM ('queued', 'processing', 'completed', 'failed', 'cancelled', 'expired');
         EXCEPTION
             WHEN duplicate_object THEN null;
         END $$;
@@ -56,6 +70,12 @@ pub async fn run_migrations(pool: &DatabasePool) -> Result<(), Error> {
     ).execute(pool).await
     .map_err(|e| Error::DatabaseError(format!("Failed to create enum: {}", e)))?;
 
+    // Databases created before job expiry existed lack the 'expired' variant
+    sqlx::query("ALTER TYPE job_status ADD VALUE IF NOT EXISTS 'expired'")
+        .execute(pool)
+        .await
+        .map_err(|e| Error::DatabaseError(format!("Failed to extend enum: {}", e)))?;
+
     // Second statement: Create jobs table
     sqlx::query(
         r#"
@@ -71,6 +91,7 @@ pub async fn run_migrations(pool: &DatabasePool) -> Result<(), Error> {
             nullifier BYTEA NOT NULL,
             amount BIGINT NOT NULL,
             outputs_hash BYTEA NOT NULL,
+            priority SMALLINT NOT NULL DEFAULT 0,
             tx_id TEXT,
             solana_signature TEXT,
             error_message TEXT,
@@ -87,7 +108,25 @@ pub async fn run_migrations(pool: &DatabasePool) -> Result<(), Error> {
     .await
     .map_err(|e| Error::DatabaseError(format!("Failed to create jobs table: {}", e)))?;
 
+    // Databases created before job priorities existed lack the column
+    sqlx::query("ALTER TABLE jobs ADD COLUMN IF NOT EXISTS priority SMALLINT NOT NULL DEFAULT 0")
+        .execute(pool)
+        .await
+        .map_err(|e| Error::DatabaseError(format!("Failed to add jobs.priority: {}", e)))?;
+
+    // Withdraw waiting for an external agent to sign and send (external submission mode)
+    for column_sql in &[
+        "ALTER TABLE jobs ADD COLUMN IF NOT EXISTS pending_tx BYTEA",
+        "ALTER TABLE jobs ADD COLUMN IF NOT EXISTS pending_tx_last_valid_block_height BIGINT",
+    ] {
+        sqlx::query(column_sql)
+            .execute(pool)
+            .await
+            .map_err(|e| Error::DatabaseError(format!("Failed to add jobs.pending_tx: {}", e)))?;
+    }
+
     // Third statement: Create nullifiers table
+    // The primary key on nullifier is what rejects concurrent double-spends (see insert_nullifier)
     sqlx::query(
         r#"
         CREATE TABLE IF NOT EXISTS nullifiers (
@@ -103,12 +142,71 @@ pub async fn run_migrations(pool: &DatabasePool) -> Result<(), Error> {
     .await
     .map_err(|e| Error::DatabaseError(format!("Failed to create nullifiers table: {}", e)))?;
 
+    // Fourth statement: Create job_events table (status transition audit trail)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS job_events (
+            id BIGSERIAL PRIMARY KEY,
+            job_id UUID NOT NULL REFERENCES jobs(id) ON DELETE CASCADE,
+            status job_status NOT NULL,
+            detail TEXT,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| Error::DatabaseError(format!("Failed to create job_events table: {}", e)))?;
+
+    // Fifth statement: Create failed_jobs table (dead-letter queue for post-mortem/resubmission)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS failed_jobs (
+            id BIGSERIAL PRIMARY KEY,
+            job_id UUID NOT NULL UNIQUE REFERENCES jobs(id) ON DELETE CASCADE,
+            request_id UUID NOT NULL,
+            proof_bytes BYTEA NOT NULL,
+            public_inputs BYTEA NOT NULL,
+            outputs_json JSONB NOT NULL,
+            error_message TEXT NOT NULL,
+            retry_count INTEGER NOT NULL,
+            failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| Error::DatabaseError(format!("Failed to create failed_jobs table: {}", e)))?;
+
+    // Sixth statement: Create swap_receipts table (audit trail of a swap withdraw's transactions)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS swap_receipts (
+            job_id UUID PRIMARY KEY REFERENCES jobs(id) ON DELETE CASCADE,
+            nullifier BYTEA NOT NULL,
+            output_mint TEXT NOT NULL,
+            withdraw_swap_signature TEXT,
+            prepare_swap_signature TEXT,
+            sync_native_signature TEXT,
+            execute_swap_signature TEXT,
+            output_amount BIGINT,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| Error::DatabaseError(format!("Failed to create swap_receipts table: {}", e)))?;
+
     // Create indexes
     for index_sql in &[
         "CREATE INDEX IF NOT EXISTS idx_jobs_status ON jobs(status)",
         "CREATE INDEX IF NOT EXISTS idx_jobs_created_at ON jobs(created_at)",
         "CREATE INDEX IF NOT EXISTS idx_jobs_request_id ON jobs(request_id)",
         "CREATE INDEX IF NOT EXISTS idx_nullifiers_created_at ON nullifiers(created_at)",
+        "CREATE INDEX IF NOT EXISTS idx_job_events_job_id ON job_events(job_id, id)",
+        "CREATE INDEX IF NOT EXISTS idx_failed_jobs_failed_at ON failed_jobs(failed_at)",
+        "CREATE INDEX IF NOT EXISTS idx_swap_receipts_nullifier ON swap_receipts(nullifier)",
     ] {
         sqlx::query(index_sql)
             .execute(pool)
@@ -11,6 +11,7 @@ pub enum JobStatus {
     Completed,
     Failed,
     Cancelled,
+    Expired,
 }
 
 impl std::fmt::Display for JobStatus {
@@ -21,6 +22,7 @@ impl std::fmt::Display for JobStatus {
             JobStatus::Completed => write!(f, "completed"),
             JobStatus::Failed => write!(f, "failed"),
             JobStatus::Cancelled => write!(f, "cancelled"),
+            JobStatus::Expired => write!(f, "expired"),
         }
     }
 }
@@ -43,6 +45,9 @@ pub struct Job {
     pub amount: i64,
     pub outputs_hash: Vec<u8>,
 
+    /// Higher runs sooner; see `worker::priority` for how waiting jobs age
+    pub priority: i16,
+
     // Processing results
     pub tx_id: Option<String>,
     pub solana_signature: Option<String>,
@@ -68,6 +73,97 @@ pub struct CreateJob {
     pub nullifier: Vec<u8>,
     pub amount: i64,
     pub outputs_hash: Vec<u8>,
+    pub priority: i16,
+}
+
+/// A withdraw built in external submission mode, waiting for an agent to sign
+/// and send it
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct PendingTransaction {
+    /// Unsigned bincode `VersionedTransaction`
+    pub tx_bytes: Vec<u8>,
+    /// Block height after which the transaction's blockhash has expired
+    pub last_valid_block_height: i64,
+}
+
+/// One status transition of a job, recorded by the repository
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct JobEvent {
+    pub id: i64,
+    pub job_id: Uuid,
+    pub status: JobStatus,
+    pub detail: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A job moved to the dead-letter table after failing permanently
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FailedJob {
+    pub id: i64,
+    pub job_id: Uuid,
+    pub request_id: Uuid,
+    pub proof_bytes: Vec<u8>,
+    pub public_inputs: Vec<u8>,
+    pub outputs_json: JsonValue,
+    pub error_message: String,
+    pub retry_count: i32,
+    pub failed_at: DateTime<Utc>,
+}
+
+/// Every transaction the relay sent to complete a swap withdraw, keyed by job
+///
+/// Each attempt merges the signatures it got confirmed into the row, so steps
+/// a retried job already completed keep the signature from the earlier attempt.
+/// `execute_swap_signature` stays `None` until the swap itself lands.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SwapReceipt {
+    pub job_id: Uuid,
+    pub nullifier: Vec<u8>,
+    pub output_mint: String,
+    pub withdraw_swap_signature: Option<String>,
+    pub prepare_swap_signature: Option<String>,
+    pub sync_native_signature: Option<String>,
+    pub execute_swap_signature: Option<String>,
+    /// Output tokens the recipient's token account gained, if both balance
+    /// reads succeeded
+    pub output_amount: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateSwapReceipt {
+    pub job_id: Uuid,
+    pub nullifier: Vec<u8>,
+    pub output_mint: String,
+    pub withdraw_swap_signature: Option<String>,
+    pub prepare_swap_signature: Option<String>,
+    pub sync_native_signature: Option<String>,
+    pub execute_swap_signature: Option<String>,
+    pub output_amount: Option<i64>,
+}
+
+impl CreateSwapReceipt {
+    /// Receipt for a swap attempt that has not confirmed anything yet
+    pub fn new(job_id: Uuid, nullifier: Vec<u8>, output_mint: String) -> Self {
+        Self {
+            job_id,
+            nullifier,
+            output_mint,
+            withdraw_swap_signature: None,
+            prepare_swap_signature: None,
+            sync_native_signature: None,
+            execute_swap_signature: None,
+            output_amount: None,
+        }
+    }
+
+    /// Whether the attempt got none of its transactions confirmed
+    pub fn is_empty(&self) -> bool {
+        self.withdraw_swap_signature.is_none()
+            && self.prepare_swap_signature.is_none()
+            && self.sync_native_signature.is_none()
+            && self.execute_swap_signature.is_none()
+    }
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
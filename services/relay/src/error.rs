@@ -6,6 +6,9 @@ pub enum Error {
     #[error("Not found")]
     NotFound,
 
+    #[error("Unauthorized")]
+    Unauthorized,
+
     #[error("Internal server error: {0}")]
     InternalServerError(String),
 
@@ -29,6 +32,57 @@ pub enum Error {
 
     #[error("Serialization error: {0}")]
     SerializationError(String),
+
+    #[error("Transaction simulation failed: {0}")]
+    SimulationFailed(String),
+
+    #[error("Nullifier already used by another withdraw")]
+    DuplicateNullifier,
+
+    #[error("Insufficient pool liquidity: pool holds {available}, withdraw needs {required}")]
+    InsufficientPoolLiquidity { available: u64, required: u64 },
+
+    /// A withdraw output would revert on-chain, e.g. a frozen SPL token account
+    #[error("Recipient {recipient} cannot receive withdraw output: {reason}")]
+    RecipientCannotReceive { recipient: String, reason: String },
+
+    /// Jupiter API/quote failure (no route, bad response, output below minimum)
+    #[error("Swap quote failed: {0}")]
+    SwapQuoteFailed(String),
+
+    /// A swap transaction was sent but reverted or never confirmed
+    #[error("Swap execution failed: {0}")]
+    SwapExecutionFailed(String),
+
+    /// SwapState PDA or its token accounts are not in the expected state
+    #[error("Swap state mismatch: {0}")]
+    SwapStateMismatch(String),
+}
+
+impl Error {
+    /// Stable machine-readable code, sent as `error` in response bodies
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::BadRequest(_) => "bad_request",
+            Error::NotFound => "not_found",
+            Error::Unauthorized => "unauthorized",
+            Error::InternalServerError(_) => "internal_error",
+            Error::DatabaseError(_) => "database_error",
+            Error::ValidationError(_) => "validation_error",
+            Error::JsonError(_) => "invalid_json",
+            Error::UuidError(_) => "invalid_uuid",
+            Error::Base64Error(_) => "invalid_base64",
+            Error::NetworkError(_) => "network_error",
+            Error::SerializationError(_) => "serialization_error",
+            Error::SimulationFailed(_) => "simulation_failed",
+            Error::DuplicateNullifier => "duplicate_nullifier",
+            Error::InsufficientPoolLiquidity { .. } => "insufficient_pool_liquidity",
+            Error::RecipientCannotReceive { .. } => "recipient_cannot_receive",
+            Error::SwapQuoteFailed(_) => "swap_quote_failed",
+            Error::SwapExecutionFailed(_) => "swap_execution_failed",
+            Error::SwapStateMismatch(_) => "swap_state_mismatch",
+        }
+    }
 }
 
 impl axum::response::IntoResponse for Error {
@@ -46,6 +100,10 @@ impl axum::response::IntoResponse for Error {
                 warn!("❌ Not found");
                 (StatusCode::NOT_FOUND, "Not found".to_string())
             }
+            Error::Unauthorized => {
+                warn!("❌ Unauthorized");
+                (StatusCode::UNAUTHORIZED, "Unauthorized".to_string())
+            }
             Error::ValidationError(msg) => {
                 warn!("❌ Validation error: {}", msg);
                 (
@@ -53,6 +111,26 @@ impl axum::response::IntoResponse for Error {
                     format!("Validation error: {}", msg),
                 )
             }
+            Error::DuplicateNullifier => {
+                warn!("❌ {}", self);
+                (StatusCode::CONFLICT, self.to_string())
+            }
+            Error::InsufficientPoolLiquidity { .. } => {
+                warn!("❌ {}", self);
+                (StatusCode::SERVICE_UNAVAILABLE, self.to_string())
+            }
+            Error::RecipientCannotReceive { .. } => {
+                warn!("❌ {}", self);
+                (StatusCode::BAD_REQUEST, self.to_string())
+            }
+            Error::SwapQuoteFailed(_) => {
+                warn!("❌ {}", self);
+                (StatusCode::BAD_GATEWAY, self.to_string())
+            }
+            Error::SwapExecutionFailed(_) | Error::SwapStateMismatch(_) => {
+                error!("❌ {}", self);
+                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
+            }
             Error::DatabaseError(msg) => {
                 error!("❌ Database error: {}", msg);
                 (
@@ -70,9 +148,12 @@ impl axum::response::IntoResponse for Error {
             }
         };
 
+        // The id is also on the request's tracing span, so support can grep
+        // the logs for the one a client reports
         let body = Json(json!({
-            "error": true,
-            "message": message
+            "error": self.code(),
+            "message": message,
+            "request_id": crate::request_id::current(),
         }));
 
         (status, body).into_response()
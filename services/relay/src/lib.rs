@@ -4,4 +4,5 @@
 //!
 
 pub mod error;
+pub mod request_id;
 pub mod swap;
@@ -5,6 +5,8 @@ mod config;
 mod db;
 mod error;
 mod planner;
+mod request_id;
+mod shutdown;
 mod solana;
 mod swap;
 mod worker;
@@ -13,6 +15,7 @@ use std::{net::SocketAddr, str::FromStr, sync::Arc};
 
 use axum::{
     body::Body,
+    extract::State,
     middleware::Next,
     response::{Json, Response},
     routing::{get, post},
@@ -34,9 +37,14 @@ use tracing::info;
 
 use crate::{
     claim_manager::ClaimFinder,
-    config::Config as RelayConfig,
-    db::repository::{PostgresJobRepository, PostgresNullifierRepository},
-    solana::SolanaService,
+    config::{Config as RelayConfig, ServerConfig},
+    db::repository::{
+        PostgresJobRepository, PostgresNullifierRepository, PostgresSwapReceiptRepository,
+    },
+    solana::{
+        subscription::{PubsubTransport, ResilientSubscription, SubscriptionTarget},
+        SolanaService,
+    },
 };
 
 #[derive(Clone)]
@@ -44,8 +52,17 @@ pub struct AppState {
     pub db_pool: db::DatabasePool,
     pub job_repo: Arc<PostgresJobRepository>,
     pub nullifier_repo: Arc<PostgresNullifierRepository>,
+    pub swap_receipt_repo: Arc<PostgresSwapReceiptRepository>,
     pub solana: Arc<SolanaService>,
     pub claim_finder: Option<Arc<ClaimFinder>>,
+    /// Websocket subscription to the scramble registry that triggers claim
+    /// refreshes between polls
+    pub claim_subscription: Option<Arc<ResilientSubscription>>,
+    /// Window scheduler settings, hot-reloadable via `/admin/reload-config`
+    pub window_config: Arc<std::sync::RwLock<worker::window_scheduler::WindowConfig>>,
+    /// Bounds concurrent job processing; its counts are reported on `/readyz`
+    pub job_limiter: Arc<worker::limiter::JobLimiter>,
+    pub admin_token: Option<String>,
 }
 
 impl AppState {
@@ -54,20 +71,19 @@ impl AppState {
         let relay_config = RelayConfig::load()?;
 
         // Database connection
-        let database_url = relay_config.database.url.clone();
-
-        let db_pool = db::connect(&database_url, relay_config.database.max_connections).await?;
+        let db_pool = db::connect_with_retry(&relay_config.database).await?;
         db::run_migrations(&db_pool).await?;
 
         // Create repositories
         let job_repo = Arc::new(PostgresJobRepository::new(db_pool.clone()));
         let nullifier_repo = Arc::new(PostgresNullifierRepository::new(db_pool.clone()));
+        let swap_receipt_repo = Arc::new(PostgresSwapReceiptRepository::new(db_pool.clone()));
 
         // Solana service
         let mut solana_service = SolanaService::new(relay_config.solana.clone()).await?;
 
         // Initialize ClaimFinder if PoW is enabled
-        let claim_finder =
+        let (claim_finder, claim_subscription) =
             if let Some(ref registry_id) = relay_config.solana.scramble_registry_program_id {
                 info!(
                     "Initializing PoW ClaimFinder with registry: {}",
@@ -86,20 +102,39 @@ impl AppState {
                 solana_service.set_claim_finder(finder.clone());
                 info!("✓ PoW ClaimFinder initialized successfully");
 
-                finder
+                let subscription = ResilientSubscription::new(
+                    Box::new(PubsubTransport::new(relay_config.solana.ws_url.clone())),
+                    SubscriptionTarget::Program(registry_program_id),
+                );
+
+                (finder, Some(Arc::new(subscription)))
             } else {
                 info!("PoW disabled - no scramble_registry_program_id configured");
-                None
+                (None, None)
             };
 
         let solana = Arc::new(solana_service);
 
+        let window_config = worker::window_scheduler::WindowConfig {
+            slot_patterns: relay_config.scheduler.slot_patterns.clone(),
+            min_batch_size: None, // No minimum - process whatever is ready
+            max_batch_size: relay_config.scheduler.max_batch_size,
+            poll_interval_secs: 1, // Check slot every second
+        };
+
         Ok(Self {
             db_pool,
             job_repo,
             nullifier_repo,
+            swap_receipt_repo,
             solana,
             claim_finder,
+            claim_subscription,
+            window_config: Arc::new(std::sync::RwLock::new(window_config)),
+            job_limiter: Arc::new(worker::limiter::JobLimiter::new(
+                relay_config.scheduler.max_concurrent_jobs,
+            )),
+            admin_token: relay_config.server.admin_token.clone(),
         })
     }
 
@@ -148,23 +183,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("Starting Cloak Relay Service");
 
-    // Load config for CORS settings
+    // Load config for CORS settings, refusing to start on a bad combination
     let relay_config = RelayConfig::load()?;
+    relay_config.validate()?;
 
     // Create application state with real connections
     let app_state = AppState::new().await?;
 
     // Configure CORS based on environment
-    let cors = create_cors_layer(&relay_config.server.cors_origins);
+    let cors = create_cors_layer(&relay_config.server);
 
     // Build our application with routes
     let app = Router::new()
         .route("/", get(root))
         .route("/health", get(health_check))
+        .route("/readyz", get(readiness_check))
         // Withdraw endpoint (with stricter rate limiting applied after with_state)
         .route("/withdraw", post(api::withdraw::handle_withdraw))
         .fallback(handle_404)
         .route("/status/:id", get(api::status::get_status))
+        // Fees and limits clients should validate against
+        .route("/params", get(api::params::get_params))
         // Miners API - backlog status
         .route("/backlog", get(api::backlog::get_backlog_status))
         // Validator Agent API
@@ -173,7 +212,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             post(api::validator_agent::create_withdraw_job),
         )
         .route("/jobs/:job_id", get(api::validator_agent::get_job))
+        .route(
+            "/jobs/:job_id/accounts",
+            get(api::validator_agent::get_job_accounts),
+        )
+        .route(
+            "/jobs/:job_id/claims-required",
+            get(api::validator_agent::get_job_claims_required),
+        )
+        .route(
+            "/jobs/:job_id/transaction",
+            get(api::validator_agent::get_job_transaction),
+        )
         .route("/submit", post(api::validator_agent::submit_tx))
+        // Admin API (bearer token, see RELAY_ADMIN_TOKEN)
+        .route("/admin/reload-config", post(api::admin::reload_config))
+        .route("/admin/failed-jobs", get(api::admin::get_failed_jobs))
         // Orchestration endpoint (planner-driven)
         .route(
             "/orchestrate/withdraw",
@@ -201,35 +255,83 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ))
         .layer(TraceLayer::new_for_http())
         .layer(axum::middleware::from_fn(log_errors))
+        .layer(axum::middleware::from_fn(request_id::assign_request_id))
         .with_state(app_state.clone())
         .layer(axum::middleware::map_response(handle_response_errors));
 
     // Spawn the window scheduler task to process jobs in batched windows
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
     let scheduler_state = app_state.clone();
-    tokio::spawn(async move {
+    let scheduler_shutdown = shutdown_rx.clone();
+    let scheduler_handle = tokio::spawn(async move {
         // Configure windowing: process when slot ends in 0 or 5
-        let window_config = worker::window_scheduler::WindowConfig {
-            slot_patterns: vec![0, 5], // Every ~5 slots (~2.5s)
-            min_batch_size: None,      // No minimum - process whatever is ready
-            max_batch_size: 50,        // Safety limit
-            poll_interval_secs: 1,     // Check slot every second
-        };
-
+        let window_config = scheduler_state.window_config.clone();
         let scheduler = Arc::new(worker::window_scheduler::WindowScheduler::new(
             scheduler_state,
             window_config,
         ));
 
-        scheduler.run().await;
+        scheduler.run(scheduler_shutdown).await;
     });
 
+    // Keep the cached PoW claim count fresh for /readyz
+    let claim_refresher_handle = app_state.claim_finder.clone().map(|finder| {
+        tokio::spawn(
+            finder.run_refresher(claim_manager::CLAIM_REFRESH_INTERVAL, shutdown_rx.clone()),
+        )
+    });
+
+    // Refresh the claim count as soon as the registry changes, not just on the poll
+    let claim_subscription_handle = app_state
+        .claim_finder
+        .clone()
+        .zip(app_state.claim_subscription.clone())
+        .map(|(finder, subscription)| {
+            let (updates_tx, updates_rx) = tokio::sync::mpsc::channel(64);
+            tokio::spawn(finder.run_notified_refresher(updates_rx));
+            let shutdown = shutdown_rx.clone();
+            tokio::spawn(async move { subscription.run(updates_tx, shutdown).await })
+        });
+
+    // Spawn the reaper that expires jobs stuck in the queue past max_job_age
+    let reaper =
+        worker::reaper::JobReaper::new(app_state.clone(), relay_config.server.max_job_age_seconds);
+    let reaper_handle = tokio::spawn(reaper.run(shutdown_rx));
+
     // Run the server
     let addr = SocketAddr::from(([0, 0, 0, 0], 3002));
     info!("Relay service listening on {}", addr);
     info!("Window scheduler spawned and running (processing on slot patterns: 0, 5)");
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            shutdown::shutdown_signal().await;
+            // Stop the scheduler from opening new windows while in-flight requests drain
+            let _ = shutdown_tx.send(true);
+        })
+        .await?;
+
+    info!("Server stopped accepting requests, waiting for window scheduler");
+    if let Err(e) = scheduler_handle.await {
+        tracing::error!("Window scheduler task failed: {}", e);
+    }
+    if let Err(e) = reaper_handle.await {
+        tracing::error!("Job reaper task failed: {}", e);
+    }
+    if let Some(handle) = claim_refresher_handle {
+        if let Err(e) = handle.await {
+            tracing::error!("PoW claim refresher task failed: {}", e);
+        }
+    }
+    if let Some(handle) = claim_subscription_handle {
+        if let Err(e) = handle.await {
+            tracing::error!("PoW claim subscription task failed: {}", e);
+        }
+    }
+
+    app_state.db_pool.close().await;
+    info!("Database pool closed, relay shut down cleanly");
 
     Ok(())
 }
@@ -269,20 +371,30 @@ fn rate_limit_general() -> GovernorLayer<SmartIpKeyExtractor, NoOpMiddleware> {
 }
 
 /// Create CORS layer based on configured origins
-fn create_cors_layer(cors_origins: &[String]) -> CorsLayer {
+fn create_cors_layer(server: &ServerConfig) -> CorsLayer {
+    let methods: Vec<axum::http::Method> = server
+        .cors_allowed_methods
+        .iter()
+        .filter_map(|method| match method.parse() {
+            Ok(method) => Some(method),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid CORS method: {}", method);
+                None
+            }
+        })
+        .collect();
+
     let mut cors = CorsLayer::new()
-        .allow_methods([
-            axum::http::Method::GET,
-            axum::http::Method::POST,
-            axum::http::Method::OPTIONS,
-        ])
+        .allow_methods(methods)
         .allow_headers([
             axum::http::header::CONTENT_TYPE,
             axum::http::header::AUTHORIZATION,
         ])
-        .max_age(std::time::Duration::from_secs(86400)); // 24 hours
+        .expose_headers([request_id::REQUEST_ID_HEADER])
+        .max_age(std::time::Duration::from_secs(server.cors_max_age_seconds));
 
     // Configure origins
+    let cors_origins = &server.cors_origins;
     if cors_origins.len() == 1 && cors_origins[0] == "*" {
         // Allow all origins in development (without credentials)
         cors = cors.allow_origin(Any);
@@ -308,8 +420,10 @@ async fn root() -> Json<Value> {
             "health": "GET /health",
             "withdraw": "POST /withdraw",
             "status": "GET /status/:id",
+            "params": "GET /params",
             "jobs_withdraw": "POST /jobs/withdraw",
             "get_job": "GET /jobs/:job_id",
+            "job_claims_required": "GET /jobs/:job_id/claims-required",
             "submit": "POST /submit"
         }
     }))
@@ -368,8 +482,9 @@ async fn handle_404() -> impl axum::response::IntoResponse {
     (
         StatusCode::NOT_FOUND,
         Json(json!({
-            "error": true,
-            "message": "Route not found"
+            "error": "not_found",
+            "message": "Route not found",
+            "request_id": request_id::current(),
         })),
     )
 }
@@ -381,3 +496,69 @@ async fn health_check() -> Json<Value> {
         "timestamp": chrono::Utc::now().to_rfc3339()
     }))
 }
+
+/// Readiness details for dashboards, including PoW claim availability so
+/// alerts can fire when miners stop producing claims
+async fn readiness_check(State(state): State<AppState>) -> Json<Value> {
+    let pow = match &state.claim_finder {
+        Some(finder) => json!({
+            "enabled": true,
+            "available_claims": finder.available_claim_count(),
+            "ws_connected": state.claim_subscription.as_ref().map(|s| s.is_connected()),
+            "ws_reconnects": state.claim_subscription.as_ref().map(|s| s.reconnects()),
+        }),
+        None => json!({ "enabled": false }),
+    };
+
+    Json(json!({
+        "status": "ok",
+        "pow": pow,
+        "treasury_fee_shortfalls": state.solana.treasury_fee_shortfalls(),
+        "jobs": {
+            "in_flight": state.job_limiter.in_flight(),
+            "waiting": state.job_limiter.waiting(),
+            "limit": state.job_limiter.limit(),
+        },
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{header, Method, Request},
+        routing::delete,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cors_layer_allows_configured_methods() {
+        let server = ServerConfig {
+            cors_origins: vec!["*".to_string()],
+            cors_allowed_methods: vec!["GET".to_string(), "DELETE".to_string()],
+            cors_max_age_seconds: 600,
+            ..Default::default()
+        };
+        let app = Router::new()
+            .route("/jobs/:job_id", delete(|| async {}))
+            .layer(create_cors_layer(&server));
+
+        let preflight = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/jobs/1")
+            .header(header::ORIGIN, "https://example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "DELETE")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(preflight).await.unwrap();
+
+        let allowed = response.headers()[header::ACCESS_CONTROL_ALLOW_METHODS]
+            .to_str()
+            .unwrap();
+        assert!(allowed.contains("DELETE"), "{}", allowed);
+        assert!(!allowed.contains("POST"), "{}", allowed);
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_MAX_AGE], "600");
+    }
+}
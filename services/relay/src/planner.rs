@@ -1,6 +1,8 @@
 use std::time::{Duration, Instant};
 
-use blake3::Hasher;
+use cloak_layouts::FeeSchedule;
+
+use crate::error::Error;
 
 /// Root metadata within the current acceptable window
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -33,23 +35,45 @@ pub struct Selected {
 
 pub mod orchestrator;
 
-/// Calculate fee based on mint decimals
-/// - Variable fee: 0.5% of amount for all cases
-/// - Note: This must match the ZK circuit's fee calculation (zk-guest-sp1/guest/src/encoding.rs)
-///   which validates the conservation law: outputs_sum + fee = amount
+/// Calculate the variable part of the fee (0.5% of amount)
+/// - Note: Together with the fixed fee this must match the ZK circuit's fee
+///   calculation (`cloak_layouts::fees`, shared with the guest and program); use
+///   `calculate_protocol_fee` for conservation checks
 #[inline(always)]
 pub fn calculate_fee(amount: u64, _decimals: u8) -> u64 {
-    // ZK circuit validates: outputs_sum + variable_fee = amount
-    // Must match guest/src/encoding.rs::calculate_fee()
-    // 0.5%
-    (amount.saturating_mul(5)) / 1_000
+    protocol_fee(amount, FeeSchedule::VARIABLE_ONLY)
+}
+
+/// Total fee the shield-pool program requires for a withdraw of `amount`:
+/// fixed 0.0025 SOL + 0.5% for native SOL pools, 0.5% only for SPL pools and
+/// swaps (the fixed part is paid in SOL separately).
+/// Must match the program's conservation check and guest/src/encoding.rs::calculate_fee()
+pub fn calculate_protocol_fee(amount: u64, is_native: bool) -> u64 {
+    if is_native {
+        protocol_fee(amount, FeeSchedule::NATIVE)
+    } else {
+        protocol_fee(amount, FeeSchedule::VARIABLE_ONLY)
+    }
+}
+
+/// Fee for `amount` under a configured per-mint `schedule`. Unlike the
+/// built-in schedules an override can overflow, which is rejected.
+pub fn calculate_scheduled_fee(amount: u64, schedule: FeeSchedule) -> Result<u64, Error> {
+    cloak_layouts::calculate_fee(amount, schedule)
+        .map_err(|e| Error::ValidationError(format!("Fee for amount {}: {}", amount, e)))
+}
+
+/// The protocol schedules charge at most 0.5% + 0.0025 SOL, which fits in a
+/// u64 for every amount, so the checked calculation cannot fail here
+fn protocol_fee(amount: u64, schedule: FeeSchedule) -> u64 {
+    cloak_layouts::calculate_fee(amount, schedule).unwrap_or(u64::MAX)
 }
 
-/// Legacy function for backwards compatibility (assumes 9 decimals / SOL)
-/// @deprecated Use calculate_fee(amount, decimals) instead
+/// Legacy function for backwards compatibility (assumes a native SOL withdraw)
+/// @deprecated Use calculate_protocol_fee(amount, is_native) instead
 #[inline(always)]
 pub fn calculate_fee_legacy(amount: u64) -> u64 {
-    calculate_fee(amount, 9)
+    calculate_protocol_fee(amount, true)
 }
 
 /// Select a single note to satisfy target_amount.
@@ -120,8 +144,23 @@ pub fn select_note(
     best.map(|n| Selected { note: n.clone() })
 }
 
+/// Compute outputs_hash exactly as the guest does: BLAKE3 over each output's
+/// `cloak_layouts::encode_output` bytes (address:32 || amount:u64_le), in
+/// `cloak_layouts::hash_order`
+pub fn compute_outputs_hash(outputs: &[Output]) -> [u8; 32] {
+    let mut ordered: Vec<&Output> = outputs.iter().collect();
+    cloak_layouts::hash_order(&mut ordered, |output| (output.address, output.amount));
+    let mut hasher = cloak_layouts::domains::hasher(cloak_layouts::domains::OUTPUTS_HASH);
+    for output in ordered {
+        hasher.update(&cloak_layouts::encode_output(
+            &output.address,
+            output.amount,
+        ));
+    }
+    *hasher.finalize().as_bytes()
+}
+
 /// Compute single-output list and outputs_hash.
-/// Hash = BLAKE3(address:32 || amount:u64_le)
 pub fn compute_outputs_single(
     recipient_addr: [u8; 32],
     recipient_amount: u64,
@@ -130,12 +169,7 @@ pub fn compute_outputs_single(
         address: recipient_addr,
         amount: recipient_amount,
     }];
-    let mut hasher = Hasher::new();
-    hasher.update(&recipient_addr);
-    hasher.update(&recipient_amount.to_le_bytes());
-    let h = hasher.finalize();
-    let mut out_hash = [0u8; 32];
-    out_hash.copy_from_slice(h.as_bytes());
+    let out_hash = compute_outputs_hash(&outputs);
     (outputs, out_hash)
 }
 
@@ -179,6 +213,131 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_protocol_fee_matches_guest() {
+        // guest/src/encoding.rs::calculate_fee: 2_500_000 + amount * 5 / 1_000
+        for (amount, expected) in [
+            (0u64, 2_500_000u64),
+            (199, 2_500_000),
+            (200, 2_500_001),
+            (10_000_000, 2_550_000),
+            (1_000_000_000, 7_500_000),
+            (123_456_789_012, 619_783_945),
+        ] {
+            assert_eq!(calculate_protocol_fee(amount, true), expected);
+            assert_eq!(calculate_protocol_fee(amount, false), expected - 2_500_000);
+        }
+    }
+
+    #[test]
+    fn test_protocol_fee_near_u64_max() {
+        // `amount * 5` alone overflows a u64 here
+        let amount = u64::MAX - 1;
+        assert_eq!(
+            calculate_fee(amount, 9),
+            (u128::from(amount) * 5 / 1_000) as u64
+        );
+        assert_eq!(
+            calculate_protocol_fee(u64::MAX, true),
+            u64::MAX / 200 + 2_500_000
+        );
+        assert_eq!(calculate_protocol_fee(u64::MAX, false), u64::MAX / 200);
+    }
+
+    #[test]
+    fn test_relay_fees_match_guest() {
+        // guest/src/encoding.rs::calculate_fee
+        let guest_fee = |amount: u64| 2_500_000 + (amount * 5) / 1_000;
+
+        for amount in [
+            10_000_000u64,
+            12_345_679,
+            100_000_000,
+            1_000_000_000,
+            2_500_000_001,
+            1_000_000_000_000,
+        ] {
+            let fee = guest_fee(amount);
+            assert_eq!(calculate_protocol_fee(amount, true), fee, "{}", amount);
+            assert_eq!(calculate_fee_legacy(amount), fee, "{}", amount);
+
+            // Planned outputs satisfy the guest's conservation check
+            let (planned_fee, recipient_amount) = compute_fee_and_recipient_amount(amount);
+            assert_eq!(planned_fee, fee, "{}", amount);
+            assert_eq!(recipient_amount + fee, amount, "{}", amount);
+        }
+    }
+
+    // Guest fixtures hash with plain BLAKE3, in input order
+    #[cfg(not(any(feature = "domain-separation", feature = "sorted-outputs")))]
+    #[test]
+    fn test_outputs_hash_matches_guest_vectors() {
+        #[derive(serde::Deserialize)]
+        struct Vector {
+            name: String,
+            inputs: serde_json::Value,
+        }
+
+        // Fixtures the guest's own tests prove against
+        let vectors: Vec<Vector> = serde_json::from_str(include_str!(
+            "../../../packages/zk-guest-sp1/guest/tests/vectors/regular.json"
+        ))
+        .unwrap();
+        assert!(!vectors.is_empty());
+
+        for vector in vectors {
+            let outputs: Vec<Output> = vector.inputs["outputs"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|o| Output {
+                    address: hex::decode(o["address"].as_str().unwrap())
+                        .unwrap()
+                        .try_into()
+                        .unwrap(),
+                    amount: o["amount"].as_u64().unwrap(),
+                })
+                .collect();
+            let expected = vector.inputs["public"]["outputs_hash"].as_str().unwrap();
+
+            assert_eq!(
+                hex::encode(compute_outputs_hash(&outputs)),
+                expected,
+                "{}",
+                vector.name
+            );
+
+            // Same outputs through the guest-side host encoding
+            let host_outputs: Vec<zk_guest_sp1_host::encoding::Output> = outputs
+                .iter()
+                .map(|o| zk_guest_sp1_host::encoding::Output {
+                    address: o.address,
+                    amount: o.amount,
+                })
+                .collect();
+            assert_eq!(
+                zk_guest_sp1_host::encoding::compute_outputs_hash(&host_outputs),
+                compute_outputs_hash(&outputs),
+                "{}",
+                vector.name
+            );
+        }
+
+        // Order is part of the hash
+        let a = Output {
+            address: [1; 32],
+            amount: 1,
+        };
+        let b = Output {
+            address: [2; 32],
+            amount: 2,
+        };
+        assert_ne!(
+            compute_outputs_hash(&[a.clone(), b.clone()]),
+            compute_outputs_hash(&[b, a])
+        );
+    }
+
     #[test]
     fn test_conservation_fee_and_outputs_hash() {
         // amount is note amount; recipient gets amount - fee
@@ -192,7 +351,7 @@ mod tests {
         assert_eq!(outs[0].amount, recipient_amount);
 
         // Recompute hash like on-chain: H(addr||amount_le)
-        let mut hasher = Hasher::new();
+        let mut hasher = cloak_layouts::domains::hasher(cloak_layouts::domains::OUTPUTS_HASH);
         hasher.update(&addr);
         hasher.update(&recipient_amount.to_le_bytes());
         let h2 = hasher.finalize();
@@ -214,7 +373,7 @@ mod tests {
         let outputs_hash = [0xCCu8; 32];
         let amount = 2_000_000_000u64;
         let (fee, recipient_amount) = compute_fee_and_recipient_amount(amount);
-        assert_eq!(fee, calculate_fee(amount, 9)); // SOL has 9 decimals
+        assert_eq!(fee, calculate_protocol_fee(amount, true));
         assert_eq!(recipient_amount + fee, amount);
 
         let buf = build_public_inputs_104(&root, &nf, &outputs_hash, amount);
@@ -1,16 +1,16 @@
 use axum::{extract::State, response::IntoResponse, Json};
-use blake3::Hasher;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 use uuid::Uuid;
 
 use crate::{
+    api::parse_hex32_field,
     db::{
         models::CreateJob,
         repository::{JobRepository, NullifierRepository},
     },
     error::Error,
-    planner::{build_public_inputs_104, calculate_fee_legacy},
+    planner::{build_public_inputs_104, calculate_fee_legacy, compute_outputs_single},
     AppState,
 };
 
@@ -25,31 +25,37 @@ pub struct OrchestrateRequest {
 #[derive(Debug, Serialize)]
 pub struct OrchestrateResponse {
     pub job_id: Uuid,
+    /// Handle for polling the job; `/status/:id` looks jobs up by request ID
+    pub request_id: Uuid,
+    pub status_url: String,
     pub status: String,
     pub root_used: String,
     pub recipient_amount: u64,
 }
 
+impl OrchestrateResponse {
+    /// Response for a job that was queued for the worker
+    fn queued(job_id: Uuid, request_id: Uuid, root_used: String, recipient_amount: u64) -> Self {
+        Self {
+            job_id,
+            request_id,
+            status_url: format!("/status/{}", request_id),
+            status: "queued".to_string(),
+            root_used,
+            recipient_amount,
+        }
+    }
+}
+
 pub async fn orchestrate_withdraw(
     State(state): State<AppState>,
     Json(req): Json<OrchestrateRequest>,
 ) -> Result<impl IntoResponse, Error> {
     // Root is provided explicitly
-    let root_hex = req.root_hex.clone();
-    if root_hex.len() != 64 {
-        return Err(Error::ValidationError("root must be 64 hex chars".into()));
-    }
-    let root_bytes = hex::decode(&root_hex).map_err(|e| Error::ValidationError(e.to_string()))?;
-    let mut root_arr = [0u8; 32];
-    root_arr.copy_from_slice(&root_bytes);
+    let root_arr = parse_hex32_field("root_hex", &req.root_hex)?;
 
     // Parse nullifier
-    if req.nf_hex.len() != 64 {
-        return Err(Error::ValidationError("nf_hex must be 64 hex chars".into()));
-    }
-    let nf_bytes = hex::decode(&req.nf_hex).map_err(|e| Error::ValidationError(e.to_string()))?;
-    let mut nf_arr = [0u8; 32];
-    nf_arr.copy_from_slice(&nf_bytes);
+    let nf_arr = parse_hex32_field("nf_hex", &req.nf_hex)?;
 
     // Compute recipient amount and outputs_hash
     let fee = calculate_fee_legacy(req.amount);
@@ -67,10 +73,9 @@ pub async fn orchestrate_withdraw(
             "recipient must decode to 32 bytes".into(),
         ));
     }
-    let mut outputs_hasher = Hasher::new();
-    outputs_hasher.update(&recipient_bytes);
-    outputs_hasher.update(&recipient_amount.to_le_bytes());
-    let outputs_hash = *outputs_hasher.finalize().as_bytes();
+    let mut recipient_addr = [0u8; 32];
+    recipient_addr.copy_from_slice(&recipient_bytes);
+    let (_, outputs_hash) = compute_outputs_single(recipient_addr, recipient_amount);
 
     // Build canonical public inputs (104 bytes)
     let public_104 = build_public_inputs_104(&root_arr, &nf_arr, &outputs_hash, req.amount);
@@ -94,6 +99,7 @@ pub async fn orchestrate_withdraw(
             nullifier: nf_arr.to_vec(),
             amount: req.amount as i64,
             outputs_hash: outputs_hash.to_vec(),
+            priority: 0,
         })
         .await?;
 
@@ -103,11 +109,32 @@ pub async fn orchestrate_withdraw(
         .create_nullifier(nf_arr.to_vec(), job.id)
         .await;
 
+    // Proving and submission happen in the worker; return right away so the
+    // client polls the status endpoint instead of holding the connection
     info!("Orchestrated withdraw job {} queued", job.id);
-    Ok(Json(OrchestrateResponse {
-        job_id: job.id,
-        status: "queued".to_string(),
-        root_used: root_hex,
+    Ok(Json(OrchestrateResponse::queued(
+        job.id,
+        request_id,
+        req.root_hex.clone(),
         recipient_amount,
-    }))
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queued_response_is_pollable_by_request_id() {
+        let job_id = Uuid::new_v4();
+        let request_id = Uuid::new_v4();
+        let resp = OrchestrateResponse::queued(job_id, request_id, "00".repeat(32), 42);
+
+        assert_eq!(resp.status, "queued");
+        assert_eq!(resp.status_url, format!("/status/{}", request_id));
+
+        let body = serde_json::to_value(&resp).unwrap();
+        assert_eq!(body["request_id"], request_id.to_string());
+        assert_eq!(body["job_id"], job_id.to_string());
+    }
 }
@@ -0,0 +1,84 @@
+//! Per-request ids so a client-reported failure can be found in the logs
+
+use axum::{
+    body::Body,
+    http::{HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+/// Response header carrying the request id
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    static REQUEST_ID: Uuid;
+}
+
+/// Id of the request being handled, when called under `assign_request_id`
+pub fn current() -> Option<Uuid> {
+    REQUEST_ID.try_with(|id| *id).ok()
+}
+
+/// Middleware giving each request a fresh id, recorded on its tracing span,
+/// echoed in the `x-request-id` header and included in error bodies
+pub async fn assign_request_id(request: Request<Body>, next: Next) -> Response {
+    let id = Uuid::new_v4();
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+
+    let mut response = REQUEST_ID
+        .scope(id, next.run(request))
+        .instrument(span)
+        .await;
+    if let Ok(value) = HeaderValue::from_str(&id.to_string()) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::error::Error;
+
+    #[tokio::test]
+    async fn test_error_body_carries_request_id_header() {
+        let app = Router::new()
+            .route("/missing", get(|| async { Err::<(), _>(Error::NotFound) }))
+            .layer(axum::middleware::from_fn(assign_request_id));
+
+        let request = Request::builder()
+            .uri("/missing")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let header = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["error"], "not_found");
+        assert_eq!(body["request_id"], header);
+        assert!(Uuid::parse_str(&header).is_ok());
+    }
+
+    #[test]
+    fn test_no_request_id_outside_middleware() {
+        assert_eq!(current(), None);
+    }
+}
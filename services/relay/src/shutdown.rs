@@ -0,0 +1,120 @@
+use std::{future::Future, time::Duration};
+
+use tokio::sync::watch;
+use tracing::info;
+
+/// Resolves when the process receives Ctrl+C or SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl+C, shutting down"),
+        _ = terminate => info!("Received SIGTERM, shutting down"),
+    }
+}
+
+/// Run `tick` every `interval` until shutdown is signalled.
+///
+/// Shutdown is only observed between ticks, so a tick that is already running
+/// (e.g. a batch being submitted) always completes before this returns.
+pub async fn run_until_shutdown<F, Fut>(
+    interval: Duration,
+    mut shutdown: watch::Receiver<bool>,
+    mut tick: F,
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    while !*shutdown.borrow() {
+        tick().await;
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            // A dropped sender also means nobody is left to keep us running
+            _ = shutdown.changed() => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use tokio::sync::{oneshot, Mutex};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_flight_batch_completes_before_exit() {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let (started_tx, started_rx) = oneshot::channel::<()>();
+        let (release_tx, release_rx) = oneshot::channel::<()>();
+        let started_tx = Arc::new(Mutex::new(Some(started_tx)));
+        let release_rx = Arc::new(Mutex::new(Some(release_rx)));
+        let batch_done = Arc::new(AtomicBool::new(false));
+
+        let done = Arc::clone(&batch_done);
+        let runner = tokio::spawn(run_until_shutdown(
+            Duration::from_millis(10),
+            shutdown_rx,
+            move || {
+                let started_tx = Arc::clone(&started_tx);
+                let release_rx = Arc::clone(&release_rx);
+                let done = Arc::clone(&done);
+                async move {
+                    if let Some(tx) = started_tx.lock().await.take() {
+                        let _ = tx.send(());
+                    }
+                    if let Some(rx) = release_rx.lock().await.take() {
+                        let _ = rx.await;
+                    }
+                    done.store(true, Ordering::SeqCst);
+                }
+            },
+        ));
+
+        // Signal shutdown while the first batch is still in flight
+        started_rx.await.unwrap();
+        shutdown_tx.send(true).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!runner.is_finished());
+        assert!(!batch_done.load(Ordering::SeqCst));
+
+        release_tx.send(()).unwrap();
+        runner.await.unwrap();
+        assert!(batch_done.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_no_tick_after_shutdown() {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        shutdown_tx.send(true).unwrap();
+
+        let ticked = AtomicBool::new(false);
+        let flag = &ticked;
+        run_until_shutdown(Duration::from_millis(10), shutdown_rx, move || async move {
+            flag.store(true, Ordering::SeqCst);
+        })
+        .await;
+
+        assert!(!ticked.load(Ordering::SeqCst));
+    }
+}
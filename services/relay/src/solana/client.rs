@@ -3,12 +3,15 @@ use std::time::Duration;
 use async_trait::async_trait;
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
-    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
-    transaction::Transaction,
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
 };
+use subtle::{Choice, ConstantTimeEq};
 use tracing::{error, info, warn};
 
-use super::SolanaClient;
+use super::{parse_commitment, SolanaClient};
 use crate::{config::SolanaConfig, error::Error};
 
 pub struct RpcSolanaClient {
@@ -20,12 +23,7 @@ impl RpcSolanaClient {
     pub async fn new(config: &SolanaConfig) -> Result<Self, Error> {
         info!("Connecting to Solana RPC");
 
-        let commitment = match config.commitment.as_str() {
-            "processed" => CommitmentConfig::processed(),
-            "confirmed" => CommitmentConfig::confirmed(),
-            "finalized" => CommitmentConfig::finalized(),
-            _ => CommitmentConfig::confirmed(),
-        };
+        let commitment = parse_commitment(&config.commitment);
 
         let client = RpcClient::new_with_commitment(config.rpc_url.clone(), commitment);
 
@@ -66,6 +64,15 @@ impl SolanaClient for RpcSolanaClient {
     async fn send_and_confirm_transaction(
         &self,
         transaction: &Transaction,
+    ) -> Result<Signature, Error> {
+        self.send_and_confirm_transaction_with_commitment(transaction, self.commitment)
+            .await
+    }
+
+    async fn send_and_confirm_transaction_with_commitment(
+        &self,
+        transaction: &Transaction,
+        commitment: CommitmentConfig,
     ) -> Result<Signature, Error> {
         // First send the transaction
         let signature = self
@@ -88,7 +95,7 @@ impl SolanaClient for RpcSolanaClient {
 
             match self
                 .client
-                .confirm_transaction_with_spinner(&signature, &blockhash, self.commitment)
+                .confirm_transaction_with_spinner(&signature, &blockhash, commitment)
                 .await
             {
                 Ok(_) => {
@@ -172,21 +179,7 @@ impl SolanaClient for RpcSolanaClient {
                 }
 
                 // Search only in the nullifier section (skip first 4 bytes)
-                // Only check up to the count
-                let nullifier_section = &data[4..];
-                let max_check = std::cmp::min(count, nullifier_section.len() / 32);
-
-                for i in 0..max_check {
-                    let offset = i * 32;
-                    if offset + 32 <= nullifier_section.len() {
-                        let chunk = &nullifier_section[offset..offset + 32];
-                        if chunk == nullifier {
-                            return Ok(true);
-                        }
-                    }
-                }
-
-                Ok(false)
+                Ok(shard_contains_nullifier(&data[4..], count, nullifier))
             }
             Err(e) => {
                 // If account doesn't exist, nullifier doesn't exist either
@@ -208,66 +201,101 @@ impl SolanaClient for RpcSolanaClient {
             .await
             .map_err(|e| Error::InternalServerError(e.to_string()))
     }
+
+    async fn get_account_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<solana_sdk::account::Account, Error> {
+        self.client
+            .get_account_with_commitment(pubkey, commitment)
+            .await
+            .map_err(|e| Error::InternalServerError(e.to_string()))?
+            .value
+            .ok_or_else(|| {
+                Error::InternalServerError(format!("AccountNotFound: pubkey={}", pubkey))
+            })
+    }
+
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Option<TransactionError>, Error> {
+        let response = self
+            .client
+            .simulate_transaction(transaction)
+            .await
+            .map_err(|e| {
+                Error::InternalServerError(format!("simulate_transaction failed: {}", e))
+            })?;
+
+        Ok(response.value.err.map(Into::into))
+    }
+}
+
+/// Check the first `count` 32-byte entries of a shard's nullifier section.
+///
+/// Uses constant-time equality and scans every entry, so timing does not
+/// reveal whether or where the nullifier matched. The on-chain
+/// `NullifierShard::contains_nullifier` keeps plain `==` to save compute units.
+fn shard_contains_nullifier(nullifier_section: &[u8], count: usize, nullifier: &[u8]) -> bool {
+    nullifier_section
+        .chunks_exact(32)
+        .take(count)
+        .fold(Choice::from(0), |found, chunk| {
+            found | chunk.ct_eq(nullifier)
+        })
+        .into()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_shard_contains_nullifier() {
+        let nullifiers = [[0x11u8; 32], [0x22u8; 32], [0x33u8; 32]];
+        let section = nullifiers.concat();
+
+        for nf in &nullifiers {
+            assert!(shard_contains_nullifier(&section, 3, nf));
+        }
+
+        // Entries beyond the stored count are ignored
+        assert!(!shard_contains_nullifier(&section, 2, &[0x33u8; 32]));
+
+        // Differs only in the last byte
+        let mut near_miss = [0x22u8; 32];
+        near_miss[31] = 0x23;
+        assert!(!shard_contains_nullifier(&section, 3, &near_miss));
+
+        // Truncated trailing entry and wrong-length input never match
+        assert!(!shard_contains_nullifier(&section[..80], 3, &[0x33u8; 32]));
+        assert!(!shard_contains_nullifier(&section, 3, &[0x11u8; 31]));
+        assert!(!shard_contains_nullifier(&[], 0, &[0x11u8; 32]));
+    }
+
     #[test]
     fn test_commitment_config_parsing() {
         let config1 = SolanaConfig {
             commitment: "processed".to_string(),
-            rpc_url: "http://localhost:8899".to_string(),
-            ws_url: "ws://localhost:8900".to_string(),
-            program_id: "11111111111111111111111111111111".to_string(),
-            mint_address: None,
-            withdraw_authority: None,
-            priority_micro_lamports: 1000,
-            jito_tip_lamports: 0,
-            max_retries: 3,
-            retry_delay_ms: 1000,
-            scramble_registry_program_id: None,
-            pool_address: Some("11111111111111111111111111111111".to_string()),
-            treasury_address: Some("11111111111111111111111111111111".to_string()),
-            roots_ring_address: Some("11111111111111111111111111111111".to_string()),
-            nullifier_shard_address: Some("11111111111111111111111111111111".to_string()),
+            deposit_commitment: "processed".to_string(),
+            withdraw_commitment: "processed".to_string(),
+            ..Default::default()
         };
 
         let config2 = SolanaConfig {
             commitment: "confirmed".to_string(),
-            rpc_url: "http://localhost:8899".to_string(),
-            ws_url: "ws://localhost:8900".to_string(),
-            program_id: "11111111111111111111111111111111".to_string(),
-            mint_address: None,
-            withdraw_authority: None,
-            priority_micro_lamports: 1000,
-            jito_tip_lamports: 0,
-            max_retries: 3,
-            retry_delay_ms: 1000,
-            scramble_registry_program_id: None,
-            pool_address: Some("11111111111111111111111111111111".to_string()),
-            treasury_address: Some("11111111111111111111111111111111".to_string()),
-            roots_ring_address: Some("11111111111111111111111111111111".to_string()),
-            nullifier_shard_address: Some("11111111111111111111111111111111".to_string()),
+            deposit_commitment: "confirmed".to_string(),
+            withdraw_commitment: "confirmed".to_string(),
+            ..Default::default()
         };
 
         let config3 = SolanaConfig {
             commitment: "finalized".to_string(),
-            rpc_url: "http://localhost:8899".to_string(),
-            ws_url: "ws://localhost:8900".to_string(),
-            program_id: "11111111111111111111111111111111".to_string(),
-            withdraw_authority: None,
-            priority_micro_lamports: 1000,
-            jito_tip_lamports: 0,
-            max_retries: 3,
-            retry_delay_ms: 1000,
-            scramble_registry_program_id: None,
-            pool_address: Some("11111111111111111111111111111111".to_string()),
-            treasury_address: Some("11111111111111111111111111111111".to_string()),
-            roots_ring_address: Some("11111111111111111111111111111111".to_string()),
-            nullifier_shard_address: Some("11111111111111111111111111111111".to_string()),
-            mint_address: None, // add spl address we wanat to
+            deposit_commitment: "finalized".to_string(),
+            withdraw_commitment: "finalized".to_string(),
+            ..Default::default()
         };
 
         // Test commitment parsing
@@ -0,0 +1,128 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+use solana_sdk::signature::Keypair;
+
+use crate::config::FeePayerStrategy;
+
+/// Picks which fee payer of a pool of `len` signs the next job
+pub trait FeePayerSelector: Send + Sync {
+    fn select(&self, len: usize) -> usize;
+}
+
+/// Cycles through the payers in order
+#[derive(Default)]
+pub struct RoundRobin {
+    next: AtomicUsize,
+}
+
+impl FeePayerSelector for RoundRobin {
+    fn select(&self, len: usize) -> usize {
+        self.next.fetch_add(1, Ordering::Relaxed) % len
+    }
+}
+
+/// Picks the payer that has gone longest without being used
+#[derive(Default)]
+pub struct LeastRecentlyUsed {
+    // Logical clock per payer; 0 = never used
+    state: Mutex<(u64, Vec<u64>)>,
+}
+
+impl FeePayerSelector for LeastRecentlyUsed {
+    fn select(&self, len: usize) -> usize {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (clock, last_used) = &mut *guard;
+        last_used.resize(len, 0);
+
+        let idx = last_used
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, used)| **used)
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+
+        *clock += 1;
+        last_used[idx] = *clock;
+        idx
+    }
+}
+
+/// Fee-payer keypairs plus the strategy used to spread jobs across them
+pub struct FeePayerPool {
+    payers: Vec<Keypair>,
+    selector: Box<dyn FeePayerSelector>,
+}
+
+impl FeePayerPool {
+    pub fn new(payers: Vec<Keypair>, strategy: FeePayerStrategy) -> Self {
+        let selector: Box<dyn FeePayerSelector> = match strategy {
+            FeePayerStrategy::RoundRobin => Box::<RoundRobin>::default(),
+            FeePayerStrategy::LeastRecentlyUsed => Box::<LeastRecentlyUsed>::default(),
+        };
+        Self { payers, selector }
+    }
+
+    /// Payer for the next job, or `None` when no keypair is configured
+    pub fn select(&self) -> Option<&Keypair> {
+        match self.payers.len() {
+            0 => None,
+            1 => self.payers.first(),
+            len => self.payers.get(self.selector.select(len)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+    use super::*;
+
+    fn pool(n: usize, strategy: FeePayerStrategy) -> (FeePayerPool, Vec<Pubkey>) {
+        let payers: Vec<Keypair> = (0..n).map(|_| Keypair::new()).collect();
+        let pubkeys = payers.iter().map(|kp| kp.pubkey()).collect();
+        (FeePayerPool::new(payers, strategy), pubkeys)
+    }
+
+    fn picks(pool: &FeePayerPool, jobs: usize) -> Vec<Pubkey> {
+        (0..jobs).map(|_| pool.select().unwrap().pubkey()).collect()
+    }
+
+    #[test]
+    fn test_round_robin_cycles_over_jobs() {
+        let (pool, keys) = pool(3, FeePayerStrategy::RoundRobin);
+        assert_eq!(
+            picks(&pool, 7),
+            vec![keys[0], keys[1], keys[2], keys[0], keys[1], keys[2], keys[0]]
+        );
+    }
+
+    #[test]
+    fn test_lru_spreads_evenly_over_jobs() {
+        let (pool, keys) = pool(3, FeePayerStrategy::LeastRecentlyUsed);
+        let chosen = picks(&pool, 9);
+        for key in &keys {
+            assert_eq!(chosen.iter().filter(|k| *k == key).count(), 3);
+        }
+        // Never reuse a payer while another one is idle
+        assert!(chosen
+            .windows(3)
+            .all(|w| w[0] != w[1] && w[1] != w[2] && w[0] != w[2]));
+    }
+
+    #[test]
+    fn test_single_payer_always_selected() {
+        for strategy in [
+            FeePayerStrategy::RoundRobin,
+            FeePayerStrategy::LeastRecentlyUsed,
+        ] {
+            let (pool, keys) = pool(1, strategy);
+            assert_eq!(picks(&pool, 4), vec![keys[0]; 4]);
+        }
+        let (empty, _) = pool(0, FeePayerStrategy::RoundRobin);
+        assert!(empty.select().is_none());
+    }
+}
@@ -0,0 +1,215 @@
+//! Jito bundle tip sizing
+//!
+//! A fixed tip is often too low to land under contention, so the percentile
+//! strategy tracks the median landed tip Jito reports on each submission and
+//! tips a percentile of that recent window, capped by config.
+
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::{config::JitoTipStrategy, error::Error};
+
+/// Bundles sent within this long of the last tip floor fetch reuse its sample
+pub const TIP_FLOOR_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A slow tip floor endpoint must not hold up the bundle
+const TIP_FLOOR_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Smallest tip Jito accepts for a bundle
+pub const MIN_TIP_LAMPORTS: u64 = 1_000;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// One tip floor snapshot; Jito reports tips in SOL
+#[derive(Debug, Deserialize)]
+struct TipFloor {
+    landed_tips_50th_percentile: f64,
+}
+
+pub struct JitoTipSizer {
+    strategy: JitoTipStrategy,
+    base_tip_lamports: u64,
+    max_tip_lamports: u64,
+    /// Median landed tip (lamports) seen at each recent refresh, oldest first
+    recent_tips: Mutex<VecDeque<u64>>,
+    /// When the tip floor was last fetched, successfully or not
+    last_refresh: Mutex<Option<Instant>>,
+    tip_floor_url: String,
+    http: reqwest::Client,
+}
+
+impl JitoTipSizer {
+    pub fn new(
+        strategy: JitoTipStrategy,
+        base_tip_lamports: u64,
+        max_tip_lamports: u64,
+        tip_floor_url: String,
+    ) -> Self {
+        Self {
+            strategy,
+            base_tip_lamports,
+            max_tip_lamports,
+            recent_tips: Mutex::new(VecDeque::new()),
+            last_refresh: Mutex::new(None),
+            tip_floor_url,
+            http: reqwest::Client::builder()
+                .timeout(TIP_FLOOR_TIMEOUT)
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+        }
+    }
+
+    /// Add a landed-tip sample, dropping the oldest beyond the window
+    pub fn record(&self, tip_lamports: u64) {
+        let JitoTipStrategy::Percentile { recent_window, .. } = self.strategy else {
+            return;
+        };
+        let mut recent = self.recent_tips.lock().unwrap();
+        recent.push_back(tip_lamports);
+        while recent.len() > recent_window.max(1) {
+            recent.pop_front();
+        }
+    }
+
+    /// Tip for the next bundle. Falls back to the base tip until a sample
+    /// has been recorded.
+    pub fn tip_lamports(&self) -> u64 {
+        match self.strategy {
+            JitoTipStrategy::Fixed => self.base_tip_lamports,
+            JitoTipStrategy::Percentile { percentile, .. } => {
+                let samples: Vec<u64> = self.recent_tips.lock().unwrap().iter().copied().collect();
+                let ceiling = self.max_tip_lamports.max(MIN_TIP_LAMPORTS);
+                percentile_tip(&samples, percentile)
+                    .unwrap_or(self.base_tip_lamports)
+                    .clamp(MIN_TIP_LAMPORTS, ceiling)
+            }
+        }
+    }
+
+    /// Record the current tip floor (percentile strategy only), at most once
+    /// per `TIP_FLOOR_REFRESH_INTERVAL`. A failed fetch keeps sizing from the
+    /// existing window.
+    pub async fn refresh(&self) {
+        if self.strategy == JitoTipStrategy::Fixed || !self.claim_refresh(Instant::now()) {
+            return;
+        }
+        match fetch_median_landed_tip(&self.http, &self.tip_floor_url).await {
+            Ok(tip) => {
+                debug!("Jito median landed tip: {} lamports", tip);
+                self.record(tip);
+            }
+            Err(e) => warn!("Failed to refresh Jito tip floor: {}", e),
+        }
+    }
+
+    /// Whether a refresh at `now` is due; if so, later callers within the
+    /// interval skip theirs
+    fn claim_refresh(&self, now: Instant) -> bool {
+        let mut last = self.last_refresh.lock().unwrap();
+        if last.is_some_and(|at| now.duration_since(at) < TIP_FLOOR_REFRESH_INTERVAL) {
+            return false;
+        }
+        *last = Some(now);
+        true
+    }
+}
+
+/// Nearest-rank `percentile` (0-100) of `samples`, or None when empty
+pub fn percentile_tip(samples: &[u64], percentile: u8) -> Option<u64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let rank = (usize::from(percentile.min(100)) * sorted.len())
+        .div_ceil(100)
+        .max(1);
+    Some(sorted[rank - 1])
+}
+
+async fn fetch_median_landed_tip(http: &reqwest::Client, url: &str) -> Result<u64, Error> {
+    let floors: Vec<TipFloor> = http
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| Error::NetworkError(format!("Jito tip floor request failed: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| Error::SerializationError(format!("Invalid Jito tip floor: {}", e)))?;
+    let latest = floors
+        .last()
+        .ok_or_else(|| Error::SerializationError("Empty Jito tip floor".to_string()))?;
+    Ok((latest.landed_tips_50th_percentile * LAMPORTS_PER_SOL).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_tip_within_bounds() {
+        let sizer = JitoTipSizer::new(
+            JitoTipStrategy::Percentile {
+                percentile: 75,
+                recent_window: 4,
+            },
+            10_000,
+            500_000,
+            String::new(),
+        );
+
+        // No data yet: the configured base tip
+        assert_eq!(sizer.tip_lamports(), 10_000);
+
+        // The first sample falls out of the 4-sample window
+        for tip in [9_000_000, 20_000, 40_000, 80_000, 60_000] {
+            sizer.record(tip);
+        }
+        assert_eq!(sizer.tip_lamports(), 60_000);
+
+        // A spike is capped at the ceiling
+        for _ in 0..4 {
+            sizer.record(2_000_000);
+        }
+        assert_eq!(sizer.tip_lamports(), 500_000);
+
+        // Dust samples are raised to Jito's minimum
+        for _ in 0..4 {
+            sizer.record(10);
+        }
+        assert_eq!(sizer.tip_lamports(), MIN_TIP_LAMPORTS);
+    }
+
+    #[test]
+    fn test_fixed_tip_ignores_samples() {
+        let sizer = JitoTipSizer::new(JitoTipStrategy::Fixed, 100_000, 1_000_000, String::new());
+        sizer.record(900_000);
+        assert_eq!(sizer.tip_lamports(), 100_000);
+    }
+
+    #[test]
+    fn test_tip_floor_refreshes_once_per_interval() {
+        let sizer = JitoTipSizer::new(JitoTipStrategy::Fixed, 100_000, 1_000_000, String::new());
+        let t0 = Instant::now();
+
+        assert!(sizer.claim_refresh(t0));
+        assert!(!sizer.claim_refresh(t0 + Duration::from_secs(1)));
+        assert!(sizer.claim_refresh(t0 + TIP_FLOOR_REFRESH_INTERVAL));
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let samples = [40, 10, 30, 20];
+        assert_eq!(percentile_tip(&samples, 0), Some(10));
+        assert_eq!(percentile_tip(&samples, 50), Some(20));
+        assert_eq!(percentile_tip(&samples, 75), Some(30));
+        assert_eq!(percentile_tip(&samples, 100), Some(40));
+        assert_eq!(percentile_tip(&[], 50), None);
+    }
+}
@@ -1,17 +1,31 @@
 pub mod client;
+pub mod fee_payer;
+#[cfg(feature = "jito")]
+pub mod jito_tip;
 pub mod jupiter;
+pub mod simulation;
 pub mod submit;
+pub mod subscription;
 pub mod swap;
 pub mod transaction_builder;
 
-use std::{str::FromStr, sync::Arc, time::Duration};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
 
 use async_trait::async_trait;
+use cloak_layouts::FeeSchedule;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    instruction::AccountMeta,
     pubkey::Pubkey,
     signature::{Keypair, Signature, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 #[cfg(feature = "jito")]
 use solana_sdk::{message::VersionedMessage, transaction::VersionedTransaction};
@@ -19,9 +33,10 @@ use tracing::{debug, error, info, warn};
 
 use crate::{
     claim_manager::{compute_batch_hash, ClaimFinder},
-    config::SolanaConfig,
-    db::models::Job,
+    config::{parse_proof_vkey_prefix, SolanaConfig, SubmissionMode},
+    db::models::{CreateSwapReceipt, Job, PendingTransaction},
     error::Error,
+    solana::fee_payer::FeePayerPool,
 };
 
 // Manual implementation of associated token account derivation
@@ -44,6 +59,97 @@ fn get_associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
     ata
 }
 
+// SPL token account `state` byte (after mint, owner, amount, delegate option)
+const SPL_TOKEN_ACCOUNT_STATE: usize = 108;
+const SPL_TOKEN_STATE_INITIALIZED: u8 = 1;
+const SPL_TOKEN_STATE_FROZEN: u8 = 2;
+
+/// A job's committed public inputs, checked against the expected length
+fn job_public_inputs(job: &Job) -> Result<[u8; transaction_builder::PUBLIC_INPUTS_LEN], Error> {
+    job.public_inputs.as_slice().try_into().map_err(|_| {
+        Error::ValidationError(format!(
+            "public inputs must be {} bytes",
+            transaction_builder::PUBLIC_INPUTS_LEN
+        ))
+    })
+}
+
+// Read the `amount` field of an SPL token account (mint: 32, owner: 32, amount: u64 LE)
+fn spl_token_account_amount(data: &[u8]) -> Option<u64> {
+    let bytes: [u8; 8] = data.get(64..72)?.try_into().ok()?;
+    Some(u64::from_le_bytes(bytes))
+}
+
+// Part of `fee` the program pays to the treasury after the scrambler's cut
+// (same rounding as the program)
+fn treasury_share(fee: u64, scrambler_fee_share_bps: u16) -> u64 {
+    let scrambler = (fee as u128 * scrambler_fee_share_bps as u128 / 10_000) as u64;
+    fee.saturating_sub(scrambler)
+}
+
+// How much less than `expected` the treasury received between two balance
+// reads, or None if it received at least that much
+fn treasury_fee_shortfall(before: u64, after: u64, expected: u64) -> Option<u64> {
+    let received = after.saturating_sub(before);
+    (received < expected).then(|| expected - received)
+}
+
+// The program verifies the 260-byte Groth16 fragment; jobs may hold either that
+// fragment or the full SP1 bundle, whose layout shifts between SP1 versions
+fn groth16_fragment(proof_bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    if proof_bytes.len() == 260 {
+        return Ok(proof_bytes.to_vec());
+    }
+
+    cloak_proof_extract::extract_groth16_260_sp1(proof_bytes)
+        .or_else(|_| cloak_proof_extract::extract_from_envelope(proof_bytes))
+        .map(|fragment| fragment.to_vec())
+        .map_err(|_| {
+            Error::ValidationError(format!(
+                "Could not extract Groth16 proof from {}-byte proof bundle",
+                proof_bytes.len()
+            ))
+        })
+}
+
+/// Reject a Groth16 fragment whose 4-byte vkey-hash prefix does not match
+/// `expected`, i.e. a proof made against a different circuit that the program
+/// would refuse on-chain
+pub fn check_proof_vkey_prefix(proof: &[u8; 260], expected: [u8; 4]) -> Result<(), Error> {
+    if proof[..4] != expected {
+        return Err(Error::ValidationError(format!(
+            "Proof vkey hash prefix {} does not match expected {}",
+            hex::encode(&proof[..4]),
+            hex::encode(expected)
+        )));
+    }
+
+    Ok(())
+}
+
+/// The client quoted the swap's minimum output right before submitting the
+/// withdraw, so the quote is at least as old as the job. Swapping after a long
+/// queue wait would execute at a stale price; a SwapState locked by an earlier
+/// attempt is refunded to the recipient once it times out
+fn ensure_swap_quote_fresh(job: &Job, swap_config: &crate::swap::SwapConfig) -> Result<(), Error> {
+    let age = (chrono::Utc::now() - job.created_at)
+        .to_std()
+        .unwrap_or_default();
+    swap::ensure_quote_fresh(age, Duration::from_secs(swap_config.quote_max_age_secs))
+}
+
+/// `Config::validate` has already rejected malformed hashes at startup
+fn proof_vkey_prefix(config: &SolanaConfig) -> Result<Option<[u8; 4]>, Error> {
+    config
+        .proof_vkey_hash
+        .as_deref()
+        .map(|hash| {
+            parse_proof_vkey_prefix(hash)
+                .ok_or_else(|| Error::ValidationError(format!("Invalid proof vkey hash: {}", hash)))
+        })
+        .transpose()
+}
+
 // Removed external TransactionResult dependency; we return Signature to callers.
 
 // Helper function to parse keypair from environment variable
@@ -56,13 +162,31 @@ fn parse_keypair_from_env(keypair_str: &str) -> Result<Keypair, Error> {
         .map_err(|e| Error::ValidationError(format!("Failed to create keypair from bytes: {}", e)))
 }
 
+/// Commitment for a configured level name. `Config::validate` rejects names
+/// outside `COMMITMENT_LEVELS` at startup, so the `confirmed` fallback only
+/// covers configs built without it.
+pub fn parse_commitment(level: &str) -> CommitmentConfig {
+    match level {
+        "processed" => CommitmentConfig::processed(),
+        "confirmed" => CommitmentConfig::confirmed(),
+        "finalized" => CommitmentConfig::finalized(),
+        _ => CommitmentConfig::confirmed(),
+    }
+}
+
 #[async_trait]
 pub trait SolanaClient: Send + Sync {
     async fn get_latest_blockhash(&self) -> Result<solana_sdk::hash::Hash, Error>;
+    /// Send and wait for the client's default commitment
     async fn send_and_confirm_transaction(
         &self,
         transaction: &Transaction,
     ) -> Result<Signature, Error>;
+    async fn send_and_confirm_transaction_with_commitment(
+        &self,
+        transaction: &Transaction,
+        commitment: CommitmentConfig,
+    ) -> Result<Signature, Error>;
     async fn get_block_height(&self) -> Result<u64, Error>;
     async fn get_slot(&self) -> Result<u64, Error>;
     async fn get_account_balance(&self, pubkey: &Pubkey) -> Result<u64, Error>;
@@ -72,15 +196,118 @@ pub trait SolanaClient: Send + Sync {
         nullifier: &[u8],
     ) -> Result<bool, Error>;
     async fn get_account(&self, pubkey: &Pubkey) -> Result<solana_sdk::account::Account, Error>;
+    async fn get_account_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<solana_sdk::account::Account, Error>;
     async fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64, Error>;
+    /// Simulate `transaction`, returning the error it would fail with (if any)
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Option<TransactionError>, Error>;
+}
+
+/// Submission settings that can be swapped at runtime via `/admin/reload-config`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubmitSettings {
+    pub priority_micro_lamports: u64,
+    pub priority_fee_escalation: f64,
+    pub max_priority_micro_lamports: u64,
+    pub max_retries: u8,
+    pub retry_delay_ms: u64,
+}
+
+impl SubmitSettings {
+    pub fn from_config(config: &SolanaConfig) -> Self {
+        Self {
+            priority_micro_lamports: config.priority_micro_lamports,
+            priority_fee_escalation: config.priority_fee_escalation,
+            max_priority_micro_lamports: config.max_priority_micro_lamports,
+            max_retries: config.max_retries,
+            retry_delay_ms: config.retry_delay_ms,
+        }
+    }
+
+    /// Priority fee for the 0-based submit `attempt`: the base fee multiplied by
+    /// the escalation factor once per retry, capped at the configured ceiling
+    pub fn priority_fee_for_attempt(&self, attempt: u32) -> u64 {
+        let factor = self.priority_fee_escalation.max(1.0);
+        let escalated = self.priority_micro_lamports as f64 * factor.powi(attempt as i32);
+        // `as` saturates, so an overflowing fee lands on the ceiling
+        (escalated as u64).min(
+            self.max_priority_micro_lamports
+                .max(self.priority_micro_lamports),
+        )
+    }
+}
+
+/// Rewrite the compute-unit-price instruction in `tx` to `micro_lamports` and
+/// re-sign it as `fee_payer`. Returns false when the transaction carries no
+/// such instruction.
+fn set_priority_fee(tx: &mut Transaction, micro_lamports: u64, fee_payer: &Keypair) -> bool {
+    use solana_sdk::compute_budget::ComputeBudgetInstruction;
+
+    let price_ix = ComputeBudgetInstruction::set_compute_unit_price(micro_lamports);
+    let keys = &tx.message.account_keys;
+    let Some(ix) = tx.message.instructions.iter_mut().find(|ix| {
+        keys.get(ix.program_id_index as usize) == Some(&price_ix.program_id)
+            && ix.data.first() == price_ix.data.first()
+    }) else {
+        return false;
+    };
+    ix.data = price_ix.data;
+
+    // Old signatures no longer cover the message
+    tx.signatures = vec![Signature::default(); tx.message.header.num_required_signatures as usize];
+    let recent = tx.message.recent_blockhash;
+    tx.sign(&[fee_payer], recent);
+    true
+}
+
+/// Blocks a blockhash stays usable after the block height it was fetched at
+const BLOCKHASH_VALID_BLOCKS: u64 = 150;
+
+/// What `submit_withdraw` did with a job's withdraw
+#[derive(Debug)]
+pub enum WithdrawSubmission {
+    /// Sent and confirmed by the relay
+    Confirmed(Signature),
+    /// Built for an external agent to sign and send (external submission mode)
+    External(PendingTransaction),
+    /// Swap withdraw: the outcome of its final step, plus every transaction
+    /// this attempt got confirmed even if a later step failed
+    Swap {
+        result: Result<Signature, Error>,
+        receipt: CreateSwapReceipt,
+    },
+}
+
+/// How far a two-transaction swap withdraw got on-chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SwapProgress {
+    /// Nullifier already spent: both transactions landed
+    Completed,
+    /// SwapState PDA exists: TX1 (WithdrawSwap) landed, resume at TX2
+    Withdrawn { lamports: u64 },
+    /// Nothing on-chain yet: start from TX1
+    NotStarted,
 }
 
 pub struct SolanaService {
     client: Box<dyn SolanaClient>,
     program_id: Pubkey,
     config: SolanaConfig,
-    fee_payer: Option<Keypair>,
+    submit_settings: RwLock<SubmitSettings>,
+    fee_payers: FeePayerPool,
     claim_finder: Option<Arc<ClaimFinder>>,
+    /// Parsed `proof_vkey_hash`, checked against every incoming proof
+    proof_vkey_prefix: Option<[u8; 4]>,
+    /// Confirmed withdraws after which the treasury received less than the expected fee
+    treasury_fee_shortfalls: AtomicU64,
+    #[cfg(feature = "jito")]
+    jito_tips: jito_tip::JitoTipSizer,
 }
 
 impl SolanaService {
@@ -90,22 +317,195 @@ impl SolanaService {
 
         let client = Box::new(client::RpcSolanaClient::new(&config).await?);
 
-        // Optionally load fee payer keypair
-        let fee_payer = if let Some(ref authority) = config.withdraw_authority {
-            Some(parse_keypair_from_env(authority)?)
-        } else {
-            None
-        };
+        // Optionally load fee payer keypairs (withdraw authority first, then any extra payers)
+        let mut payers = Vec::new();
+        if let Some(ref authority) = config.withdraw_authority {
+            payers.push(parse_keypair_from_env(authority)?);
+        }
+        if let Some(ref extra) = config.fee_payer_keypairs {
+            let keys: Vec<serde_json::Value> = serde_json::from_str(extra).map_err(|e| {
+                Error::ValidationError(format!("Failed to parse fee payer keypairs: {}", e))
+            })?;
+            for key in keys {
+                payers.push(parse_keypair_from_env(&key.to_string())?);
+            }
+        }
+        if payers.len() > 1 {
+            info!(
+                "Rotating {} fee payers ({:?})",
+                payers.len(),
+                config.fee_payer_strategy
+            );
+        }
+        let fee_payers = FeePayerPool::new(payers, config.fee_payer_strategy);
+        let proof_vkey_prefix = proof_vkey_prefix(&config)?;
+
+        Ok(Self {
+            client,
+            program_id,
+            submit_settings: RwLock::new(SubmitSettings::from_config(&config)),
+            #[cfg(feature = "jito")]
+            jito_tips: jito_tip::JitoTipSizer::new(
+                config.jito_tip_strategy,
+                config.jito_tip_lamports,
+                config.max_jito_tip_lamports,
+                config.jito_tip_floor_url.clone(),
+            ),
+            config,
+            fee_payers,
+            claim_finder: None,
+            proof_vkey_prefix,
+            treasury_fee_shortfalls: AtomicU64::new(0),
+        })
+    }
 
+    /// Build a service around an existing client (used by tests with a mock RPC)
+    #[cfg(test)]
+    pub(crate) fn with_client(
+        config: SolanaConfig,
+        client: Box<dyn SolanaClient>,
+    ) -> Result<Self, Error> {
+        let program_id = Pubkey::from_str(&config.program_id)
+            .map_err(|e| Error::ValidationError(format!("Invalid program ID: {}", e)))?;
+        let fee_payers = FeePayerPool::new(Vec::new(), config.fee_payer_strategy);
+        let proof_vkey_prefix = proof_vkey_prefix(&config)?;
         Ok(Self {
             client,
             program_id,
+            submit_settings: RwLock::new(SubmitSettings::from_config(&config)),
+            #[cfg(feature = "jito")]
+            jito_tips: jito_tip::JitoTipSizer::new(
+                config.jito_tip_strategy,
+                config.jito_tip_lamports,
+                config.max_jito_tip_lamports,
+                config.jito_tip_floor_url.clone(),
+            ),
             config,
-            fee_payer,
+            fee_payers,
             claim_finder: None,
+            proof_vkey_prefix,
+            treasury_fee_shortfalls: AtomicU64::new(0),
+        })
+    }
+
+    /// Shield-pool program ID this service submits to
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    /// Number of treasury fee shortfalls seen by `check_treasury_fee`
+    pub fn treasury_fee_shortfalls(&self) -> u64 {
+        self.treasury_fee_shortfalls.load(Ordering::Relaxed)
+    }
+
+    /// Whether withdraws consume PoW claims
+    pub fn pow_enabled(&self) -> bool {
+        self.claim_finder.is_some()
+    }
+
+    /// Build the unsigned withdraw for `job` that an external agent signs and
+    /// sends as fee payer
+    pub async fn build_external_transaction(&self, job: &Job) -> Result<PendingTransaction, Error> {
+        // The relay signs the swap leg itself
+        if job.outputs_json.get("swap").is_some() {
+            return Err(Error::ValidationError(
+                "swap withdraws cannot be submitted externally".into(),
+            ));
+        }
+        let outputs = self.job_outputs(job)?;
+
+        // Read before the blockhash is fetched, so the expiry errs on the early side
+        let block_height = self.client.get_block_height().await?;
+        let transaction = self.build_withdraw_transaction(job, &outputs, None).await?;
+        let tx_bytes = bincode::serialize(&solana_sdk::transaction::VersionedTransaction::from(
+            transaction,
+        ))
+        .map_err(|e| Error::SerializationError(format!("Failed to encode transaction: {}", e)))?;
+
+        Ok(PendingTransaction {
+            tx_bytes,
+            last_valid_block_height: (block_height + BLOCKHASH_VALID_BLOCKS) as i64,
         })
     }
 
+    /// Whether the blockhash of `pending` has expired, so it can no longer land
+    pub async fn pending_transaction_expired(
+        &self,
+        pending: &PendingTransaction,
+    ) -> Result<bool, Error> {
+        let block_height = self.client.get_block_height().await?;
+        Ok(block_height as i64 > pending.last_valid_block_height)
+    }
+
+    /// Fee payer the external agent signs as, in external submission mode
+    fn external_fee_payer(&self) -> Result<Option<Pubkey>, Error> {
+        if self.config.submission != SubmissionMode::External {
+            return Ok(None);
+        }
+        self.config
+            .external_fee_payer
+            .as_deref()
+            .map(|key| {
+                Pubkey::from_str(key).map_err(|e| {
+                    Error::ValidationError(format!("Invalid external fee payer pubkey: {}", e))
+                })
+            })
+            .transpose()
+    }
+
+    /// PoW claims the withdraw for `job` will consume. Every job is submitted
+    /// as a single withdraw transaction, which consumes one claim; swap
+    /// withdraws don't consume claims. Jobs the builder would reject for
+    /// carrying too many outputs are rejected here too.
+    pub fn claims_required(&self, job: &Job) -> Result<usize, Error> {
+        let outputs = self.job_outputs(job)?;
+        check_output_count(outputs.len(), self.config.max_outputs)?;
+        let is_swap = job.outputs_json.get("swap").is_some();
+        Ok(usize::from(!is_swap))
+    }
+
+    /// Check a Groth16 fragment against the configured vkey hash, if any
+    pub fn check_proof_vkey(&self, proof: &[u8; 260]) -> Result<(), Error> {
+        match self.proof_vkey_prefix {
+            Some(expected) => check_proof_vkey_prefix(proof, expected),
+            None => Ok(()),
+        }
+    }
+
+    /// Configured maximum number of outputs per withdraw
+    pub fn max_outputs(&self) -> usize {
+        self.config.max_outputs
+    }
+
+    /// Configured dust threshold for each withdraw output
+    pub fn min_output_amount(&self) -> u64 {
+        self.config.min_output_amount
+    }
+
+    /// Configured bounds on a withdraw's total amount, `None` when unbounded
+    pub fn withdraw_amount_bounds(&self) -> (Option<u64>, Option<u64>) {
+        (
+            self.config.min_withdraw_amount,
+            self.config.max_withdraw_amount,
+        )
+    }
+
+    /// Current priority fee / retry settings
+    pub fn submit_settings(&self) -> SubmitSettings {
+        *self
+            .submit_settings
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace the priority fee / retry settings; applies to the next build
+    pub fn update_submit_settings(&self, settings: SubmitSettings) {
+        *self
+            .submit_settings
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+
     /// Set the ClaimFinder (for PoW support)
     pub fn set_claim_finder(&mut self, claim_finder: Option<Arc<ClaimFinder>>) {
         if claim_finder.is_some() {
@@ -142,22 +542,88 @@ impl SolanaService {
             .await
     }
 
-    /// Submit a withdraw transaction to Solana
-    pub async fn submit_withdraw(&self, job: &Job) -> Result<Signature, Error> {
+    /// Reject a proof root that is no longer in the on-chain roots ring, or
+    /// has had more than `max_root_age` newer roots pushed after it.
+    /// The program only accepts roots still in the ring, so such a withdraw
+    /// is a guaranteed failure.
+    pub async fn check_root_fresh(&self, root: &[u8; 32]) -> Result<(), Error> {
+        let (_, _, _, roots_ring_pda, _) = self.resolve_pool_accounts()?;
+        let account = self
+            .client
+            .get_account_with_commitment(&roots_ring_pda, self.deposit_commitment())
+            .await?;
+        let ring = cloak_layouts::decode_roots_ring(&account.data).ok_or_else(|| {
+            Error::InternalServerError(format!(
+                "Roots ring account has invalid data size: {} bytes",
+                account.data.len()
+            ))
+        })?;
+
+        match ring.root_age(root) {
+            Some(age) if age < self.config.max_root_age => Ok(()),
+            Some(age) => Err(Error::ValidationError(format!(
+                "Proof root is too old: {} newer roots on-chain (max {}); regenerate the proof against a recent root",
+                age, self.config.max_root_age
+            ))),
+            None => Err(Error::ValidationError(
+                "Proof root is not in the on-chain roots ring; regenerate the proof against a recent root"
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Reject a proof whose committed `generated_at` slot is more than
+    /// `max_proof_age_slots` behind the current slot, or ahead of it. The
+    /// program enforces the same bound, so such a withdraw is a guaranteed failure.
+    #[cfg(feature = "proof-freshness")]
+    pub async fn check_proof_fresh(&self, public_inputs: &[u8]) -> Result<(), Error> {
+        let generated_at = public_inputs
+            .get(104..112)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or_else(|| {
+                Error::ValidationError("public inputs are missing generated_at".to_string())
+            })?;
+        let current_slot = self.client.get_slot().await?;
+
+        match current_slot.checked_sub(generated_at) {
+            Some(age) if age <= self.config.max_proof_age_slots => Ok(()),
+            Some(age) => Err(Error::ValidationError(format!(
+                "Proof is too old: generated {} slots ago (max {}); regenerate the proof",
+                age, self.config.max_proof_age_slots
+            ))),
+            None => Err(Error::ValidationError(format!(
+                "Proof generated_at slot {} is ahead of the current slot {}",
+                generated_at, current_slot
+            ))),
+        }
+    }
+
+    /// Proofs only carry a generation slot with `proof-freshness`
+    #[cfg(not(feature = "proof-freshness"))]
+    pub async fn check_proof_fresh(&self, _public_inputs: &[u8]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Submit a withdraw transaction to Solana. In external submission mode the
+    /// transaction is only built, for the caller to hand to an agent.
+    pub async fn submit_withdraw(&self, job: &Job) -> Result<WithdrawSubmission, Error> {
         info!(
             "Submitting withdraw transaction for job: {}",
             job.request_id
         );
 
+        if self.config.submission == SubmissionMode::External {
+            let pending = self.build_external_transaction(job).await?;
+            info!(
+                "Withdraw for job {} built for external submission",
+                job.request_id
+            );
+            return Ok(WithdrawSubmission::External(pending));
+        }
+
         // 1. Parse outputs from JSON
-        let outputs_value = if job.outputs_json.is_object() {
-            // New format: { "outputs": [...], "swap": {...} }
-            job.outputs_json.get("outputs").unwrap_or(&job.outputs_json)
-        } else {
-            // Legacy format: [...]
-            &job.outputs_json
-        };
-        let outputs = self.parse_outputs(outputs_value)?;
+        let outputs = self.job_outputs(job)?;
 
         // 2. Check if swap is requested
         let swap_config: Option<crate::swap::SwapConfig> =
@@ -176,17 +642,39 @@ impl SolanaService {
                 None
             };
 
-        // 3. Build and submit transaction(s)
+        // 3. Pick the fee payer for this job (rotates when several are configured)
+        let fee_payer = self.fee_payers.select();
+
+        // 4. Build and submit transaction(s)
         if let Some(swap_config) = swap_config {
             // Two-transaction flow: withdraw to relay temp account, then swap to final recipient
-            self.submit_withdraw_with_swap(job, &outputs, &swap_config)
-                .await
+            let mut receipt = CreateSwapReceipt::new(
+                job.id,
+                job.nullifier.clone(),
+                swap_config.output_mint.clone(),
+            );
+            let result = self
+                .submit_withdraw_with_swap(job, &outputs, &swap_config, fee_payer, &mut receipt)
+                .await;
+            Ok(WithdrawSubmission::Swap { result, receipt })
         } else {
             // Single-transaction flow: just withdraw
-            let transaction = self.build_withdraw_transaction(job, &outputs).await?;
-            let signature = self.submit_and_confirm(&transaction, job, &outputs).await?;
+            let transaction = self
+                .build_withdraw_transaction(job, &outputs, fee_payer)
+                .await?;
+            let treasury_before = if self.config.verify_treasury_fee {
+                self.read_treasury_balance().await
+            } else {
+                None
+            };
+            let signature = self
+                .submit_and_confirm(&transaction, job, &outputs, fee_payer)
+                .await?;
             info!("Withdraw transaction confirmed: {}", signature);
-            Ok(signature)
+            if let Some(before) = treasury_before {
+                self.check_treasury_fee(job, before).await;
+            }
+            Ok(WithdrawSubmission::Confirmed(signature))
         }
     }
 
@@ -204,18 +692,27 @@ impl SolanaService {
     /// Check if a SwapState PDA exists for a given nullifier
     /// Returns Ok(true) if exists, Ok(false) if not found
     pub async fn check_swap_state_exists(&self, nullifier: &[u8; 32]) -> Result<bool, Error> {
+        Ok(self.fetch_swap_state(nullifier).await?.is_some())
+    }
+
+    /// Fetch the SwapState PDA for a nullifier, `None` if it doesn't exist
+    async fn fetch_swap_state(
+        &self,
+        nullifier: &[u8; 32],
+    ) -> Result<Option<solana_sdk::account::Account>, Error> {
         let (swap_state_pda, _) =
             transaction_builder::derive_swap_state_pda(&self.program_id, nullifier);
 
         match self.client.get_account(&swap_state_pda).await {
-            Ok(_) => Ok(true),
+            Ok(account) => Ok(Some(account)),
+            Err(Error::NotFound) => Ok(None),
             Err(e) => {
                 // Check if it's "account not found" error
                 let error_str = e.to_string();
                 if error_str.contains("AccountNotFound")
                     || error_str.contains("could not find account")
                 {
-                    Ok(false)
+                    Ok(None)
                 } else {
                     // Some other error occurred
                     Err(e)
@@ -224,14 +721,56 @@ impl SolanaService {
         }
     }
 
+    /// Determine how far a swap withdraw got on-chain.
+    ///
+    /// The SwapState PDA is fetched once here and the resulting snapshot drives
+    /// every later stage, so resuming a swap costs a single account lookup.
+    async fn swap_progress(&self, nullifier: &[u8; 32]) -> Result<SwapProgress, Error> {
+        // Nullifier already used means the swap fully completed
+        if self.check_nullifier_exists(nullifier).await? {
+            return Ok(SwapProgress::Completed);
+        }
+
+        match self.fetch_swap_state(nullifier).await {
+            Ok(Some(account)) => Ok(SwapProgress::Withdrawn {
+                lamports: account.lamports,
+            }),
+            Ok(None) => Ok(SwapProgress::NotStarted),
+            Err(e) => {
+                warn!(
+                    "⚠️  Could not check SwapState PDA existence: {}, assuming TX1 not done",
+                    e
+                );
+                Ok(SwapProgress::NotStarted)
+            }
+        }
+    }
+
     /// Get current commitment configuration
     pub fn get_commitment(&self) -> CommitmentConfig {
-        match self.config.commitment.as_str() {
-            "processed" => CommitmentConfig::processed(),
-            "confirmed" => CommitmentConfig::confirmed(),
-            "finalized" => CommitmentConfig::finalized(),
-            _ => CommitmentConfig::confirmed(), // Default
-        }
+        parse_commitment(&self.config.commitment)
+    }
+
+    /// Commitment the roots ring is read at, so a withdraw is only accepted
+    /// against deposits that reached `deposit_commitment`
+    pub fn deposit_commitment(&self) -> CommitmentConfig {
+        parse_commitment(&self.config.deposit_commitment)
+    }
+
+    /// Commitment withdraw transactions are confirmed at before a job completes
+    pub fn withdraw_commitment(&self) -> CommitmentConfig {
+        parse_commitment(&self.config.withdraw_commitment)
+    }
+
+    /// Parse a job's outputs, accepting both the `{ "outputs": [...], "swap": {...} }`
+    /// format and the legacy bare array
+    fn job_outputs(&self, job: &Job) -> Result<Vec<Output>, Error> {
+        let outputs_value = if job.outputs_json.is_object() {
+            job.outputs_json.get("outputs").unwrap_or(&job.outputs_json)
+        } else {
+            &job.outputs_json
+        };
+        self.parse_outputs(outputs_value)
     }
 
     /// Parse outputs from JSON with validation
@@ -253,6 +792,9 @@ impl SolanaService {
             ));
         }
 
+        #[cfg(feature = "sorted-outputs")]
+        let outputs = sort_outputs_canonical(outputs)?;
+
         Ok(outputs)
     }
 
@@ -265,6 +807,8 @@ impl SolanaService {
         job: &Job,
         outputs: &[Output],
         swap_config: &crate::swap::SwapConfig,
+        fee_payer: Option<&Keypair>,
+        receipt: &mut CreateSwapReceipt,
     ) -> Result<Signature, Error> {
         info!(
             "Starting PDA-based withdraw+swap flow for job {}",
@@ -272,7 +816,7 @@ impl SolanaService {
         );
 
         // Relay fee payer is required (pays PDA rent and signs Jupiter swap; reimbursed via ExecuteSwap)
-        let relay_keypair = self.fee_payer.as_ref().ok_or_else(|| {
+        let relay_keypair = fee_payer.ok_or_else(|| {
             Error::ValidationError("Relay fee payer keypair required for swap withdrawals".into())
         })?;
         let relay_pubkey = relay_keypair.pubkey();
@@ -282,13 +826,8 @@ impl SolanaService {
             .map_err(|e| Error::ValidationError(format!("Invalid output mint: {}", e)))?;
 
         // Parse public inputs -> nullifier and public_amount
-        if job.public_inputs.len() != 104 {
-            return Err(Error::ValidationError(
-                "public inputs must be 104 bytes".into(),
-            ));
-        }
-        let mut public_104 = [0u8; 104];
-        public_104.copy_from_slice(&job.public_inputs);
+        let public_104 = job_public_inputs(job)?;
+        self.check_proof_fresh(&public_104).await?;
         let mut nullifier = [0u8; 32];
         nullifier.copy_from_slice(&public_104[32..64]);
         let public_amount = u64::from_le_bytes(public_104[96..104].try_into().unwrap());
@@ -333,40 +872,29 @@ impl SolanaService {
         // 1. If nullifier is already used on-chain → swap fully completed, return success
         // 2. If SwapState PDA exists → TX1 done, proceed to TX2
         // 3. If neither → start from TX1
-
-        // First check if nullifier is already used (swap fully completed)
-        if self.check_nullifier_exists(&nullifier).await? {
-            info!("✅ Nullifier already used on-chain - swap fully completed");
-            // Return a dummy signature since we can't retrieve the original
-            return Ok(Signature::default());
-        }
-
-        // Check if TX1 (WithdrawSwap) was already completed
-        // If SwapState PDA exists, TX1 is done and we can skip to TX2
-        let tx1_already_done = match self.check_swap_state_exists(&nullifier).await {
-            Ok(exists) => {
-                if exists {
-                    info!("✓ SwapState PDA already exists - TX1 (WithdrawSwap) was previously completed");
-                    info!("  Skipping TX1, proceeding directly to TX2 (swap + close)");
-                    true
-                } else {
-                    false
-                }
+        let tx1_already_done = match self.swap_progress(&nullifier).await? {
+            SwapProgress::Completed => {
+                info!("✅ Nullifier already used on-chain - swap fully completed");
+                // Return a dummy signature since we can't retrieve the original
+                return Ok(Signature::default());
             }
-            Err(e) => {
-                warn!(
-                    "⚠️  Could not check SwapState PDA existence: {}, assuming TX1 not done",
-                    e
+            SwapProgress::Withdrawn { lamports } => {
+                info!(
+                    "✓ SwapState PDA already exists ({} lamports) - TX1 (WithdrawSwap) was previously completed",
+                    lamports
                 );
-                false
+                info!("  Skipping TX1, proceeding directly to TX2 (swap + close)");
+                true
             }
+            SwapProgress::NotStarted => false,
         };
 
         // TX1: WithdrawSwap — lock SOL in SwapState PDA (skip if already done)
         if !tx1_already_done {
+            ensure_swap_quote_fresh(job, swap_config)?;
             let recent = self.client.get_latest_blockhash().await?;
             let withdraw_swap_tx = transaction_builder::build_withdraw_swap_transaction(
-                job.proof_bytes.clone(),
+                groth16_fragment(&job.proof_bytes)?,
                 public_104,
                 output_mint,
                 recipient_ata,
@@ -379,7 +907,7 @@ impl SolanaService {
                 swap_state_pda,
                 relay_pubkey,
                 recent,
-                self.config.priority_micro_lamports,
+                self.submit_settings().priority_micro_lamports,
             )?;
             info!("Submitting WithdrawSwap (1/2)...");
             let mut signed_withdraw = withdraw_swap_tx.clone();
@@ -387,9 +915,14 @@ impl SolanaService {
             signed_withdraw.sign(&[relay_keypair], bh1);
             let withdraw_sig = self
                 .client
-                .send_and_confirm_transaction(&signed_withdraw)
-                .await?;
+                .send_and_confirm_transaction_with_commitment(
+                    &signed_withdraw,
+                    self.withdraw_commitment(),
+                )
+                .await
+                .map_err(|e| Error::SwapExecutionFailed(format!("WithdrawSwap failed: {}", e)))?;
             info!("✓ WithdrawSwap confirmed: {}", withdraw_sig);
+            receipt.withdraw_swap_signature = Some(withdraw_sig.to_string());
         }
 
         // TX2: ExecuteSwapViaOrca - Atomic on-chain CPI swap directly to recipient
@@ -412,7 +945,9 @@ impl SolanaService {
             relay_keypair,
         )
         .await
-        .map_err(|e| Error::InternalServerError(e.to_string()))?;
+        .map_err(|e| {
+            Error::SwapExecutionFailed(format!("Failed to create recipient ATA: {}", e))
+        })?;
 
         // Get Orca pool information and build ExecuteSwapViaOrca instruction
         let wsol_mint = Pubkey::from_str("So11111111111111111111111111111111111111112")
@@ -438,16 +973,18 @@ impl SolanaService {
             relay_keypair,
         )
         .await
-        .map_err(|e| Error::InternalServerError(format!("Failed to create wSOL ATA: {}", e)))?;
+        .map_err(|e| Error::SwapExecutionFailed(format!("Failed to create wSOL ATA: {}", e)))?;
 
         // Check if PrepareSwapSol + SyncNative were already called by checking wSOL ATA token balance
         // If wSOL ATA has tokens, wrapping is complete; otherwise we need to prepare
         let wsol_ata_account = self.client.get_account(&swap_wsol_ata).await?;
-        let wsol_token_amount = if wsol_ata_account.data.len() >= 72 {
-            u64::from_le_bytes(wsol_ata_account.data[64..72].try_into().unwrap_or([0u8; 8]))
-        } else {
-            0
-        };
+        let wsol_token_amount =
+            spl_token_account_amount(&wsol_ata_account.data).ok_or_else(|| {
+                Error::SwapStateMismatch(format!(
+                    "SwapState wSOL ATA {} is not a token account",
+                    swap_wsol_ata
+                ))
+            })?;
 
         info!("📊 wSOL ATA token balance: {} wSOL", wsol_token_amount);
         let needs_prepare = wsol_token_amount == 0;
@@ -468,11 +1005,16 @@ impl SolanaService {
             let mut prepare_tx = Transaction::new_with_payer(&[prepare_ix], Some(&relay_pubkey));
             prepare_tx.sign(&[relay_keypair], recent);
 
-            self.client
-                .send_and_confirm_transaction(&prepare_tx)
+            let sig = self
+                .client
+                .send_and_confirm_transaction_with_commitment(
+                    &prepare_tx,
+                    self.withdraw_commitment(),
+                )
                 .await
-                .map_err(|e| Error::InternalServerError(format!("PrepareSwapSol failed: {}", e)))?;
-            info!("✓ PrepareSwapSol confirmed");
+                .map_err(|e| Error::SwapExecutionFailed(format!("PrepareSwapSol failed: {}", e)))?;
+            info!("✓ PrepareSwapSol confirmed: {}", sig);
+            receipt.prepare_swap_signature = Some(sig.to_string());
 
             // Step 1b: SyncNative - Wrap SOL → wSOL
             info!("Step 1b/3: Wrapping SOL to wSOL (SyncNative)...");
@@ -486,15 +1028,22 @@ impl SolanaService {
             let mut sync_tx = Transaction::new_with_payer(&[sync_native_ix], Some(&relay_pubkey));
             sync_tx.sign(&[relay_keypair], recent);
 
-            self.client
-                .send_and_confirm_transaction(&sync_tx)
+            let sig = self
+                .client
+                .send_and_confirm_transaction_with_commitment(&sync_tx, self.withdraw_commitment())
                 .await
-                .map_err(|e| Error::InternalServerError(format!("SyncNative failed: {}", e)))?;
-            info!("✓ SyncNative confirmed");
+                .map_err(|e| Error::SwapExecutionFailed(format!("SyncNative failed: {}", e)))?;
+            info!("✓ SyncNative confirmed: {}", sig);
+            receipt.sync_native_signature = Some(sig.to_string());
         } else {
             info!("✓ SwapState already prepared (lamports drained), skipping PrepareSwapSol and SyncNative");
         }
 
+        // Recipient balance before the swap, to record how much it actually received
+        let output_before = self.token_account_amount(&recipient_ata).await;
+
+        ensure_swap_quote_fresh(job, swap_config)?;
+
         // Try multiple tick spacings until we find a pool that works
         let tick_spacings = vec![64, 8, 128, 1];
         let mut swap_sig: Option<Signature> = None;
@@ -580,7 +1129,7 @@ impl SolanaService {
             // Step 2: Build and submit ExecuteSwapViaOrca instruction
             // This performs the actual Orca swap CPI
             // Note: actual amount is public_amount - fee (0.5%), which is what was transferred to wSOL ATA
-            let variable_fee = (public_amount * 5) / 1_000;
+            let variable_fee = crate::planner::calculate_fee(public_amount, 9);
             let actual_swap_amount = public_amount - variable_fee;
 
             info!(
@@ -610,7 +1159,11 @@ impl SolanaService {
             let mut swap_tx = Transaction::new_with_payer(&[swap_ix], Some(&relay_pubkey));
             swap_tx.sign(&[relay_keypair], recent);
 
-            match self.client.send_and_confirm_transaction(&swap_tx).await {
+            match self
+                .client
+                .send_and_confirm_transaction_with_commitment(&swap_tx, self.withdraw_commitment())
+                .await
+            {
                 Ok(sig) => {
                     info!("✓ ExecuteSwapViaOrca confirmed: {}", sig);
                     swap_sig = Some(sig);
@@ -627,87 +1180,65 @@ impl SolanaService {
         }
 
         let signature = swap_sig.ok_or_else(|| {
-            Error::InternalServerError(
+            Error::SwapExecutionFailed(
                 "All Orca pools failed. Devnet pools may be too imbalanced.".to_string(),
             )
         })?;
 
+        let output_after = self.token_account_amount(&recipient_ata).await;
+        let output_amount = match (output_before, output_after) {
+            (Some(before), Some(after)) => {
+                let received = after.saturating_sub(before);
+                info!(
+                    "✓ Recipient received {} tokens of {}",
+                    received, output_mint
+                );
+                i64::try_from(received).ok()
+            }
+            _ => None,
+        };
+        receipt.execute_swap_signature = Some(signature.to_string());
+        receipt.output_amount = output_amount;
+
         Ok(signature)
     }
 
-    /// Build withdraw transaction using the canonical shield-pool layout and PDAs
-    /// If PoW is enabled (claim_finder present), will query for wildcard claims
-    /// and use the PoW-enabled transaction builder
-    async fn build_withdraw_transaction(
-        &self,
-        job: &Job,
-        outputs: &[Output],
-    ) -> Result<Transaction, Error> {
-        let recent_blockhash = self.client.get_latest_blockhash().await?;
-
-        // Validate outputs (1-10 allowed)
-        if outputs.is_empty() || outputs.len() > 10 {
-            return Err(Error::ValidationError(
-                "Number of outputs must be between 1 and 10".into(),
-            ));
+    /// Token balance of an SPL token account, or None if it can't be read
+    async fn token_account_amount(&self, token_account: &Pubkey) -> Option<u64> {
+        match self.client.get_account(token_account).await {
+            Ok(account) => spl_token_account_amount(&account.data),
+            Err(e) => {
+                warn!("Could not read token account {}: {}", token_account, e);
+                None
+            }
         }
+    }
 
-        // Convert API Output to planner Output
-        use crate::planner::Output as PlannerOutput;
-        let planner_outputs: Vec<PlannerOutput> = outputs
-            .iter()
-            .map(|o| {
-                let pubkey = o.to_pubkey()?;
-                Ok(PlannerOutput {
-                    address: pubkey.to_bytes(),
-                    amount: o.amount,
-                })
-            })
-            .collect::<Result<_, Error>>()?;
-
-        // Collect recipient pubkeys (1..N)
-        let recipient_pubkeys: Vec<Pubkey> = planner_outputs
-            .iter()
-            .map(|o| Pubkey::new_from_array(o.address))
-            .collect();
+    /// The configured pool mint; `Pubkey::default()` means native SOL
+    fn pool_mint(&self) -> Result<Pubkey, Error> {
+        match self.config.mint_address.as_deref() {
+            None | Some("") => Ok(Pubkey::default()),
+            Some(mint_str) => Pubkey::from_str(mint_str)
+                .map_err(|e| Error::ValidationError(format!("Invalid mint address: {}", e))),
+        }
+    }
 
-        // Get first recipient for fee payer fallback
-        let recipient_pubkey = outputs[0].to_pubkey()?;
+    /// Fee schedule for withdraws from the configured pool
+    pub fn fee_schedule(&self) -> Result<FeeSchedule, Error> {
+        Ok(self.fee_schedule_for(&self.pool_mint()?))
+    }
 
-        if job.proof_bytes.is_empty() {
-            return Err(Error::ValidationError(
-                "proof bytes must be non-empty".into(),
-            ));
-        }
-        let proof_bytes = job.proof_bytes.clone();
-        if proof_bytes.len() >= 4 {
-            let prefix = hex::encode(&proof_bytes[..4]);
-            info!(
-                proof_prefix = prefix.as_str(),
-                proof_len = proof_bytes.len()
-            );
-        }
+    /// Fee schedule for withdraws from `mint`'s pool, including any
+    /// per-mint override
+    pub fn fee_schedule_for(&self, mint: &Pubkey) -> FeeSchedule {
+        self.config.fee_schedule(mint)
+    }
 
-        if job.public_inputs.len() != 104 {
-            return Err(Error::ValidationError(
-                "public inputs must be 104 bytes".into(),
-            ));
-        }
-        let mut public_104 = [0u8; 104];
-        public_104.copy_from_slice(&job.public_inputs);
-
-        // Parse mint address (empty = native SOL)
-        let mint = if let Some(mint_str) = &self.config.mint_address {
-            if mint_str.is_empty() {
-                Pubkey::default() // Native SOL
-            } else {
-                Pubkey::from_str(mint_str)
-                    .map_err(|e| Error::ValidationError(format!("Invalid mint address: {}", e)))?
-            }
-        } else {
-            Pubkey::default() // Default to native SOL
-        };
-        let is_spl_mint = mint != Pubkey::default();
+    /// Resolve the configured mint and the shield-pool accounts for it.
+    /// Uses configured addresses when all four are set, otherwise derives the PDAs.
+    /// Returns (mint, pool, treasury, roots_ring, nullifier_shard).
+    fn resolve_pool_accounts(&self) -> Result<(Pubkey, Pubkey, Pubkey, Pubkey, Pubkey), Error> {
+        let mint = self.pool_mint()?;
 
         // Get Shield Pool account addresses (use configured addresses if available, otherwise derive PDAs)
         let (pool_pda, treasury_pda, roots_ring_pda, nullifier_shard_pda) = if let (
@@ -746,9 +1277,394 @@ impl SolanaService {
             transaction_builder::derive_shield_pool_pdas(&self.program_id, &mint)
         };
 
-        // Fee payer pubkey: prefer loaded keypair, else withdraw_authority pubkey, else recipient
-        let fee_payer_pubkey = if let Some(ref kp) = self.fee_payer {
+        Ok((
+            mint,
+            pool_pda,
+            treasury_pda,
+            roots_ring_pda,
+            nullifier_shard_pda,
+        ))
+    }
+
+    /// Ensure the pool's token account can cover the outputs plus the fee.
+    /// Native SOL pools are not checked; the program enforces lamport balances itself.
+    async fn check_pool_liquidity(
+        &self,
+        job: &Job,
+        outputs: &[Output],
+        mint: &Pubkey,
+        pool_pda: &Pubkey,
+    ) -> Result<(), Error> {
+        if *mint == Pubkey::default() {
+            return Ok(());
+        }
+
+        let outputs_total = outputs
+            .iter()
+            .try_fold(0u64, |acc, o| acc.checked_add(o.amount))
+            .ok_or_else(|| Error::ValidationError("Output amounts overflow".into()))?;
+        let fee = crate::planner::calculate_scheduled_fee(
+            job.amount.max(0) as u64,
+            self.config.fee_schedule(mint),
+        )?;
+        let required = outputs_total
+            .checked_add(fee)
+            .ok_or_else(|| Error::ValidationError("Withdraw amount overflows".into()))?;
+
+        let pool_token_account = get_associated_token_address(pool_pda, mint);
+        let account = self.client.get_account(&pool_token_account).await?;
+        let available = spl_token_account_amount(&account.data).ok_or_else(|| {
+            Error::InternalServerError(format!(
+                "Pool token account {} is not a valid SPL token account",
+                pool_token_account
+            ))
+        })?;
+
+        if available < required {
+            return Err(Error::InsufficientPoolLiquidity {
+                available,
+                required,
+            });
+        }
+
+        debug!(
+            "Pool liquidity OK: {} available, {} required",
+            available, required
+        );
+        Ok(())
+    }
+
+    /// Ensure every output's destination can be credited before spending the
+    /// proof and claim on a transaction that would revert.
+    ///
+    /// Native SOL recipients must not be executable, and a new account must
+    /// receive at least the rent-exempt minimum. SPL recipients' token accounts
+    /// must be initialized and not frozen, or missing with `create_recipient_atas` on.
+    async fn check_recipients_can_receive(
+        &self,
+        outputs: &[Output],
+        mint: &Pubkey,
+    ) -> Result<(), Error> {
+        let is_spl_mint = *mint != Pubkey::default();
+        let rejected = |output: &Output, reason: &str| Error::RecipientCannotReceive {
+            recipient: output.recipient.clone(),
+            reason: reason.to_string(),
+        };
+
+        for output in outputs {
+            let recipient = output.to_pubkey()?;
+            let address = if is_spl_mint {
+                get_associated_token_address(&recipient, mint)
+            } else {
+                recipient
+            };
+
+            let account = match self.client.get_account(&address).await {
+                Ok(account) => Some(account),
+                Err(Error::NotFound) => None,
+                Err(e) if e.to_string().contains("AccountNotFound") => None,
+                Err(e) => return Err(e),
+            };
+
+            match (account, is_spl_mint) {
+                (None, true) if !self.config.create_recipient_atas => {
+                    return Err(rejected(output, "token account does not exist"));
+                }
+                (None, true) => {}
+                (None, false) => {
+                    let rent_exempt = self
+                        .client
+                        .get_minimum_balance_for_rent_exemption(0)
+                        .await?;
+                    if output.amount < rent_exempt {
+                        return Err(rejected(
+                            output,
+                            &format!(
+                                "new account needs at least {} lamports for rent",
+                                rent_exempt
+                            ),
+                        ));
+                    }
+                }
+                (Some(account), false) => {
+                    if account.executable {
+                        return Err(rejected(output, "account is executable"));
+                    }
+                }
+                (Some(account), true) => {
+                    if account.owner != spl_token::id() {
+                        return Err(rejected(output, "token account not owned by SPL Token"));
+                    }
+                    match account.data.get(SPL_TOKEN_ACCOUNT_STATE).copied() {
+                        Some(SPL_TOKEN_STATE_INITIALIZED) => {}
+                        Some(SPL_TOKEN_STATE_FROZEN) => {
+                            return Err(rejected(output, "token account is frozen"));
+                        }
+                        _ => return Err(rejected(output, "token account is not initialized")),
+                    }
+                }
+            }
+        }
+
+        debug!("All {} recipients can receive", outputs.len());
+        Ok(())
+    }
+
+    /// Treasury balance in the pool's asset: lamports for native SOL, otherwise
+    /// the amount held by the treasury's token account
+    async fn treasury_balance(&self, mint: &Pubkey, treasury_pda: &Pubkey) -> Result<u64, Error> {
+        if *mint == Pubkey::default() {
+            return self.client.get_account_balance(treasury_pda).await;
+        }
+
+        let treasury_token_account = get_associated_token_address(treasury_pda, mint);
+        let account = self.client.get_account(&treasury_token_account).await?;
+        spl_token_account_amount(&account.data).ok_or_else(|| {
+            Error::InternalServerError(format!(
+                "Treasury token account {} is not a valid SPL token account",
+                treasury_token_account
+            ))
+        })
+    }
+
+    /// Treasury balance before submitting, for `check_treasury_fee`
+    async fn read_treasury_balance(&self) -> Option<u64> {
+        let result = async {
+            let (mint, _, treasury_pda, _, _) = self.resolve_pool_accounts()?;
+            self.treasury_balance(&mint, &treasury_pda).await
+        }
+        .await;
+
+        match result {
+            Ok(balance) => Some(balance),
+            Err(e) => {
+                warn!("Could not read treasury balance, skipping fee check: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Part of `fee` the treasury should receive: all of it, unless PoW is
+    /// enabled and the registry gives scramblers a share
+    async fn expected_treasury_share(&self, fee: u64) -> Result<u64, Error> {
+        let (Some(_), Some(registry_program_id)) = (
+            &self.claim_finder,
+            &self.config.scramble_registry_program_id,
+        ) else {
+            return Ok(fee);
+        };
+
+        let registry_program_id = Pubkey::from_str(registry_program_id).map_err(|e| {
+            Error::InternalServerError(format!("Invalid scramble registry program ID: {}", e))
+        })?;
+        let (registry_pda, _) = Pubkey::find_program_address(&[b"registry"], &registry_program_id);
+        let account = self.client.get_account(&registry_pda).await?;
+        let registry = cloak_layouts::decode_registry(&account.data).ok_or_else(|| {
+            Error::InternalServerError(format!(
+                "Registry account has invalid data size: {} bytes",
+                account.data.len()
+            ))
+        })?;
+
+        Ok(treasury_share(fee, registry.fee_share_bps))
+    }
+
+    /// Warn and count it if the treasury received less than the protocol
+    /// fee for `job` since `before` was read, which points at a program bug or
+    /// a misconfigured treasury. Other withdraws landing in between only add to
+    /// the balance, so this is a lower bound; it never fails the job.
+    async fn check_treasury_fee(&self, job: &Job, before: u64) {
+        let result = async {
+            let (mint, _, treasury_pda, _, _) = self.resolve_pool_accounts()?;
+            let fee = crate::planner::calculate_scheduled_fee(
+                job.amount.max(0) as u64,
+                self.config.fee_schedule(&mint),
+            )?;
+            let expected = self.expected_treasury_share(fee).await?;
+            let after = self.treasury_balance(&mint, &treasury_pda).await?;
+            Ok::<_, Error>((expected, after))
+        }
+        .await;
+
+        match result {
+            Ok((expected, after)) => {
+                if let Some(shortfall) = treasury_fee_shortfall(before, after, expected) {
+                    warn!(
+                        "⚠️ Treasury received {} less than the expected fee of {} for job {} (balance {} -> {})",
+                        shortfall, expected, job.request_id, before, after
+                    );
+                    self.treasury_fee_shortfalls.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    debug!(
+                        "Treasury fee OK for job {}: expected at least {}",
+                        job.request_id, expected
+                    );
+                }
+            }
+            Err(e) => warn!(
+                "Could not verify treasury fee for job {}: {}",
+                job.request_id, e
+            ),
+        }
+    }
+
+    /// Account metas of the withdraw instruction the relay would submit for `job`,
+    /// so external validator agents can assemble the transaction themselves.
+    ///
+    /// Mirrors `build_withdraw_transaction`, including the PoW accounts when a
+    /// claim is available, but needs neither a blockhash nor the proof.
+    pub async fn withdraw_account_metas(&self, job: &Job) -> Result<Vec<AccountMeta>, Error> {
+        let outputs = self.job_outputs(job)?;
+        let recipient_pubkeys: Vec<Pubkey> = outputs
+            .iter()
+            .map(|o| o.to_pubkey())
+            .collect::<Result<_, Error>>()?;
+
+        let (mint, pool_pda, treasury_pda, roots_ring_pda, nullifier_shard_pda) =
+            self.resolve_pool_accounts()?;
+        let is_spl_mint = mint != Pubkey::default();
+
+        let pool_token_account =
+            is_spl_mint.then(|| get_associated_token_address(&pool_pda, &mint));
+        let treasury_token_account =
+            is_spl_mint.then(|| get_associated_token_address(&treasury_pda, &mint));
+        let recipient_token_accounts: Option<Vec<Pubkey>> = is_spl_mint.then(|| {
+            recipient_pubkeys
+                .iter()
+                .map(|pk| get_associated_token_address(pk, &mint))
+                .collect()
+        });
+
+        let Some(ref claim_finder) = self.claim_finder else {
+            let ix = transaction_builder::build_withdraw_instruction(
+                self.program_id,
+                &[],
+                pool_pda,
+                treasury_pda,
+                roots_ring_pda,
+                nullifier_shard_pda,
+                &recipient_pubkeys,
+                is_spl_mint.then_some(mint),
+                pool_token_account,
+                recipient_token_accounts.as_deref(),
+                treasury_token_account,
+            );
+            return Ok(ix.accounts);
+        };
+
+        let batch_hash = compute_batch_hash(&job.request_id.to_string());
+        let claim = claim_finder
+            .find_claim(&batch_hash)
+            .await
+            .map_err(|e| Error::InternalServerError(format!("Claim query failed: {}", e)))?
+            .ok_or_else(|| {
+                Error::InternalServerError(
+                    "No PoW claims available yet - waiting for miners to produce claims"
+                        .to_string(),
+                )
+            })?;
+        let scramble_registry_program_id = self
+            .config
+            .scramble_registry_program_id
+            .as_ref()
+            .and_then(|id| Pubkey::from_str(id).ok())
+            .ok_or_else(|| {
+                Error::ValidationError("Scramble registry program ID not configured".into())
+            })?;
+        let miner_token_account =
+            is_spl_mint.then(|| get_associated_token_address(&claim.miner_authority, &mint));
+
+        let ix = transaction_builder::build_withdraw_instruction_with_pow(
+            self.program_id,
+            &[],
+            pool_pda,
+            treasury_pda,
+            roots_ring_pda,
+            nullifier_shard_pda,
+            &recipient_pubkeys,
+            scramble_registry_program_id,
+            claim.claim_pda,
+            claim.miner_pda,
+            claim.registry_pda,
+            claim.miner_authority,
+            is_spl_mint.then_some(mint),
+            pool_token_account,
+            recipient_token_accounts.as_deref(),
+            treasury_token_account,
+            miner_token_account,
+        );
+        Ok(ix.accounts)
+    }
+
+    /// Build withdraw transaction using the canonical shield-pool layout and PDAs
+    /// If PoW is enabled (claim_finder present), will query for wildcard claims
+    /// and use the PoW-enabled transaction builder
+    async fn build_withdraw_transaction(
+        &self,
+        job: &Job,
+        outputs: &[Output],
+        fee_payer: Option<&Keypair>,
+    ) -> Result<Transaction, Error> {
+        let recent_blockhash = self.client.get_latest_blockhash().await?;
+
+        check_output_count(outputs.len(), self.config.max_outputs)?;
+
+        // Convert API Output to planner Output
+        use crate::planner::Output as PlannerOutput;
+        let planner_outputs: Vec<PlannerOutput> = outputs
+            .iter()
+            .map(|o| {
+                let pubkey = o.to_pubkey()?;
+                Ok(PlannerOutput {
+                    address: pubkey.to_bytes(),
+                    amount: o.amount,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        // Collect recipient pubkeys (1..N)
+        let recipient_pubkeys: Vec<Pubkey> = planner_outputs
+            .iter()
+            .map(|o| Pubkey::new_from_array(o.address))
+            .collect();
+
+        // Get first recipient for fee payer fallback
+        let recipient_pubkey = outputs[0].to_pubkey()?;
+
+        if job.proof_bytes.is_empty() {
+            return Err(Error::ValidationError(
+                "proof bytes must be non-empty".into(),
+            ));
+        }
+        let proof_bytes = groth16_fragment(&job.proof_bytes)?;
+        if proof_bytes.len() >= 4 {
+            let prefix = hex::encode(&proof_bytes[..4]);
+            info!(
+                proof_prefix = prefix.as_str(),
+                proof_len = proof_bytes.len()
+            );
+        }
+
+        let public_104 = job_public_inputs(job)?;
+        // A proof past the program's age window would only revert on-chain
+        self.check_proof_fresh(&public_104).await?;
+
+        let (mint, pool_pda, treasury_pda, roots_ring_pda, nullifier_shard_pda) =
+            self.resolve_pool_accounts()?;
+        let is_spl_mint = mint != Pubkey::default();
+
+        // Fail early rather than letting the program reject an underfunded pool
+        self.check_pool_liquidity(job, outputs, &mint, &pool_pda)
+            .await?;
+        // The proof binds every output, so one unreceivable recipient reverts them all
+        self.check_recipients_can_receive(outputs, &mint).await?;
+
+        // Fee payer pubkey: prefer loaded keypair, else the external agent, else
+        // withdraw_authority pubkey, else recipient
+        let fee_payer_pubkey = if let Some(kp) = fee_payer {
             kp.pubkey()
+        } else if let Some(agent) = self.external_fee_payer()? {
+            agent
         } else if let Some(ref auth) = self.config.withdraw_authority {
             Pubkey::from_str(auth).map_err(|e| {
                 Error::ValidationError(format!("Invalid withdraw authority pubkey: {}", e))
@@ -757,8 +1673,8 @@ impl SolanaService {
             recipient_pubkey
         };
 
-        // Priority fee (micro-lamports per CU) from config
-        let priority_micro_lamports: u64 = self.config.priority_micro_lamports;
+        // Priority fee (micro-lamports per CU) from the current submit settings
+        let priority_micro_lamports: u64 = self.submit_settings().priority_micro_lamports;
 
         // Pre-compute SPL token accounts when using an SPL mint
         let pool_token_account = if is_spl_mint {
@@ -843,6 +1759,7 @@ impl SolanaService {
                         recipient_token_accounts_slice,
                         treasury_token_account,
                         miner_token_account,
+                        self.config.create_recipient_atas,
                     )?;
 
                     // Check if transaction size exceeds Solana's limit (1644 bytes base64-encoded)
@@ -878,6 +1795,7 @@ impl SolanaService {
                             pool_token_account,
                             recipient_token_accounts_slice,
                             treasury_token_account,
+                            self.config.create_recipient_atas,
                         )?
                     } else {
                         info!(
@@ -924,6 +1842,7 @@ impl SolanaService {
                 pool_token_account,
                 recipient_token_accounts_slice,
                 treasury_token_account,
+                self.config.create_recipient_atas,
             )?
         };
 
@@ -937,12 +1856,14 @@ impl SolanaService {
         transaction: &Transaction,
         job: &Job,
         outputs: &[Output],
+        fee_payer: Option<&Keypair>,
     ) -> Result<Signature, Error> {
         // Suppress warnings when jito feature is not enabled
         #[cfg(not(feature = "jito"))]
         let _ = (job, outputs);
         let mut retries = 0;
-        let max_retries = self.config.max_retries;
+        let settings = self.submit_settings();
+        let max_retries = settings.max_retries;
 
         // Choose submit path: Jito (feature + env) or RPC
         let use_jito =
@@ -964,6 +1885,14 @@ impl SolanaService {
                     Error::InternalServerError(format!("fetch tip account failed: {}", e))
                 })?;
 
+                // Size the tip from recent landed tips (no-op for the fixed strategy)
+                self.jito_tips.refresh().await;
+                let tip_lamports = self.jito_tips.tip_lamports();
+                debug!(
+                    "Jito tip for job {}: {} lamports",
+                    job.request_id, tip_lamports
+                );
+
                 // Rebuild transaction with tip instruction
                 let recent_blockhash = self.client.get_latest_blockhash().await?;
                 let recipient_pubkey = outputs[0].to_pubkey()?;
@@ -975,10 +1904,9 @@ impl SolanaService {
                         "proof bytes must be non-empty".into(),
                     ));
                 }
-                let proof_bytes = job.proof_bytes.clone();
+                let proof_bytes = groth16_fragment(&job.proof_bytes)?;
 
-                let mut public_104 = [0u8; 104];
-                public_104.copy_from_slice(&job.public_inputs);
+                let public_104 = job_public_inputs(job)?;
 
                 // Parse mint address (empty = native SOL)
                 let mint = if let Some(mint_str) = &self.config.mint_address {
@@ -1028,7 +1956,7 @@ impl SolanaService {
                     transaction_builder::derive_shield_pool_pdas(&self.program_id, &mint)
                 };
 
-                let fee_payer_pubkey = if let Some(ref kp) = self.fee_payer {
+                let fee_payer_pubkey = if let Some(kp) = fee_payer {
                     kp.pubkey()
                 } else if let Some(ref auth) = self.config.withdraw_authority {
                     Pubkey::from_str(auth).map_err(|e| {
@@ -1038,28 +1966,31 @@ impl SolanaService {
                     recipient_pubkey
                 };
 
-                let mut vtx = transaction_builder::build_withdraw_versioned_with_tip(
-                    proof_bytes,
-                    public_104,
-                    recipient_addr_32,
-                    recipient_amount,
-                    self.program_id,
-                    pool_pda,
-                    roots_ring_pda,
-                    nullifier_shard_pda,
-                    treasury_pda,
-                    recipient_pubkey,
-                    fee_payer_pubkey,
-                    recent_blockhash,
-                    self.config.priority_micro_lamports,
-                    tip_account,
-                    self.config.jito_tip_lamports,
-                )?;
-
-                // Sign with fee payer
-                if let Some(ref kp) = self.fee_payer {
-                    vtx.sign(&[kp], recent_blockhash);
-                }
+                // Built and signed afresh for each attempt's priority fee
+                let signed_bundle_tx = |priority_micro_lamports: u64| {
+                    let mut vtx = transaction_builder::build_withdraw_versioned_with_tip(
+                        proof_bytes.clone(),
+                        public_104,
+                        recipient_addr_32,
+                        recipient_amount,
+                        self.program_id,
+                        pool_pda,
+                        roots_ring_pda,
+                        nullifier_shard_pda,
+                        treasury_pda,
+                        recipient_pubkey,
+                        fee_payer_pubkey,
+                        recent_blockhash,
+                        priority_micro_lamports,
+                        tip_account,
+                        tip_lamports,
+                    )?;
+                    if let Some(kp) = fee_payer {
+                        vtx.sign(&[kp], recent_blockhash);
+                    }
+                    Ok::<_, Error>(vtx)
+                };
+                let mut vtx = signed_bundle_tx(settings.priority_micro_lamports)?;
 
                 // Submit via Jito with retries
                 while retries < max_retries {
@@ -1074,7 +2005,10 @@ impl SolanaService {
                                 return Err(Error::InternalServerError(e.to_string()));
                             }
                             let delay =
-                                Duration::from_millis(self.config.retry_delay_ms * retries as u64);
+                                Duration::from_millis(settings.retry_delay_ms * retries as u64);
+                            let priority = settings.priority_fee_for_attempt(retries as u32);
+                            vtx = signed_bundle_tx(priority)?;
+                            debug!("Escalated priority fee to {} micro-lamports", priority);
                             warn!(
                                 "Jito attempt {} failed, retrying in {:?}: {}",
                                 retries, delay, e
@@ -1095,13 +2029,35 @@ impl SolanaService {
 
         // RPC path: sign and submit the provided transaction
         let mut tx = transaction.clone();
-        if let Some(ref kp) = self.fee_payer {
+        if let Some(kp) = fee_payer {
             let recent = tx.message.recent_blockhash;
             tx.sign(&[kp], recent);
         }
 
+        // Simulate first so deterministic failures (spent nullifier, bad proof) don't
+        // cost a slot or a PoW claim
+        match self.client.simulate_transaction(&tx).await {
+            Ok(Some(err)) => {
+                let reason = simulation::describe_simulation_error(&err);
+                if simulation::is_fatal_simulation_error(&err) {
+                    error!("Simulation failed, not broadcasting: {}", reason);
+                    return Err(Error::SimulationFailed(reason));
+                }
+                warn!(
+                    "Simulation reported transient error, sending anyway: {}",
+                    reason
+                );
+            }
+            Ok(None) => debug!("Simulation succeeded"),
+            Err(e) => warn!("Simulation unavailable, sending anyway: {}", e),
+        }
+
         while retries < max_retries {
-            match self.client.send_and_confirm_transaction(&tx).await {
+            match self
+                .client
+                .send_and_confirm_transaction_with_commitment(&tx, self.withdraw_commitment())
+                .await
+            {
                 Ok(signature) => {
                     debug!(
                         "Transaction confirmed: {} (attempt {})",
@@ -1116,7 +2072,15 @@ impl SolanaService {
                         error!("Transaction failed after {} attempts: {}", max_retries, e);
                         return Err(e);
                     }
-                    let delay = Duration::from_millis(self.config.retry_delay_ms * retries as u64);
+                    let delay = Duration::from_millis(settings.retry_delay_ms * retries as u64);
+                    // Without a relay key the transaction can't be re-signed,
+                    // so it is resent as is
+                    if let Some(kp) = fee_payer {
+                        let priority = settings.priority_fee_for_attempt(retries as u32);
+                        if set_priority_fee(&mut tx, priority, kp) {
+                            debug!("Escalated priority fee to {} micro-lamports", priority);
+                        }
+                    }
                     warn!(
                         "Transaction attempt {} failed, retrying in {:?}: {}",
                         retries, delay, e
@@ -1132,6 +2096,68 @@ impl SolanaService {
     }
 }
 
+/// Reject withdraws with no outputs or more than the configured `max_outputs`
+pub fn check_output_count(count: usize, max_outputs: usize) -> Result<(), Error> {
+    if count == 0 || count > max_outputs {
+        return Err(Error::ValidationError(format!(
+            "Number of outputs must be between 1 and {} (got {})",
+            max_outputs, count
+        )));
+    }
+    Ok(())
+}
+
+/// Reject zero-amount outputs and dust below the configured `min_output_amount`
+pub fn check_output_amount(index: usize, amount: u64, min_output_amount: u64) -> Result<(), Error> {
+    if amount == 0 {
+        return Err(Error::ValidationError(format!(
+            "Output {} amount cannot be zero",
+            index
+        )));
+    }
+    if amount < min_output_amount {
+        return Err(Error::ValidationError(format!(
+            "Output {} amount {} is below the minimum of {} lamports",
+            index, amount, min_output_amount
+        )));
+    }
+    Ok(())
+}
+
+/// Reject withdraw amounts outside the configured bounds, if any
+pub fn check_withdraw_amount(
+    amount: u64,
+    min_amount: Option<u64>,
+    max_amount: Option<u64>,
+) -> Result<(), Error> {
+    if let Some(min) = min_amount.filter(|&min| amount < min) {
+        return Err(Error::ValidationError(format!(
+            "Withdraw amount {} is below the minimum of {}",
+            amount, min
+        )));
+    }
+    if let Some(max) = max_amount.filter(|&max| amount > max) {
+        return Err(Error::ValidationError(format!(
+            "Withdraw amount {} exceeds the maximum of {}",
+            amount, max
+        )));
+    }
+    Ok(())
+}
+
+/// Sort outputs by recipient pubkey bytes, then amount: the order a guest built
+/// with `sorted-outputs` hashes them in. The program hashes recipients in
+/// instruction order, so the withdraw must list them this way too.
+#[cfg(feature = "sorted-outputs")]
+pub fn sort_outputs_canonical(outputs: Vec<Output>) -> Result<Vec<Output>, Error> {
+    let mut keyed = outputs
+        .into_iter()
+        .map(|output| Ok(((output.to_pubkey()?.to_bytes(), output.amount), output)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    cloak_layouts::hash_order(&mut keyed, |(key, _)| *key);
+    Ok(keyed.into_iter().map(|(_, output)| output).collect())
+}
+
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct Output {
     pub recipient: String, // Base58 encoded public key
@@ -1151,26 +2177,758 @@ mod tests {
 
     use super::*;
 
-    #[test]
-    fn test_parse_outputs() {
-        let _config = SolanaConfig {
-            rpc_url: "http://localhost:8899".to_string(),
-            ws_url: "ws://localhost:8900".to_string(),
-            commitment: "confirmed".to_string(),
-            program_id: "11111111111111111111111111111111".to_string(),
-            withdraw_authority: None,
+    /// Offline stand-in for the RPC client
+    #[derive(Default)]
+    struct MockSolanaClient {
+        /// When set, `get_account` returns an SPL token account holding this amount
+        token_balance: Option<u64>,
+        /// Error reported by `simulate_transaction`
+        simulation_error: Option<TransactionError>,
+        /// Number of transactions broadcast
+        sent: Arc<std::sync::atomic::AtomicUsize>,
+        /// Transactions broadcast, in order
+        sent_transactions: Arc<std::sync::Mutex<Vec<Transaction>>>,
+        /// Number of leading sends that fail
+        failing_sends: usize,
+        /// When set, `get_account` returns a roots ring holding these roots, oldest first
+        roots: Option<Vec<[u8; 32]>>,
+        /// Number of `get_account` calls
+        account_fetches: Arc<std::sync::atomic::AtomicUsize>,
+        /// Token accounts `get_account` reports as frozen
+        frozen_accounts: Vec<Pubkey>,
+        /// Slot reported by `get_slot`
+        slot: u64,
+        /// Block height reported by `get_block_height`
+        block_height: Arc<AtomicU64>,
+        /// Commitments requested from `*_with_commitment` calls, in order
+        commitments: Arc<std::sync::Mutex<Vec<CommitmentConfig>>>,
+    }
+
+    #[async_trait]
+    impl SolanaClient for MockSolanaClient {
+        async fn get_latest_blockhash(&self) -> Result<solana_sdk::hash::Hash, Error> {
+            Ok(solana_sdk::hash::Hash::new_unique())
+        }
+        async fn send_and_confirm_transaction(
+            &self,
+            transaction: &Transaction,
+        ) -> Result<Signature, Error> {
+            self.send_and_confirm_transaction_with_commitment(
+                transaction,
+                CommitmentConfig::confirmed(),
+            )
+            .await
+        }
+        async fn send_and_confirm_transaction_with_commitment(
+            &self,
+            transaction: &Transaction,
+            commitment: CommitmentConfig,
+        ) -> Result<Signature, Error> {
+            self.commitments.lock().unwrap().push(commitment);
+            let attempt = self.sent.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.sent_transactions
+                .lock()
+                .unwrap()
+                .push(transaction.clone());
+            if attempt < self.failing_sends {
+                return Err(Error::NetworkError("blockhash expired".to_string()));
+            }
+            Ok(Signature::default())
+        }
+        async fn get_block_height(&self) -> Result<u64, Error> {
+            Ok(self.block_height.load(Ordering::SeqCst))
+        }
+        async fn get_slot(&self) -> Result<u64, Error> {
+            Ok(self.slot)
+        }
+        async fn get_account_balance(&self, _pubkey: &Pubkey) -> Result<u64, Error> {
+            Ok(0)
+        }
+        async fn check_nullifier_exists(
+            &self,
+            _nullifier_shard: &Pubkey,
+            _nullifier: &[u8],
+        ) -> Result<bool, Error> {
+            Ok(false)
+        }
+        async fn get_account(
+            &self,
+            pubkey: &Pubkey,
+        ) -> Result<solana_sdk::account::Account, Error> {
+            self.account_fetches
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if let Some(roots) = &self.roots {
+                return Ok(roots_ring_account(roots));
+            }
+            let amount = self.token_balance.ok_or(Error::NotFound)?;
+            let mut data = vec![0u8; 165];
+            data[64..72].copy_from_slice(&amount.to_le_bytes());
+            data[SPL_TOKEN_ACCOUNT_STATE] = if self.frozen_accounts.contains(pubkey) {
+                SPL_TOKEN_STATE_FROZEN
+            } else {
+                SPL_TOKEN_STATE_INITIALIZED
+            };
+            Ok(solana_sdk::account::Account {
+                lamports: 0,
+                data,
+                owner: spl_token::id(),
+                executable: false,
+                rent_epoch: 0,
+            })
+        }
+        async fn get_account_with_commitment(
+            &self,
+            pubkey: &Pubkey,
+            commitment: CommitmentConfig,
+        ) -> Result<solana_sdk::account::Account, Error> {
+            self.commitments.lock().unwrap().push(commitment);
+            self.get_account(pubkey).await
+        }
+        async fn get_minimum_balance_for_rent_exemption(
+            &self,
+            _data_len: usize,
+        ) -> Result<u64, Error> {
+            Ok(0)
+        }
+        async fn simulate_transaction(
+            &self,
+            _transaction: &Transaction,
+        ) -> Result<Option<TransactionError>, Error> {
+            Ok(self.simulation_error.clone())
+        }
+    }
+
+    /// RootsRing account after pushing `roots` in order
+    fn roots_ring_account(roots: &[[u8; 32]]) -> solana_sdk::account::Account {
+        use cloak_layouts::roots_ring;
+
+        let mut data = vec![0u8; roots_ring::SIZE];
+        let mut head = 0;
+        for root in roots {
+            head = (head + 1) % roots_ring::MAX_ROOTS;
+            let offset = roots_ring::root_offset(head);
+            data[offset..offset + 32].copy_from_slice(root);
+        }
+        data[roots_ring::HEAD] = head as u8;
+
+        solana_sdk::account::Account {
+            lamports: 0,
+            data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn test_config() -> SolanaConfig {
+        SolanaConfig {
+            program_id: Pubkey::new_unique().to_string(),
             priority_micro_lamports: 1000,
             jito_tip_lamports: 0,
             max_retries: 3,
             retry_delay_ms: 1000,
+            ..Default::default()
+        }
+    }
+
+    fn test_job(outputs_json: serde_json::Value) -> Job {
+        let now = chrono::Utc::now();
+        Job {
+            id: uuid::Uuid::new_v4(),
+            request_id: uuid::Uuid::new_v4(),
+            status: crate::db::models::JobStatus::Queued,
+            proof_bytes: vec![0xAB; 260],
+            public_inputs: vec![0u8; 104],
+            outputs_json,
+            fee_bps: 0,
+            root_hash: vec![0u8; 32],
+            nullifier: vec![0u8; 32],
+            amount: 10_000_000,
+            outputs_hash: vec![0u8; 32],
+            priority: 0,
+            tx_id: None,
+            solana_signature: None,
+            error_message: None,
+            retry_count: 0,
+            max_retries: 3,
+            created_at: now,
+            updated_at: now,
+            started_at: None,
+            completed_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_withdraw_account_metas_match_built_transaction() {
+        let service =
+            SolanaService::with_client(test_config(), Box::new(MockSolanaClient::default()))
+                .expect("service");
+        let job = test_job(json!([
+            { "recipient": Pubkey::new_unique().to_string(), "amount": 4_000_000u64 },
+            { "recipient": Pubkey::new_unique().to_string(), "amount": 3_000_000u64 }
+        ]));
+
+        let metas = service.withdraw_account_metas(&job).await.expect("metas");
+
+        let outputs = service.job_outputs(&job).expect("outputs");
+        let tx = service
+            .build_withdraw_transaction(&job, &outputs, None)
+            .await
+            .expect("tx");
+        let msg = &tx.message;
+        let withdraw_ix = msg.instructions.last().expect("withdraw ix");
+        assert_eq!(
+            msg.account_keys[withdraw_ix.program_id_index as usize],
+            service.program_id()
+        );
+
+        let tx_keys: Vec<Pubkey> = withdraw_ix
+            .accounts
+            .iter()
+            .map(|&idx| msg.account_keys[idx as usize])
+            .collect();
+        let meta_keys: Vec<Pubkey> = metas.iter().map(|m| m.pubkey).collect();
+        assert_eq!(tx_keys, meta_keys);
+
+        // pool, treasury, roots ring, nullifier shard, 2 recipients, system program
+        assert_eq!(metas.len(), 7);
+        assert!(!metas[2].is_writable, "roots ring is read-only");
+    }
+
+    #[tokio::test]
+    async fn test_swap_progress_fetches_swap_state_once() {
+        use std::sync::atomic::Ordering;
+
+        let nullifier = [7u8; 32];
+
+        // SwapState present: TX1 already landed
+        let fetches = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let client = MockSolanaClient {
+            token_balance: Some(0),
+            account_fetches: fetches.clone(),
+            ..Default::default()
+        };
+        let service = SolanaService::with_client(test_config(), Box::new(client)).expect("service");
+        assert_eq!(
+            service.swap_progress(&nullifier).await.unwrap(),
+            SwapProgress::Withdrawn { lamports: 0 }
+        );
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // SwapState absent: start from TX1
+        let fetches = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let client = MockSolanaClient {
+            account_fetches: fetches.clone(),
+            ..Default::default()
+        };
+        let service = SolanaService::with_client(test_config(), Box::new(client)).expect("service");
+        assert_eq!(
+            service.swap_progress(&nullifier).await.unwrap(),
+            SwapProgress::NotStarted
+        );
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_spl_withdraw_rejected_when_pool_underfunded() {
+        let mut config = test_config();
+        config.mint_address = Some(Pubkey::new_unique().to_string());
+        let job = test_job(json!([
+            { "recipient": Pubkey::new_unique().to_string(), "amount": 9_950_000u64 }
+        ]));
+        // 0.5% of 10_000_000
+        let required = 9_950_000 + 50_000;
+
+        let client = MockSolanaClient {
+            token_balance: Some(required - 1),
+            ..Default::default()
+        };
+        let service = SolanaService::with_client(config.clone(), Box::new(client)).unwrap();
+        let outputs = service.job_outputs(&job).unwrap();
+        match service
+            .build_withdraw_transaction(&job, &outputs, None)
+            .await
+        {
+            Err(Error::InsufficientPoolLiquidity {
+                available,
+                required: needed,
+            }) => {
+                assert_eq!(available, required - 1);
+                assert_eq!(needed, required);
+            }
+            other => panic!("expected InsufficientPoolLiquidity, got {:?}", other),
+        }
+
+        let client = MockSolanaClient {
+            token_balance: Some(required),
+            ..Default::default()
+        };
+        let service = SolanaService::with_client(config, Box::new(client)).unwrap();
+        assert!(service
+            .build_withdraw_transaction(&job, &outputs, None)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_spl_liquidity_check_uses_mint_fee_schedule() {
+        let mint = Pubkey::new_unique();
+        let mut config = test_config();
+        config.mint_address = Some(mint.to_string());
+        config.fee_schedules.insert(
+            mint.to_string(),
+            FeeSchedule {
+                fixed: 0,
+                variable_per_mille: 10,
+            },
+        );
+        let job = test_job(json!([
+            { "recipient": Pubkey::new_unique().to_string(), "amount": 9_950_000u64 }
+        ]));
+        // 1% of 10_000_000 under the override, not the default 0.5%
+        let required = 9_950_000 + 100_000;
+
+        let client = MockSolanaClient {
+            token_balance: Some(required - 1),
+            ..Default::default()
+        };
+        let service = SolanaService::with_client(config, Box::new(client)).unwrap();
+        let outputs = service.job_outputs(&job).unwrap();
+        match service
+            .build_withdraw_transaction(&job, &outputs, None)
+            .await
+        {
+            Err(Error::InsufficientPoolLiquidity {
+                required: needed, ..
+            }) => {
+                assert_eq!(needed, required);
+            }
+            other => panic!("expected InsufficientPoolLiquidity, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_frozen_spl_recipient_rejected_before_submission() {
+        let mint = Pubkey::new_unique();
+        let mut config = test_config();
+        config.mint_address = Some(mint.to_string());
+        let ok_recipient = Pubkey::new_unique();
+        let frozen_recipient = Pubkey::new_unique();
+        let job = test_job(json!([
+            { "recipient": ok_recipient.to_string(), "amount": 4_000_000u64 },
+            { "recipient": frozen_recipient.to_string(), "amount": 5_950_000u64 }
+        ]));
+
+        let sent = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let client = MockSolanaClient {
+            token_balance: Some(u64::MAX),
+            frozen_accounts: vec![get_associated_token_address(&frozen_recipient, &mint)],
+            sent: sent.clone(),
+            ..Default::default()
+        };
+        let service = SolanaService::with_client(config, Box::new(client)).unwrap();
+        let outputs = service.job_outputs(&job).unwrap();
+
+        match service
+            .build_withdraw_transaction(&job, &outputs, None)
+            .await
+        {
+            Err(Error::RecipientCannotReceive { recipient, reason }) => {
+                assert_eq!(recipient, frozen_recipient.to_string());
+                assert!(reason.contains("frozen"), "unexpected reason: {}", reason);
+            }
+            other => panic!("expected RecipientCannotReceive, got {:?}", other),
+        }
+        assert_eq!(sent.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_treasury_fee_shortfall() {
+        let fee = crate::planner::calculate_protocol_fee(1_000_000_000, true);
+        assert_eq!(fee, 7_500_000);
+
+        assert_eq!(treasury_fee_shortfall(100, 100 + fee, fee), None);
+        // Concurrent withdraws only add to the balance
+        assert_eq!(treasury_fee_shortfall(100, 100 + 2 * fee, fee), None);
+        assert_eq!(treasury_fee_shortfall(100, 100 + fee - 1, fee), Some(1));
+        assert_eq!(treasury_fee_shortfall(100, 50, fee), Some(fee));
+
+        // Scramblers take their share of the fee before the treasury
+        assert_eq!(treasury_share(fee, 0), fee);
+        assert_eq!(treasury_share(fee, 2_500), 5_625_000);
+        assert_eq!(treasury_share(fee, 10_000), 0);
+    }
+
+    #[tokio::test]
+    async fn test_check_root_fresh() {
+        let roots: Vec<[u8; 32]> = (1..=10u8).map(|n| [n; 32]).collect();
+        let client = MockSolanaClient {
+            roots: Some(roots),
+            ..Default::default()
+        };
+        let mut config = test_config();
+        config.max_root_age = 5;
+        let service = SolanaService::with_client(config, Box::new(client)).unwrap();
+
+        // Newest root and the oldest one still within max_root_age
+        assert!(service.check_root_fresh(&[10; 32]).await.is_ok());
+        assert!(service.check_root_fresh(&[6; 32]).await.is_ok());
+
+        let err = service.check_root_fresh(&[5; 32]).await.unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)));
+        assert!(err.to_string().contains("too old"), "{}", err);
+
+        let err = service.check_root_fresh(&[0xEE; 32]).await.unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)));
+        assert!(
+            err.to_string().contains("not in the on-chain roots ring"),
+            "{}",
+            err
+        );
+    }
+
+    #[cfg(feature = "proof-freshness")]
+    #[tokio::test]
+    async fn test_check_proof_fresh() {
+        let client = MockSolanaClient {
+            slot: 10_000,
+            ..Default::default()
+        };
+        let mut config = test_config();
+        config.max_proof_age_slots = 100;
+        let service = SolanaService::with_client(config, Box::new(client)).unwrap();
+
+        let public_inputs = |generated_at: u64| {
+            let mut public = [0u8; transaction_builder::PUBLIC_INPUTS_LEN];
+            public[104..112].copy_from_slice(&generated_at.to_le_bytes());
+            public
+        };
+
+        // Oldest proof still within max_proof_age_slots
+        assert!(service
+            .check_proof_fresh(&public_inputs(10_000))
+            .await
+            .is_ok());
+        assert!(service
+            .check_proof_fresh(&public_inputs(9_900))
+            .await
+            .is_ok());
+
+        let err = service
+            .check_proof_fresh(&public_inputs(9_899))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)));
+        assert!(err.to_string().contains("too old"), "{}", err);
+
+        let err = service
+            .check_proof_fresh(&public_inputs(10_001))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("ahead of the current slot"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_simulation_is_not_broadcast() {
+        let sent = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let client = MockSolanaClient {
+            simulation_error: Some(TransactionError::InstructionError(
+                2,
+                solana_sdk::instruction::InstructionError::Custom(0x1023),
+            )),
+            sent: Arc::clone(&sent),
+            ..Default::default()
+        };
+        let service = SolanaService::with_client(test_config(), Box::new(client)).unwrap();
+        let job = test_job(json!([
+            { "recipient": Pubkey::new_unique().to_string(), "amount": 9_950_000u64 }
+        ]));
+        let outputs = service.job_outputs(&job).unwrap();
+        let tx = service
+            .build_withdraw_transaction(&job, &outputs, None)
+            .await
+            .unwrap();
+
+        let err = service
+            .submit_and_confirm(&tx, &job, &outputs, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::SimulationFailed(_)));
+        assert!(err.to_string().contains("NullifierAlreadyUsed"), "{}", err);
+        assert_eq!(sent.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_external_submission_builds_without_broadcast() {
+        let sent = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let block_height = Arc::new(AtomicU64::new(1_000));
+        let client = MockSolanaClient {
+            sent: Arc::clone(&sent),
+            block_height: Arc::clone(&block_height),
+            ..Default::default()
+        };
+        let agent = Pubkey::new_unique();
+        let mut config = test_config();
+        config.submission = SubmissionMode::External;
+        config.external_fee_payer = Some(agent.to_string());
+        let service = SolanaService::with_client(config, Box::new(client)).unwrap();
+        let job = test_job(json!([
+            { "recipient": Pubkey::new_unique().to_string(), "amount": 9_950_000u64 }
+        ]));
+
+        let WithdrawSubmission::External(pending) = service.submit_withdraw(&job).await.unwrap()
+        else {
+            panic!("expected an external transaction");
+        };
+        assert_eq!(sent.load(std::sync::atomic::Ordering::SeqCst), 0);
+        assert_eq!(
+            pending.last_valid_block_height,
+            (1_000 + BLOCKHASH_VALID_BLOCKS) as i64
+        );
+
+        let tx: solana_sdk::transaction::VersionedTransaction =
+            bincode::deserialize(&pending.tx_bytes).unwrap();
+        // The agent pays and signs; the relay never does
+        let keys = tx.message.static_account_keys();
+        assert_eq!(keys[0], agent);
+        assert!(tx
+            .message
+            .instructions()
+            .iter()
+            .any(|ix| keys[ix.program_id_index as usize] == service.program_id()));
+
+        // Usable through its last valid block height, expired after it
+        assert!(!service.pending_transaction_expired(&pending).await.unwrap());
+        block_height.store(1_000 + BLOCKHASH_VALID_BLOCKS + 1, Ordering::SeqCst);
+        assert!(service.pending_transaction_expired(&pending).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_per_operation_commitment_levels() {
+        let mut config = test_config();
+        config.deposit_commitment = "processed".to_string();
+        config.withdraw_commitment = "finalized".to_string();
+        let commitments = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        // Proof roots come from deposits, so the roots ring is read at the
+        // deposit commitment
+        let client = MockSolanaClient {
+            roots: Some(vec![[1; 32]]),
+            commitments: Arc::clone(&commitments),
+            ..Default::default()
+        };
+        let service = SolanaService::with_client(config.clone(), Box::new(client)).unwrap();
+        service.check_root_fresh(&[1; 32]).await.unwrap();
+        assert_eq!(
+            *commitments.lock().unwrap(),
+            [CommitmentConfig::processed()]
+        );
+
+        commitments.lock().unwrap().clear();
+        let client = MockSolanaClient {
+            commitments: Arc::clone(&commitments),
+            ..Default::default()
+        };
+        let service = SolanaService::with_client(config, Box::new(client)).unwrap();
+        let job = test_job(json!([
+            { "recipient": Pubkey::new_unique().to_string(), "amount": 9_950_000u64 }
+        ]));
+        let outputs = service.job_outputs(&job).unwrap();
+        let tx = service
+            .build_withdraw_transaction(&job, &outputs, None)
+            .await
+            .unwrap();
+        service
+            .submit_and_confirm(&tx, &job, &outputs, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            *commitments.lock().unwrap(),
+            [CommitmentConfig::finalized()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_priority_fee_escalates_on_each_retry() {
+        let mut config = test_config();
+        config.priority_fee_escalation = 2.0;
+        config.retry_delay_ms = 0;
+        let sent_transactions = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let client = MockSolanaClient {
+            sent_transactions: Arc::clone(&sent_transactions),
+            failing_sends: 2,
+            ..Default::default()
+        };
+        let service = SolanaService::with_client(config, Box::new(client)).unwrap();
+        let job = test_job(json!([
+            { "recipient": Pubkey::new_unique().to_string(), "amount": 9_950_000u64 }
+        ]));
+        let outputs = service.job_outputs(&job).unwrap();
+        let fee_payer = Keypair::new();
+        let tx = service
+            .build_withdraw_transaction(&job, &outputs, Some(&fee_payer))
+            .await
+            .unwrap();
+
+        service
+            .submit_and_confirm(&tx, &job, &outputs, Some(&fee_payer))
+            .await
+            .unwrap();
+
+        // Third attempt pays base * factor^2 and is signed for the new fee
+        let sent = sent_transactions.lock().unwrap();
+        assert_eq!(sent.len(), 3);
+        let expected =
+            solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(4_000);
+        assert_eq!(sent[2].message.instructions[1].data, expected.data);
+        assert!(sent[2].verify().is_ok());
+    }
+
+    #[test]
+    fn test_escalated_priority_fee_is_capped() {
+        let settings = SubmitSettings {
+            priority_micro_lamports: 1_000,
+            priority_fee_escalation: 10.0,
+            max_priority_micro_lamports: 50_000,
+            max_retries: 5,
+            retry_delay_ms: 0,
+        };
+        assert_eq!(settings.priority_fee_for_attempt(0), 1_000);
+        assert_eq!(settings.priority_fee_for_attempt(1), 10_000);
+        assert_eq!(settings.priority_fee_for_attempt(2), 50_000);
+        assert_eq!(settings.priority_fee_for_attempt(40), 50_000);
+    }
+
+    #[tokio::test]
+    async fn test_reloaded_priority_fee_applies_to_next_build() {
+        let service =
+            SolanaService::with_client(test_config(), Box::new(MockSolanaClient::default()))
+                .unwrap();
+        let job = test_job(json!([
+            { "recipient": Pubkey::new_unique().to_string(), "amount": 9_950_000u64 }
+        ]));
+        let outputs = service.job_outputs(&job).unwrap();
+
+        service.update_submit_settings(SubmitSettings {
+            priority_micro_lamports: 25_000,
+            ..service.submit_settings()
+        });
+        let tx = service
+            .build_withdraw_transaction(&job, &outputs, None)
+            .await
+            .unwrap();
+
+        // [compute unit limit, compute unit price, withdraw]
+        let expected =
+            solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_price(25_000);
+        assert_eq!(tx.message.instructions[1].data, expected.data);
+    }
+
+    #[tokio::test]
+    async fn test_builder_enforces_configured_max_outputs() {
+        let mut config = test_config();
+        config.max_outputs = 5;
+        let service =
+            SolanaService::with_client(config, Box::new(MockSolanaClient::default())).unwrap();
+
+        let outputs: Vec<serde_json::Value> = (0..6)
+            .map(|_| json!({ "recipient": Pubkey::new_unique().to_string(), "amount": 1_000_000u64 }))
+            .collect();
+        let job = test_job(serde_json::Value::Array(outputs));
+        let outputs = service.job_outputs(&job).unwrap();
+
+        let err = service
+            .build_withdraw_transaction(&job, &outputs, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("between 1 and 5"), "{}", err);
+
+        assert!(service
+            .build_withdraw_transaction(&job, &outputs[..5], None)
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn test_claims_required_for_job() {
+        let mut config = test_config();
+        config.max_outputs = 5;
+        let service =
+            SolanaService::with_client(config, Box::new(MockSolanaClient::default())).unwrap();
+
+        let output =
+            || json!({ "recipient": Pubkey::new_unique().to_string(), "amount": 1_000_000u64 });
+        let single = test_job(json!([output()]));
+        assert_eq!(service.claims_required(&single).unwrap(), 1);
+        let full = test_job(serde_json::Value::Array((0..5).map(|_| output()).collect()));
+        assert_eq!(service.claims_required(&full).unwrap(), 1);
+
+        // The relay never splits a job across withdraws, so it can't be claimed for
+        let oversized = test_job(serde_json::Value::Array((0..6).map(|_| output()).collect()));
+        assert!(service.claims_required(&oversized).is_err());
+    }
+
+    #[test]
+    fn test_groth16_fragment_extracted_from_stored_bundle() {
+        // bincode-style bundle: header, u64 LE length prefix, fragment, trailing public values
+        let fragment: Vec<u8> = (0..260u32).map(|i| (i % 251) as u8 + 1).collect();
+        let mut bundle = vec![0xFFu8; 100];
+        bundle.extend_from_slice(&260u64.to_le_bytes());
+        bundle.extend_from_slice(&fragment);
+        bundle.extend_from_slice(&[0x11; 104]);
+
+        let known_slice = &bundle[108..108 + 260];
+        assert_eq!(groth16_fragment(&bundle).unwrap(), known_slice);
+
+        // Already-extracted fragments pass through untouched
+        assert_eq!(groth16_fragment(&fragment).unwrap(), fragment);
+        assert!(groth16_fragment(&[0u8; 64]).is_err());
+    }
+
+    #[test]
+    fn test_swap_aborts_once_job_outlives_quote() {
+        let swap_config = crate::swap::SwapConfig {
+            output_mint: Pubkey::new_unique().to_string(),
+            slippage_bps: 50,
+            min_output_amount: 1_000,
+            quote_max_age_secs: 30,
+        };
+
+        let mut job = test_job(serde_json::json!([]));
+        assert!(ensure_swap_quote_fresh(&job, &swap_config).is_ok());
+
+        job.created_at = chrono::Utc::now() - chrono::Duration::seconds(31);
+        assert!(matches!(
+            ensure_swap_quote_fresh(&job, &swap_config),
+            Err(Error::SwapQuoteFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_proof_vkey_prefix() {
+        let expected = [0x00, 0xa1, 0xb2, 0xc3];
+        let mut proof = [0x42u8; 260];
+        proof[..4].copy_from_slice(&expected);
+        assert!(check_proof_vkey_prefix(&proof, expected).is_ok());
+
+        // Proof made against a different circuit
+        proof[3] = 0xc4;
+        let err = check_proof_vkey_prefix(&proof, expected).unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)), "{}", err);
+        assert!(err.to_string().contains("00a1b2c4"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_outputs() {
+        let _config = SolanaConfig {
             scramble_registry_program_id: Some(
                 "scb1q9pSBXbmAj2rc58wqKKsvuf4t4z9CKx6Xk79Js4".to_string(),
             ),
-            mint_address: None, // Default to native SOL
-            pool_address: Some("11111111111111111111111111111111".to_string()),
-            treasury_address: Some("11111111111111111111111111111111".to_string()),
-            roots_ring_address: Some("11111111111111111111111111111111".to_string()),
-            nullifier_shard_address: Some("11111111111111111111111111111111".to_string()),
+            ..Default::default()
         };
 
         // This would need to be updated when we implement the actual service
@@ -1193,6 +2951,52 @@ mod tests {
         assert_eq!(outputs_json.as_array().unwrap().len(), 2);
     }
 
+    #[cfg(feature = "sorted-outputs")]
+    #[test]
+    fn test_sorted_outputs_hash_consistently() {
+        let parse = |value| serde_json::from_value::<Vec<Output>>(value).unwrap();
+        let a = json!({ "recipient": "11111111111111111111111111111113", "amount": 2_000_000 });
+        let b = json!({ "recipient": "11111111111111111111111111111112", "amount": 1_000_000 });
+        let c = json!({ "recipient": "11111111111111111111111111111112", "amount": 500_000 });
+
+        let forward = sort_outputs_canonical(parse(json!([a, b, c]))).unwrap();
+        let reversed = sort_outputs_canonical(parse(json!([c, b, a]))).unwrap();
+        let recipients = |outputs: &[Output]| -> Vec<(String, u64)> {
+            outputs
+                .iter()
+                .map(|o| (o.recipient.clone(), o.amount))
+                .collect()
+        };
+        assert_eq!(recipients(&forward), recipients(&reversed));
+        assert_eq!(
+            recipients(&forward),
+            vec![
+                ("11111111111111111111111111111112".to_string(), 500_000),
+                ("11111111111111111111111111111112".to_string(), 1_000_000),
+                ("11111111111111111111111111111113".to_string(), 2_000_000),
+            ]
+        );
+
+        // The relay's hash matches whichever order the client sent, and the
+        // submitted (sorted) order the program hashes over
+        let hash = |outputs: &[Output]| {
+            let outputs: Vec<crate::planner::Output> = outputs
+                .iter()
+                .map(|o| crate::planner::Output {
+                    address: o.to_pubkey().unwrap().to_bytes(),
+                    amount: o.amount,
+                })
+                .collect();
+            crate::planner::compute_outputs_hash(&outputs)
+        };
+        assert_eq!(hash(&parse(json!([a, b, c]))), hash(&forward));
+        assert_eq!(hash(&parse(json!([c, a, b]))), hash(&forward));
+
+        // Unparseable recipients are rejected rather than sorted arbitrarily
+        let bad = parse(json!([{ "recipient": "not-a-pubkey", "amount": 1 }]));
+        assert!(sort_outputs_canonical(bad).is_err());
+    }
+
     #[test]
     fn test_output_to_pubkey() {
         let output = Output {
@@ -0,0 +1,73 @@
+use shield_pool::error::ShieldPoolError;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+/// Whether a simulation error means the transaction would fail deterministically
+/// if broadcast. Transient conditions (stale blockhash, account locks, block
+/// limits) are left for the normal send/retry path.
+pub fn is_fatal_simulation_error(err: &TransactionError) -> bool {
+    !matches!(
+        err,
+        TransactionError::BlockhashNotFound
+            | TransactionError::AccountInUse
+            | TransactionError::WouldExceedMaxBlockCostLimit
+            | TransactionError::WouldExceedMaxAccountCostLimit
+            | TransactionError::WouldExceedMaxVoteCostLimit
+            | TransactionError::WouldExceedAccountDataBlockLimit
+            | TransactionError::TooManyAccountLocks
+            | TransactionError::ClusterMaintenance
+    )
+}
+
+/// Human-readable simulation error, naming shield-pool custom errors.
+///
+/// Keeps the `custom program error: 0x....` form so the worker's retry
+/// classification keeps matching it.
+pub fn describe_simulation_error(err: &TransactionError) -> String {
+    match err {
+        TransactionError::InstructionError(idx, InstructionError::Custom(code)) => {
+            match shield_pool_error_name(*code) {
+                Some(name) => format!(
+                    "instruction {} failed: custom program error: {:#x} ({})",
+                    idx, code, name
+                ),
+                None => format!(
+                    "instruction {} failed: custom program error: {:#x}",
+                    idx, code
+                ),
+            }
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Name of a `ShieldPoolError` code, as the program defines it
+fn shield_pool_error_name(code: u32) -> Option<String> {
+    ShieldPoolError::try_from(code)
+        .ok()
+        .map(|err| format!("{:?}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_errors_are_fatal_and_named() {
+        let err = TransactionError::InstructionError(2, InstructionError::Custom(0x1023));
+        assert!(is_fatal_simulation_error(&err));
+        let msg = describe_simulation_error(&err);
+        assert!(msg.contains("custom program error: 0x1023"), "{}", msg);
+        assert!(msg.contains("NullifierAlreadyUsed"), "{}", msg);
+    }
+
+    #[test]
+    fn test_transient_errors_are_not_fatal() {
+        assert!(!is_fatal_simulation_error(
+            &TransactionError::BlockhashNotFound
+        ));
+        assert!(!is_fatal_simulation_error(&TransactionError::AccountInUse));
+        assert!(is_fatal_simulation_error(
+            &TransactionError::InsufficientFundsForFee
+        ));
+    }
+}
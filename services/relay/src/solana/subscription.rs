@@ -0,0 +1,357 @@
+//! Websocket subscriptions that survive disconnects
+//!
+//! A `PubsubClient` stream simply ends when the socket drops, so anything
+//! subscribed over `ws_url` would silently stop seeing updates. A
+//! [`ResilientSubscription`] reconnects with exponential backoff, resubscribes
+//! and exposes whether it is currently connected for health checks.
+
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_sdk::pubkey::Pubkey;
+use tokio::sync::{mpsc, oneshot, watch};
+use tracing::{debug, info, warn};
+
+use crate::error::Error;
+
+/// Delay before the first reconnect attempt, doubled after each failure
+pub const RECONNECT_INITIAL_DELAY: Duration = Duration::from_millis(500);
+/// Upper bound on the reconnect delay
+pub const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Notifications buffered per connection before the websocket task waits
+const NOTIFICATION_BUFFER: usize = 64;
+
+/// What to subscribe to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubscriptionTarget {
+    /// A single account, e.g. a nullifier shard
+    Account(Pubkey),
+    /// Every account owned by a program, e.g. the scramble registry's claims
+    Program(Pubkey),
+}
+
+/// An account change pushed over the websocket
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountNotification {
+    pub pubkey: Pubkey,
+    pub slot: u64,
+}
+
+/// One websocket connection's worth of notifications
+#[async_trait]
+pub trait SubscriptionTransport: Send + Sync {
+    /// Connect and subscribe to `target`. The receiver closes when the
+    /// connection drops.
+    async fn subscribe(
+        &self,
+        target: SubscriptionTarget,
+    ) -> Result<mpsc::Receiver<AccountNotification>, Error>;
+}
+
+/// Solana `PubsubClient` transport
+pub struct PubsubTransport {
+    ws_url: String,
+}
+
+impl PubsubTransport {
+    pub fn new(ws_url: String) -> Self {
+        Self { ws_url }
+    }
+}
+
+#[async_trait]
+impl SubscriptionTransport for PubsubTransport {
+    async fn subscribe(
+        &self,
+        target: SubscriptionTarget,
+    ) -> Result<mpsc::Receiver<AccountNotification>, Error> {
+        let client = PubsubClient::new(&self.ws_url).await.map_err(|e| {
+            Error::NetworkError(format!(
+                "Websocket connect to {} failed: {}",
+                self.ws_url, e
+            ))
+        })?;
+
+        // The subscription stream borrows the client, so both live in a task
+        // that reports whether subscribing worked
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let (tx, rx) = mpsc::channel(NOTIFICATION_BUFFER);
+        tokio::spawn(forward_notifications(client, target, ready_tx, tx));
+
+        ready_rx.await.map_err(|_| {
+            Error::NetworkError("Websocket task exited before subscribing".to_string())
+        })??;
+        Ok(rx)
+    }
+}
+
+async fn forward_notifications(
+    client: PubsubClient,
+    target: SubscriptionTarget,
+    ready: oneshot::Sender<Result<(), Error>>,
+    tx: mpsc::Sender<AccountNotification>,
+) {
+    forward_subscription(&client, target, ready, tx).await;
+    if let Err(e) = client.shutdown().await {
+        debug!("Websocket closed with error: {}", e);
+    }
+}
+
+async fn forward_subscription(
+    client: &PubsubClient,
+    target: SubscriptionTarget,
+    ready: oneshot::Sender<Result<(), Error>>,
+    tx: mpsc::Sender<AccountNotification>,
+) {
+    let subscribed =
+        match target {
+            SubscriptionTarget::Account(pubkey) => client
+                .account_subscribe(&pubkey, None)
+                .await
+                .map(|(stream, unsubscribe)| {
+                    let stream: BoxStream<'_, AccountNotification> = stream
+                        .map(move |update| AccountNotification {
+                            pubkey,
+                            slot: update.context.slot,
+                        })
+                        .boxed();
+                    (stream, unsubscribe)
+                }),
+            SubscriptionTarget::Program(program_id) => client
+                .program_subscribe(&program_id, None)
+                .await
+                .map(|(stream, unsubscribe)| {
+                    let stream: BoxStream<'_, AccountNotification> = stream
+                        .filter_map(|update| async move {
+                            let pubkey = update.value.pubkey.parse().ok()?;
+                            Some(AccountNotification {
+                                pubkey,
+                                slot: update.context.slot,
+                            })
+                        })
+                        .boxed();
+                    (stream, unsubscribe)
+                }),
+        };
+
+    let (mut stream, unsubscribe) = match subscribed {
+        Ok(subscribed) => {
+            let _ = ready.send(Ok(()));
+            subscribed
+        }
+        Err(e) => {
+            let _ = ready.send(Err(Error::NetworkError(format!(
+                "Websocket subscribe to {:?} failed: {}",
+                target, e
+            ))));
+            return;
+        }
+    };
+
+    loop {
+        tokio::select! {
+            update = stream.next() => match update {
+                Some(notification) => {
+                    if tx.send(notification).await.is_err() {
+                        break;
+                    }
+                }
+                // The socket dropped
+                None => break,
+            },
+            // The subscriber went away
+            _ = tx.closed() => break,
+        }
+    }
+
+    drop(stream);
+    unsubscribe().await;
+}
+
+/// A subscription that reconnects and resubscribes whenever its websocket
+/// drops
+pub struct ResilientSubscription {
+    transport: Box<dyn SubscriptionTransport>,
+    target: SubscriptionTarget,
+    initial_delay: Duration,
+    max_delay: Duration,
+    connected: AtomicBool,
+    reconnects: AtomicU64,
+}
+
+impl ResilientSubscription {
+    pub fn new(transport: Box<dyn SubscriptionTransport>, target: SubscriptionTarget) -> Self {
+        Self {
+            transport,
+            target,
+            initial_delay: RECONNECT_INITIAL_DELAY,
+            max_delay: RECONNECT_MAX_DELAY,
+            connected: AtomicBool::new(false),
+            reconnects: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_reconnect_delay(mut self, initial_delay: Duration, max_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Whether the websocket is currently subscribed
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Successful resubscriptions after a dropped or failed connection
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    /// Forward notifications to `updates` until shutdown or until the
+    /// receiver is dropped, reconnecting whenever the websocket drops
+    pub async fn run(
+        &self,
+        updates: mpsc::Sender<AccountNotification>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        let mut delay = self.initial_delay;
+        let mut ever_connected = false;
+
+        while !*shutdown.borrow() {
+            match self.transport.subscribe(self.target).await {
+                Ok(mut notifications) => {
+                    self.connected.store(true, Ordering::Relaxed);
+                    if ever_connected {
+                        let reconnects = self.reconnects.fetch_add(1, Ordering::Relaxed) + 1;
+                        info!(
+                            "🔌 Websocket resubscribed to {:?} (reconnect #{})",
+                            self.target, reconnects
+                        );
+                    } else {
+                        info!("🔌 Websocket subscribed to {:?}", self.target);
+                    }
+                    ever_connected = true;
+                    delay = self.initial_delay;
+
+                    loop {
+                        tokio::select! {
+                            notification = notifications.recv() => match notification {
+                                Some(notification) => {
+                                    if updates.send(notification).await.is_err() {
+                                        self.connected.store(false, Ordering::Relaxed);
+                                        return;
+                                    }
+                                }
+                                None => break,
+                            },
+                            // A dropped sender also means nobody is left to keep us running
+                            _ = shutdown.changed() => {
+                                self.connected.store(false, Ordering::Relaxed);
+                                return;
+                            }
+                        }
+                    }
+
+                    self.connected.store(false, Ordering::Relaxed);
+                    warn!(
+                        "⚠️  Websocket subscription to {:?} dropped, reconnecting in {:?}",
+                        self.target, delay
+                    );
+                }
+                Err(e) => warn!(
+                    "⚠️  Websocket subscription to {:?} failed, retrying in {:?}: {}",
+                    self.target, delay, e
+                ),
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = shutdown.changed() => break,
+            }
+            delay = (delay * 2).min(self.max_delay);
+        }
+
+        self.connected.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::AtomicUsize, Arc, Mutex};
+
+    use super::*;
+
+    /// Transport whose first connection delivers one notification and drops;
+    /// later connections stay open
+    struct DropOnceTransport {
+        subscribes: Arc<AtomicUsize>,
+        live: Mutex<Vec<mpsc::Sender<AccountNotification>>>,
+    }
+
+    #[async_trait]
+    impl SubscriptionTransport for DropOnceTransport {
+        async fn subscribe(
+            &self,
+            target: SubscriptionTarget,
+        ) -> Result<mpsc::Receiver<AccountNotification>, Error> {
+            let SubscriptionTarget::Account(pubkey) = target else {
+                panic!("unexpected target {:?}", target);
+            };
+            let attempt = self.subscribes.fetch_add(1, Ordering::SeqCst);
+            let (tx, rx) = mpsc::channel(1);
+            tx.try_send(AccountNotification {
+                pubkey,
+                slot: attempt as u64,
+            })
+            .unwrap();
+            if attempt > 0 {
+                self.live.lock().unwrap().push(tx);
+            }
+            Ok(rx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resubscribes_after_drop() {
+        let subscribes = Arc::new(AtomicUsize::new(0));
+        let transport = DropOnceTransport {
+            subscribes: subscribes.clone(),
+            live: Mutex::new(Vec::new()),
+        };
+        let pubkey = Pubkey::new_unique();
+        let subscription = Arc::new(
+            ResilientSubscription::new(Box::new(transport), SubscriptionTarget::Account(pubkey))
+                .with_reconnect_delay(Duration::from_millis(1), Duration::from_millis(1)),
+        );
+        assert!(!subscription.is_connected());
+
+        let (updates_tx, mut updates_rx) = mpsc::channel(8);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handle = tokio::spawn({
+            let subscription = subscription.clone();
+            async move { subscription.run(updates_tx, shutdown_rx).await }
+        });
+
+        // One notification from the connection that drops, then one after
+        // resubscribing
+        let first = updates_rx.recv().await.unwrap();
+        assert_eq!(first, AccountNotification { pubkey, slot: 0 });
+        let second = updates_rx.recv().await.unwrap();
+        assert_eq!(second, AccountNotification { pubkey, slot: 1 });
+
+        assert!(subscription.is_connected());
+        assert_eq!(subscription.reconnects(), 1);
+        assert_eq!(subscribes.load(Ordering::SeqCst), 2);
+
+        shutdown_tx.send(true).unwrap();
+        handle.await.unwrap();
+        assert!(!subscription.is_connected());
+        assert_eq!(subscribes.load(Ordering::SeqCst), 2);
+    }
+}
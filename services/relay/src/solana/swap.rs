@@ -1,4 +1,7 @@
-use std::str::FromStr;
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use reqwest;
@@ -12,7 +15,7 @@ use spl_associated_token_account::get_associated_token_address;
 use spl_token;
 use tracing::{error, info, warn};
 
-use crate::solana::{Error, SolanaClient};
+use crate::solana::{spl_token_account_amount, Error, SolanaClient};
 
 const JUPITER_QUOTE_API_V1: &str = "https://lite-api.jup.ag/swap/v1/quote";
 const JUPITER_SWAP_API_V1: &str = "https://lite-api.jup.ag/swap/v1/swap";
@@ -113,6 +116,7 @@ struct JupiterSwapResponse {
 /// * `output_mint` - The token mint to swap to
 /// * `min_output_amount` - Minimum output amount expected
 /// * `recipient_ata` - The recipient's associated token account for output tokens
+/// * `quote_max_age_secs` - Refetch/abort if the Jupiter quote is older than this at submission
 pub async fn perform_swap(
     client: &dyn SolanaClient,
     relay_keypair: &Keypair,
@@ -120,6 +124,7 @@ pub async fn perform_swap(
     output_mint: Pubkey,
     min_output_amount: u64,
     recipient_ata: Pubkey,
+    quote_max_age_secs: u64,
 ) -> Result<String, Error> {
     info!(
         "🔄 Starting token swap: {} SOL → {} (min: {})",
@@ -129,13 +134,14 @@ pub async fn perform_swap(
     );
 
     // Try Jupiter first
-    match perform_jupiter_swap(
+    let jupiter_err = match perform_jupiter_swap(
         client,
         relay_keypair,
         input_amount_lamports,
         output_mint,
         min_output_amount,
         recipient_ata,
+        Duration::from_secs(quote_max_age_secs),
     )
     .await
     {
@@ -145,11 +151,12 @@ pub async fn perform_swap(
         }
         Err(e) => {
             warn!("⚠️ Jupiter swap failed: {}. Trying Orca...", e);
+            e
         }
-    }
+    };
 
     // Fall back to Orca (not yet implemented)
-    match perform_orca_swap(
+    let orca_err = match perform_orca_swap(
         client,
         relay_keypair,
         input_amount_lamports,
@@ -165,11 +172,64 @@ pub async fn perform_swap(
         }
         Err(e) => {
             warn!("⚠️ Orca swap failed: {}", e);
+            e
         }
-    }
+    };
 
     error!("❌ Swap failed");
-    Err(Error::InternalServerError("Swap failed".to_string()))
+    Err(combine_swap_errors(jupiter_err, orca_err))
+}
+
+/// Pick the error to report when both swap routes failed.
+///
+/// A quote failure on both routes is reported as such; a PDA/account state
+/// problem on either wins since retrying won't fix it; anything else means a
+/// swap was attempted on-chain and reverted.
+fn combine_swap_errors(jupiter: Error, orca: Error) -> Error {
+    match (jupiter, orca) {
+        (Error::SwapQuoteFailed(j), Error::SwapQuoteFailed(o)) => {
+            Error::SwapQuoteFailed(format!("Jupiter: {}; Orca: {}", j, o))
+        }
+        (e @ Error::SwapStateMismatch(_), _) | (_, e @ Error::SwapStateMismatch(_)) => e,
+        (j, o) => Error::SwapExecutionFailed(format!("Jupiter: {}; Orca: {}", j, o)),
+    }
+}
+
+/// Parse the quoted output amount and check it against the proof's minimum
+fn check_quote_output(out_amount: &str, min_output_amount: u64) -> Result<u64, Error> {
+    let out_amount = out_amount
+        .parse::<u64>()
+        .map_err(|e| Error::SwapQuoteFailed(format!("Failed to parse output amount: {}", e)))?;
+
+    if out_amount < min_output_amount {
+        return Err(Error::SwapQuoteFailed(format!(
+            "Output amount {} is less than minimum required {}",
+            out_amount, min_output_amount
+        )));
+    }
+
+    Ok(out_amount)
+}
+
+/// Token balance of the relay's output ATA after a swap
+fn received_token_amount(data: &[u8]) -> Result<u64, Error> {
+    spl_token_account_amount(data)
+        .ok_or_else(|| Error::SwapStateMismatch("Invalid token account data".to_string()))
+}
+
+/// Quotes are refetched at most this many times before the swap is aborted
+const MAX_QUOTE_FETCHES: usize = 2;
+
+/// Fail if a quote that is `age` old has outlived `max_age`
+pub(crate) fn ensure_quote_fresh(age: Duration, max_age: Duration) -> Result<(), Error> {
+    if age > max_age {
+        return Err(Error::SwapQuoteFailed(format!(
+            "Quote is {:.1}s old, exceeding max age of {}s",
+            age.as_secs_f64(),
+            max_age.as_secs()
+        )));
+    }
+    Ok(())
 }
 
 async fn perform_jupiter_swap(
@@ -179,11 +239,104 @@ async fn perform_jupiter_swap(
     output_mint: Pubkey,
     min_output_amount: u64,
     recipient_ata: Pubkey,
+    quote_max_age: Duration,
 ) -> Result<String, Error> {
     info!("Attempting Jupiter swap...");
 
     let http_client = reqwest::Client::new();
 
+    let mut fetches = 0;
+    let (out_amount, signature) = loop {
+        fetches += 1;
+        let (out_amount, mut transaction, quoted_at) = fetch_jupiter_swap(
+            &http_client,
+            relay_keypair,
+            input_amount_lamports,
+            output_mint,
+            min_output_amount,
+        )
+        .await?;
+
+        // Step 3: Sign and send the swap transaction
+        let recent_blockhash = client.get_latest_blockhash().await?;
+
+        // The quote must still be fresh right before submission
+        match ensure_quote_fresh(quoted_at.elapsed(), quote_max_age) {
+            Ok(()) => {}
+            Err(e) if fetches < MAX_QUOTE_FETCHES => {
+                warn!("  {}; refetching quote", e);
+                continue;
+            }
+            Err(e) => return Err(e),
+        }
+
+        transaction.sign(&[relay_keypair], recent_blockhash);
+
+        let signature = client
+            .send_and_confirm_transaction(&transaction)
+            .await
+            .map_err(|e| Error::SwapExecutionFailed(format!("Jupiter swap failed: {}", e)))?;
+        break (out_amount, signature);
+    };
+
+    info!("  Swap transaction confirmed: {}", signature);
+
+    // Step 4: Transfer output tokens to recipient
+    // The swap sends tokens to relay's ATA, we need to transfer to recipient's ATA
+    let relay_ata = get_associated_token_address(&relay_keypair.pubkey(), &output_mint);
+
+    // Fetch the actual balance received
+    let relay_ata_account = client.get_account(&relay_ata).await?;
+
+    // Parse token account data to get balance
+    let actual_output_amount = received_token_amount(&relay_ata_account.data)?;
+
+    info!(
+        "  Received {} tokens from Jupiter swap (expected: {})",
+        actual_output_amount, out_amount
+    );
+
+    // Transfer ALL tokens to recipient
+    let transfer_ix = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &relay_ata,
+        &recipient_ata,
+        &relay_keypair.pubkey(),
+        &[&relay_keypair.pubkey()],
+        actual_output_amount, // Transfer exact amount received
+    )
+    .map_err(|e| {
+        Error::InternalServerError(format!("Failed to create transfer instruction: {}", e))
+    })?;
+
+    let recent_blockhash2 = client.get_latest_blockhash().await?;
+    let mut transfer_tx =
+        Transaction::new_with_payer(&[transfer_ix], Some(&relay_keypair.pubkey()));
+    transfer_tx.sign(&[relay_keypair], recent_blockhash2);
+
+    let transfer_sig = client
+        .send_and_confirm_transaction(&transfer_tx)
+        .await
+        .map_err(|e| Error::SwapExecutionFailed(format!("Token transfer failed: {}", e)))?;
+
+    info!("  Token transfer confirmed: {}", transfer_sig);
+
+    // Return the transfer signature since that's the transaction that actually changes
+    // the recipient's USDC balance
+    Ok(transfer_sig.to_string())
+}
+
+/// Fetch a Jupiter quote and the matching swap transaction.
+///
+/// Returns the quoted output amount, the unsigned transaction and when the
+/// quote was received.
+async fn fetch_jupiter_swap(
+    http_client: &reqwest::Client,
+    relay_keypair: &Keypair,
+    input_amount_lamports: u64,
+    output_mint: Pubkey,
+    min_output_amount: u64,
+) -> Result<(u64, Transaction, Instant), Error> {
     // Step 1: Get quote
     let quote_params = JupiterQuoteRequest {
         input_mint: WRAPPED_SOL.to_string(),
@@ -206,10 +359,11 @@ async fn perform_jupiter_swap(
         .get(&quote_url)
         .send()
         .await
-        .map_err(|e| Error::NetworkError(format!("Failed to send quote request: {}", e)))?
+        .map_err(|e| Error::SwapQuoteFailed(format!("Failed to send quote request: {}", e)))?
         .json::<JupiterQuoteResponse>()
         .await
-        .map_err(|e| Error::NetworkError(format!("Failed to parse Jupiter quote: {}", e)))?;
+        .map_err(|e| Error::SwapQuoteFailed(format!("Failed to parse Jupiter quote: {}", e)))?;
+    let quoted_at = Instant::now();
 
     info!(
         "  Quote: {} SOL → {} tokens",
@@ -218,17 +372,7 @@ async fn perform_jupiter_swap(
     );
 
     // Check if output meets minimum requirement
-    let out_amount = quote_response
-        .out_amount
-        .parse::<u64>()
-        .map_err(|e| Error::ValidationError(format!("Failed to parse output amount: {}", e)))?;
-
-    if out_amount < min_output_amount {
-        return Err(Error::ValidationError(format!(
-            "Output amount {} is less than minimum required {}",
-            out_amount, min_output_amount
-        )));
-    }
+    let out_amount = check_quote_output(&quote_response.out_amount, min_output_amount)?;
 
     // Step 2: Get swap transaction
     // Note: We use relay's keypair but specify recipient_ata for output
@@ -246,78 +390,21 @@ async fn perform_jupiter_swap(
         .json(&swap_request)
         .send()
         .await
-        .map_err(|e| Error::NetworkError(format!("Failed to send swap request: {}", e)))?
+        .map_err(|e| Error::SwapQuoteFailed(format!("Failed to send swap request: {}", e)))?
         .json::<JupiterSwapResponse>()
         .await
         .map_err(|e| {
-            Error::NetworkError(format!("Failed to parse Jupiter swap response: {}", e))
+            Error::SwapQuoteFailed(format!("Failed to parse Jupiter swap response: {}", e))
         })?;
 
-    // Step 3: Sign and send the swap transaction
     let tx_bytes = BASE64
         .decode(&swap_response.swap_transaction)
-        .map_err(|e| Error::SerializationError(format!("Failed to decode transaction: {}", e)))?;
-
-    let mut transaction: Transaction = bincode::deserialize(&tx_bytes).map_err(|e| {
-        Error::SerializationError(format!("Failed to deserialize transaction: {}", e))
-    })?;
-
-    let recent_blockhash = client.get_latest_blockhash().await?;
-    transaction.sign(&[relay_keypair], recent_blockhash);
-
-    let signature = client.send_and_confirm_transaction(&transaction).await?;
-
-    info!("  Swap transaction confirmed: {}", signature);
-
-    // Step 4: Transfer output tokens to recipient
-    // The swap sends tokens to relay's ATA, we need to transfer to recipient's ATA
-    let relay_ata = get_associated_token_address(&relay_keypair.pubkey(), &output_mint);
+        .map_err(|e| Error::SwapQuoteFailed(format!("Failed to decode transaction: {}", e)))?;
 
-    // Fetch the actual balance received
-    let relay_ata_account = client.get_account(&relay_ata).await?;
+    let transaction: Transaction = bincode::deserialize(&tx_bytes)
+        .map_err(|e| Error::SwapQuoteFailed(format!("Failed to deserialize transaction: {}", e)))?;
 
-    // Parse token account data to get balance
-    // Token account data layout: [mint(32), owner(32), amount(8), ...]
-    if relay_ata_account.data.len() < 72 {
-        return Err(Error::InternalServerError(
-            "Invalid token account data".to_string(),
-        ));
-    }
-    let amount_bytes: [u8; 8] = relay_ata_account.data[64..72]
-        .try_into()
-        .map_err(|_| Error::InternalServerError("Failed to parse amount".to_string()))?;
-    let actual_output_amount = u64::from_le_bytes(amount_bytes);
-
-    info!(
-        "  Received {} tokens from Jupiter swap (expected: {})",
-        actual_output_amount, out_amount
-    );
-
-    // Transfer ALL tokens to recipient
-    let transfer_ix = spl_token::instruction::transfer(
-        &spl_token::id(),
-        &relay_ata,
-        &recipient_ata,
-        &relay_keypair.pubkey(),
-        &[&relay_keypair.pubkey()],
-        actual_output_amount, // Transfer exact amount received
-    )
-    .map_err(|e| {
-        Error::InternalServerError(format!("Failed to create transfer instruction: {}", e))
-    })?;
-
-    let recent_blockhash2 = client.get_latest_blockhash().await?;
-    let mut transfer_tx =
-        Transaction::new_with_payer(&[transfer_ix], Some(&relay_keypair.pubkey()));
-    transfer_tx.sign(&[relay_keypair], recent_blockhash2);
-
-    let transfer_sig = client.send_and_confirm_transaction(&transfer_tx).await?;
-
-    info!("  Token transfer confirmed: {}", transfer_sig);
-
-    // Return the transfer signature since that's the transaction that actually changes
-    // the recipient's USDC balance
-    Ok(transfer_sig.to_string())
+    Ok((out_amount, transaction, quoted_at))
 }
 
 async fn perform_orca_swap(
@@ -407,7 +494,7 @@ async fn perform_orca_swap(
         }
     }
 
-    Err(Error::InternalServerError(
+    Err(Error::SwapExecutionFailed(
         "All Orca pools failed. Devnet pools may be too imbalanced.".to_string(),
     ))
 }
@@ -557,7 +644,10 @@ async fn perform_orca_swap_with_pool(
     transaction.sign(&[relay_keypair], recent_blockhash);
 
     // Send transaction
-    let signature = client.send_and_confirm_transaction(&transaction).await?;
+    let signature = client
+        .send_and_confirm_transaction(&transaction)
+        .await
+        .map_err(|e| Error::SwapExecutionFailed(format!("Orca swap failed: {}", e)))?;
 
     info!("  Orca swap confirmed: {}", signature);
 
@@ -565,16 +655,7 @@ async fn perform_orca_swap_with_pool(
     let relay_ata_account = client.get_account(&relay_output_ata).await?;
 
     // Parse token account data to get balance
-    // Token account data layout: [mint(32), owner(32), amount(8), ...]
-    if relay_ata_account.data.len() < 72 {
-        return Err(Error::InternalServerError(
-            "Invalid token account data".to_string(),
-        ));
-    }
-    let amount_bytes: [u8; 8] = relay_ata_account.data[64..72]
-        .try_into()
-        .map_err(|_| Error::InternalServerError("Failed to parse amount".to_string()))?;
-    let actual_output_amount = u64::from_le_bytes(amount_bytes);
+    let actual_output_amount = received_token_amount(&relay_ata_account.data)?;
 
     info!(
         "  Received {} tokens from Orca swap (min required: {})",
@@ -599,7 +680,10 @@ async fn perform_orca_swap_with_pool(
         Transaction::new_with_payer(&[transfer_ix], Some(&relay_keypair.pubkey()));
     transfer_tx.sign(&[relay_keypair], recent_blockhash2);
 
-    let transfer_sig = client.send_and_confirm_transaction(&transfer_tx).await?;
+    let transfer_sig = client
+        .send_and_confirm_transaction(&transfer_tx)
+        .await
+        .map_err(|e| Error::SwapExecutionFailed(format!("Token transfer failed: {}", e)))?;
 
     info!("  Token transfer to recipient confirmed: {}", transfer_sig);
 
@@ -685,3 +769,68 @@ pub async fn ensure_ata_exists(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_failures_map_to_swap_quote_failed() {
+        assert_eq!(check_quote_output("1500", 1000).unwrap(), 1500);
+        assert!(matches!(
+            check_quote_output("not-a-number", 1000),
+            Err(Error::SwapQuoteFailed(_))
+        ));
+        assert!(matches!(
+            check_quote_output("999", 1000),
+            Err(Error::SwapQuoteFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_stale_quote_is_rejected() {
+        let max_age = Duration::from_secs(30);
+
+        assert!(ensure_quote_fresh(Duration::ZERO, max_age).is_ok());
+        assert!(ensure_quote_fresh(Duration::from_secs(30), max_age).is_ok());
+
+        let err = ensure_quote_fresh(Duration::from_secs(31), max_age).unwrap_err();
+        assert!(matches!(err, Error::SwapQuoteFailed(_)));
+        assert!(err.to_string().contains("max age of 30s"), "{}", err);
+    }
+
+    #[test]
+    fn test_bad_token_account_maps_to_swap_state_mismatch() {
+        let mut data = vec![0u8; 165];
+        data[64..72].copy_from_slice(&42u64.to_le_bytes());
+        assert_eq!(received_token_amount(&data).unwrap(), 42);
+        assert!(matches!(
+            received_token_amount(&data[..40]),
+            Err(Error::SwapStateMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn test_combined_route_failures_keep_category() {
+        let quote = || Error::SwapQuoteFailed("no route".into());
+        let exec = || Error::SwapExecutionFailed("slippage exceeded".into());
+        let state = || Error::SwapStateMismatch("bad ATA".into());
+
+        assert!(matches!(
+            combine_swap_errors(quote(), quote()),
+            Error::SwapQuoteFailed(_)
+        ));
+        assert!(matches!(
+            combine_swap_errors(quote(), exec()),
+            Error::SwapExecutionFailed(_)
+        ));
+        assert!(matches!(
+            combine_swap_errors(exec(), state()),
+            Error::SwapStateMismatch(_)
+        ));
+        assert!(matches!(
+            combine_swap_errors(Error::NetworkError("rpc down".into()), exec()),
+            Error::SwapExecutionFailed(_)
+        ));
+    }
+}
@@ -34,7 +34,12 @@ fn get_associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
     ata
 }
 
-const PUBLIC_INPUTS_LEN: usize = 104;
+/// root (32) || nullifier (32) || outputs_hash (32) || amount (8)
+#[cfg(not(feature = "proof-freshness"))]
+pub(crate) const PUBLIC_INPUTS_LEN: usize = 104;
+/// root (32) || nullifier (32) || outputs_hash (32) || amount (8) || generated_at slot (8)
+#[cfg(feature = "proof-freshness")]
+pub(crate) const PUBLIC_INPUTS_LEN: usize = 112;
 const DUPLICATE_NULLIFIER_LEN: usize = 32;
 const NUM_OUTPUTS_LEN: usize = 1;
 const RECIPIENT_ADDR_LEN: usize = 32;
@@ -440,7 +445,55 @@ pub fn derive_scramble_registry_pdas(
     (registry_pda, miner_pda, claim_pda)
 }
 
+/// Idempotent create-ATA instructions for each recipient's token account,
+/// paid by `fee_payer`. Existing ATAs are left untouched, so these are safe
+/// to include when it is unknown whether a recipient has one.
+pub fn build_create_recipient_ata_instructions(
+    fee_payer: &Pubkey,
+    recipients: &[Pubkey],
+    mint: &Pubkey,
+) -> Vec<Instruction> {
+    recipients
+        .iter()
+        .map(|recipient| {
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                fee_payer,
+                recipient,
+                mint,
+                &spl_token::id(),
+            )
+        })
+        .collect()
+}
+
+/// Compute budget and priority fee, then (for SPL withdraws with
+/// `create_recipient_atas`) recipient ATA creation, then the withdraw itself
+fn withdraw_transaction_instructions(
+    withdraw_ix: Instruction,
+    recipients: &[Pubkey],
+    fee_payer: Pubkey,
+    priority_micro_lamports: u64,
+    mint: Option<Pubkey>,
+    create_recipient_atas: bool,
+) -> Vec<Instruction> {
+    let mut instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(400_000),
+        ComputeBudgetInstruction::set_compute_unit_price(priority_micro_lamports),
+    ];
+    if let (true, Some(mint)) = (create_recipient_atas, mint) {
+        instructions.extend(build_create_recipient_ata_instructions(
+            &fee_payer, recipients, &mint,
+        ));
+    }
+    instructions.push(withdraw_ix);
+    instructions
+}
+
 /// Build a full legacy Transaction including compute budget and priority fee (no PoW).
+///
+/// For SPL withdraws, `create_recipient_atas` prepends idempotent ATA creation
+/// for every recipient so fresh wallets can receive tokens; callers that know
+/// the ATAs exist can pass `false` to keep the transaction smaller.
 pub fn build_withdraw_transaction(
     proof_bytes: Vec<u8>,
     public_104: [u8; PUBLIC_INPUTS_LEN],
@@ -458,6 +511,7 @@ pub fn build_withdraw_transaction(
     pool_token_account: Option<Pubkey>,
     recipient_token_accounts: Option<&[Pubkey]>,
     treasury_token_account: Option<Pubkey>,
+    create_recipient_atas: bool,
 ) -> Result<Transaction, Error> {
     let body = build_withdraw_ix_body(proof_bytes.as_slice(), &public_104, outputs)?;
     let withdraw_ix = build_withdraw_instruction(
@@ -474,11 +528,15 @@ pub fn build_withdraw_transaction(
         treasury_token_account,
     );
 
-    // Optimize compute units for transaction size
-    let cu_ix = ComputeBudgetInstruction::set_compute_unit_limit(400_000);
-    let pri_ix = ComputeBudgetInstruction::set_compute_unit_price(priority_micro_lamports);
-
-    let mut msg = Message::new(&[cu_ix, pri_ix, withdraw_ix], Some(&fee_payer));
+    let instructions = withdraw_transaction_instructions(
+        withdraw_ix,
+        recipients,
+        fee_payer,
+        priority_micro_lamports,
+        mint,
+        create_recipient_atas,
+    );
+    let mut msg = Message::new(&instructions, Some(&fee_payer));
     msg.recent_blockhash = recent_blockhash;
     let tx = Transaction::new_unsigned(msg);
     Ok(tx)
@@ -580,6 +638,7 @@ pub fn build_release_swap_funds_instruction(
 /// Build a full legacy Transaction with PoW support.
 ///
 /// This variant includes the PoW scrambler accounts and batch_hash in instruction data.
+/// `create_recipient_atas` behaves as in [`build_withdraw_transaction`].
 #[allow(clippy::too_many_arguments)]
 pub fn build_withdraw_transaction_with_pow(
     proof_bytes: Vec<u8>,
@@ -605,6 +664,7 @@ pub fn build_withdraw_transaction_with_pow(
     recipient_token_accounts: Option<&[Pubkey]>,
     treasury_token_account: Option<Pubkey>,
     miner_token_account: Option<Pubkey>,
+    create_recipient_atas: bool,
 ) -> Result<Transaction, Error> {
     let body =
         build_withdraw_ix_body_with_pow(proof_bytes.as_slice(), &public_104, outputs, &batch_hash)?;
@@ -628,10 +688,15 @@ pub fn build_withdraw_transaction_with_pow(
         miner_token_account,
     );
 
-    let cu_ix = ComputeBudgetInstruction::set_compute_unit_limit(400_000);
-    let pri_ix = ComputeBudgetInstruction::set_compute_unit_price(priority_micro_lamports);
-
-    let mut msg = Message::new(&[cu_ix, pri_ix, withdraw_ix], Some(&fee_payer));
+    let instructions = withdraw_transaction_instructions(
+        withdraw_ix,
+        recipients,
+        fee_payer,
+        priority_micro_lamports,
+        mint,
+        create_recipient_atas,
+    );
+    let mut msg = Message::new(&instructions, Some(&fee_payer));
     msg.recent_blockhash = recent_blockhash;
     let tx = Transaction::new_unsigned(msg);
     Ok(tx)
@@ -895,6 +960,7 @@ pub fn build_withdraw_instruction_legacy(
         pool_token_account,
         recipient_token_accounts_slice,
         treasury_token_account,
+        false,
     )
 }
 
@@ -1237,6 +1303,78 @@ mod tests {
         assert_eq!(&body[amount_start..amount_end], &out_amt.to_le_bytes());
     }
 
+    #[test]
+    fn test_spl_withdraw_creates_recipient_atas_first() {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let fee_payer = Pubkey::new_unique();
+        let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let outputs: Vec<Output> = recipients
+            .iter()
+            .map(|r| Output {
+                address: r.to_bytes(),
+                amount: 500_000,
+            })
+            .collect();
+        let (pool, treasury, roots_ring, nullifier_shard) =
+            derive_shield_pool_pdas(&program_id, &mint);
+        let recipient_atas: Vec<Pubkey> = recipients
+            .iter()
+            .map(|r| get_associated_token_address(r, &mint))
+            .collect();
+
+        let build = |create_recipient_atas: bool| {
+            build_withdraw_transaction(
+                vec![0xABu8; 260],
+                [0u8; PUBLIC_INPUTS_LEN],
+                &outputs,
+                program_id,
+                pool,
+                roots_ring,
+                nullifier_shard,
+                treasury,
+                &recipients,
+                fee_payer,
+                solana_sdk::hash::Hash::new_unique(),
+                1_000,
+                Some(mint),
+                Some(get_associated_token_address(&pool, &mint)),
+                Some(&recipient_atas),
+                Some(get_associated_token_address(&treasury, &mint)),
+                create_recipient_atas,
+            )
+            .expect("tx")
+        };
+
+        let tx = build(true);
+        let msg = tx.message();
+        let program_of = |i: usize| msg.account_keys[msg.instructions[i].program_id_index as usize];
+        assert_eq!(
+            msg.instructions.len(),
+            5,
+            "CU, fee, 2 ATA creations, withdraw"
+        );
+        assert_eq!(msg.account_keys[0], fee_payer);
+
+        for (i, ata) in recipient_atas.iter().enumerate() {
+            let ix = &msg.instructions[2 + i];
+            assert_eq!(program_of(2 + i), spl_associated_token_account::id());
+            // CreateIdempotent, funded by the fee payer
+            assert_eq!(ix.data, vec![1u8]);
+            assert_eq!(msg.account_keys[ix.accounts[0] as usize], fee_payer);
+            assert_eq!(msg.account_keys[ix.accounts[1] as usize], *ata);
+        }
+        assert_eq!(program_of(4), program_id);
+
+        // Callers that know the ATAs exist skip them
+        let tx = build(false);
+        assert_eq!(tx.message().instructions.len(), 3);
+        assert_eq!(
+            tx.message().account_keys[tx.message().instructions[2].program_id_index as usize],
+            program_id
+        );
+    }
+
     #[test]
     fn test_legacy_builder_derives_pdas_and_accounts_order() {
         // Program id and PDAs
@@ -78,6 +78,15 @@ pub struct SwapConfig {
     /// Minimum output amount (from Jupiter quote, baked into ZK proof)
     /// This MUST match the value used when generating the proof
     pub min_output_amount: u64,
+    /// Maximum age of the quote behind `min_output_amount` when the swap
+    /// executes, counted from when the withdraw was accepted; older swaps are
+    /// aborted to avoid executing at a stale price
+    #[serde(default = "default_quote_max_age_secs")]
+    pub quote_max_age_secs: u64,
+}
+
+fn default_quote_max_age_secs() -> u64 {
+    30
 }
 
 impl SwapConfig {
@@ -92,6 +101,11 @@ impl SwapConfig {
             return Err("Slippage too high (max 10%)".to_string());
         }
 
+        // Validate quote deadline (1s - 5min)
+        if !(1..=300).contains(&self.quote_max_age_secs) {
+            return Err("Quote max age must be between 1 and 300 seconds".to_string());
+        }
+
         Ok(())
     }
 }
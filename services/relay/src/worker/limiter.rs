@@ -0,0 +1,108 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Caps how many jobs the relay processes at once across every window, so a
+/// burst of jobs queues instead of running enough submissions (or server-side
+/// proofs) in parallel to exhaust memory.
+pub struct JobLimiter {
+    semaphore: Arc<Semaphore>,
+    limit: usize,
+    in_flight: Arc<AtomicUsize>,
+    waiting: AtomicUsize,
+}
+
+/// Slot held while a job runs; frees it for the next queued job on drop
+pub struct JobPermit {
+    _permit: OwnedSemaphorePermit,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for JobPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl JobLimiter {
+    /// A limit of 0 is treated as 1 so jobs can still make progress
+    pub fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(limit)),
+            limit,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            waiting: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait for a free slot
+    pub async fn acquire(&self) -> JobPermit {
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        let permit = Arc::clone(&self.semaphore)
+            .acquire_owned()
+            .await
+            .expect("job limiter semaphore is never closed");
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+
+        JobPermit {
+            _permit: permit,
+            in_flight: Arc::clone(&self.in_flight),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Jobs currently holding a slot
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Jobs queued behind the limit
+    pub fn waiting(&self) -> usize {
+        self.waiting.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_second_job_waits_for_first_at_limit_one() {
+        let limiter = Arc::new(JobLimiter::new(1));
+
+        let first = limiter.acquire().await;
+        assert_eq!(limiter.in_flight(), 1);
+
+        let second = tokio::spawn({
+            let limiter = Arc::clone(&limiter);
+            async move { limiter.acquire().await }
+        });
+
+        // The second job queues while the first holds the only slot
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!second.is_finished());
+        assert_eq!(limiter.in_flight(), 1);
+        assert_eq!(limiter.waiting(), 1);
+
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_secs(1), second)
+            .await
+            .expect("second job should start once the first finishes")
+            .unwrap();
+        assert_eq!(limiter.in_flight(), 1);
+        assert_eq!(limiter.waiting(), 0);
+
+        drop(second);
+        assert_eq!(limiter.in_flight(), 0);
+    }
+}
@@ -1,4 +1,7 @@
+pub mod limiter;
+pub mod priority;
 pub mod processor;
+pub mod reaper;
 pub mod window_scheduler;
 
 use std::{sync::Arc, time::Duration};
@@ -10,7 +13,6 @@ use crate::{db::repository::JobRepository, AppState};
 pub struct Worker {
     state: AppState,
     poll_interval: Duration,
-    max_concurrent_jobs: usize,
 }
 
 impl Worker {
@@ -18,7 +20,6 @@ impl Worker {
         Self {
             state,
             poll_interval: Duration::from_secs(1),
-            max_concurrent_jobs: 10,
         }
     }
 
@@ -27,18 +28,11 @@ impl Worker {
         self
     }
 
-    pub fn with_max_concurrent_jobs(mut self, max: usize) -> Self {
-        self.max_concurrent_jobs = max;
-        self
-    }
-
     /// Start the worker loop
     pub async fn run(self) {
         info!("🚀 Worker started");
         info!("   Poll interval: {:?}", self.poll_interval);
-        info!("   Max concurrent jobs: {}", self.max_concurrent_jobs);
-
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(self.max_concurrent_jobs));
+        info!("   Max concurrent jobs: {}", self.state.job_limiter.limit());
 
         loop {
             // Poll database for queued jobs
@@ -49,11 +43,11 @@ impl Worker {
 
                         // Spawn a task to process the job
                         let state = self.state.clone();
-                        let semaphore = Arc::clone(&semaphore);
+                        let limiter = Arc::clone(&self.state.job_limiter);
 
                         tokio::spawn(async move {
-                            // Acquire semaphore permit
-                            let _permit = semaphore.acquire().await.unwrap();
+                            // Wait for a slot under the shared concurrency limit
+                            let _permit = limiter.acquire().await;
 
                             // Process the job
                             if let Err(e) = processor::process_job_direct(job, state).await {
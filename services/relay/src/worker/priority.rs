@@ -0,0 +1,180 @@
+//! Priority ordering for queued jobs
+//!
+//! Higher-priority jobs run first, but every `PRIORITY_AGING_SECS` a job has
+//! waited counts as one extra priority level, so a steady stream of
+//! high-priority jobs can't starve older low-priority ones. The database query
+//! that picks up queued jobs and the window scheduler's buffer share this order.
+
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::db::models::Job;
+
+/// Highest priority a withdraw may request
+pub const MAX_JOB_PRIORITY: i16 = 10;
+
+/// Waiting this many seconds is worth one priority level
+pub const PRIORITY_AGING_SECS: i64 = 30;
+
+/// When the job would have been created had it waited out its priority;
+/// earlier runs first
+pub fn effective_created_at(job: &Job) -> DateTime<Utc> {
+    job.created_at - Duration::seconds(i64::from(job.priority) * PRIORITY_AGING_SECS)
+}
+
+struct Entry {
+    key: DateTime<Utc>,
+    /// Insertion order, so jobs with the same key stay first-in first-out
+    seq: u64,
+    job: Job,
+}
+
+impl Ord for Entry {
+    // `BinaryHeap` pops the greatest entry, so the earliest key compares greatest
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .key
+            .cmp(&self.key)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+/// Jobs waiting for a processing slot, popped in priority order with aging
+#[derive(Default)]
+pub struct JobPriorityQueue {
+    heap: BinaryHeap<Entry>,
+    next_seq: u64,
+}
+
+impl JobPriorityQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, job: Job) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.heap.push(Entry {
+            key: effective_created_at(&job),
+            seq,
+            job,
+        });
+    }
+
+    /// The job that should run next
+    pub fn pop(&mut self) -> Option<Job> {
+        self.heap.pop().map(|entry| entry.job)
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, id: Uuid) -> bool {
+        self.heap.iter().any(|entry| entry.job.id == id)
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&Job) -> bool) {
+        self.heap.retain(|entry| keep(&entry.job));
+    }
+
+    /// Remove every job, in the order they should run
+    pub fn drain_in_order(&mut self) -> Vec<Job> {
+        let mut jobs = Vec::with_capacity(self.len());
+        while let Some(job) = self.pop() {
+            jobs.push(job);
+        }
+        jobs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::JobStatus;
+
+    fn job(priority: i16, created_at: DateTime<Utc>) -> Job {
+        Job {
+            id: Uuid::new_v4(),
+            request_id: Uuid::new_v4(),
+            status: JobStatus::Queued,
+            proof_bytes: vec![],
+            public_inputs: vec![0u8; 104],
+            outputs_json: serde_json::json!([]),
+            fee_bps: 0,
+            root_hash: vec![0u8; 32],
+            nullifier: vec![0u8; 32],
+            amount: 0,
+            outputs_hash: vec![0u8; 32],
+            priority,
+            tx_id: None,
+            solana_signature: None,
+            error_message: None,
+            retry_count: 0,
+            max_retries: 3,
+            created_at,
+            updated_at: created_at,
+            started_at: None,
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_high_priority_jumps_ahead_of_earlier_low_priority() {
+        let t0 = Utc::now();
+        let low = job(0, t0);
+        let high = job(1, t0 + Duration::seconds(10));
+        let (low_id, high_id) = (low.id, high.id);
+
+        let mut queue = JobPriorityQueue::new();
+        queue.push(low);
+        queue.push(high);
+
+        assert_eq!(queue.pop().unwrap().id, high_id);
+        assert_eq!(queue.pop().unwrap().id, low_id);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_starved_low_priority_job_eventually_runs() {
+        let t0 = Utc::now();
+        let low = job(0, t0);
+        let low_id = low.id;
+
+        // A max-priority job arrives every second for well past the aging window
+        let aging_window = i64::from(MAX_JOB_PRIORITY) * PRIORITY_AGING_SECS;
+        let mut queue = JobPriorityQueue::new();
+        queue.push(low);
+        for secs in 1..=aging_window + 60 {
+            queue.push(job(MAX_JOB_PRIORITY, t0 + Duration::seconds(secs)));
+        }
+
+        // Only the high-priority jobs that arrived within the window go first
+        let order = queue.drain_in_order();
+        let position = order.iter().position(|job| job.id == low_id).unwrap();
+        assert_eq!(position as i64, aging_window - 1);
+        assert!(order[position + 1..]
+            .iter()
+            .all(|job| job.created_at >= t0 + Duration::seconds(aging_window)));
+    }
+}
@@ -1,15 +1,16 @@
 use std::time::{Duration, Instant};
 
-use blake3::Hasher;
 use bs58;
 use tracing::{error, info, warn};
 
 use crate::{
     db::{
         models::{Job, JobStatus},
-        repository::{JobRepository, NullifierRepository},
+        repository::{JobRepository, NullifierRepository, SwapReceiptRepository},
     },
     error::Error,
+    planner::{compute_outputs_hash, Output},
+    solana::WithdrawSubmission,
     AppState,
 };
 
@@ -85,8 +86,8 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
         return Ok(());
     }
 
-    // Optional preflights when public_inputs are canonical 104B
-    if job.public_inputs.len() == 104 {
+    // Optional preflights when public_inputs carry the canonical 104B prefix
+    if job.public_inputs.len() >= 104 {
         if let Some(arr) = job.outputs_json.as_array() {
             if arr.len() == 1 {
                 if let (Some(recipient), Some(amount)) = (
@@ -95,12 +96,9 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
                 ) {
                     // outputs_hash preflight
                     if let Ok(addr_bytes) = bs58::decode(recipient).into_vec() {
-                        if addr_bytes.len() == 32 {
-                            let mut hasher = Hasher::new();
-                            hasher.update(&addr_bytes);
-                            hasher.update(&amount.to_le_bytes());
-                            let calc = hasher.finalize();
-                            if calc.as_bytes() != job.outputs_hash.as_slice() {
+                        if let Ok(address) = <[u8; 32]>::try_from(addr_bytes) {
+                            let calc = compute_outputs_hash(&[Output { address, amount }]);
+                            if calc.as_slice() != job.outputs_hash.as_slice() {
                                 warn!("Job {} outputs_hash != recomputed; continuing but this will fail on-chain", job_id);
                             }
                         }
@@ -146,7 +144,7 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
             // Store nullifier to prevent double-spending (if not already in local DB)
             if let Err(e) = state
                 .nullifier_repo
-                .create_nullifier(job.nullifier.clone(), job_id)
+                .insert_nullifier(job.nullifier.clone(), job_id)
                 .await
             {
                 // Ignore duplicate key errors - nullifier might already be in our DB
@@ -196,7 +194,7 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
             // Store nullifier to prevent double-spending (if not already in local DB)
             if let Err(e) = state
                 .nullifier_repo
-                .create_nullifier(job.nullifier.clone(), job_id)
+                .insert_nullifier(job.nullifier.clone(), job_id)
                 .await
             {
                 // Ignore duplicate key errors - nullifier might already be in our DB
@@ -218,7 +216,15 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
     }
 
     match process_withdraw(&job, &state).await {
-        Ok(signature) => {
+        Ok(None) => {
+            // The job stays processing until the agent's transaction confirms via /submit
+            info!(
+                "📤 Job {} transaction stored for external submission",
+                job_id
+            );
+            Ok(())
+        }
+        Ok(Some(signature)) => {
             info!("✅ Job {} completed successfully", job_id);
             info!("   Transaction signature: {}", signature);
 
@@ -235,7 +241,7 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
             // Store nullifier to prevent double-spending
             if let Err(e) = state
                 .nullifier_repo
-                .create_nullifier(job.nullifier.clone(), job_id)
+                .insert_nullifier(job.nullifier.clone(), job_id)
                 .await
             {
                 error!("⚠️  Failed to store nullifier for job {}: {}", job_id, e);
@@ -318,7 +324,7 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
                                 // Store nullifier to prevent double-spending
                                 if let Err(e) = state
                                     .nullifier_repo
-                                    .create_nullifier(job.nullifier.clone(), job_id)
+                                    .insert_nullifier(job.nullifier.clone(), job_id)
                                     .await
                                 {
                                     tracing::debug!("Nullifier storage: {}", e);
@@ -361,7 +367,7 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
                 // Store nullifier to prevent double-spending (if not already in local DB)
                 if let Err(e) = state
                     .nullifier_repo
-                    .create_nullifier(job.nullifier.clone(), job_id)
+                    .insert_nullifier(job.nullifier.clone(), job_id)
                     .await
                 {
                     // Ignore duplicate key errors
@@ -404,7 +410,7 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
 
                         if let Err(e) = state
                             .nullifier_repo
-                            .create_nullifier(job.nullifier.clone(), job_id)
+                            .insert_nullifier(job.nullifier.clone(), job_id)
                             .await
                         {
                             tracing::debug!("Nullifier storage: {}", e);
@@ -432,31 +438,31 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
 
             // Check if we should retry based on error type
             let should_retry = !error_str.contains("MissingAccounts") && // Don't retry account errors
-                !error_str.contains("ProofInvalid"); // Don't retry proof errors
+                !error_str.contains("ProofInvalid") && // Don't retry proof errors
+                !matches!(e, Error::RecipientCannotReceive { .. }); // Recipient won't fix itself
 
             if should_retry {
-                warn!("🔄 Retrying job {} - Error: {}", job_id, e);
-
-                // Update status to queued (for retry)
-                if let Err(status_err) = state
+                // Requeue (for retry) until max_retries is used up, then dead-letter
+                match state
                     .job_repo
-                    .update_job_status(job_id, JobStatus::Queued)
+                    .record_failed_attempt(job_id, error_str.clone())
                     .await
                 {
-                    error!(
+                    Ok(false) => warn!("🔄 Retrying job {} - Error: {}", job_id, e),
+                    Ok(true) => error!(
+                        "❌ Job {} exhausted its retries, moved to failed_jobs: {}",
+                        job_id, e
+                    ),
+                    Err(status_err) => error!(
                         "❌ Failed to update job {} status for retry: {}",
                         job_id, status_err
-                    );
+                    ),
                 }
             } else {
                 error!("❌ Job {} failed permanently: {}", job_id, e);
 
-                // Update job status to failed
-                if let Err(e) = state
-                    .job_repo
-                    .update_job_status(job_id, JobStatus::Failed)
-                    .await
-                {
+                // Update job status to failed and keep it for post-mortem
+                if let Err(e) = state.job_repo.dead_letter_job(job_id, error_str).await {
                     error!("❌ Failed to update job {} status to failed: {}", job_id, e);
                 }
             }
@@ -466,15 +472,39 @@ pub async fn process_job_direct(job: Job, state: AppState) -> Result<(), Error>
     }
 }
 
-/// Process a withdraw transaction (placeholder - needs actual Solana integration)
-async fn process_withdraw(job: &crate::db::models::Job, state: &AppState) -> Result<String, Error> {
+/// Process a withdraw transaction, returning its signature, or `None` when it
+/// was left for an external agent to submit
+async fn process_withdraw(
+    job: &crate::db::models::Job,
+    state: &AppState,
+) -> Result<Option<String>, Error> {
     info!(
         "🔐 Building & submitting withdraw transaction for job {}",
         job.id
     );
-    let sig = state.solana.submit_withdraw(job).await?;
+    let sig = match state.solana.submit_withdraw(job).await? {
+        WithdrawSubmission::Confirmed(sig) => sig,
+        WithdrawSubmission::Swap { result, receipt } => {
+            // Stored even when a later step failed, so the steps a retry
+            // skips keep their signatures
+            if !receipt.is_empty() {
+                if let Err(e) = state.swap_receipt_repo.insert_swap_receipt(receipt).await {
+                    error!("⚠️  Failed to store swap receipt for job {}: {}", job.id, e);
+                }
+            }
+            result?
+        }
+        WithdrawSubmission::External(pending) => {
+            // Persisted so the agent can still fetch it after a relay restart
+            state
+                .job_repo
+                .set_pending_transaction(job.id, pending)
+                .await?;
+            return Ok(None);
+        }
+    };
     info!("✅ Transaction submitted: {}", sig);
-    Ok(sig.to_string())
+    Ok(Some(sig.to_string()))
 }
 
 #[cfg(test)]
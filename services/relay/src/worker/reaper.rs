@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::{db::repository::JobRepository, shutdown::run_until_shutdown, AppState};
+
+/// Periodically expires queued jobs that were never picked up (e.g. no PoW claim
+/// became available), so clients see a terminal state instead of polling forever.
+pub struct JobReaper {
+    state: AppState,
+    max_job_age_secs: u64,
+    interval: Duration,
+}
+
+impl JobReaper {
+    pub fn new(state: AppState, max_job_age_secs: u64) -> Self {
+        Self {
+            state,
+            max_job_age_secs,
+            interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Start the reaper loop
+    pub async fn run(self, shutdown: watch::Receiver<bool>) {
+        info!(
+            "🧹 Job reaper started (max job age: {}s, interval: {:?})",
+            self.max_job_age_secs, self.interval
+        );
+
+        let reaper = &self;
+        run_until_shutdown(self.interval, shutdown, move || async move {
+            match reaper
+                .state
+                .job_repo
+                .expire_stale_jobs(reaper.max_job_age_secs)
+                .await
+            {
+                Ok(expired) if !expired.is_empty() => {
+                    info!("⌛ Expired {} stale jobs: {:?}", expired.len(), expired);
+                }
+                Ok(_) => {}
+                Err(e) => warn!("⚠️  Failed to expire stale jobs: {}", e),
+            }
+        })
+        .await;
+
+        info!("🛑 Job reaper stopped");
+    }
+}
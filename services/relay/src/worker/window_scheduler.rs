@@ -1,10 +1,15 @@
-use std::{collections::VecDeque, sync::Arc, time::Duration};
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
-use tokio::sync::Mutex;
+use tokio::sync::{watch, Mutex};
 use tracing::{debug, info, warn};
 
+use super::priority::JobPriorityQueue;
 use crate::{
     db::{models::Job, repository::JobRepository},
+    shutdown::run_until_shutdown,
     AppState,
 };
 
@@ -43,45 +48,64 @@ impl WindowConfig {
 /// Accumulates jobs and processes them in time windows based on Solana slot numbers
 pub struct WindowScheduler {
     state: AppState,
-    config: WindowConfig,
-    job_buffer: Arc<Mutex<VecDeque<Job>>>,
+    /// Shared with `AppState` so `/admin/reload-config` can swap it at runtime
+    config: Arc<RwLock<WindowConfig>>,
+    /// Collected jobs, highest (aged) priority first
+    job_buffer: Arc<Mutex<JobPriorityQueue>>,
     last_processed_slot: Arc<Mutex<u64>>,
 }
 
 impl WindowScheduler {
-    pub fn new(state: AppState, config: WindowConfig) -> Self {
+    pub fn new(state: AppState, config: Arc<RwLock<WindowConfig>>) -> Self {
         Self {
             state,
             config,
-            job_buffer: Arc::new(Mutex::new(VecDeque::new())),
+            job_buffer: Arc::new(Mutex::new(JobPriorityQueue::new())),
             last_processed_slot: Arc::new(Mutex::new(0)),
         }
     }
 
-    /// Start the window scheduler loop
-    pub async fn run(self: Arc<Self>) {
+    /// Snapshot of the current window config
+    fn config(&self) -> WindowConfig {
+        self.config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Start the window scheduler loop.
+    ///
+    /// Returns once `shutdown` fires and the window being processed (if any) has finished.
+    pub async fn run(self: Arc<Self>, shutdown: watch::Receiver<bool>) {
+        let config = self.config();
         info!("🚀 Window Scheduler started");
-        info!("   Slot patterns: {:?}", self.config.slot_patterns);
-        info!("   Min batch size: {:?}", self.config.min_batch_size);
-        info!("   Max batch size: {}", self.config.max_batch_size);
-        info!("   Poll interval: {}s", self.config.poll_interval_secs);
+        info!("   Slot patterns: {:?}", config.slot_patterns);
+        info!("   Min batch size: {:?}", config.min_batch_size);
+        info!("   Max batch size: {}", config.max_batch_size);
+        info!("   Poll interval: {}s", config.poll_interval_secs);
+        info!("   Max concurrent jobs: {}", self.state.job_limiter.limit());
 
-        let poll_interval = Duration::from_secs(self.config.poll_interval_secs);
+        // Fixed for the lifetime of the loop; the other fields are re-read every tick
+        let poll_interval = Duration::from_secs(config.poll_interval_secs);
 
         // Spawn job collection task
         let collector = Arc::clone(&self);
-        tokio::spawn(async move {
+        let collector_handle = tokio::spawn(async move {
             collector.collect_jobs_loop().await;
         });
 
         // Main window processing loop
-        loop {
-            if let Err(e) = self.check_and_process_window().await {
+        let scheduler = &self;
+        run_until_shutdown(poll_interval, shutdown, move || async move {
+            if let Err(e) = scheduler.check_and_process_window().await {
                 warn!("⚠️  Window processing error: {}", e);
             }
+        })
+        .await;
 
-            tokio::time::sleep(poll_interval).await;
-        }
+        // Buffered jobs are still queued in the database and get picked up on restart
+        collector_handle.abort();
+        info!("🛑 Window Scheduler stopped");
     }
 
     /// Continuously collect queued jobs into buffer
@@ -94,7 +118,7 @@ impl WindowScheduler {
             };
 
             // Don't overfill the buffer
-            if buffer_size >= self.config.max_batch_size {
+            if buffer_size >= self.config().max_batch_size {
                 debug!(
                     "Buffer full ({} jobs), waiting for window to process",
                     buffer_size
@@ -120,9 +144,8 @@ impl WindowScheduler {
                             }
 
                             // Check if job is already in buffer to avoid duplicates
-                            let already_buffered = buffer.iter().any(|j| j.id == job.id);
-                            if !already_buffered {
-                                buffer.push_back(job);
+                            if !buffer.contains(job.id) {
+                                buffer.push(job);
                                 added += 1;
                             } else {
                                 debug!("Job {} already in buffer, skipping", job.id);
@@ -149,11 +172,13 @@ impl WindowScheduler {
 
     /// Check if current slot matches window pattern and process if ready
     async fn check_and_process_window(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let config = self.config();
+
         // Get current Solana slot
         let current_slot = self.state.solana.get_slot().await?;
 
         // Check if this is a window slot
-        if !self.config.is_window_slot(current_slot) {
+        if !config.is_window_slot(current_slot) {
             debug!("Slot {} - not a window slot, waiting...", current_slot);
             return Ok(());
         }
@@ -178,7 +203,7 @@ impl WindowScheduler {
             let count = buffer.len();
 
             // Check minimum batch size requirement
-            if let Some(min_size) = self.config.min_batch_size {
+            if let Some(min_size) = config.min_batch_size {
                 if count < min_size {
                     debug!(
                         "Slot {} - only {} jobs buffered, waiting for {} (min batch)",
@@ -194,9 +219,8 @@ impl WindowScheduler {
                 return Ok(());
             }
 
-            // Take all jobs from buffer
-            let jobs: Vec<Job> = buffer.drain(..).collect();
-            jobs
+            // Take all jobs from buffer, in the order they should run
+            buffer.drain_in_order()
         };
 
         // Update last processed slot
@@ -220,16 +244,17 @@ impl WindowScheduler {
         let batch_size = jobs.len();
         let start_time = std::time::Instant::now();
 
-        // Process jobs concurrently with semaphore to limit parallelism
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(10)); // Max 10 concurrent
+        // Process jobs concurrently, bounded by the relay-wide job limit. Slots
+        // are taken here rather than in each task so that when the limit is
+        // reached, jobs start in priority order.
         let mut handles = Vec::new();
 
         for job in jobs {
             let state = self.state.clone();
-            let semaphore = Arc::clone(&semaphore);
+            let permit = self.state.job_limiter.acquire().await;
 
             let handle = tokio::spawn(async move {
-                let _permit = semaphore.acquire().await.unwrap();
+                let _permit = permit;
 
                 if let Err(e) = super::processor::process_job_direct(job.clone(), state).await {
                     warn!("❌ Failed to process job {}: {}", job.id, e);
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use bincode;
+use cloak_layouts::FeeSchedule;
 use cloak_proof_extract::extract_groth16_260_sp1;
 use hex;
 use rand;
@@ -20,8 +21,8 @@ use sp1_sdk::{network::FulfillmentStrategy, HashableKey, Prover, ProverClient, S
 use std::str::FromStr;
 use std::time::{Duration, Instant};
 use test_complete_flow_rust::shared::{
-    check_cluster_health, ensure_user_funding, load_keypair, print_config, MerkleProof, TestConfig,
-    SOL_TO_LAMPORTS,
+    check_cluster_health, ensure_user_funding, load_keypair, print_config, withdraw_fee,
+    MerkleProof, TestConfig, SOL_TO_LAMPORTS,
 };
 use tokio::time::timeout;
 use zk_guest_sp1_host::{
@@ -596,11 +597,7 @@ fn prepare_proof_inputs(
     });
 
     // Calculate fee
-    let fee = {
-        let fixed_fee = 2_500_000; // 0.0025 SOL
-        let variable_fee = (test_data.amount * 5) / 1_000; // 0.5%
-        fixed_fee + variable_fee
-    };
+    let fee = withdraw_fee(test_data.amount, FeeSchedule::NATIVE)?;
     let recipient_amount = test_data.amount - fee;
 
     println!("   - Amount: {} lamports", test_data.amount);
@@ -758,11 +755,7 @@ fn validate_circuit_constraints_local(
         .sum();
 
     // Fee calculation must mirror on-chain logic: 0.0025 SOL + 0.5%
-    let fee = {
-        let fixed_fee = 2_500_000;
-        let variable_fee = (amount * 5) / 1_000;
-        fixed_fee + variable_fee
-    };
+    let fee = withdraw_fee(amount, FeeSchedule::NATIVE)?;
     let total_spent = outputs_sum + fee;
 
     println!("         ✓ Outputs sum: {} lamports", outputs_sum);
@@ -1424,6 +1417,7 @@ fn push_root_to_program(
     let admin_push_root_ix = test_complete_flow_rust::shared::create_admin_push_root_instruction(
         &admin_keypair.pubkey(),
         &accounts.roots_ring,
+        &accounts.pool,
         program_id,
         &merkle_root_array,
     );
@@ -1488,9 +1482,7 @@ async fn execute_withdraw_via_relay(
     println!("      - amount: {} lamports", amount);
 
     // Prepare the withdraw request for the relay using current fee policy
-    let fixed_fee = 2_500_000u64;
-    let variable_fee = (test_data.amount.saturating_mul(5)) / 1_000; // 0.5%
-    let total_fee = fixed_fee.saturating_add(variable_fee);
+    let total_fee = withdraw_fee(test_data.amount, FeeSchedule::NATIVE)?;
     let relay_recipient_amount = test_data.amount.saturating_sub(total_fee);
     let effective_fee_bps = if test_data.amount == 0 {
         0u16
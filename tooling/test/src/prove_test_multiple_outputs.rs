@@ -4,6 +4,7 @@ use std::{
 };
 
 use anyhow::Result;
+use cloak_layouts::FeeSchedule;
 use cloak_proof_extract::extract_groth16_260_sp1;
 use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
@@ -16,8 +17,8 @@ use solana_sdk::{
 };
 use sp1_sdk::{network::FulfillmentStrategy, HashableKey, Prover, ProverClient, SP1Stdin};
 use test_complete_flow_rust::shared::{
-    check_cluster_health, ensure_user_funding, load_keypair, print_config, MerkleProof, TestConfig,
-    SOL_TO_LAMPORTS,
+    check_cluster_health, ensure_user_funding, load_keypair, print_config, withdraw_fee,
+    MerkleProof, TestConfig, SOL_TO_LAMPORTS,
 };
 use tokio::time::timeout;
 use zk_guest_sp1_host::{
@@ -631,11 +632,7 @@ fn prepare_proof_inputs(
     });
 
     // Calculate fee (must match zk-guest-sp1/guest/src/encoding.rs::calculate_fee)
-    let fee = {
-        let fixed_fee = 2_500_000; // 0.0025 SOL
-        let variable_fee = (test_data.amount * 5) / 1_000; // 0.5%
-        fixed_fee + variable_fee
-    };
+    let fee = withdraw_fee(test_data.amount, FeeSchedule::NATIVE)?;
     let distributable_amount = test_data.amount - fee;
 
     println!("   - Amount: {} lamports", test_data.amount);
@@ -833,11 +830,7 @@ fn validate_circuit_constraints_local(
         .sum();
 
     // Fee calculation must match zk-guest-sp1/guest/src/encoding.rs::calculate_fee
-    let fee = {
-        let fixed_fee = 2_500_000; // 0.0025 SOL
-        let variable_fee = (amount * 5) / 1_000; // 0.5%
-        fixed_fee + variable_fee
-    };
+    let fee = withdraw_fee(amount, FeeSchedule::NATIVE)?;
     let total_spent = outputs_sum + fee;
 
     println!("         ✓ Outputs sum: {} lamports", outputs_sum);
@@ -1451,6 +1444,7 @@ fn push_root_to_program(
     let admin_push_root_ix = test_complete_flow_rust::shared::create_admin_push_root_instruction(
         &admin_keypair.pubkey(),
         &accounts.roots_ring,
+        &accounts.pool,
         program_id,
         &merkle_root_array,
     );
@@ -1490,11 +1484,7 @@ async fn execute_withdraw_via_relay(
     );
 
     // Calculate fee_bps based on the circuit's fee calculation
-    let fee = {
-        let fixed_fee = 2_500_000; // 0.0025 SOL
-        let variable_fee = (total_amount * 5) / 1_000; // 0.5%
-        fixed_fee + variable_fee
-    };
+    let fee = withdraw_fee(total_amount, FeeSchedule::NATIVE)?;
 
     let effective_fee_bps = if total_amount == 0 {
         0u16
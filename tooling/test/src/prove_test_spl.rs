@@ -4,6 +4,7 @@ use std::{
 };
 
 use anyhow::Result;
+use cloak_layouts::FeeSchedule;
 use cloak_proof_extract::extract_groth16_260_sp1;
 use serde::{Deserialize, Serialize};
 use shield_pool::CommitmentQueue;
@@ -20,7 +21,7 @@ use solana_sdk::{
 use sp1_sdk::{network::FulfillmentStrategy, HashableKey, Prover, ProverClient, SP1Stdin};
 use test_complete_flow_rust::shared::{
     check_cluster_health, ensure_user_funding, get_pda_addresses, load_keypair, print_config,
-    MerkleProof, TestConfig, SOL_TO_LAMPORTS,
+    withdraw_fee, MerkleProof, TestConfig, SOL_TO_LAMPORTS,
 };
 use tokio::time::timeout;
 use zk_guest_sp1_host::{
@@ -572,6 +573,7 @@ fn push_root_to_program_spl(
         accounts: vec![
             AccountMeta::new(admin_keypair.pubkey(), true),
             AccountMeta::new(accounts.roots_ring, false),
+            AccountMeta::new_readonly(accounts.pool, false),
         ],
         data: {
             let mut data = vec![1u8]; // AdminPushRoot discriminator
@@ -639,9 +641,7 @@ async fn execute_spl_withdraw_via_relay(
     println!("      - amount: {} lamports", amount);
 
     // Prepare the withdraw request for the relay using current fee policy
-    let fixed_fee = 2_500_000u64;
-    let variable_fee = (test_data.amount.saturating_mul(5)) / 1_000; // 0.5%
-    let total_fee = fixed_fee.saturating_add(variable_fee);
+    let total_fee = withdraw_fee(test_data.amount, FeeSchedule::NATIVE)?;
     let relay_recipient_amount = test_data.amount.saturating_sub(total_fee);
     let effective_fee_bps = if test_data.amount == 0 {
         0u16
@@ -1048,11 +1048,7 @@ fn prepare_proof_inputs(
     });
 
     // Calculate fee
-    let fee = {
-        let fixed_fee = 2_500_000; // 0.0025 SOL
-        let variable_fee = (test_data.amount * 5) / 1_000; // 0.5%
-        fixed_fee + variable_fee
-    };
+    let fee = withdraw_fee(test_data.amount, FeeSchedule::NATIVE)?;
     let recipient_amount = test_data.amount - fee;
 
     println!("   - Amount: {} lamports", test_data.amount);
@@ -4,6 +4,7 @@ use std::{
 };
 
 use anyhow::Result;
+use cloak_layouts::FeeSchedule;
 use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
@@ -16,7 +17,7 @@ use solana_sdk::{
 };
 use test_complete_flow_rust::shared::{
     check_cluster_health, ensure_user_funding, get_pda_addresses, load_keypair, print_config,
-    MerkleProof, TestConfig, SOL_TO_LAMPORTS,
+    withdraw_fee, MerkleProof, TestConfig, SOL_TO_LAMPORTS,
 };
 
 // SPL Token constants
@@ -399,9 +400,7 @@ async fn main() -> Result<()> {
     println!("\n💱 Step 4.5: Getting Jupiter quote for SOL → USDC swap...");
     let slippage_bps: u16 = 100; // 1%
                                  // Calculate total fee: fixed + variable (must match circuit calculation)
-    let fixed_fee = 2_500_000; // 0.0025 SOL
-    let variable_fee = (deposit_amount * 5) / 1_000; // 0.5%
-    let total_fee = fixed_fee + variable_fee;
+    let total_fee = withdraw_fee(deposit_amount, FeeSchedule::NATIVE)?;
     let sol_to_swap = deposit_amount
         .checked_sub(total_fee)
         .ok_or_else(|| anyhow::anyhow!("Fees exceed deposit amount"))?;
@@ -505,9 +504,8 @@ async fn main() -> Result<()> {
     );
 
     // Calculate fee for conservation used by current circuit/TEE: fixed + variable fee
-    let fixed_fee = 2_500_000; // 0.0025 SOL
-    let variable_fee = (deposit_amount * 5) / 1_000; // 0.5% = 5/1000
-    let total_fee = fixed_fee + variable_fee;
+    let variable_fee = withdraw_fee(deposit_amount, FeeSchedule::VARIABLE_ONLY)?;
+    let total_fee = withdraw_fee(deposit_amount, FeeSchedule::NATIVE)?;
     let withdraw_amount = deposit_amount - total_fee;
 
     // Calculate fee_bps for relay.
@@ -714,9 +712,9 @@ fn prepare_proof_inputs(
     });
 
     // Calculate fee for conservation used by current circuit/TEE: fixed + variable fee
-    let fixed_fee = 2_500_000; // 0.0025 SOL
-    let variable_fee = (test_data.amount * 5) / 1_000; // 0.5% = 5/1000
-    let total_fee = fixed_fee + variable_fee;
+    let fixed_fee = FeeSchedule::NATIVE.fixed;
+    let variable_fee = withdraw_fee(test_data.amount, FeeSchedule::VARIABLE_ONLY)?;
+    let total_fee = withdraw_fee(test_data.amount, FeeSchedule::NATIVE)?;
 
     println!("   - Amount: {} lamports", test_data.amount);
     println!("   - Fixed fee: {} lamports", fixed_fee);
@@ -918,9 +916,9 @@ fn validate_circuit_constraints_local(
         .sum();
 
     // Circuit uses fixed + variable fee for conservation
-    let fixed_fee = 2_500_000; // 0.0025 SOL
-    let variable_fee = (amount * 5) / 1_000; // 0.5% = 5/1000
-    let total_fee = fixed_fee + variable_fee;
+    let fixed_fee = FeeSchedule::NATIVE.fixed;
+    let variable_fee = withdraw_fee(amount, FeeSchedule::VARIABLE_ONLY)?;
+    let total_fee = withdraw_fee(amount, FeeSchedule::NATIVE)?;
 
     if is_swap {
         // For swap mode: outputs should be empty (all goes to swap)
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use cloak_layouts::FeeSchedule;
 use serde::{Deserialize, Serialize};
 use shield_pool::instructions::ShieldPoolInstruction;
 use solana_client::rpc_client::RpcClient;
@@ -83,6 +84,12 @@ impl TestConfig {
 /// Common test data structures
 pub const SOL_TO_LAMPORTS: u64 = 1_000_000_000;
 
+/// Withdraw fee for `amount` as the guest and program compute it
+pub fn withdraw_fee(amount: u64, schedule: FeeSchedule) -> Result<u64> {
+    cloak_layouts::calculate_fee(amount, schedule)
+        .map_err(|e| anyhow::anyhow!("fee for {} lamports: {}", amount, e))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MerkleProof {
     #[serde(rename = "pathElements")]
@@ -149,6 +156,7 @@ pub fn create_deposit_instruction(
 pub fn create_admin_push_root_instruction(
     admin_pubkey: &Pubkey,
     roots_ring_pubkey: &Pubkey,
+    pool_pubkey: &Pubkey,
     program_id: &Pubkey,
     merkle_root: &[u8; 32],
 ) -> Instruction {
@@ -161,6 +169,7 @@ pub fn create_admin_push_root_instruction(
         accounts: vec![
             AccountMeta::new(*admin_pubkey, true),
             AccountMeta::new(*roots_ring_pubkey, false),
+            AccountMeta::new_readonly(*pool_pubkey, false),
         ],
         data,
     }
@@ -0,0 +1,311 @@
+//! End-to-end shield pool flow against a local `solana-test-validator`
+//!
+//! Boots a fresh validator with the shield-pool program preloaded, then runs
+//! deposit -> admin root push -> withdraw using the instruction builders in
+//! `test_complete_flow_rust::shared` and a locally generated SP1 proof,
+//! asserting the on-chain balance changes at each step. The validator and its
+//! ledger are torn down when the test finishes, whether it passes or not.
+//!
+//! Prerequisites:
+//! - `solana-test-validator` on `PATH`
+//! - the program built with `cargo build-sbf --manifest-path programs/shield-pool/Cargo.toml`
+//!   (override the artifact with `SHIELD_POOL_SO`)
+//! - the pool admin keypair at `ADMIN_KEYPAIR` or `~/.config/solana/id.json`
+//!
+//! Local proving takes minutes, so the test only builds with the `integration` feature:
+//!
+//! ```bash
+//! cargo test -p test-complete-flow-rust --features integration --test e2e -- --nocapture
+//! ```
+
+#![cfg(feature = "integration")]
+
+use std::{
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use cloak_layouts::MERKLE_TREE_DEPTH;
+use cloak_proof_extract::extract_groth16_260_sp1;
+use rand::RngCore;
+use shield_pool::instructions::ShieldPoolInstruction;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use test_complete_flow_rust::shared::{
+    create_admin_push_root_instruction, create_deposit_instruction, create_withdraw_instruction,
+    get_pda_addresses_sol, load_keypair, SOL_TO_LAMPORTS,
+};
+use zk_guest_sp1_host::encoding::{
+    calculate_fee, compute_commitment, compute_nullifier, compute_outputs_hash, compute_pk_spend,
+    Output,
+};
+
+const RPC_PORT: u16 = 18899;
+const FAUCET_PORT: u16 = 19900;
+const VALIDATOR_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+const AIRDROP_TIMEOUT: Duration = Duration::from_secs(30);
+const FEE_BPS: u16 = 5;
+
+/// A `solana-test-validator` process, killed and its ledger removed on drop
+struct TestValidator {
+    child: Child,
+    ledger: PathBuf,
+    rpc_url: String,
+}
+
+impl TestValidator {
+    fn start(program_id: &Pubkey, program_so: &str) -> Result<Self> {
+        let ledger = std::env::temp_dir().join(format!("cloak-e2e-ledger-{}", std::process::id()));
+        let child = Command::new("solana-test-validator")
+            .args(["--reset", "--quiet", "--ledger"])
+            .arg(&ledger)
+            .args(["--rpc-port", &RPC_PORT.to_string()])
+            .args(["--faucet-port", &FAUCET_PORT.to_string()])
+            .args(["--bpf-program", &program_id.to_string(), program_so])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to spawn solana-test-validator: {}", e))?;
+
+        // Construct the guard first so a failed startup still cleans up
+        let mut validator = Self {
+            child,
+            ledger,
+            rpc_url: format!("http://127.0.0.1:{}", RPC_PORT),
+        };
+        validator.wait_until_healthy()?;
+        Ok(validator)
+    }
+
+    fn wait_until_healthy(&mut self) -> Result<()> {
+        let client = RpcClient::new(self.rpc_url.clone());
+        let deadline = Instant::now() + VALIDATOR_STARTUP_TIMEOUT;
+        loop {
+            if let Some(status) = self.child.try_wait()? {
+                return Err(anyhow!("solana-test-validator exited early: {}", status));
+            }
+            if client.get_health().is_ok() {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "solana-test-validator not healthy after {:?}",
+                    VALIDATOR_STARTUP_TIMEOUT
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(500));
+        }
+    }
+
+    fn client(&self) -> RpcClient {
+        RpcClient::new_with_commitment(self.rpc_url.clone(), CommitmentConfig::confirmed())
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.ledger);
+    }
+}
+
+fn airdrop(client: &RpcClient, pubkey: &Pubkey, lamports: u64) -> Result<()> {
+    let before = client.get_balance(pubkey)?;
+    client.request_airdrop(pubkey, lamports)?;
+
+    let deadline = Instant::now() + AIRDROP_TIMEOUT;
+    while client.get_balance(pubkey)? < before + lamports {
+        if Instant::now() >= deadline {
+            return Err(anyhow!("Airdrop to {} not confirmed", pubkey));
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    Ok(())
+}
+
+fn send(client: &RpcClient, instructions: &[Instruction], payer: &Keypair) -> Result<()> {
+    let mut tx = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    tx.sign(&[payer], client.get_latest_blockhash()?);
+    client.send_and_confirm_transaction(&tx)?;
+    Ok(())
+}
+
+fn load_admin_keypair() -> Result<Keypair> {
+    let path = std::env::var("ADMIN_KEYPAIR").unwrap_or_else(|_| {
+        std::env::var("HOME")
+            .map(|home| format!("{}/.config/solana/id.json", home))
+            .unwrap_or_else(|_| "admin-keypair.json".to_string())
+    });
+    load_keypair(&path)
+}
+
+/// Merkle path for the first leaf of an otherwise empty tree, and its root
+fn first_leaf_path(leaf: &[u8; 32]) -> (Vec<[u8; 32]>, Vec<u8>, [u8; 32]) {
+    let mut path_elements = Vec::with_capacity(MERKLE_TREE_DEPTH);
+    let mut zero = [0u8; 32];
+    let mut current = *leaf;
+    for _ in 0..MERKLE_TREE_DEPTH {
+        path_elements.push(zero);
+        current = hash_pair(&current, &zero);
+        zero = hash_pair(&zero, &zero);
+    }
+    (path_elements, vec![0u8; MERKLE_TREE_DEPTH], current)
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_deposit_push_root_withdraw() -> Result<()> {
+    let program_id = Pubkey::new_from_array(shield_pool::ID);
+    let program_so = std::env::var("SHIELD_POOL_SO").unwrap_or_else(|_| {
+        format!(
+            "{}/../../target/deploy/shield_pool.so",
+            env!("CARGO_MANIFEST_DIR")
+        )
+    });
+
+    let validator = TestValidator::start(&program_id, &program_so)?;
+    let client = validator.client();
+
+    let admin = load_admin_keypair()?;
+    let user = Keypair::new();
+    let recipient = Keypair::new();
+    airdrop(&client, &admin.pubkey(), 10 * SOL_TO_LAMPORTS)?;
+    airdrop(&client, &user.pubkey(), 2 * SOL_TO_LAMPORTS)?;
+
+    // Initialize the native SOL pool; the program creates the PDAs itself
+    let (pool, commitments, roots_ring, nullifier_shard, treasury) =
+        get_pda_addresses_sol(&program_id);
+    let mut init_data = vec![ShieldPoolInstruction::Initialize as u8];
+    init_data.extend_from_slice(&Pubkey::default().to_bytes());
+    let initialize_ix = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(commitments, false),
+            AccountMeta::new(roots_ring, false),
+            AccountMeta::new(nullifier_shard, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+        data: init_data,
+    };
+    send(&client, &[initialize_ix], &admin)?;
+
+    // Deposit
+    let amount = SOL_TO_LAMPORTS / 10;
+    let mut sk_spend = [0u8; 32];
+    let mut r = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut sk_spend);
+    rand::thread_rng().fill_bytes(&mut r);
+    let commitment = compute_commitment(amount, &r, &compute_pk_spend(&sk_spend));
+
+    let pool_before_deposit = client.get_balance(&pool)?;
+    let deposit_ix = create_deposit_instruction(
+        &user.pubkey(),
+        &pool,
+        &commitments,
+        &program_id,
+        amount,
+        &commitment,
+    );
+    send(&client, &[deposit_ix], &user)?;
+    assert_eq!(client.get_balance(&pool)?, pool_before_deposit + amount);
+
+    // Push the root of a tree holding only our commitment
+    let (path_elements, path_indices, root) = first_leaf_path(&commitment);
+    let push_root_ix =
+        create_admin_push_root_instruction(&admin.pubkey(), &roots_ring, &pool, &program_id, &root);
+    send(&client, &[push_root_ix], &admin)?;
+
+    // Prove
+    let leaf_index = 0u32;
+    let nullifier = compute_nullifier(&sk_spend, leaf_index);
+    let fee = calculate_fee(amount, FEE_BPS)
+        .map_err(|e| anyhow!("fee for {} lamports: {}", amount, e))?;
+    let recipient_amount = amount - fee;
+    let outputs_hash = compute_outputs_hash(&[Output {
+        address: recipient.pubkey().to_bytes(),
+        amount: recipient_amount,
+    }]);
+
+    let private_inputs = serde_json::json!({
+        "amount": amount,
+        "r": hex::encode(r),
+        "sk_spend": hex::encode(sk_spend),
+        "leaf_index": leaf_index,
+        "merkle_path": {
+            "path_elements": path_elements.iter().map(hex::encode).collect::<Vec<_>>(),
+            "path_indices": path_indices
+        }
+    })
+    .to_string();
+    let public_inputs = serde_json::json!({
+        "root": hex::encode(root),
+        "nf": hex::encode(nullifier),
+        "outputs_hash": hex::encode(outputs_hash),
+        "amount": amount
+    })
+    .to_string();
+    let outputs = serde_json::json!([{
+        "address": hex::encode(recipient.pubkey().to_bytes()),
+        "amount": recipient_amount
+    }])
+    .to_string();
+
+    let proof = tokio::task::spawn_blocking(move || {
+        zk_guest_sp1_host::generate_proof(&private_inputs, &public_inputs, &outputs)
+    })
+    .await??;
+    let groth16 = extract_groth16_260_sp1(&proof.proof_bytes)?;
+
+    // Withdraw
+    let pool_before_withdraw = client.get_balance(&pool)?;
+    let treasury_before_withdraw = client.get_balance(&treasury)?;
+    let withdraw_ix = create_withdraw_instruction(
+        &pool,
+        &treasury,
+        &roots_ring,
+        &nullifier_shard,
+        &[recipient.pubkey()],
+        &program_id,
+        &groth16,
+        &proof.public_inputs,
+        &nullifier,
+        &[(recipient.pubkey(), recipient_amount)],
+    );
+    send(
+        &client,
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+            withdraw_ix,
+        ],
+        &admin,
+    )?;
+
+    assert_eq!(client.get_balance(&recipient.pubkey())?, recipient_amount);
+    assert_eq!(client.get_balance(&pool)?, pool_before_withdraw - amount);
+    assert_eq!(
+        client.get_balance(&treasury)?,
+        treasury_before_withdraw + fee
+    );
+
+    drop(validator);
+    Ok(())
+}